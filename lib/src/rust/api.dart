@@ -4,6 +4,7 @@
 // ignore_for_file: invalid_use_of_internal_member, unused_import, unnecessary_import
 
 import 'frb_generated.dart';
+import 'http.dart';
 import 'logger.dart';
 import 'models/exchange_rates.dart';
 import 'models/mempool.dart';
@@ -77,3 +78,10 @@ List<FiatCurrency> getSupportedCurrencies() =>
 
 String currencyCode({required FiatCurrency currency}) =>
     RustLib.instance.api.crateApiCurrencyCode(currency: currency);
+
+/// Configure timeouts, retries and circuit breaking for outbound network calls.
+void configureHttp({required HttpConfig config}) =>
+    RustLib.instance.api.crateApiConfigureHttp(config: config);
+
+/// The current network settings, defaults until `configure_http` is called.
+HttpConfig getHttpConfig() => RustLib.instance.api.crateApiGetHttpConfig();
@@ -0,0 +1,139 @@
+// This file is automatically generated, so please do not edit it.
+// @generated by `flutter_rust_bridge`@ 2.11.1.
+
+// ignore_for_file: invalid_use_of_internal_member, unused_import, unnecessary_import
+
+import '../ark/tx_metadata_db.dart';
+import '../frb_generated.dart';
+import '../lendasat/models.dart';
+import '../lendaswap.dart';
+import 'package:flutter_rust_bridge/flutter_rust_bridge_for_generated.dart';
+
+// These functions are ignored because they are not marked as `pub`: `feed_page`, `load_activity`, `load_contracts`, `load_owners`, `load_swaps`, `merge_activity`, `now_unix`, `owner_item`, `owner_items`, `parse_timestamp`, `tx_amount`, `tx_item`, `tx_parts`, `tx_timestamp`
+// These types are ignored because they are neither used by any `pub` functions nor (for structs and enums) marked `#[frb(unignore)]`: `FeedCursor`, `OwnerIndex`, `Owners`, `SwapEntry`
+
+/// Load one page of the unified activity feed, newest first.
+///
+/// Pass `None` as cursor for the first page. Only the first page syncs the
+/// history cache and reloads swaps and loans when they are out of date; later
+/// pages are read from the cache, so they do not shift while scrolling.
+///
+/// Swaps and loans are only included when LendaSwap respectively Lendasat
+/// are initialized. Failing to load them is logged and does not fail the
+/// feed, so the wallet history is always shown.
+Future<ActivityPage> activityFeed({String? cursor, required int limit}) =>
+    RustLib.instance.api
+        .crateApiActivityApiActivityFeed(cursor: cursor, limit: limit);
+
+/// A single entry of the activity feed.
+class ActivityItem {
+  /// Txid for plain transactions, swap id or contract id otherwise
+  final String id;
+  final ActivityKind kind;
+
+  /// Unix timestamp used for ordering
+  final PlatformInt64 timestamp;
+
+  /// Balance change in satoshis (negative for outgoing)
+  final PlatformInt64 amountSats;
+  final bool isPending;
+
+  /// Ark txids belonging to this item
+  final List<String> txids;
+
+  /// Locally recorded metadata of the primary transaction
+  final TxMetadata? metadata;
+  final SwapInfo? swap;
+  final Contract? contract;
+
+  const ActivityItem({
+    required this.id,
+    required this.kind,
+    required this.timestamp,
+    required this.amountSats,
+    required this.isPending,
+    required this.txids,
+    this.metadata,
+    this.swap,
+    this.contract,
+  });
+
+  @override
+  int get hashCode =>
+      id.hashCode ^
+      kind.hashCode ^
+      timestamp.hashCode ^
+      amountSats.hashCode ^
+      isPending.hashCode ^
+      txids.hashCode ^
+      metadata.hashCode ^
+      swap.hashCode ^
+      contract.hashCode;
+
+  @override
+  bool operator ==(Object other) =>
+      identical(this, other) ||
+      other is ActivityItem &&
+          runtimeType == other.runtimeType &&
+          id == other.id &&
+          kind == other.kind &&
+          timestamp == other.timestamp &&
+          amountSats == other.amountSats &&
+          isPending == other.isPending &&
+          txids == other.txids &&
+          metadata == other.metadata &&
+          swap == other.swap &&
+          contract == other.contract;
+}
+
+/// What an activity item represents.
+enum ActivityKind {
+  /// On-chain deposit into the Ark boarding address
+  boarding,
+
+  /// Off-chain payment received from another Ark user
+  arkReceive,
+
+  /// Off-chain payment sent to another Ark user
+  arkSend,
+
+  /// Batch settlement of VTXOs
+  settlement,
+
+  /// Collaborative redemption to a Bitcoin address
+  onchainSend,
+
+  /// Lightning payment via Boltz submarine swap
+  lightningPayment,
+
+  /// LendaSwap swap between BTC and a stablecoin
+  swap,
+
+  /// Lendasat loan contract
+  loan,
+  ;
+}
+
+/// One page of the activity feed.
+class ActivityPage {
+  final List<ActivityItem> items;
+
+  /// Pass to `activity_feed` to load the next page, `None` at the end
+  final String? nextCursor;
+
+  const ActivityPage({
+    required this.items,
+    this.nextCursor,
+  });
+
+  @override
+  int get hashCode => items.hashCode ^ nextCursor.hashCode;
+
+  @override
+  bool operator ==(Object other) =>
+      identical(this, other) ||
+      other is ActivityPage &&
+          runtimeType == other.runtimeType &&
+          items == other.items &&
+          nextCursor == other.nextCursor;
+}
@@ -3,11 +3,20 @@
 
 // ignore_for_file: invalid_use_of_internal_member, unused_import, unnecessary_import
 
+import '../ark/client.dart';
+import '../ark/esplora.dart';
+import '../ark/history_db.dart';
+import '../ark/message_signing.dart';
+import '../ark/migration.dart';
+import '../ark/psbt_analysis.dart';
+import '../ark/signer.dart';
+import '../ark/tx_metadata_db.dart';
 import '../frb_generated.dart';
 import 'package:flutter_rust_bridge/flutter_rust_bridge_for_generated.dart';
 import 'package:freezed_annotation/freezed_annotation.dart' hide protected;
 part 'ark_api.freezed.dart';
 
+// These functions are ignored because they are not marked as `pub`: `load_tx_metadata`, `parse_network`, `refresh_tx_history`, `sequence_to_secs`, `to_api_transaction`
 // These function are ignored because they are on traits that is not defined in current crate (put an empty `#[frb]` on it to unignore): `clone`, `clone`, `fmt`, `fmt`
 
 Future<bool> walletExists({required String dataDir}) =>
@@ -41,6 +50,39 @@ Future<String> loadExistingWallet(
         server: server,
         boltzUrl: boltzUrl);
 
+/// Start or resume moving all funds from `old_server` to `new_server`.
+///
+/// Progress is stored in the data directory, so this can be called again
+/// after a restart or while the offboard transaction is unconfirmed. When the
+/// returned step is `Completed` the wallet uses the new server and the app
+/// should store `new_server` as its Ark server URL.
+///
+/// `old_server` must be the server the wallet is connected to, and pending
+/// boarding outputs on it have to be settled before a migration can start.
+Future<MigrationState> migrateArkServer(
+        {required String dataDir,
+        required String network,
+        required String esplora,
+        required String oldServer,
+        required String newServer,
+        required String boltzUrl}) =>
+    RustLib.instance.api.crateApiArkApiMigrateArkServer(
+        dataDir: dataDir,
+        network: network,
+        esplora: esplora,
+        oldServer: oldServer,
+        newServer: newServer,
+        boltzUrl: boltzUrl);
+
+/// Get the progress of a started server migration, if any.
+Future<MigrationState?> getArkServerMigration({required String dataDir}) =>
+    RustLib.instance.api.crateApiArkApiGetArkServerMigration(dataDir: dataDir);
+
+/// Forget a server migration that has not moved funds yet or has completed.
+Future<void> clearArkServerMigration({required String dataDir}) =>
+    RustLib.instance.api
+        .crateApiArkApiClearArkServerMigration(dataDir: dataDir);
+
 /// Restore a wallet from a mnemonic phrase (12 or 24 words)
 Future<String> restoreWallet(
         {required String mnemonicWords,
@@ -57,31 +99,115 @@ Future<String> restoreWallet(
         server: server,
         boltzUrl: boltzUrl);
 
+/// Get the wallet balance.
+///
+/// If the Ark server cannot be reached, the last known balance is returned
+/// and flagged as stale (see `get_cache_status`). A successful refresh after
+/// the wallet was offline also replays actions queued in the meantime.
 Future<Balance> balance() => RustLib.instance.api.crateApiArkApiBalance();
 
 Future<Addresses> address({BigInt? amount}) =>
     RustLib.instance.api.crateApiArkApiAddress(amount: amount);
 
+/// Get the full transaction history, newest first.
+///
+/// Syncs the local history cache first if it is out of date. If the server
+/// cannot be reached, the cached history is returned instead.
 Future<List<Transaction>> txHistory() =>
     RustLib.instance.api.crateApiArkApiTxHistory();
 
-Future<String> send({
-  required String address,
-  required BigInt amountSats,
-  BigInt? feeSats,
-}) =>
+/// Fetch the transaction history from the server and update the local cache,
+/// even if the cache was synced recently (e.g. on pull to refresh).
+Future<HistorySyncStats> syncTxHistory() =>
+    RustLib.instance.api.crateApiArkApiSyncTxHistory();
+
+/// Load a page of the cached transaction history, newest first.
+///
+/// Does not contact the server; call `sync_tx_history` to refresh the cache.
+/// Pass `None` as cursor for the first page.
+Future<TxHistoryPage> txHistoryPage(
+        {String? cursor, required int limit, TxHistoryFilter? filter}) =>
+    RustLib.instance.api.crateApiArkApiTxHistoryPage(
+        cursor: cursor, limit: limit, filter: filter);
+
+/// Set the user's note, category or counterparty for a transaction.
+///
+/// Fields passed as `None` keep their current value, empty strings clear it.
+Future<TxMetadata> setTxMetadata(
+        {required String txid,
+        String? note,
+        String? category,
+        String? counterparty}) =>
+    RustLib.instance.api.crateApiArkApiSetTxMetadata(
+        txid: txid, note: note, category: category, counterparty: counterparty);
+
+/// Get the locally stored metadata for a transaction.
+Future<TxMetadata?> getTxMetadata({required String txid}) =>
+    RustLib.instance.api.crateApiArkApiGetTxMetadata(txid: txid);
+
+/// Remove the locally stored metadata for a transaction.
+Future<void> deleteTxMetadata({required String txid}) =>
+    RustLib.instance.api.crateApiArkApiDeleteTxMetadata(txid: txid);
+
+/// Export transaction notes as BIP329 JSON Lines.
+Future<String> exportTxLabels() =>
+    RustLib.instance.api.crateApiArkApiExportTxLabels();
+
+/// Import transaction labels from BIP329 JSON Lines.
+/// Returns the number of imported labels.
+Future<int> importTxLabels({required String jsonl}) =>
+    RustLib.instance.api.crateApiArkApiImportTxLabels(jsonl: jsonl);
+
+Future<String> send(
+        {required String address,
+        required BigInt amountSats,
+        BigInt? feeSats}) =>
     RustLib.instance.api.crateApiArkApiSend(
-      address: address,
-      amountSats: amountSats,
-      feeSats: feeSats,
-    );
+        address: address, amountSats: amountSats, feeSats: feeSats);
 
 /// Pay a BOLT11 Lightning invoice using Ark funds via Boltz submarine swap
 Future<LnPaymentResult> payLnInvoice({required String invoice}) =>
     RustLib.instance.api.crateApiArkApiPayLnInvoice(invoice: invoice);
 
+/// Settle all VTXOs and boarding outputs. Queued for later if the Ark server
+/// cannot be reached.
 Future<void> settle() => RustLib.instance.api.crateApiArkApiSettle();
 
+/// Re-run key discovery with the given gap limit and scan all boarding addresses.
+///
+/// Progress is streamed to `progress_sink`; the stream ends with `Completed`
+/// or `Failed`. A failed rescan can simply be started again.
+Stream<RescanProgress> rescanWallet({required int gapLimit}) =>
+    RustLib.instance.api.crateApiArkApiRescanWallet(gapLimit: gapLimit);
+
+/// Whether key discovery failed while loading the wallet and a rescan is recommended.
+bool walletNeedsRescan() =>
+    RustLib.instance.api.crateApiArkApiWalletNeedsRescan();
+
+/// Get the Esplora endpoints in use, the primary endpoint first.
+Future<List<String>> getEsploraEndpoints() =>
+    RustLib.instance.api.crateApiArkApiGetEsploraEndpoints();
+
+/// Set additional Esplora endpoints used when the primary one fails.
+///
+/// The endpoints are stored in the data directory and applied immediately to
+/// Ark requests. The on-chain wallet picks its endpoint when the wallet is
+/// loaded and keeps it until the next load.
+Future<void> setEsploraEndpoints(
+        {required String dataDir,
+        required String primary,
+        required List<String> fallbacks}) =>
+    RustLib.instance.api.crateApiArkApiSetEsploraEndpoints(
+        dataDir: dataDir, primary: primary, fallbacks: fallbacks);
+
+/// Health of the Esplora endpoints as observed by recent requests.
+Future<List<EsploraEndpointHealth>> getEsploraHealth() =>
+    RustLib.instance.api.crateApiArkApiGetEsploraHealth();
+
+/// Actively probe all Esplora endpoints and return their health.
+Future<List<EsploraEndpointHealth>> checkEsploraHealth() =>
+    RustLib.instance.api.crateApiArkApiCheckEsploraHealth();
+
 /// Get pending boarding UTXOs (on-chain funds at the boarding address that haven't been settled yet)
 Future<List<BoardingUtxo>> getBoardingUtxos() =>
     RustLib.instance.api.crateApiArkApiGetBoardingUtxos();
@@ -121,18 +247,85 @@ Future<String> getMnemonic({required String dataDir}) =>
 Future<void> resetWallet({required String dataDir}) =>
     RustLib.instance.api.crateApiArkApiResetWallet(dataDir: dataDir);
 
-/// Sign an Ark PSBT using the Ark SDK's key provider and signing functions.
+/// Prove ownership of a boarding address with a BIP-322 signature.
+///
+/// Boarding outputs can only be spent by the wallet through their timelocked
+/// exit path, so the signature uses the BIP-322 full format. Returns it base64
+/// encoded.
+Future<String> signMessageBip322(
+        {required String address, required String message}) =>
+    RustLib.instance.api
+        .crateApiArkApiSignMessageBip322(address: address, message: message);
+
+/// Verify a BIP-322 signature (simple or full format) for an address.
+Future<bool> verifyMessageBip322(
+        {required String address,
+        required String message,
+        required String signature}) =>
+    RustLib.instance.api.crateApiArkApiVerifyMessageBip322(
+        address: address, message: message, signature: signature);
+
+/// Sign a message with the Ark identity key (BIP-340 Schnorr over SHA256 of the message).
+///
+/// Uses the same identity as `sign_psbt_with_ark_identity`.
+Future<IdentitySignature> signMessageWithArkIdentity(
+        {required String message}) =>
+    RustLib.instance.api
+        .crateApiArkApiSignMessageWithArkIdentity(message: message);
+
+/// Verify a signature made with `sign_message_with_ark_identity`.
+///
+/// `pubkey` is the x-only public key as hex.
+Future<bool> verifyArkIdentitySignature(
+        {required String pubkey,
+        required String message,
+        required String signature}) =>
+    RustLib.instance.api.crateApiArkApiVerifyArkIdentitySignature(
+        pubkey: pubkey, message: message, signature: signature);
+
+/// Sign an Ark PSBT with the stable Ark identity key (m/83696968'/11811'/0/0).
 ///
-/// This uses:
-/// - The SDK's key provider to get the keypair (not manual derivation)
-/// - ark_core::send::sign_ark_transaction for proper script-path signing
+/// The identity never changes, unlike `vtxo.owner_pk()` which changes with each
+/// VTXO, so it matches the borrower key used when a Lendasat contract was
+/// created. This is the equivalent of Arkade's `SingleKey` signing.
 ///
-/// This matches exactly how Arkade wallet signs PSBTs - using the SDK's
-/// identity.sign() equivalent functionality.
+/// Works with either the Ark wallet loaded or Lendasat initialized; without
+/// a loaded wallet the key is derived from the mnemonic in Lendasat's data
+/// directory.
 Future<String> signPsbtWithArkIdentity({required String psbtHex}) =>
     RustLib.instance.api
         .crateApiArkApiSignPsbtWithArkIdentity(psbtHex: psbtHex);
 
+/// Sign an Ark PSBT (script-path spends of VTXOs or contracts) with wallet keys.
+///
+/// With `key` set every input is signed with that key, otherwise each input is
+/// signed with the wallet keys referenced by its tapscripts. PSBTs are hex.
+Future<String> signArkPsbt({required String psbtHex, SigningKey? key}) =>
+    RustLib.instance.api.crateApiArkApiSignArkPsbt(psbtHex: psbtHex, key: key);
+
+/// Sign an on-chain PSBT (P2WPKH or Taproot inputs) with wallet keys.
+///
+/// Inputs are matched by their key origins or by the public keys they commit
+/// to; with `key` set only that key is used. PSBTs are hex.
+Future<String> signOnchainPsbt({required String psbtHex, SigningKey? key}) =>
+    RustLib.instance.api
+        .crateApiArkApiSignOnchainPsbt(psbtHex: psbtHex, key: key);
+
+/// The x-only public key of a wallet key, as hex.
+Future<String> getSigningPubkey({required SigningKey key}) =>
+    RustLib.instance.api.crateApiArkApiGetSigningPubkey(key: key);
+
+/// Decode a PSBT (hex or base64) into a summary of what signing it would do:
+/// spent inputs, outputs paying to the wallet vs third parties, fee, estimated
+/// fee rate and warnings. Show this to the user before signing.
+///
+/// `wallet_addresses` are additionally treated as wallet-owned, e.g. the payout
+/// address registered with a loan contract.
+Future<PsbtSummary> analyzePsbt(
+        {required String psbt, required List<String> walletAddresses}) =>
+    RustLib.instance.api.crateApiArkApiAnalyzePsbt(
+        psbt: psbt, walletAddresses: walletAddresses);
+
 Future<Info> information() => RustLib.instance.api.crateApiArkApiInformation();
 
 Future<PaymentReceived> waitForPayment(
@@ -158,8 +351,8 @@ Future<FeeEstimate> estimateOnchainFee(
 
 /// Estimate fee for Arkade (off-chain) send
 ///
-/// Ark-to-Ark transfers are essentially free since they happen off-chain.
-/// Returns 0 fee.
+/// Ark-to-Ark transfers happen off-chain, so the only cost is the server's
+/// batch fee.
 Future<FeeEstimate> estimateArkadeFee(
         {required String address, required BigInt amountSats}) =>
     RustLib.instance.api.crateApiArkApiEstimateArkadeFee(
@@ -282,20 +475,51 @@ class FeeEstimate {
   /// Estimated fee in satoshis
   final BigInt feeSats;
 
-  /// Fee rate used (sat/vB for on-chain, percentage for Lightning)
-  final double feeRate;
+  /// Fee rate used: the network rate (sat/vB) behind the miner fee of
+  /// on-chain sends, the percentage for Lightning. `None` for Arkade sends
+  /// and when the network rate is unavailable.
+  final double? feeRate;
 
-  /// Number of VTXOs that would be used (for on-chain sends)
+  /// Number of VTXOs that would be spent to cover amount and fee
   final int numInputs;
 
+  /// Virtual size the send adds on-chain (0 for Arkade and Lightning sends)
+  final BigInt vbytes;
+
+  /// Part of the fee charged by the Ark server (offboard or batch fee)
+  final BigInt serverFeeSats;
+
+  /// Part of the fee paying for on-chain block space
+  final BigInt minerFeeSats;
+
+  /// Part of the fee charged by Boltz as a percentage of the amount
+  final BigInt serviceFeeSats;
+
+  /// Whether the split above is estimated from the network fee rate rather
+  /// than reported by the server (on-chain sends), show it as approximate
+  final bool splitIsEstimate;
+
   const FeeEstimate({
     required this.feeSats,
-    required this.feeRate,
+    this.feeRate,
     required this.numInputs,
+    required this.vbytes,
+    required this.serverFeeSats,
+    required this.minerFeeSats,
+    required this.serviceFeeSats,
+    required this.splitIsEstimate,
   });
 
   @override
-  int get hashCode => feeSats.hashCode ^ feeRate.hashCode ^ numInputs.hashCode;
+  int get hashCode =>
+      feeSats.hashCode ^
+      feeRate.hashCode ^
+      numInputs.hashCode ^
+      vbytes.hashCode ^
+      serverFeeSats.hashCode ^
+      minerFeeSats.hashCode ^
+      serviceFeeSats.hashCode ^
+      splitIsEstimate.hashCode;
 
   @override
   bool operator ==(Object other) =>
@@ -304,20 +528,99 @@ class FeeEstimate {
           runtimeType == other.runtimeType &&
           feeSats == other.feeSats &&
           feeRate == other.feeRate &&
-          numInputs == other.numInputs;
+          numInputs == other.numInputs &&
+          vbytes == other.vbytes &&
+          serverFeeSats == other.serverFeeSats &&
+          minerFeeSats == other.minerFeeSats &&
+          serviceFeeSats == other.serviceFeeSats &&
+          splitIsEstimate == other.splitIsEstimate;
 }
 
+/// Parameters of the connected Ark server.
+///
+/// Durations are in seconds. Block-based delays are converted assuming
+/// 10 minutes per block.
 class Info {
   final String serverPk;
   final String network;
 
+  /// Server software version
+  final String version;
+  final String forfeitPk;
+
+  /// Address receiving forfeited VTXOs
+  final String forfeitAddress;
+
+  /// Duration of a batch session
+  final BigInt sessionDurationSecs;
+
+  /// Lifetime of a VTXO before it must be renewed
+  final BigInt? vtxoExpirySecs;
+
+  /// Delay before funds can be claimed after a unilateral exit
+  final BigInt? unilateralExitDelaySecs;
+
+  /// Delay after which boarding funds can be reclaimed on-chain without the server
+  final BigInt? boardingExitDelaySecs;
+
+  /// Smallest output the server accepts
+  final BigInt dustSats;
+  final BigInt? vtxoMinAmountSats;
+  final BigInt? vtxoMaxAmountSats;
+  final BigInt? utxoMinAmountSats;
+  final BigInt? utxoMaxAmountSats;
+  final ServerFees? fees;
+
+  /// Whole days until a freshly settled VTXO expires
+  final int? vtxoExpiryDays;
+
+  /// Smallest amount that can be sent off-chain
+  final BigInt minSendableSats;
+
+  /// Smallest amount that can be sent to a Bitcoin address
+  final BigInt minOnchainSats;
+
   const Info({
     required this.serverPk,
     required this.network,
+    required this.version,
+    required this.forfeitPk,
+    required this.forfeitAddress,
+    required this.sessionDurationSecs,
+    this.vtxoExpirySecs,
+    this.unilateralExitDelaySecs,
+    this.boardingExitDelaySecs,
+    required this.dustSats,
+    this.vtxoMinAmountSats,
+    this.vtxoMaxAmountSats,
+    this.utxoMinAmountSats,
+    this.utxoMaxAmountSats,
+    this.fees,
+    this.vtxoExpiryDays,
+    required this.minSendableSats,
+    required this.minOnchainSats,
   });
 
   @override
-  int get hashCode => serverPk.hashCode ^ network.hashCode;
+  int get hashCode =>
+      serverPk.hashCode ^
+      network.hashCode ^
+      version.hashCode ^
+      forfeitPk.hashCode ^
+      forfeitAddress.hashCode ^
+      sessionDurationSecs.hashCode ^
+      vtxoExpirySecs.hashCode ^
+      unilateralExitDelaySecs.hashCode ^
+      boardingExitDelaySecs.hashCode ^
+      dustSats.hashCode ^
+      vtxoMinAmountSats.hashCode ^
+      vtxoMaxAmountSats.hashCode ^
+      utxoMinAmountSats.hashCode ^
+      utxoMaxAmountSats.hashCode ^
+      fees.hashCode ^
+      vtxoExpiryDays.hashCode ^
+      minSendableSats.hashCode ^
+      minOnchainSats.hashCode;
 
   @override
   bool operator ==(Object other) =>
@@ -325,7 +628,23 @@ class Info {
       other is Info &&
           runtimeType == other.runtimeType &&
           serverPk == other.serverPk &&
-          network == other.network;
+          network == other.network &&
+          version == other.version &&
+          forfeitPk == other.forfeitPk &&
+          forfeitAddress == other.forfeitAddress &&
+          sessionDurationSecs == other.sessionDurationSecs &&
+          vtxoExpirySecs == other.vtxoExpirySecs &&
+          unilateralExitDelaySecs == other.unilateralExitDelaySecs &&
+          boardingExitDelaySecs == other.boardingExitDelaySecs &&
+          dustSats == other.dustSats &&
+          vtxoMinAmountSats == other.vtxoMinAmountSats &&
+          vtxoMaxAmountSats == other.vtxoMaxAmountSats &&
+          utxoMinAmountSats == other.utxoMinAmountSats &&
+          utxoMaxAmountSats == other.utxoMaxAmountSats &&
+          fees == other.fees &&
+          vtxoExpiryDays == other.vtxoExpiryDays &&
+          minSendableSats == other.minSendableSats &&
+          minOnchainSats == other.minOnchainSats;
 }
 
 /// Result of paying a Lightning invoice
@@ -409,6 +728,44 @@ class PaymentReceived {
           amountSats == other.amountSats;
 }
 
+/// Fee schedule of the Ark server.
+///
+/// Intent fees are expressions evaluated by the server per input or output.
+class ServerFees {
+  final String txFeeRate;
+  final String offchainInput;
+  final String offchainOutput;
+  final String onchainInput;
+  final String onchainOutput;
+
+  const ServerFees({
+    required this.txFeeRate,
+    required this.offchainInput,
+    required this.offchainOutput,
+    required this.onchainInput,
+    required this.onchainOutput,
+  });
+
+  @override
+  int get hashCode =>
+      txFeeRate.hashCode ^
+      offchainInput.hashCode ^
+      offchainOutput.hashCode ^
+      onchainInput.hashCode ^
+      onchainOutput.hashCode;
+
+  @override
+  bool operator ==(Object other) =>
+      identical(this, other) ||
+      other is ServerFees &&
+          runtimeType == other.runtimeType &&
+          txFeeRate == other.txFeeRate &&
+          offchainInput == other.offchainInput &&
+          offchainOutput == other.offchainOutput &&
+          onchainInput == other.onchainInput &&
+          onchainOutput == other.onchainOutput;
+}
+
 @freezed
 sealed class Transaction with _$Transaction {
   const Transaction._();
@@ -417,17 +774,20 @@ sealed class Transaction with _$Transaction {
     required String txid,
     required BigInt amountSats,
     PlatformInt64? confirmedAt,
+    TxMetadata? metadata,
   }) = Transaction_Boarding;
   const factory Transaction.round({
     required String txid,
     required PlatformInt64 amountSats,
     required PlatformInt64 createdAt,
+    TxMetadata? metadata,
   }) = Transaction_Round;
   const factory Transaction.redeem({
     required String txid,
     required PlatformInt64 amountSats,
     required bool isSettled,
     required PlatformInt64 createdAt,
+    TxMetadata? metadata,
   }) = Transaction_Redeem;
 
   /// On-chain send (collaborative redeem) - funds sent from Ark to on-chain address
@@ -435,5 +795,36 @@ sealed class Transaction with _$Transaction {
     required String txid,
     required PlatformInt64 amountSats,
     PlatformInt64? confirmedAt,
+    TxMetadata? metadata,
   }) = Transaction_Offboard;
 }
+
+/// A page of the cached transaction history.
+class TxHistoryPage {
+  final List<Transaction> items;
+
+  /// Pass to `tx_history_page` to load the next page, `None` at the end
+  final String? nextCursor;
+
+  /// Unix timestamp of the last successful sync with the server
+  final PlatformInt64? lastSyncedAt;
+
+  const TxHistoryPage({
+    required this.items,
+    this.nextCursor,
+    this.lastSyncedAt,
+  });
+
+  @override
+  int get hashCode =>
+      items.hashCode ^ nextCursor.hashCode ^ lastSyncedAt.hashCode;
+
+  @override
+  bool operator ==(Object other) =>
+      identical(this, other) ||
+      other is TxHistoryPage &&
+          runtimeType == other.runtimeType &&
+          items == other.items &&
+          nextCursor == other.nextCursor &&
+          lastSyncedAt == other.lastSyncedAt;
+}
@@ -0,0 +1,35 @@
+// This file is automatically generated, so please do not edit it.
+// @generated by `flutter_rust_bridge`@ 2.11.1.
+
+// ignore_for_file: invalid_use_of_internal_member, unused_import, unnecessary_import
+
+import '../frb_generated.dart';
+import '../models/exchange_rates.dart';
+import 'package:flutter_rust_bridge/flutter_rust_bridge_for_generated.dart';
+
+// These functions are ignored because they are not marked as `pub`: `escape_csv`, `fmt_amount`, `load_price_history`, `price_at`, `round_fiat`, `to_csv`, `to_record`, `token_symbol`
+// These types are ignored because they are neither used by any `pub` functions nor (for structs and enums) marked `#[frb(unignore)]`: `ExportRecord`
+
+/// Export the complete wallet history for accounting.
+///
+/// BTC prices are taken from the historical price service at `server_url`
+/// (in USD) and converted to `currency` with the ECB reference rate of the
+/// same day.
+///
+/// # Arguments
+/// * `server_url` - Backend URL serving `/api/historical-prices`
+/// * `format` - CSV (Koinly universal format) or JSON
+/// * `currency` - Fiat currency for prices and values
+Future<String> exportWalletHistory(
+        {required String serverUrl,
+        required ExportFormat format,
+        required FiatCurrency currency}) =>
+    RustLib.instance.api.crateApiExportApiExportWalletHistory(
+        serverUrl: serverUrl, format: format, currency: currency);
+
+/// Output format of the export.
+enum ExportFormat {
+  csv,
+  json,
+  ;
+}
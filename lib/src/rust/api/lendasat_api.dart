@@ -3,14 +3,21 @@
 
 // ignore_for_file: invalid_use_of_internal_member, unused_import, unnecessary_import
 
+import '../ark/psbt_analysis.dart';
 import '../frb_generated.dart';
+import '../lendasat/collateral.dart';
+import '../lendasat/extension.dart';
+import '../lendasat/ltv.dart';
 import '../lendasat/models.dart';
+import '../lendasat/policy.dart';
+import '../lendasat/repayment.dart';
+import '../lendasat/watch.dart';
 import 'package:flutter_rust_bridge/flutter_rust_bridge_for_generated.dart';
 import 'package:freezed_annotation/freezed_annotation.dart' hide protected;
 part 'lendasat_api.freezed.dart';
 
-// These functions are ignored because they are not marked as `pub`: `get_ark_address`, `get_auth_headers`, `get_state_lock`
-// These types are ignored because they are neither used by any `pub` functions nor (for structs and enums) marked `#[frb(unignore)]`: `ContractSummary`, `LendasatState`, `OfferSummary`
+// These functions are ignored because they are not marked as `pub`: `assess_active_contracts`, `btc_price`, `ensure_approved_tx`, `ensure_collateral_funds`, `fetch_all_contracts`, `fetch_contracts`, `get_ark_address`, `get_auth_headers`, `get_state_lock`, `lendasat_data_dir`, `mark_installment_paid`, `now_unix`, `reauthenticate`, `recorded_funding`, `refusal`, `request_error`, `response_error`, `send_authenticated`, `sign_collateral_spend`, `status_error`, `token_expiring`, `unknown_route`, `wallet_view`
+// These types are ignored because they are neither used by any `pub` functions nor (for structs and enums) marked `#[frb(unignore)]`: `LendasatState`, `OfferSummary`
 // These function are ignored because they are on traits that is not defined in current crate (put an empty `#[frb]` on it to unignore): `clone`, `clone`, `clone`, `fmt`, `fmt`, `fmt`, `from`, `from`
 
 /// Reset the Lendasat client state.
@@ -78,6 +85,9 @@ Future<LoanOffer> lendasatGetOffer({required String offerId}) =>
     RustLib.instance.api.crateApiLendasatApiLendasatGetOffer(offerId: offerId);
 
 /// Get user's contracts.
+///
+/// If Lendasat cannot be reached, the last known response for the same filters
+/// is returned and flagged as stale (see `get_cache_status`).
 Future<PaginatedContractsResponse> lendasatGetContracts(
         {ContractFilters? filters}) =>
     RustLib.instance.api
@@ -88,6 +98,28 @@ Future<Contract> lendasatGetContract({required String contractId}) =>
     RustLib.instance.api
         .crateApiLendasatApiLendasatGetContract(contractId: contractId);
 
+/// Stream changes to the user's contracts.
+///
+/// Contracts are polled every `interval_secs`. On start every contract is
+/// emitted once; afterwards an update is emitted whenever a contract's
+/// status, installments or transactions change, or a new contract appears.
+/// Every poll ends with a `Polled` event. Runs until the stream is closed.
+Stream<ContractWatchEvent> lendasatSubscribeContracts(
+        {required BigInt intervalSecs}) =>
+    RustLib.instance.api.crateApiLendasatApiLendasatSubscribeContracts(
+        intervalSecs: intervalSecs);
+
+/// Check that a contract's collateral address matches its terms.
+///
+/// The address is rebuilt locally from the borrower and lender keys and the
+/// contract's timelocks. The result is advisory: the collateral tree is not
+/// confirmed against a real contract yet, so funding only refuses contracts
+/// locked to another borrower key.
+Future<CollateralCheck> lendasatVerifyCollateral(
+        {required String contractId}) =>
+    RustLib.instance.api
+        .crateApiLendasatApiLendasatVerifyCollateral(contractId: contractId);
+
 /// Create a new loan contract by taking an offer.
 ///
 /// IMPORTANT: This function uses the Ark identity public key as `borrower_pk`.
@@ -115,7 +147,96 @@ Future<void> lendasatCancelContract({required String contractId}) =>
     RustLib.instance.api
         .crateApiLendasatApiLendasatCancelContract(contractId: contractId);
 
-/// Mark an installment as paid.
+/// Quote extending a contract by `extension_days`.
+///
+/// Uses the contract's extension rate and maximum duration; no request is
+/// sent to the lender. The origination fee is estimated from the original
+/// contract's fee rate, the extended contract holds the final terms.
+Future<ExtensionQuote> lendasatQuoteExtension(
+        {required String contractId, required int extensionDays}) =>
+    RustLib.instance.api.crateApiLendasatApiLendasatQuoteExtension(
+        contractId: contractId, extensionDays: extensionDays);
+
+/// Request an extension of a contract by `extension_days`.
+///
+/// Returns the new contract, which references the extended one through
+/// `extends_contract`. Sent as `POST /api/contracts/{id}/extend`; the route is
+/// not documented in any Lendasat API description this repo has, so a server
+/// that does not know it is reported as not supporting extensions.
+Future<Contract> lendasatRequestExtension(
+        {required String contractId, required int extensionDays}) =>
+    RustLib.instance.api.crateApiLendasatApiLendasatRequestExtension(
+        contractId: contractId, extensionDays: extensionDays);
+
+/// Get the chain of extensions a contract belongs to, oldest first.
+Future<List<ContractSummary>> lendasatGetContractChain(
+        {required String contractId}) =>
+    RustLib.instance.api
+        .crateApiLendasatApiLendasatGetContractChain(contractId: contractId);
+
+/// Current LTV of a contract at the live BTC price.
+Future<LtvStatus> lendasatGetLtv({required String contractId}) =>
+    RustLib.instance.api
+        .crateApiLendasatApiLendasatGetLtv(contractId: contractId);
+
+/// Watch the LTV of all active contracts and stream alerts.
+///
+/// Every `interval_secs` the active contracts and the BTC price are fetched
+/// and each contract is assessed locally. An alert is emitted whenever a
+/// contract moves into a different band, and on start for contracts that
+/// are already past a threshold. Every check ends with a `Checked` event.
+/// Runs until the stream is closed.
+Stream<LtvMonitorEvent> lendasatMonitorLtv({required BigInt intervalSecs}) =>
+    RustLib.instance.api
+        .crateApiLendasatApiLendasatMonitorLtv(intervalSecs: intervalSecs);
+
+/// Deposit the collateral of an approved contract from the Ark wallet.
+///
+/// Sends the outstanding collateral plus origination fee to the contract
+/// address after checking that it is locked to the wallet's key (see
+/// `lendasat_verify_collateral`).
+/// Arkade collateral is paid from VTXOs, Bitcoin collateral through a
+/// collaborative redemption; confirmed boarding funds are settled into VTXOs
+/// first if the offchain balance falls short. The funding transaction is
+/// labelled with the contract, then the contract is polled until the server
+/// has seen the deposit.
+///
+/// Calling this again for a contract that already has a recorded funding
+/// transaction does not send again; it waits for that deposit instead. The
+/// deposit is recorded before sending, so an interrupted send is looked up
+/// in the wallet history before anything is sent again.
+Future<CollateralFunding> lendasatFundCollateral(
+        {required String contractId}) =>
+    RustLib.instance.api
+        .crateApiLendasatApiLendasatFundCollateral(contractId: contractId);
+
+/// Add collateral to an active contract to bring it back to `target_ltv`.
+///
+/// Computes the sats needed at the live BTC price and, unless `dry_run` is
+/// set, sends them from the Ark wallet to the contract address and
+/// reports the deposit to Lendasat. If the report fails after the send, it is
+/// queued for replay and `reported` is false. A dry run returns the same
+/// figures without sending anything.
+Future<CollateralTopUp> lendasatTopUpCollateral(
+        {required String contractId,
+        required double targetLtv,
+        required bool dryRun}) =>
+    RustLib.instance.api.crateApiLendasatApiLendasatTopUpCollateral(
+        contractId: contractId, targetLtv: targetLtv, dryRun: dryRun);
+
+/// Report collateral sent to a contract outside the initial deposit.
+///
+/// Sent as `POST /api/contracts/{id}/collateral-top-up`, which, like the
+/// extension route, is not documented in any Lendasat API description this
+/// repo has.
+Future<void> lendasatReportCollateralTopUp(
+        {required String contractId,
+        required String txid,
+        required BigInt amountSats}) =>
+    RustLib.instance.api.crateApiLendasatApiLendasatReportCollateralTopUp(
+        contractId: contractId, txid: txid, amountSats: amountSats);
+
+/// Mark an installment as paid. Queued for later if Lendasat cannot be reached.
 Future<void> lendasatMarkInstallmentPaid(
         {required String contractId,
         required String installmentId,
@@ -125,12 +246,58 @@ Future<void> lendasatMarkInstallmentPaid(
         installmentId: installmentId,
         paymentTxid: paymentTxid);
 
+/// Repay the next installment of a stablecoin loan from the Ark wallet.
+///
+/// Starts or resumes a repayment: quotes the next installment, swaps BTC to
+/// the loan asset at `loan_repayment_address` through LendaSwap, claims it via
+/// Gelato and marks the installment paid with the EVM claim transaction.
+/// Runs as many steps as possible and returns the reached state; while the
+/// swap is still processing, call again later to finish it. Requires the
+/// LendaSwap client to be initialized.
+Future<RepaymentState> lendasatRepayInstallment({required String contractId}) =>
+    RustLib.instance.api
+        .crateApiLendasatApiLendasatRepayInstallment(contractId: contractId);
+
+/// Get the stored repayment progress of a contract, if any.
+Future<RepaymentState?> lendasatGetRepayment({required String contractId}) =>
+    RustLib.instance.api
+        .crateApiLendasatApiLendasatGetRepayment(contractId: contractId);
+
+/// Fund the swap of an interrupted repayment again.
+///
+/// A repayment whose funding send was interrupted stops with an error if its
+/// payment cannot be found. Call this only after the user has checked the
+/// wallet history and no payment to the swap went out; the next
+/// `lendasat_repay_installment` then funds the swap.
+Future<RepaymentState> lendasatRetryRepaymentFunding(
+        {required String contractId}) =>
+    RustLib.instance.api.crateApiLendasatApiLendasatRetryRepaymentFunding(
+        contractId: contractId);
+
+/// Forget the repayment progress of a contract.
+///
+/// Fails while sats sent to the swap have not been paid out yet.
+Future<void> lendasatClearRepayment({required String contractId}) =>
+    RustLib.instance.api
+        .crateApiLendasatApiLendasatClearRepayment(contractId: contractId);
+
 /// Get the PSBT for claiming collateral (standard Bitcoin).
 Future<ClaimPsbtResponse> lendasatGetClaimPsbt(
         {required String contractId, required int feeRate}) =>
     RustLib.instance.api.crateApiLendasatApiLendasatGetClaimPsbt(
         contractId: contractId, feeRate: feeRate);
 
+/// Inspect a claim, recovery or settlement PSBT of a contract before signing it.
+///
+/// Like `analyze_psbt`, with the payout address stored when the contract was
+/// created counted as wallet-owned since that is where the collateral is
+/// returned to. The `borrower_btc_address` reported by the server is not
+/// trusted for this.
+Future<PsbtSummary> lendasatAnalyzePsbt(
+        {required String contractId, required String psbt}) =>
+    RustLib.instance.api.crateApiLendasatApiLendasatAnalyzePsbt(
+        contractId: contractId, psbt: psbt);
+
 /// Finalize a signed PSBT and extract the raw transaction.
 ///
 /// CRITICAL: This step was missing and caused broadcast failures!
@@ -139,6 +306,10 @@ Future<ClaimPsbtResponse> lendasatGetClaimPsbt(
 ///
 /// The LendaSat API's broadcast-claim and broadcast-recover endpoints expect
 /// the RAW TRANSACTION hex, not the signed PSBT hex.
+///
+/// Only PSBTs signed with `lendasat_sign_claim_psbt` or
+/// `lendasat_sign_recover_psbt`, which check them against the local policy,
+/// are finalized.
 Future<String> lendasatFinalizePsbt({required String signedPsbtHex}) =>
     RustLib.instance.api
         .crateApiLendasatApiLendasatFinalizePsbt(signedPsbtHex: signedPsbtHex);
@@ -160,6 +331,8 @@ Future<String> lendasatPsbtHexToBase64({required String hexPsbt}) =>
         .crateApiLendasatApiLendasatPsbtHexToBase64(hexPsbt: hexPsbt);
 
 /// Broadcast a signed claim transaction.
+///
+/// Refuses transactions that were not signed with `lendasat_sign_claim_psbt`.
 Future<String> lendasatBroadcastClaimTx(
         {required String contractId, required String signedTx}) =>
     RustLib.instance.api.crateApiLendasatApiLendasatBroadcastClaimTx(
@@ -204,7 +377,84 @@ Future<ClaimPsbtResponse> lendasatGetRecoverPsbt(
     RustLib.instance.api.crateApiLendasatApiLendasatGetRecoverPsbt(
         contractId: contractId, feeRate: feeRate);
 
+/// Sign a claim PSBT from `lendasat_get_claim_psbt` with the borrower key.
+///
+/// Refuses to sign unless the PSBT passes the local policy: it only spends the
+/// contract's collateral, returns the collateral to the wallet and pays a fee
+/// close to `fee_rate`. Returns the signed PSBT as hex, ready for
+/// `lendasat_finalize_psbt`.
+Future<String> lendasatSignClaimPsbt(
+        {required String contractId,
+        required String psbt,
+        required int feeRate}) =>
+    RustLib.instance.api.crateApiLendasatApiLendasatSignClaimPsbt(
+        contractId: contractId, psbt: psbt, feeRate: feeRate);
+
+/// Sign a recovery PSBT from `lendasat_get_recover_psbt` with the borrower key,
+/// under the same policy as `lendasat_sign_claim_psbt`.
+Future<String> lendasatSignRecoverPsbt(
+        {required String contractId,
+        required String psbt,
+        required int feeRate}) =>
+    RustLib.instance.api.crateApiLendasatApiLendasatSignRecoverPsbt(
+        contractId: contractId, psbt: psbt, feeRate: feeRate);
+
+/// Sign the PSBTs from `lendasat_get_claim_ark_psbt` with the Ark identity.
+///
+/// Refuses to sign unless the checkpoints spend the contract's collateral and
+/// the Ark transaction returns it to the wallet. Returns the signed PSBTs as
+/// hex, ready for `lendasat_broadcast_claim_ark_tx`.
+Future<SignedArkClaim> lendasatSignClaimArkPsbt(
+        {required String contractId,
+        required String arkPsbt,
+        required List<String> checkpointPsbts}) =>
+    RustLib.instance.api.crateApiLendasatApiLendasatSignClaimArkPsbt(
+        contractId: contractId,
+        arkPsbt: arkPsbt,
+        checkpointPsbts: checkpointPsbts);
+
+/// Sign the PSBTs from `lendasat_get_settle_ark_psbt` with the Ark identity.
+///
+/// Refuses to sign unless the intent only spends the contract's collateral and
+/// registers outputs of the wallet. Takes and returns base64, as used by the
+/// settle-ark endpoints.
+Future<SignedArkSettlement> lendasatSignSettleArkPsbt(
+        {required String contractId,
+        required String intentPsbt,
+        required List<String> forfeitPsbts}) =>
+    RustLib.instance.api.crateApiLendasatApiLendasatSignSettleArkPsbt(
+        contractId: contractId,
+        intentPsbt: intentPsbt,
+        forfeitPsbts: forfeitPsbts);
+
+/// Whether a payout address is stored for the contract, see
+/// `lendasat_confirm_payout_address`.
+Future<bool> lendasatHasPayoutAddress({required String contractId}) =>
+    RustLib.instance.api
+        .crateApiLendasatApiLendasatHasPayoutAddress(contractId: contractId);
+
+/// Accept the payout address recorded with a contract that was created
+/// before this wallet stored payout addresses.
+///
+/// The address comes from the server, so only call this after the user has
+/// confirmed it is theirs: claims paying to it are signed from then on. Keeps
+/// an address that is already stored. Returns the stored address.
+Future<String> lendasatConfirmPayoutAddress({required String contractId}) =>
+    RustLib.instance.api.crateApiLendasatApiLendasatConfirmPayoutAddress(
+        contractId: contractId);
+
+/// Set the limits checked before signing claim and recovery PSBTs.
+void lendasatConfigurePsbtPolicy({required PsbtPolicy policy}) =>
+    RustLib.instance.api
+        .crateApiLendasatApiLendasatConfigurePsbtPolicy(policy: policy);
+
+/// The current claim and recovery PSBT limits.
+PsbtPolicy lendasatGetPsbtPolicy() =>
+    RustLib.instance.api.crateApiLendasatApiLendasatGetPsbtPolicy();
+
 /// Broadcast a signed recovery transaction.
+///
+/// Refuses transactions that were not signed with `lendasat_sign_recover_psbt`.
 Future<String> lendasatBroadcastRecoverTx(
         {required String contractId, required String signedTx}) =>
     RustLib.instance.api.crateApiLendasatApiLendasatBroadcastRecoverTx(
@@ -220,12 +470,13 @@ Future<String> lendasatBroadcastRecoverTx(
 /// IMPORTANT: This key MUST be used as `borrower_pk` when creating LendaSat contracts
 /// because the collateral is locked to this key, not the Lendasat derivation path key.
 ///
-/// Derives the STABLE identity key at path m/83696968'/11811'/0/0 from the mnemonic.
+/// Uses the STABLE identity key at path m/83696968'/11811'/0/0 of the loaded wallet.
 /// This is equivalent to Arkade wallet's `SingleKey.fromHex(privateKey)` - always
 /// the same key, unlike vtxo.owner_pk() which changes when VTXOs are spent/created.
 Future<String> getArkIdentityPubkey() =>
     RustLib.instance.api.crateApiLendasatApiGetArkIdentityPubkey();
 
+/// Result of authentication attempt.
 @freezed
 sealed class AuthResult with _$AuthResult {
   const AuthResult._();
@@ -239,3 +490,236 @@ sealed class AuthResult with _$AuthResult {
     required String pubkey,
   }) = AuthResult_NeedsRegistration;
 }
+
+/// Result of funding a contract's collateral.
+class CollateralFunding {
+  final String txid;
+
+  /// The contract address the collateral was sent to
+  final String address;
+  final BigInt amountSats;
+
+  /// Contract status when polling stopped
+  final ContractStatus status;
+
+  /// Whether the server saw the deposit before polling timed out
+  final bool collateralSeen;
+
+  const CollateralFunding({
+    required this.txid,
+    required this.address,
+    required this.amountSats,
+    required this.status,
+    required this.collateralSeen,
+  });
+
+  @override
+  int get hashCode =>
+      txid.hashCode ^
+      address.hashCode ^
+      amountSats.hashCode ^
+      status.hashCode ^
+      collateralSeen.hashCode;
+
+  @override
+  bool operator ==(Object other) =>
+      identical(this, other) ||
+      other is CollateralFunding &&
+          runtimeType == other.runtimeType &&
+          txid == other.txid &&
+          address == other.address &&
+          amountSats == other.amountSats &&
+          status == other.status &&
+          collateralSeen == other.collateralSeen;
+}
+
+/// Result of adding collateral to a contract.
+class CollateralTopUp {
+  final String contractId;
+
+  /// Whether this was only a preview and nothing was sent
+  final bool dryRun;
+
+  /// BTC price in the loan currency the amounts were computed with
+  final double btcPrice;
+  final double currentLtv;
+  final double targetLtv;
+
+  /// LTV once the top-up is counted as collateral
+  final double resultingLtv;
+  final BigInt amountSats;
+  final BigInt feeSats;
+
+  /// The contract address the collateral is sent to
+  final String address;
+  final String? txid;
+
+  /// Whether Lendasat was notified; if not, the report is queued while
+  /// offline or can be retried with `lendasat_report_collateral_top_up`
+  final bool reported;
+
+  const CollateralTopUp({
+    required this.contractId,
+    required this.dryRun,
+    required this.btcPrice,
+    required this.currentLtv,
+    required this.targetLtv,
+    required this.resultingLtv,
+    required this.amountSats,
+    required this.feeSats,
+    required this.address,
+    this.txid,
+    required this.reported,
+  });
+
+  @override
+  int get hashCode =>
+      contractId.hashCode ^
+      dryRun.hashCode ^
+      btcPrice.hashCode ^
+      currentLtv.hashCode ^
+      targetLtv.hashCode ^
+      resultingLtv.hashCode ^
+      amountSats.hashCode ^
+      feeSats.hashCode ^
+      address.hashCode ^
+      txid.hashCode ^
+      reported.hashCode;
+
+  @override
+  bool operator ==(Object other) =>
+      identical(this, other) ||
+      other is CollateralTopUp &&
+          runtimeType == other.runtimeType &&
+          contractId == other.contractId &&
+          dryRun == other.dryRun &&
+          btcPrice == other.btcPrice &&
+          currentLtv == other.currentLtv &&
+          targetLtv == other.targetLtv &&
+          resultingLtv == other.resultingLtv &&
+          amountSats == other.amountSats &&
+          feeSats == other.feeSats &&
+          address == other.address &&
+          txid == other.txid &&
+          reported == other.reported;
+}
+
+/// Simplified contract info for list display.
+class ContractSummary {
+  final String id;
+  final ContractStatus status;
+  final double loanAmount;
+  final LoanAsset loanAsset;
+  final CollateralAsset collateralAsset;
+  final PlatformInt64 collateralSats;
+  final double interestRate;
+  final String expiry;
+  final double balanceOutstanding;
+  final String lenderName;
+
+  /// Contract this one extends, if any
+  final String? extendsContract;
+
+  /// Contract that replaced this one through an extension, if any
+  final String? extendedByContract;
+  final bool canExtend;
+  final String createdAt;
+
+  const ContractSummary({
+    required this.id,
+    required this.status,
+    required this.loanAmount,
+    required this.loanAsset,
+    required this.collateralAsset,
+    required this.collateralSats,
+    required this.interestRate,
+    required this.expiry,
+    required this.balanceOutstanding,
+    required this.lenderName,
+    this.extendsContract,
+    this.extendedByContract,
+    required this.canExtend,
+    required this.createdAt,
+  });
+
+  @override
+  int get hashCode =>
+      id.hashCode ^
+      status.hashCode ^
+      loanAmount.hashCode ^
+      loanAsset.hashCode ^
+      collateralAsset.hashCode ^
+      collateralSats.hashCode ^
+      interestRate.hashCode ^
+      expiry.hashCode ^
+      balanceOutstanding.hashCode ^
+      lenderName.hashCode ^
+      extendsContract.hashCode ^
+      extendedByContract.hashCode ^
+      canExtend.hashCode ^
+      createdAt.hashCode;
+
+  @override
+  bool operator ==(Object other) =>
+      identical(this, other) ||
+      other is ContractSummary &&
+          runtimeType == other.runtimeType &&
+          id == other.id &&
+          status == other.status &&
+          loanAmount == other.loanAmount &&
+          loanAsset == other.loanAsset &&
+          collateralAsset == other.collateralAsset &&
+          collateralSats == other.collateralSats &&
+          interestRate == other.interestRate &&
+          expiry == other.expiry &&
+          balanceOutstanding == other.balanceOutstanding &&
+          lenderName == other.lenderName &&
+          extendsContract == other.extendsContract &&
+          extendedByContract == other.extendedByContract &&
+          canExtend == other.canExtend &&
+          createdAt == other.createdAt;
+}
+
+/// Ark claim PSBTs signed under the collateral policy, as hex.
+class SignedArkClaim {
+  final String arkPsbt;
+  final List<String> checkpointPsbts;
+
+  const SignedArkClaim({
+    required this.arkPsbt,
+    required this.checkpointPsbts,
+  });
+
+  @override
+  int get hashCode => arkPsbt.hashCode ^ checkpointPsbts.hashCode;
+
+  @override
+  bool operator ==(Object other) =>
+      identical(this, other) ||
+      other is SignedArkClaim &&
+          runtimeType == other.runtimeType &&
+          arkPsbt == other.arkPsbt &&
+          checkpointPsbts == other.checkpointPsbts;
+}
+
+/// Ark settlement PSBTs signed under the collateral policy, as base64.
+class SignedArkSettlement {
+  final String intentPsbt;
+  final List<String> forfeitPsbts;
+
+  const SignedArkSettlement({
+    required this.intentPsbt,
+    required this.forfeitPsbts,
+  });
+
+  @override
+  int get hashCode => intentPsbt.hashCode ^ forfeitPsbts.hashCode;
+
+  @override
+  bool operator ==(Object other) =>
+      identical(this, other) ||
+      other is SignedArkSettlement &&
+          runtimeType == other.runtimeType &&
+          intentPsbt == other.intentPsbt &&
+          forfeitPsbts == other.forfeitPsbts;
+}
@@ -7,6 +7,7 @@ import '../frb_generated.dart';
 import '../lendaswap.dart';
 import 'package:flutter_rust_bridge/flutter_rust_bridge_for_generated.dart';
 
+// These functions are ignored because they are not marked as `pub`: `record_swap_tx`, `swap_error`
 // These function are ignored because they are on traits that is not defined in current crate (put an empty `#[frb]` on it to unignore): `clone`, `clone`, `clone`, `clone`, `clone`, `fmt`, `fmt`, `fmt`, `fmt`, `fmt`
 
 /// Initialize the LendaSwap client.
@@ -124,15 +125,20 @@ Future<SwapInfo> lendaswapGetSwap({required String swapId}) =>
     RustLib.instance.api.crateApiLendaswapApiLendaswapGetSwap(swapId: swapId);
 
 /// List all swaps.
+///
+/// If LendaSwap cannot be reached, the last known list is returned and flagged
+/// as stale (see `get_cache_status`).
 Future<List<SwapInfo>> lendaswapListSwaps() =>
     RustLib.instance.api.crateApiLendaswapApiLendaswapListSwaps();
 
-/// Claim a swap via Gelato (gasless).
+/// Claim a swap via Gelato (gasless). Queued for later if the network is
+/// unreachable.
 Future<void> lendaswapClaimGelato({required String swapId}) =>
     RustLib.instance.api
         .crateApiLendaswapApiLendaswapClaimGelato(swapId: swapId);
 
-/// Claim VHTLC for an EVM to BTC swap.
+/// Claim VHTLC for an EVM to BTC swap. Queued for later if the network is
+/// unreachable.
 ///
 /// Returns the transaction ID.
 Future<String> lendaswapClaimVhtlc({required String swapId}) =>
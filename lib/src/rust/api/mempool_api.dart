@@ -31,6 +31,10 @@ Future<List<BitcoinTransaction>> getBlockTransactions(
 Future<RecommendedFees> getRecommendedFees() =>
     RustLib.instance.api.crateApiMempoolApiGetRecommendedFees();
 
+/// Get the current BTC price
+Future<Conversions> getPrices() =>
+    RustLib.instance.api.crateApiMempoolApiGetPrices();
+
 /// Get mining hashrate data for a specific time period
 /// period: "1D", "1W", "1M", "1Y", "3Y"
 Future<HashrateData> getHashrateData({required String period}) =>
@@ -0,0 +1,79 @@
+// This file is automatically generated, so please do not edit it.
+// @generated by `flutter_rust_bridge`@ 2.11.1.
+
+// ignore_for_file: invalid_use_of_internal_member, unused_import, unnecessary_import
+
+import '../frb_generated.dart';
+import '../offline.dart';
+import 'package:flutter_rust_bridge/flutter_rust_bridge_for_generated.dart';
+
+// These functions are ignored because they are not marked as `pub`: `already_reported`, `now_unix`, `resume_queued_actions`, `run_action`, `run_queue`
+
+/// Freshness of the cached balance, history, swaps and contracts.
+Future<List<CacheStatus>> getCacheStatus() =>
+    RustLib.instance.api.crateApiOfflineApiGetCacheStatus();
+
+/// Queue an action to run once connectivity returns.
+Future<void> queueAction({required QueuedAction action}) =>
+    RustLib.instance.api.crateApiOfflineApiQueueAction(action: action);
+
+/// All queued actions, oldest first.
+Future<List<QueuedOperation>> listQueuedActions() =>
+    RustLib.instance.api.crateApiOfflineApiListQueuedActions();
+
+/// Drop a queued action without running it.
+Future<void> removeQueuedAction({required PlatformInt64 id}) =>
+    RustLib.instance.api.crateApiOfflineApiRemoveQueuedAction(id: id);
+
+/// Run all queued actions that are not parked, in order.
+///
+/// Stops at the first action that fails because the network is still
+/// unreachable. Actions the backend rejects (e.g. an expired swap) are parked
+/// right away, others after failing [`MAX_REPLAY_ATTEMPTS`] times. Parked
+/// actions keep their error so the user can retry or remove them.
+Future<QueueRunResult> processQueuedActions() =>
+    RustLib.instance.api.crateApiOfflineApiProcessQueuedActions();
+
+/// Replay a parked action again, then run the queue.
+Future<QueueRunResult> retryQueuedAction({required PlatformInt64 id}) =>
+    RustLib.instance.api.crateApiOfflineApiRetryQueuedAction(id: id);
+
+/// Outcome of replaying the queue.
+class QueueRunResult {
+  /// Actions that succeeded and were removed from the queue
+  final int completed;
+
+  /// Actions that failed and stay queued for another attempt
+  final int failed;
+
+  /// Actions that were rejected or failed too often and are no longer
+  /// replayed automatically
+  final int parked;
+
+  /// Actions still replayed automatically afterwards, including failed ones
+  final int remaining;
+
+  const QueueRunResult({
+    required this.completed,
+    required this.failed,
+    required this.parked,
+    required this.remaining,
+  });
+
+  @override
+  int get hashCode =>
+      completed.hashCode ^
+      failed.hashCode ^
+      parked.hashCode ^
+      remaining.hashCode;
+
+  @override
+  bool operator ==(Object other) =>
+      identical(this, other) ||
+      other is QueueRunResult &&
+          runtimeType == other.runtimeType &&
+          completed == other.completed &&
+          failed == other.failed &&
+          parked == other.parked &&
+          remaining == other.remaining;
+}
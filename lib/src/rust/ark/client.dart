@@ -0,0 +1,75 @@
+// This file is automatically generated, so please do not edit it.
+// @generated by `flutter_rust_bridge`@ 2.11.1.
+
+// ignore_for_file: invalid_use_of_internal_member, unused_import, unnecessary_import
+
+import '../frb_generated.dart';
+import 'package:flutter_rust_bridge/flutter_rust_bridge_for_generated.dart';
+import 'package:freezed_annotation/freezed_annotation.dart' hide protected;
+part 'client.freezed.dart';
+
+/// Progress of a wallet rescan.
+@freezed
+sealed class RescanProgress with _$RescanProgress {
+  const RescanProgress._();
+
+  /// Key discovery started with the given gap limit
+  const factory RescanProgress.started({
+    required int gapLimit,
+  }) = RescanProgress_Started;
+
+  /// Key discovery finished; `new_keys` were not known before the rescan
+  const factory RescanProgress.keysDiscovered({
+    required int totalKeys,
+    required List<String> newKeys,
+  }) = RescanProgress_KeysDiscovered;
+
+  /// Checking a boarding address for on-chain funds
+  const factory RescanProgress.scanningBoardingAddress({
+    required int index,
+    required int total,
+    required String address,
+  }) = RescanProgress_ScanningBoardingAddress;
+  const factory RescanProgress.completed(
+    RescanResult field0,
+  ) = RescanProgress_Completed;
+
+  /// The rescan failed and can be retried
+  const factory RescanProgress.failed({
+    required String error,
+  }) = RescanProgress_Failed;
+}
+
+/// Outcome of a wallet rescan.
+class RescanResult {
+  final int totalKeys;
+
+  /// Public keys (x-only, hex) found by this rescan
+  final List<String> newKeys;
+  final int boardingUtxos;
+  final BigInt boardingSats;
+
+  const RescanResult({
+    required this.totalKeys,
+    required this.newKeys,
+    required this.boardingUtxos,
+    required this.boardingSats,
+  });
+
+  @override
+  int get hashCode =>
+      totalKeys.hashCode ^
+      newKeys.hashCode ^
+      boardingUtxos.hashCode ^
+      boardingSats.hashCode;
+
+  @override
+  bool operator ==(Object other) =>
+      identical(this, other) ||
+      other is RescanResult &&
+          runtimeType == other.runtimeType &&
+          totalKeys == other.totalKeys &&
+          newKeys == other.newKeys &&
+          boardingUtxos == other.boardingUtxos &&
+          boardingSats == other.boardingSats;
+}
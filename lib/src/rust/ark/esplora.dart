@@ -0,0 +1,57 @@
+// This file is automatically generated, so please do not edit it.
+// @generated by `flutter_rust_bridge`@ 2.11.1.
+
+// ignore_for_file: invalid_use_of_internal_member, unused_import, unnecessary_import
+
+import '../frb_generated.dart';
+import 'package:flutter_rust_bridge/flutter_rust_bridge_for_generated.dart';
+
+/// Health of a single Esplora endpoint.
+class EsploraEndpointHealth {
+  final String url;
+
+  /// Smoothed request latency in milliseconds, if measured
+  final double? latencyMs;
+  final int consecutiveFailures;
+  final String? lastError;
+  final int? tipHeight;
+
+  /// Tip is more than a few blocks behind the other endpoints
+  final bool isLagging;
+
+  /// Endpoint is currently skipped in favour of healthy ones
+  final bool inCooldown;
+
+  const EsploraEndpointHealth({
+    required this.url,
+    this.latencyMs,
+    required this.consecutiveFailures,
+    this.lastError,
+    this.tipHeight,
+    required this.isLagging,
+    required this.inCooldown,
+  });
+
+  @override
+  int get hashCode =>
+      url.hashCode ^
+      latencyMs.hashCode ^
+      consecutiveFailures.hashCode ^
+      lastError.hashCode ^
+      tipHeight.hashCode ^
+      isLagging.hashCode ^
+      inCooldown.hashCode;
+
+  @override
+  bool operator ==(Object other) =>
+      identical(this, other) ||
+      other is EsploraEndpointHealth &&
+          runtimeType == other.runtimeType &&
+          url == other.url &&
+          latencyMs == other.latencyMs &&
+          consecutiveFailures == other.consecutiveFailures &&
+          lastError == other.lastError &&
+          tipHeight == other.tipHeight &&
+          isLagging == other.isLagging &&
+          inCooldown == other.inCooldown;
+}
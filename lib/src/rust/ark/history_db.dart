@@ -0,0 +1,87 @@
+// This file is automatically generated, so please do not edit it.
+// @generated by `flutter_rust_bridge`@ 2.11.1.
+
+// ignore_for_file: invalid_use_of_internal_member, unused_import, unnecessary_import
+
+import '../frb_generated.dart';
+import 'package:flutter_rust_bridge/flutter_rust_bridge_for_generated.dart';
+
+/// Result of syncing the cache with the server.
+class HistorySyncStats {
+  final int added;
+  final int updated;
+
+  /// Unconfirmed entries the server no longer reports (dropped or replaced)
+  final int removed;
+  final int total;
+
+  const HistorySyncStats({
+    required this.added,
+    required this.updated,
+    required this.removed,
+    required this.total,
+  });
+
+  @override
+  int get hashCode =>
+      added.hashCode ^ updated.hashCode ^ removed.hashCode ^ total.hashCode;
+
+  @override
+  bool operator ==(Object other) =>
+      identical(this, other) ||
+      other is HistorySyncStats &&
+          runtimeType == other.runtimeType &&
+          added == other.added &&
+          updated == other.updated &&
+          removed == other.removed &&
+          total == other.total;
+}
+
+/// Direction of funds relative to the wallet.
+enum TxDirection {
+  incoming,
+  outgoing,
+  ;
+}
+
+/// Filter for paging through the cached history. Unset fields match everything.
+class TxHistoryFilter {
+  final TxDirection? direction;
+  final List<TxKind>? kinds;
+
+  /// Only transactions at or after this unix timestamp
+  final PlatformInt64? from;
+
+  /// Only transactions before this unix timestamp
+  final PlatformInt64? to;
+
+  const TxHistoryFilter({
+    this.direction,
+    this.kinds,
+    this.from,
+    this.to,
+  });
+
+  @override
+  int get hashCode =>
+      direction.hashCode ^ kinds.hashCode ^ from.hashCode ^ to.hashCode;
+
+  @override
+  bool operator ==(Object other) =>
+      identical(this, other) ||
+      other is TxHistoryFilter &&
+          runtimeType == other.runtimeType &&
+          direction == other.direction &&
+          kinds == other.kinds &&
+          from == other.from &&
+          to == other.to;
+}
+
+/// Type of a cached transaction.
+enum TxKind {
+  boarding,
+  round,
+  redeem,
+  offboard,
+  ;
+}
@@ -0,0 +1,32 @@
+// This file is automatically generated, so please do not edit it.
+// @generated by `flutter_rust_bridge`@ 2.11.1.
+
+// ignore_for_file: invalid_use_of_internal_member, unused_import, unnecessary_import
+
+import '../frb_generated.dart';
+import 'package:flutter_rust_bridge/flutter_rust_bridge_for_generated.dart';
+
+/// A Schnorr signature made with the Ark identity key.
+class IdentitySignature {
+  /// X-only public key of the Ark identity, hex encoded
+  final String pubkey;
+
+  /// BIP-340 signature over SHA256(message), hex encoded
+  final String signature;
+
+  const IdentitySignature({
+    required this.pubkey,
+    required this.signature,
+  });
+
+  @override
+  int get hashCode => pubkey.hashCode ^ signature.hashCode;
+
+  @override
+  bool operator ==(Object other) =>
+      identical(this, other) ||
+      other is IdentitySignature &&
+          runtimeType == other.runtimeType &&
+          pubkey == other.pubkey &&
+          signature == other.signature;
+}
@@ -0,0 +1,73 @@
+// This file is automatically generated, so please do not edit it.
+// @generated by `flutter_rust_bridge`@ 2.11.1.
+
+// ignore_for_file: invalid_use_of_internal_member, unused_import, unnecessary_import
+
+import '../frb_generated.dart';
+import 'package:flutter_rust_bridge/flutter_rust_bridge_for_generated.dart';
+
+/// Persisted progress of a server migration.
+class MigrationState {
+  final String oldServer;
+  final String newServer;
+  final MigrationStep step;
+
+  /// Boarding address on the new server receiving the funds
+  final String? boardingAddress;
+  final String? offboardTxid;
+  final BigInt? amountSats;
+  final PlatformInt64 startedAt;
+  final PlatformInt64 updatedAt;
+
+  const MigrationState({
+    required this.oldServer,
+    required this.newServer,
+    required this.step,
+    this.boardingAddress,
+    this.offboardTxid,
+    this.amountSats,
+    required this.startedAt,
+    required this.updatedAt,
+  });
+
+  @override
+  int get hashCode =>
+      oldServer.hashCode ^
+      newServer.hashCode ^
+      step.hashCode ^
+      boardingAddress.hashCode ^
+      offboardTxid.hashCode ^
+      amountSats.hashCode ^
+      startedAt.hashCode ^
+      updatedAt.hashCode;
+
+  @override
+  bool operator ==(Object other) =>
+      identical(this, other) ||
+      other is MigrationState &&
+          runtimeType == other.runtimeType &&
+          oldServer == other.oldServer &&
+          newServer == other.newServer &&
+          step == other.step &&
+          boardingAddress == other.boardingAddress &&
+          offboardTxid == other.offboardTxid &&
+          amountSats == other.amountSats &&
+          startedAt == other.startedAt &&
+          updatedAt == other.updatedAt;
+}
+
+/// Step a migration has reached.
+enum MigrationStep {
+  /// Connected to the new server, nothing moved yet
+  started,
+
+  /// Offboard to the new server's boarding address was requested
+  offboarding,
+
+  /// Offboard transaction broadcast, waiting for confirmation
+  offboarded,
+
+  /// Funds boarded on the new server; the wallet now uses it
+  completed,
+  ;
+}
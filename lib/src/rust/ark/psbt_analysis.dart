@@ -0,0 +1,225 @@
+// This file is automatically generated, so please do not edit it.
+// @generated by `flutter_rust_bridge`@ 2.11.1.
+
+// ignore_for_file: invalid_use_of_internal_member, unused_import, unnecessary_import
+
+import '../frb_generated.dart';
+import 'package:flutter_rust_bridge/flutter_rust_bridge_for_generated.dart';
+import 'package:freezed_annotation/freezed_annotation.dart' hide protected;
+part 'psbt_analysis.freezed.dart';
+
+enum OutputOwner {
+  /// Pays to the wallet
+  wallet,
+
+  /// Pays to a third party
+  external_,
+
+  /// Zero-value P2A anchor used to bump Ark transactions
+  anchor,
+
+  /// OP_RETURN data carrier
+  data,
+  ;
+}
+
+class PsbtInputSummary {
+  /// Spent outpoint as `txid:vout`
+  final String outpoint;
+  final BigInt? amountSats;
+  final String? address;
+
+  /// The spent output pays to a wallet script
+  final bool isMine;
+
+  /// A wallet key can sign this input
+  final bool walletCanSign;
+  final int sequence;
+  final String? sighash;
+
+  const PsbtInputSummary({
+    required this.outpoint,
+    this.amountSats,
+    this.address,
+    required this.isMine,
+    required this.walletCanSign,
+    required this.sequence,
+    this.sighash,
+  });
+
+  @override
+  int get hashCode =>
+      outpoint.hashCode ^
+      amountSats.hashCode ^
+      address.hashCode ^
+      isMine.hashCode ^
+      walletCanSign.hashCode ^
+      sequence.hashCode ^
+      sighash.hashCode;
+
+  @override
+  bool operator ==(Object other) =>
+      identical(this, other) ||
+      other is PsbtInputSummary &&
+          runtimeType == other.runtimeType &&
+          outpoint == other.outpoint &&
+          amountSats == other.amountSats &&
+          address == other.address &&
+          isMine == other.isMine &&
+          walletCanSign == other.walletCanSign &&
+          sequence == other.sequence &&
+          sighash == other.sighash;
+}
+
+class PsbtOutputSummary {
+  final int index;
+  final BigInt amountSats;
+  final String? address;
+  final String scriptPubkey;
+  final OutputOwner owner;
+
+  const PsbtOutputSummary({
+    required this.index,
+    required this.amountSats,
+    this.address,
+    required this.scriptPubkey,
+    required this.owner,
+  });
+
+  @override
+  int get hashCode =>
+      index.hashCode ^
+      amountSats.hashCode ^
+      address.hashCode ^
+      scriptPubkey.hashCode ^
+      owner.hashCode;
+
+  @override
+  bool operator ==(Object other) =>
+      identical(this, other) ||
+      other is PsbtOutputSummary &&
+          runtimeType == other.runtimeType &&
+          index == other.index &&
+          amountSats == other.amountSats &&
+          address == other.address &&
+          scriptPubkey == other.scriptPubkey &&
+          owner == other.owner;
+}
+
+/// Decoded view of a PSBT.
+class PsbtSummary {
+  final String txid;
+  final int version;
+  final int lockTime;
+  final List<PsbtInputSummary> inputs;
+  final List<PsbtOutputSummary> outputs;
+
+  /// Sum of the spent amounts, `None` if an input amount is unknown or the
+  /// sum overflows
+  final BigInt? totalInputSats;
+  final BigInt totalOutputSats;
+
+  /// Amount paid to wallet-owned outputs
+  final BigInt toWalletSats;
+
+  /// Amount paid to third-party outputs
+  final BigInt toExternalSats;
+  final BigInt? feeSats;
+
+  /// Estimated virtual size once all inputs are signed
+  final BigInt estimatedVsize;
+  final double? feeRateSatVb;
+  final List<PsbtWarning> warnings;
+
+  const PsbtSummary({
+    required this.txid,
+    required this.version,
+    required this.lockTime,
+    required this.inputs,
+    required this.outputs,
+    this.totalInputSats,
+    required this.totalOutputSats,
+    required this.toWalletSats,
+    required this.toExternalSats,
+    this.feeSats,
+    required this.estimatedVsize,
+    this.feeRateSatVb,
+    required this.warnings,
+  });
+
+  @override
+  int get hashCode =>
+      txid.hashCode ^
+      version.hashCode ^
+      lockTime.hashCode ^
+      inputs.hashCode ^
+      outputs.hashCode ^
+      totalInputSats.hashCode ^
+      totalOutputSats.hashCode ^
+      toWalletSats.hashCode ^
+      toExternalSats.hashCode ^
+      feeSats.hashCode ^
+      estimatedVsize.hashCode ^
+      feeRateSatVb.hashCode ^
+      warnings.hashCode;
+
+  @override
+  bool operator ==(Object other) =>
+      identical(this, other) ||
+      other is PsbtSummary &&
+          runtimeType == other.runtimeType &&
+          txid == other.txid &&
+          version == other.version &&
+          lockTime == other.lockTime &&
+          inputs == other.inputs &&
+          outputs == other.outputs &&
+          totalInputSats == other.totalInputSats &&
+          totalOutputSats == other.totalOutputSats &&
+          toWalletSats == other.toWalletSats &&
+          toExternalSats == other.toExternalSats &&
+          feeSats == other.feeSats &&
+          estimatedVsize == other.estimatedVsize &&
+          feeRateSatVb == other.feeRateSatVb &&
+          warnings == other.warnings;
+}
+
+/// Something about a PSBT the user should know before signing.
+@freezed
+sealed class PsbtWarning with _$PsbtWarning {
+  const PsbtWarning._();
+
+  /// The spent amount of an input is unknown, so the fee cannot be computed
+  const factory PsbtWarning.missingInputAmount({
+    required int input,
+  }) = PsbtWarning_MissingInputAmount;
+
+  /// The outputs spend more than the inputs provide
+  const factory PsbtWarning.outputsExceedInputs({
+    required BigInt inputSats,
+    required BigInt outputSats,
+  }) = PsbtWarning_OutputsExceedInputs;
+
+  /// The fee is unusually high in absolute or relative terms
+  const factory PsbtWarning.highFee({
+    required BigInt feeSats,
+    required double feeRateSatVb,
+  }) = PsbtWarning_HighFee;
+
+  /// No output pays back to the wallet
+  const factory PsbtWarning.noWalletOutput() = PsbtWarning_NoWalletOutput;
+
+  /// An output is below the dust limit of its script type
+  const factory PsbtWarning.dustOutput({
+    required int output,
+    required BigInt amountSats,
+  }) = PsbtWarning_DustOutput;
+
+  /// An input commits to less than the whole transaction
+  const factory PsbtWarning.unusualSighash({
+    required int input,
+    required String sighash,
+  }) = PsbtWarning_UnusualSighash;
+
+  /// No input can be signed by the wallet
+  const factory PsbtWarning.nothingToSign() = PsbtWarning_NothingToSign;
+}
@@ -0,0 +1,28 @@
+// This file is automatically generated, so please do not edit it.
+// @generated by `flutter_rust_bridge`@ 2.11.1.
+
+// ignore_for_file: invalid_use_of_internal_member, unused_import, unnecessary_import
+
+import '../frb_generated.dart';
+import 'package:flutter_rust_bridge/flutter_rust_bridge_for_generated.dart';
+import 'package:freezed_annotation/freezed_annotation.dart' hide protected;
+part 'signer.freezed.dart';
+
+/// The wallet key a PSBT is signed with.
+@freezed
+sealed class SigningKey with _$SigningKey {
+  const SigningKey._();
+
+  /// The stable Ark identity at m/83696968'/11811'/0/0
+  const factory SigningKey.identity() = SigningKey_Identity;
+
+  /// The HD key at a full derivation path, e.g. "m/83696968'/11811'/0/3"
+  const factory SigningKey.path({
+    required String path,
+  }) = SigningKey_Path;
+
+  /// The wallet key (HD or boarding) with this public key, as x-only or compressed hex
+  const factory SigningKey.pubkey({
+    required String pubkey,
+  }) = SigningKey_Pubkey;
+}
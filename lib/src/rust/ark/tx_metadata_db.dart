@@ -0,0 +1,89 @@
+// This file is automatically generated, so please do not edit it.
+// @generated by `flutter_rust_bridge`@ 2.11.1.
+
+// ignore_for_file: invalid_use_of_internal_member, unused_import, unnecessary_import
+
+import '../frb_generated.dart';
+import 'package:flutter_rust_bridge/flutter_rust_bridge_for_generated.dart';
+
+/// Where the funds of a transaction were sent.
+enum DestinationType {
+  /// Off-chain send to another Ark address
+  ark,
+
+  /// Collaborative redemption to a Bitcoin address
+  onchain,
+
+  /// Lightning payment via Boltz submarine swap
+  lightning,
+
+  /// Funding, claim or refund of a LendaSwap swap
+  lendaswapSwap,
+
+  /// Collateral deposit for a Lendasat loan
+  lendasatCollateral,
+
+  /// Repayment of a Lendasat loan
+  lendasatRepayment,
+  ;
+}
+
+/// User-facing context for a single transaction.
+class TxMetadata {
+  final String txid;
+  final DestinationType? destinationType;
+
+  /// Address or node the funds went to
+  final String? counterparty;
+
+  /// Id of the related Boltz swap, LendaSwap swap or Lendasat contract
+  final String? reference;
+
+  /// Free-form note entered by the user
+  final String? note;
+
+  /// User-chosen category (e.g. "groceries", "savings")
+  final String? category;
+
+  /// Unix timestamp of when the metadata was first recorded
+  final PlatformInt64 createdAt;
+
+  /// Unix timestamp of the last change
+  final PlatformInt64 updatedAt;
+
+  const TxMetadata({
+    required this.txid,
+    this.destinationType,
+    this.counterparty,
+    this.reference,
+    this.note,
+    this.category,
+    required this.createdAt,
+    required this.updatedAt,
+  });
+
+  @override
+  int get hashCode =>
+      txid.hashCode ^
+      destinationType.hashCode ^
+      counterparty.hashCode ^
+      reference.hashCode ^
+      note.hashCode ^
+      category.hashCode ^
+      createdAt.hashCode ^
+      updatedAt.hashCode;
+
+  @override
+  bool operator ==(Object other) =>
+      identical(this, other) ||
+      other is TxMetadata &&
+          runtimeType == other.runtimeType &&
+          txid == other.txid &&
+          destinationType == other.destinationType &&
+          counterparty == other.counterparty &&
+          reference == other.reference &&
+          note == other.note &&
+          category == other.category &&
+          createdAt == other.createdAt &&
+          updatedAt == other.updatedAt;
+}
@@ -0,0 +1,55 @@
+// This file is automatically generated, so please do not edit it.
+// @generated by `flutter_rust_bridge`@ 2.11.1.
+
+// ignore_for_file: invalid_use_of_internal_member, unused_import, unnecessary_import
+
+import 'frb_generated.dart';
+import 'package:flutter_rust_bridge/flutter_rust_bridge_for_generated.dart';
+import 'package:freezed_annotation/freezed_annotation.dart' hide protected;
+part 'error.freezed.dart';
+
+/// Error returned by the public API.
+@freezed
+sealed class ApiError with _$ApiError implements FrbException {
+  const ApiError._();
+
+  /// A client or store was used before it was set up, e.g. "Ark client"
+  const factory ApiError.notInitialized({
+    required String component,
+  }) = ApiError_NotInitialized;
+
+  /// The wallet cannot cover the amount including fees
+  const factory ApiError.insufficientFunds({
+    required BigInt neededSats,
+    required BigInt availableSats,
+  }) = ApiError_InsufficientFunds;
+  const factory ApiError.invalidAddress({
+    required String address,
+    required String reason,
+  }) = ApiError_InvalidAddress;
+
+  /// An address or server belongs to a different Bitcoin network
+  const factory ApiError.networkMismatch({
+    required String expected,
+    required String actual,
+  }) = ApiError_NetworkMismatch;
+
+  /// A backend could not be reached or answered with a server error
+  const factory ApiError.serverUnavailable({
+    required String message,
+  }) = ApiError_ServerUnavailable;
+  const factory ApiError.timeout() = ApiError_Timeout;
+
+  /// The swap can no longer be completed
+  const factory ApiError.swapExpired({
+    required String swapId,
+  }) = ApiError_SwapExpired;
+
+  /// Lendasat requires a (new) login
+  const factory ApiError.authRequired() = ApiError_AuthRequired;
+
+  /// Any other failure, with a human readable description
+  const factory ApiError.other({
+    required String message,
+  }) = ApiError_Other;
+}
@@ -0,0 +1,80 @@
+// This file is automatically generated, so please do not edit it.
+// @generated by `flutter_rust_bridge`@ 2.11.1.
+
+// ignore_for_file: invalid_use_of_internal_member, unused_import, unnecessary_import
+
+import 'frb_generated.dart';
+import 'package:flutter_rust_bridge/flutter_rust_bridge_for_generated.dart';
+
+/// Retry, timeout and circuit breaker settings for outbound network calls.
+class HttpConfig {
+  /// Retries after the first attempt, 0 disables retrying
+  final int maxRetries;
+
+  /// Delay before the first retry, doubled on every further attempt
+  final BigInt baseDelayMs;
+
+  /// Upper bound for a single backoff delay
+  final BigInt maxDelayMs;
+
+  /// Consecutive failures after which a service is considered down, 0 disables the breaker
+  final int circuitFailureThreshold;
+
+  /// How long calls to a down service fail fast before a trial call is let through
+  final BigInt circuitOpenSecs;
+  final BigInt mempoolTimeoutSecs;
+  final BigInt moonpayTimeoutSecs;
+  final BigInt pricesTimeoutSecs;
+  final BigInt lendasatTimeoutSecs;
+  final BigInt arkTimeoutSecs;
+  final BigInt boltzTimeoutSecs;
+  final BigInt fearGreedTimeoutSecs;
+
+  const HttpConfig({
+    required this.maxRetries,
+    required this.baseDelayMs,
+    required this.maxDelayMs,
+    required this.circuitFailureThreshold,
+    required this.circuitOpenSecs,
+    required this.mempoolTimeoutSecs,
+    required this.moonpayTimeoutSecs,
+    required this.pricesTimeoutSecs,
+    required this.lendasatTimeoutSecs,
+    required this.arkTimeoutSecs,
+    required this.boltzTimeoutSecs,
+    required this.fearGreedTimeoutSecs,
+  });
+
+  @override
+  int get hashCode =>
+      maxRetries.hashCode ^
+      baseDelayMs.hashCode ^
+      maxDelayMs.hashCode ^
+      circuitFailureThreshold.hashCode ^
+      circuitOpenSecs.hashCode ^
+      mempoolTimeoutSecs.hashCode ^
+      moonpayTimeoutSecs.hashCode ^
+      pricesTimeoutSecs.hashCode ^
+      lendasatTimeoutSecs.hashCode ^
+      arkTimeoutSecs.hashCode ^
+      boltzTimeoutSecs.hashCode ^
+      fearGreedTimeoutSecs.hashCode;
+
+  @override
+  bool operator ==(Object other) =>
+      identical(this, other) ||
+      other is HttpConfig &&
+          runtimeType == other.runtimeType &&
+          maxRetries == other.maxRetries &&
+          baseDelayMs == other.baseDelayMs &&
+          maxDelayMs == other.maxDelayMs &&
+          circuitFailureThreshold == other.circuitFailureThreshold &&
+          circuitOpenSecs == other.circuitOpenSecs &&
+          mempoolTimeoutSecs == other.mempoolTimeoutSecs &&
+          moonpayTimeoutSecs == other.moonpayTimeoutSecs &&
+          pricesTimeoutSecs == other.pricesTimeoutSecs &&
+          lendasatTimeoutSecs == other.lendasatTimeoutSecs &&
+          arkTimeoutSecs == other.arkTimeoutSecs &&
+          boltzTimeoutSecs == other.boltzTimeoutSecs &&
+          fearGreedTimeoutSecs == other.fearGreedTimeoutSecs;
+}
@@ -0,0 +1,45 @@
+// This file is automatically generated, so please do not edit it.
+// @generated by `flutter_rust_bridge`@ 2.11.1.
+
+// ignore_for_file: invalid_use_of_internal_member, unused_import, unnecessary_import
+
+import '../frb_generated.dart';
+import 'package:flutter_rust_bridge/flutter_rust_bridge_for_generated.dart';
+
+/// Outcome of checking a contract's collateral address.
+class CollateralCheck {
+  final String contractId;
+
+  /// Whether the contract address matches the rebuilt one
+  final bool verified;
+
+  /// The address rebuilt from the contract terms, if they could be read
+  final String? expectedAddress;
+
+  /// Why the check failed
+  final String? reason;
+
+  const CollateralCheck({
+    required this.contractId,
+    required this.verified,
+    this.expectedAddress,
+    this.reason,
+  });
+
+  @override
+  int get hashCode =>
+      contractId.hashCode ^
+      verified.hashCode ^
+      expectedAddress.hashCode ^
+      reason.hashCode;
+
+  @override
+  bool operator ==(Object other) =>
+      identical(this, other) ||
+      other is CollateralCheck &&
+          runtimeType == other.runtimeType &&
+          contractId == other.contractId &&
+          verified == other.verified &&
+          expectedAddress == other.expectedAddress &&
+          reason == other.reason;
+}
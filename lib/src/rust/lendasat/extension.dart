@@ -0,0 +1,86 @@
+// This file is automatically generated, so please do not edit it.
+// @generated by `flutter_rust_bridge`@ 2.11.1.
+
+// ignore_for_file: invalid_use_of_internal_member, unused_import, unnecessary_import
+
+import '../frb_generated.dart';
+import 'package:flutter_rust_bridge/flutter_rust_bridge_for_generated.dart';
+
+/// Cost of extending a contract, quoted locally from its terms.
+///
+/// The `estimated_` amounts follow the assumptions in the module docs, not a
+/// quote of the lender.
+class ExtensionQuote {
+  final String contractId;
+  final int extensionDays;
+
+  /// Annual interest rate of the extension period
+  final double interestRate;
+
+  /// Principal carried over to the extended contract
+  final double principal;
+
+  /// Interest for the extension period, assuming simple pro rata interest
+  final double estimatedInterest;
+
+  /// Origination fee rate implied by the original contract, as a fraction
+  /// of the principal
+  final double estimatedOriginationFeeRate;
+
+  /// Origination fee for the extension at that rate, in the loan asset
+  final double estimatedOriginationFee;
+
+  /// Cost of the extension itself: interest plus origination fee
+  final double estimatedCost;
+
+  /// Everything due at the new expiry: the outstanding balance plus the
+  /// extension cost
+  final double estimatedTotalDue;
+  final String currentExpiry;
+  final String newExpiry;
+
+  const ExtensionQuote({
+    required this.contractId,
+    required this.extensionDays,
+    required this.interestRate,
+    required this.principal,
+    required this.estimatedInterest,
+    required this.estimatedOriginationFeeRate,
+    required this.estimatedOriginationFee,
+    required this.estimatedCost,
+    required this.estimatedTotalDue,
+    required this.currentExpiry,
+    required this.newExpiry,
+  });
+
+  @override
+  int get hashCode =>
+      contractId.hashCode ^
+      extensionDays.hashCode ^
+      interestRate.hashCode ^
+      principal.hashCode ^
+      estimatedInterest.hashCode ^
+      estimatedOriginationFeeRate.hashCode ^
+      estimatedOriginationFee.hashCode ^
+      estimatedCost.hashCode ^
+      estimatedTotalDue.hashCode ^
+      currentExpiry.hashCode ^
+      newExpiry.hashCode;
+
+  @override
+  bool operator ==(Object other) =>
+      identical(this, other) ||
+      other is ExtensionQuote &&
+          runtimeType == other.runtimeType &&
+          contractId == other.contractId &&
+          extensionDays == other.extensionDays &&
+          interestRate == other.interestRate &&
+          principal == other.principal &&
+          estimatedInterest == other.estimatedInterest &&
+          estimatedOriginationFeeRate == other.estimatedOriginationFeeRate &&
+          estimatedOriginationFee == other.estimatedOriginationFee &&
+          estimatedCost == other.estimatedCost &&
+          estimatedTotalDue == other.estimatedTotalDue &&
+          currentExpiry == other.currentExpiry &&
+          newExpiry == other.newExpiry;
+}
@@ -0,0 +1,142 @@
+// This file is automatically generated, so please do not edit it.
+// @generated by `flutter_rust_bridge`@ 2.11.1.
+
+// ignore_for_file: invalid_use_of_internal_member, unused_import, unnecessary_import
+
+import '../frb_generated.dart';
+import 'watch.dart';
+import 'package:flutter_rust_bridge/flutter_rust_bridge_for_generated.dart';
+import 'package:freezed_annotation/freezed_annotation.dart' hide protected;
+part 'ltv.freezed.dart';
+
+/// Emitted when a contract's LTV moves into a different band.
+class LtvAlert {
+  final LtvLevel? previousLevel;
+  final LtvStatus status;
+
+  const LtvAlert({
+    this.previousLevel,
+    required this.status,
+  });
+
+  @override
+  int get hashCode => previousLevel.hashCode ^ status.hashCode;
+
+  @override
+  bool operator ==(Object other) =>
+      identical(this, other) ||
+      other is LtvAlert &&
+          runtimeType == other.runtimeType &&
+          previousLevel == other.previousLevel &&
+          status == other.status;
+}
+
+/// Threshold band the current LTV falls in, from least to most severe.
+enum LtvLevel {
+  healthy,
+  marginCall1,
+  marginCall2,
+  liquidation,
+  ;
+}
+
+/// Item of the LTV monitor stream.
+@freezed
+sealed class LtvMonitorEvent with _$LtvMonitorEvent {
+  const LtvMonitorEvent._();
+
+  /// A contract moved into a different band
+  const factory LtvMonitorEvent.alert(LtvAlert field0) = LtvMonitorEvent_Alert;
+
+  /// A check of all active contracts finished
+  const factory LtvMonitorEvent.checked(
+    PollRound field0,
+  ) = LtvMonitorEvent_Checked;
+}
+
+/// Current LTV of a contract at a given BTC price.
+class LtvStatus {
+  final String contractId;
+
+  /// Loan currency the price and balance are expressed in
+  final String currency;
+  final double btcPrice;
+  final PlatformInt64 collateralSats;
+  final double balanceOutstanding;
+  final double ltv;
+  final LtvLevel level;
+  final List<LtvThreshold> thresholds;
+
+  const LtvStatus({
+    required this.contractId,
+    required this.currency,
+    required this.btcPrice,
+    required this.collateralSats,
+    required this.balanceOutstanding,
+    required this.ltv,
+    required this.level,
+    required this.thresholds,
+  });
+
+  @override
+  int get hashCode =>
+      contractId.hashCode ^
+      currency.hashCode ^
+      btcPrice.hashCode ^
+      collateralSats.hashCode ^
+      balanceOutstanding.hashCode ^
+      ltv.hashCode ^
+      level.hashCode ^
+      thresholds.hashCode;
+
+  @override
+  bool operator ==(Object other) =>
+      identical(this, other) ||
+      other is LtvStatus &&
+          runtimeType == other.runtimeType &&
+          contractId == other.contractId &&
+          currency == other.currency &&
+          btcPrice == other.btcPrice &&
+          collateralSats == other.collateralSats &&
+          balanceOutstanding == other.balanceOutstanding &&
+          ltv == other.ltv &&
+          level == other.level &&
+          thresholds == other.thresholds;
+}
+
+/// Price at which a contract reaches one of its LTV thresholds.
+class LtvThreshold {
+  final LtvLevel level;
+  final double ltv;
+
+  /// BTC price in the loan currency at which the threshold is reached
+  final double triggerPrice;
+
+  /// How far the price may fall before the threshold is reached, in percent
+  /// of the current price; negative once it is crossed
+  final double priceDistancePercent;
+
+  const LtvThreshold({
+    required this.level,
+    required this.ltv,
+    required this.triggerPrice,
+    required this.priceDistancePercent,
+  });
+
+  @override
+  int get hashCode =>
+      level.hashCode ^
+      ltv.hashCode ^
+      triggerPrice.hashCode ^
+      priceDistancePercent.hashCode;
+
+  @override
+  bool operator ==(Object other) =>
+      identical(this, other) ||
+      other is LtvThreshold &&
+          runtimeType == other.runtimeType &&
+          level == other.level &&
+          ltv == other.ltv &&
+          triggerPrice == other.triggerPrice &&
+          priceDistancePercent == other.priceDistancePercent;
+}
@@ -0,0 +1,28 @@
+// This file is automatically generated, so please do not edit it.
+// @generated by `flutter_rust_bridge`@ 2.11.1.
+
+// ignore_for_file: invalid_use_of_internal_member, unused_import, unnecessary_import
+
+import '../frb_generated.dart';
+import 'package:flutter_rust_bridge/flutter_rust_bridge_for_generated.dart';
+
+/// Limits applied to claim and recovery PSBTs before signing.
+class PsbtPolicy {
+  /// How far the fee rate may exceed the requested one, in percent. Covers
+  /// the uncertainty of estimating the size of the signed transaction.
+  final int maxFeeRateOvershootPercent;
+
+  const PsbtPolicy({
+    required this.maxFeeRateOvershootPercent,
+  });
+
+  @override
+  int get hashCode => maxFeeRateOvershootPercent.hashCode;
+
+  @override
+  bool operator ==(Object other) =>
+      identical(this, other) ||
+      other is PsbtPolicy &&
+          runtimeType == other.runtimeType &&
+          maxFeeRateOvershootPercent == other.maxFeeRateOvershootPercent;
+}
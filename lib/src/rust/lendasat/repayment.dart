@@ -0,0 +1,114 @@
+// This file is automatically generated, so please do not edit it.
+// @generated by `flutter_rust_bridge`@ 2.11.1.
+
+// ignore_for_file: invalid_use_of_internal_member, unused_import, unnecessary_import
+
+import '../frb_generated.dart';
+import 'package:flutter_rust_bridge/flutter_rust_bridge_for_generated.dart';
+
+/// Persisted progress of an installment repayment.
+class RepaymentState {
+  final String contractId;
+  final String installmentId;
+  final double amountUsd;
+  final String targetToken;
+  final String targetChain;
+  final String repaymentAddress;
+  final RepaymentStep step;
+  final String? swapId;
+
+  /// Arkade HTLC address funding the swap
+  final String? htlcAddress;
+  final BigInt? satsToSend;
+  final String? fundingTxid;
+
+  /// EVM transaction delivering the stablecoins, reported to Lendasat
+  final String? evmClaimTxid;
+
+  /// Why the repayment failed
+  final String? error;
+  final PlatformInt64 startedAt;
+  final PlatformInt64 updatedAt;
+
+  const RepaymentState({
+    required this.contractId,
+    required this.installmentId,
+    required this.amountUsd,
+    required this.targetToken,
+    required this.targetChain,
+    required this.repaymentAddress,
+    required this.step,
+    this.swapId,
+    this.htlcAddress,
+    this.satsToSend,
+    this.fundingTxid,
+    this.evmClaimTxid,
+    this.error,
+    required this.startedAt,
+    required this.updatedAt,
+  });
+
+  @override
+  int get hashCode =>
+      contractId.hashCode ^
+      installmentId.hashCode ^
+      amountUsd.hashCode ^
+      targetToken.hashCode ^
+      targetChain.hashCode ^
+      repaymentAddress.hashCode ^
+      step.hashCode ^
+      swapId.hashCode ^
+      htlcAddress.hashCode ^
+      satsToSend.hashCode ^
+      fundingTxid.hashCode ^
+      evmClaimTxid.hashCode ^
+      error.hashCode ^
+      startedAt.hashCode ^
+      updatedAt.hashCode;
+
+  @override
+  bool operator ==(Object other) =>
+      identical(this, other) ||
+      other is RepaymentState &&
+          runtimeType == other.runtimeType &&
+          contractId == other.contractId &&
+          installmentId == other.installmentId &&
+          amountUsd == other.amountUsd &&
+          targetToken == other.targetToken &&
+          targetChain == other.targetChain &&
+          repaymentAddress == other.repaymentAddress &&
+          step == other.step &&
+          swapId == other.swapId &&
+          htlcAddress == other.htlcAddress &&
+          satsToSend == other.satsToSend &&
+          fundingTxid == other.fundingTxid &&
+          evmClaimTxid == other.evmClaimTxid &&
+          error == other.error &&
+          startedAt == other.startedAt &&
+          updatedAt == other.updatedAt;
+}
+
+/// Step a repayment has reached.
+enum RepaymentStep {
+  /// Installment quoted, no swap created yet
+  quoted,
+
+  /// Swap created, waiting to be funded
+  swapCreated,
+
+  /// Payment to the swap HTLC was requested
+  funding,
+
+  /// HTLC funded, waiting for LendaSwap to lock the stablecoins
+  funded,
+
+  /// Stablecoins claimed via Gelato, waiting for the EVM claim transaction
+  claimed,
+
+  /// Installment reported as paid to Lendasat
+  completed,
+
+  /// Swap expired or failed; funds may need a refund through LendaSwap
+  failed,
+  ;
+}
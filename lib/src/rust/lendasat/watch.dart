@@ -0,0 +1,102 @@
+// This file is automatically generated, so please do not edit it.
+// @generated by `flutter_rust_bridge`@ 2.11.1.
+
+// ignore_for_file: invalid_use_of_internal_member, unused_import, unnecessary_import
+
+import '../frb_generated.dart';
+import 'models.dart';
+import 'package:flutter_rust_bridge/flutter_rust_bridge_for_generated.dart';
+import 'package:freezed_annotation/freezed_annotation.dart' hide protected;
+part 'watch.freezed.dart';
+
+/// What changed in a contract since it was last seen.
+class ContractUpdate {
+  final String contractId;
+
+  /// Status before the change, `None` when the contract is seen for the first time
+  final ContractStatus? previousStatus;
+  final ContractStatus status;
+
+  /// Installments that are new or whose status changed
+  final List<Installment> changedInstallments;
+
+  /// Transactions that are new or whose type, txid or timestamp changed
+  final List<LoanTransaction> changedTransactions;
+
+  /// The contract as it is now
+  final Contract contract;
+
+  const ContractUpdate({
+    required this.contractId,
+    this.previousStatus,
+    required this.status,
+    required this.changedInstallments,
+    required this.changedTransactions,
+    required this.contract,
+  });
+
+  @override
+  int get hashCode =>
+      contractId.hashCode ^
+      previousStatus.hashCode ^
+      status.hashCode ^
+      changedInstallments.hashCode ^
+      changedTransactions.hashCode ^
+      contract.hashCode;
+
+  @override
+  bool operator ==(Object other) =>
+      identical(this, other) ||
+      other is ContractUpdate &&
+          runtimeType == other.runtimeType &&
+          contractId == other.contractId &&
+          previousStatus == other.previousStatus &&
+          status == other.status &&
+          changedInstallments == other.changedInstallments &&
+          changedTransactions == other.changedTransactions &&
+          contract == other.contract;
+}
+
+/// Item of the contract subscription stream.
+@freezed
+sealed class ContractWatchEvent with _$ContractWatchEvent {
+  const ContractWatchEvent._();
+
+  /// A contract changed or was seen for the first time
+  const factory ContractWatchEvent.update(
+    ContractUpdate field0,
+  ) = ContractWatchEvent_Update;
+
+  /// A poll of all contracts finished
+  const factory ContractWatchEvent.polled(
+    PollRound field0,
+  ) = ContractWatchEvent_Polled;
+}
+
+/// End of a polling round of a contract stream, sent after every round.
+///
+/// Lets the app show when the data was last refreshed. Sending it is also how
+/// a stream notices it was closed while nothing changed.
+class PollRound {
+  /// Unix timestamp of the end of the round
+  final PlatformInt64 finishedAt;
+
+  /// Number of contracts looked at, `None` if the round failed
+  final int? contracts;
+
+  const PollRound({
+    required this.finishedAt,
+    this.contracts,
+  });
+
+  @override
+  int get hashCode => finishedAt.hashCode ^ contracts.hashCode;
+
+  @override
+  bool operator ==(Object other) =>
+      identical(this, other) ||
+      other is PollRound &&
+          runtimeType == other.runtimeType &&
+          finishedAt == other.finishedAt &&
+          contracts == other.contracts;
+}
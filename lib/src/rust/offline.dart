@@ -0,0 +1,130 @@
+// This file is automatically generated, so please do not edit it.
+// @generated by `flutter_rust_bridge`@ 2.11.1.
+
+// ignore_for_file: invalid_use_of_internal_member, unused_import, unnecessary_import
+
+import 'frb_generated.dart';
+import 'package:flutter_rust_bridge/flutter_rust_bridge_for_generated.dart';
+import 'package:freezed_annotation/freezed_annotation.dart' hide protected;
+part 'offline.freezed.dart';
+
+/// Freshness of the cached state of a subsystem.
+class CacheStatus {
+  final Subsystem subsystem;
+
+  /// Unix timestamp of the last successful refresh, `None` if there was none yet
+  final PlatformInt64? updatedAt;
+
+  /// Whether the last refresh failed and the cached state was served instead
+  final bool isStale;
+
+  const CacheStatus({
+    required this.subsystem,
+    this.updatedAt,
+    required this.isStale,
+  });
+
+  @override
+  int get hashCode =>
+      subsystem.hashCode ^ updatedAt.hashCode ^ isStale.hashCode;
+
+  @override
+  bool operator ==(Object other) =>
+      identical(this, other) ||
+      other is CacheStatus &&
+          runtimeType == other.runtimeType &&
+          subsystem == other.subsystem &&
+          updatedAt == other.updatedAt &&
+          isStale == other.isStale;
+}
+
+/// An action that is replayed once the backend can be reached again.
+@freezed
+sealed class QueuedAction with _$QueuedAction {
+  const QueuedAction._();
+
+  /// Settle all VTXOs and boarding outputs
+  const factory QueuedAction.settle() = QueuedAction_Settle;
+
+  /// Claim a LendaSwap swap via Gelato
+  const factory QueuedAction.claimSwapGelato({
+    required String swapId,
+  }) = QueuedAction_ClaimSwapGelato;
+
+  /// Claim the VHTLC of a LendaSwap EVM to BTC swap
+  const factory QueuedAction.claimSwapVhtlc({
+    required String swapId,
+  }) = QueuedAction_ClaimSwapVhtlc;
+
+  /// Report a Lendasat installment payment
+  const factory QueuedAction.markInstallmentPaid({
+    required String contractId,
+    required String installmentId,
+    required String paymentTxid,
+  }) = QueuedAction_MarkInstallmentPaid;
+
+  /// Report additional collateral sent to a Lendasat contract
+  const factory QueuedAction.reportCollateralTopUp({
+    required String contractId,
+    required String txid,
+    required BigInt amountSats,
+  }) = QueuedAction_ReportCollateralTopUp;
+}
+
+/// A queued action as stored.
+class QueuedOperation {
+  final PlatformInt64 id;
+  final QueuedAction action;
+
+  /// Unix timestamp of when the action was queued
+  final PlatformInt64 createdAt;
+
+  /// Number of replays that failed while the backend was reachable
+  final int attempts;
+
+  /// Error of the last failed replay
+  final String? lastError;
+
+  /// No longer replayed automatically because it was rejected or failed
+  /// too often; see `retry_queued_action`
+  final bool isParked;
+
+  const QueuedOperation({
+    required this.id,
+    required this.action,
+    required this.createdAt,
+    required this.attempts,
+    this.lastError,
+    required this.isParked,
+  });
+
+  @override
+  int get hashCode =>
+      id.hashCode ^
+      action.hashCode ^
+      createdAt.hashCode ^
+      attempts.hashCode ^
+      lastError.hashCode ^
+      isParked.hashCode;
+
+  @override
+  bool operator ==(Object other) =>
+      identical(this, other) ||
+      other is QueuedOperation &&
+          runtimeType == other.runtimeType &&
+          id == other.id &&
+          action == other.action &&
+          createdAt == other.createdAt &&
+          attempts == other.attempts &&
+          lastError == other.lastError &&
+          isParked == other.isParked;
+}
+
+/// Part of the app whose state is cached for offline use.
+enum Subsystem {
+  balance,
+  txHistory,
+  swaps,
+  contracts,
+  ;
+}
@@ -1,8 +1,8 @@
 import 'package:ark_flutter/src/constants/bitcoin_constants.dart';
 import 'package:ark_flutter/src/logger/logger.dart';
 import 'package:ark_flutter/src/models/swap_token.dart';
-import 'package:ark_flutter/src/rust/api/ark_api.dart' as ark_api;
 import 'package:ark_flutter/src/rust/api/lendasat_api.dart' as lendasat_api;
+import 'package:ark_flutter/src/rust/error.dart';
 import 'package:ark_flutter/src/rust/lendasat/models.dart';
 import 'package:ark_flutter/src/services/settings_service.dart';
 import 'package:flutter/foundation.dart';
@@ -321,15 +321,12 @@ class LendasatService extends ChangeNotifier {
     }
   }
 
-  /// Helper that catches 401 errors, re-authenticates, and retries once.
+  /// Helper that catches expired sessions, re-authenticates, and retries once.
   Future<T> _withAutoReauth<T>(Future<T> Function() apiCall) async {
     try {
       return await apiCall();
     } catch (e) {
-      final errorStr = e.toString();
-      if (errorStr.contains('401') ||
-          errorStr.contains('Unauthorized') ||
-          errorStr.contains('Invalid token')) {
+      if (e is ApiError_AuthRequired) {
         logger.w('Lendasat: Token expired, attempting re-authentication...');
         try {
           await authenticate();
@@ -433,6 +430,30 @@ class LendasatService extends ChangeNotifier {
   // Ark Collateral Claim
   // =====================
 
+  /// Whether the payout address of a contract is stored locally. Contracts
+  /// created by older app versions need [confirmPayoutAddress] before their
+  /// collateral can be claimed.
+  Future<bool> hasPayoutAddress(String contractId) async {
+    return await lendasat_api.lendasatHasPayoutAddress(
+      contractId: contractId,
+    );
+  }
+
+  /// Trust the payout address recorded with the contract. Only call this
+  /// after the user confirmed the address is theirs.
+  Future<String> confirmPayoutAddress(String contractId) async {
+    try {
+      final address = await lendasat_api.lendasatConfirmPayoutAddress(
+        contractId: contractId,
+      );
+      logger.i('Lendasat: Confirmed payout address for $contractId');
+      return address;
+    } catch (e) {
+      logger.e('Error confirming payout address: $e');
+      rethrow;
+    }
+  }
+
   /// Get the PSBTs for claiming Ark collateral.
   Future<ArkClaimPsbtResponse> getClaimArkPsbt(String contractId) async {
     try {
@@ -522,29 +543,18 @@ class LendasatService extends ChangeNotifier {
     logger.i(
         'Lendasat: Contract derivation_path: ${arkResponse.derivationPath ?? "not set"}');
 
-    logger.i('Lendasat: Signing main Ark PSBT with Ark identity...');
+    logger.i('Lendasat: Checking and signing Ark claim PSBTs...');
 
-    // Sign using the Ark SDK's identity (same as Arkade wallet's identity.sign())
+    // Signs with the Ark identity (same as Arkade wallet's identity.sign()),
+    // but only after checking that the PSBTs return the collateral to us.
     // The collateral VTXO is locked to the Ark identity at m/83696968'/11811'/0/0.
-    final signedArkPsbt = await ark_api.signPsbtWithArkIdentity(
-      psbtHex: arkResponse.arkPsbt,
+    final signed = await lendasat_api.lendasatSignClaimArkPsbt(
+      contractId: contractId,
+      arkPsbt: arkResponse.arkPsbt,
+      checkpointPsbts: arkResponse.checkpointPsbts,
     );
-
-    logger.i(
-        'Lendasat: Main Ark PSBT signed, now signing ${arkResponse.checkpointPsbts.length} checkpoint PSBTs...');
-
-    // Sign all checkpoint PSBTs with Ark identity
-    final signedCheckpointPsbts = <String>[];
-    for (int i = 0; i < arkResponse.checkpointPsbts.length; i++) {
-      final checkpointPsbt = arkResponse.checkpointPsbts[i];
-      logger.d(
-          'Lendasat: Signing checkpoint PSBT ${i + 1}/${arkResponse.checkpointPsbts.length}...');
-
-      final signedCheckpoint = await ark_api.signPsbtWithArkIdentity(
-        psbtHex: checkpointPsbt,
-      );
-      signedCheckpointPsbts.add(signedCheckpoint);
-    }
+    final signedArkPsbt = signed.arkPsbt;
+    final signedCheckpointPsbts = signed.checkpointPsbts;
 
     logger.i(
         'Lendasat: All ${signedCheckpointPsbts.length + 1} PSBTs signed with Ark identity, broadcasting...');
@@ -588,48 +598,16 @@ class LendasatService extends ChangeNotifier {
         'Lendasat: Got settle Ark PSBTs (${settleResponse.forfeitPsbts.length} forfeits)');
     logger.i('Lendasat: Contract userPk: ${settleResponse.userPk}');
 
-    // Convert intent proof from BASE64 to HEX for signing
-    final intentProofHex = await lendasat_api.lendasatPsbtBase64ToHex(
-      base64Psbt: settleResponse.intentProof,
-    );
-
-    // Sign the intent proof PSBT using Ark identity (same as Arkade wallet)
-    final signedIntentHex = await ark_api.signPsbtWithArkIdentity(
-      psbtHex: intentProofHex,
-    );
-
-    // Convert signed intent proof back to BASE64 for API
-    final signedIntentBase64 = await lendasat_api.lendasatPsbtHexToBase64(
-      hexPsbt: signedIntentHex,
+    // Sign the intent proof and forfeit PSBTs with the Ark identity (same as
+    // Arkade wallet), after checking that the intent only settles the
+    // collateral into our wallet. Both stay in BASE64 as the API expects.
+    final signed = await lendasat_api.lendasatSignSettleArkPsbt(
+      contractId: contractId,
+      intentPsbt: settleResponse.intentProof,
+      forfeitPsbts: settleResponse.forfeitPsbts,
     );
-
-    logger.i(
-        'Lendasat: Intent proof signed, now signing ${settleResponse.forfeitPsbts.length} forfeit PSBTs...');
-
-    // Sign all forfeit PSBTs with Ark identity
-    final signedForfeitPsbtsBase64 = <String>[];
-    for (int i = 0; i < settleResponse.forfeitPsbts.length; i++) {
-      final forfeitPsbtBase64 = settleResponse.forfeitPsbts[i];
-
-      // Convert BASE64 to HEX
-      final forfeitHex = await lendasat_api.lendasatPsbtBase64ToHex(
-        base64Psbt: forfeitPsbtBase64,
-      );
-
-      // Sign with Ark identity (same as Arkade wallet)
-      logger.d(
-          'Lendasat: Signing forfeit PSBT ${i + 1}/${settleResponse.forfeitPsbts.length}...');
-      final signedForfeitHex = await ark_api.signPsbtWithArkIdentity(
-        psbtHex: forfeitHex,
-      );
-
-      // Convert back to BASE64
-      final signedForfeitBase64 = await lendasat_api.lendasatPsbtHexToBase64(
-        hexPsbt: signedForfeitHex,
-      );
-
-      signedForfeitPsbtsBase64.add(signedForfeitBase64);
-    }
+    final signedIntentBase64 = signed.intentPsbt;
+    final signedForfeitPsbtsBase64 = signed.forfeitPsbts;
 
     logger.i('Lendasat: All settlement PSBTs signed, finishing settlement...');
 
@@ -11,6 +11,7 @@ import 'package:ark_flutter/src/services/payment_monitoring_service.dart';
 import 'package:ark_flutter/src/services/recipient_storage_service.dart';
 import 'package:ark_flutter/src/ui/widgets/bitnet/long_button_widget.dart';
 import 'package:ark_flutter/src/ui/widgets/utility/ark_bottom_sheet.dart';
+import 'package:ark_flutter/src/utils/api_error_formatter.dart';
 import 'package:ark_flutter/theme.dart';
 import 'package:flutter/material.dart';
 import 'package:provider/provider.dart';
@@ -118,7 +119,7 @@ class PendingTransactionService extends ChangeNotifier {
 
       // Update pending status
       pending.status = PendingTransactionStatus.failed;
-      pending.errorMessage = e.toString();
+      pending.errorMessage = ApiErrorFormatter.message(e);
       notifyListeners();
 
       // Show error bottom sheet
@@ -420,14 +421,7 @@ class _SendCompletionSheetState extends State<_SendCompletionSheet> {
   String _cleanErrorMessage(String? error) {
     if (error == null) return 'Unknown error';
 
-    // Extract the main error message from AnyhowException wrapper
     String cleaned = error;
-    if (cleaned.contains('AnyhowException(')) {
-      cleaned = cleaned.replaceFirst('AnyhowException(', '');
-      if (cleaned.endsWith(')')) {
-        cleaned = cleaned.substring(0, cleaned.length - 1);
-      }
-    }
 
     // Check for specific known errors and provide user-friendly messages
     if (cleaned.contains('INVALID_PSBT_INPUT') &&
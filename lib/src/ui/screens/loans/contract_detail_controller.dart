@@ -15,6 +15,7 @@ import 'package:ark_flutter/src/services/overlay_service.dart';
 import 'package:ark_flutter/src/services/payment_overlay_service.dart';
 import 'package:ark_flutter/src/services/settings_service.dart';
 import 'package:ark_flutter/src/ui/widgets/bitcoin_chart/bitcoin_chart_card.dart';
+import 'package:ark_flutter/src/utils/api_error_formatter.dart';
 import 'package:ark_flutter/src/logger/logger.dart';
 import 'contract_detail_config.dart';
 import 'contract_detail_state.dart';
@@ -91,7 +92,7 @@ class ContractDetailController extends ChangeNotifier {
     } catch (e) {
       logger.e('Error loading contract: $e');
       _updateState(_state.copyWith(
-        errorMessage: e.toString(),
+        errorMessage: ApiErrorFormatter.message(e),
         isLoading: false,
       ));
     }
@@ -244,6 +245,29 @@ class ContractDetailController extends ChangeNotifier {
     }
   }
 
+  /// Whether the payout address must be confirmed before claiming, because
+  /// the contract was created before payout addresses were stored.
+  Future<bool> needsPayoutConfirmation() async {
+    try {
+      return !await _lendasatService.hasPayoutAddress(contractId);
+    } catch (e) {
+      logger.w('Could not check payout address: $e');
+      return false;
+    }
+  }
+
+  /// Accept the payout address recorded with the contract.
+  Future<bool> confirmPayoutAddress() async {
+    try {
+      await _lendasatService.confirmPayoutAddress(contractId);
+      return true;
+    } catch (e) {
+      logger.e('Error confirming payout address: $e');
+      OverlayService().showError('Error: ${e.toString()}');
+      return false;
+    }
+  }
+
   /// Claim collateral for a completed/defaulted contract.
   Future<void> claimCollateral() async {
     if (_state.contract == null) return;
@@ -487,21 +511,10 @@ class ContractDetailController extends ChangeNotifier {
   }
 
   /// Get user-friendly error message.
-  String? get displayErrorMessage {
-    if (_state.errorMessage == null) return null;
-
-    String message = _state.errorMessage!;
-    if (message.contains('401 Unauthorized') ||
-        message.contains('Invalid token')) {
-      return 'Session expired. Please go back and try again.';
-    } else if (message.contains('AnyhowException')) {
-      final match = RegExp(r'AnyhowException\(([^)]+)').firstMatch(message);
-      if (match != null) {
-        return match.group(1) ?? message;
-      }
-    }
-    return message;
-  }
+  ///
+  /// Errors are stored already formatted by [ApiErrorFormatter], which maps
+  /// `ApiError_AuthRequired` to the session expired message.
+  String? get displayErrorMessage => _state.errorMessage;
 
   void _updateState(ContractDetailState newState) {
     _state = newState;
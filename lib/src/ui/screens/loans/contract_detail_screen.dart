@@ -1147,7 +1147,10 @@ class _ActionButtons extends StatelessWidget {
             title: 'CLAIM COLLATERAL',
             buttonType: ButtonType.primary,
             customWidth: double.infinity,
-            onTap: state.isActionLoading ? null : controller.claimCollateral,
+            onTap: state.isActionLoading
+                ? null
+                : () => _claimWithPayoutCheck(
+                    context, controller.claimCollateral),
           ),
         ],
         if (contract.canRecover) ...[
@@ -1156,7 +1159,10 @@ class _ActionButtons extends StatelessWidget {
             title: 'RECOVER COLLATERAL',
             buttonType: ButtonType.primary,
             customWidth: double.infinity,
-            onTap: state.isActionLoading ? null : controller.recoverCollateral,
+            onTap: state.isActionLoading
+                ? null
+                : () => _claimWithPayoutCheck(
+                    context, controller.recoverCollateral),
           ),
         ],
         if (state.canCancel) ...[
@@ -1243,6 +1249,38 @@ class _ActionButtons extends StatelessWidget {
     );
   }
 
+  /// Run [claim], first asking the user to confirm the payout address of
+  /// contracts created before it was stored locally.
+  Future<void> _claimWithPayoutCheck(
+    BuildContext context,
+    Future<void> Function() claim,
+  ) async {
+    if (!await controller.needsPayoutConfirmation()) {
+      await claim();
+      return;
+    }
+    if (!context.mounted) return;
+
+    final contract = state.contract!;
+    await arkBottomSheet(
+      context: context,
+      child: ConfirmationSheet(
+        title: 'Confirm Payout Address',
+        message:
+            'The collateral will be returned to ${contract.borrowerBtcAddress}. '
+            'Only continue if this is an address of your wallet.',
+        confirmText: 'Confirm',
+        cancelText: 'Cancel',
+        onConfirm: () async {
+          Navigator.pop(context);
+          if (await controller.confirmPayoutAddress()) {
+            await claim();
+          }
+        },
+      ),
+    );
+  }
+
   Future<void> _showCancelConfirmation(BuildContext context) async {
     await arkBottomSheet(
       context: context,
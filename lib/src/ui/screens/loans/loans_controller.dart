@@ -1,6 +1,7 @@
 import 'dart:async';
 import 'package:flutter/material.dart';
 import 'package:ark_flutter/src/logger/logger.dart';
+import 'package:ark_flutter/src/rust/error.dart';
 import 'package:ark_flutter/src/services/lendasat_service.dart';
 import 'package:ark_flutter/src/services/overlay_service.dart';
 import 'package:ark_flutter/src/services/settings_service.dart';
@@ -256,13 +257,8 @@ class LoansController extends ChangeNotifier {
     _updateState(_state.copyWith(showDebugInfo: false));
   }
 
-  /// Check if error is a 401 Unauthorized error.
-  bool _isUnauthorizedError(dynamic error) {
-    final errorStr = error.toString().toLowerCase();
-    return errorStr.contains('401') ||
-        errorStr.contains('unauthorized') ||
-        errorStr.contains('invalid token');
-  }
+  /// Check if the Lendasat session expired.
+  bool _isUnauthorizedError(Object error) => error is ApiError_AuthRequired;
 
   /// Sync state from service.
   void _syncFromService() {
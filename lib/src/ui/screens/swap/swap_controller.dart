@@ -4,6 +4,7 @@ import 'package:ark_flutter/src/constants/bitcoin_constants.dart';
 import 'package:ark_flutter/src/models/swap_token.dart';
 import 'package:ark_flutter/src/rust/api/ark_api.dart' as ark_api;
 import 'package:ark_flutter/src/rust/api/lendaswap_api.dart' as lendaswap_api;
+import 'package:ark_flutter/src/rust/error.dart';
 import 'package:ark_flutter/src/services/amount_widget_service.dart'
     show CurrencyType;
 import 'package:ark_flutter/src/services/bitcoin_price_service.dart'
@@ -17,6 +18,7 @@ import 'package:ark_flutter/src/services/payment_overlay_service.dart';
 import 'package:ark_flutter/src/services/swap_monitoring_service.dart';
 import 'package:ark_flutter/src/ui/screens/swap/swap_config.dart';
 import 'package:ark_flutter/src/ui/screens/swap/swap_state.dart';
+import 'package:ark_flutter/src/utils/api_error_formatter.dart';
 import 'package:ark_flutter/src/logger/logger.dart';
 import 'package:flutter/material.dart';
 
@@ -542,7 +544,7 @@ class SwapController extends ChangeNotifier {
       }
     } catch (e) {
       logger.e('Swap failed: $e');
-      return SwapError(parseSwapError(e.toString()));
+      return SwapError(parseSwapError(e));
     } finally {
       _updateState(_state.copyWith(isExecuting: false));
       Future.delayed(AppTimeouts.mediumDelay, () {
@@ -582,8 +584,7 @@ class SwapController extends ChangeNotifier {
         logger.i('Swap funded: ${result.swapId}');
       } catch (e) {
         logger.e('Failed to fund swap: $e');
-        return SwapError(
-            'Failed to send funds: ${parseSwapError(e.toString())}');
+        return SwapError('Failed to send funds: ${parseSwapError(e)}');
       }
     }
 
@@ -672,8 +673,19 @@ class SwapController extends ChangeNotifier {
   String _formatUsd(double usd) => usd.toStringAsFixed(2);
 
   /// Parse swap error into user-friendly message
-  static String parseSwapError(String error) {
-    final errorLower = error.toLowerCase();
+  static String parseSwapError(Object error) {
+    if (error is ApiError_InsufficientFunds) {
+      return 'Insufficient balance for this swap.';
+    }
+    if (error is ApiError_ServerUnavailable) {
+      return 'Network error. Please check your connection.';
+    }
+    if (error is ApiError_Timeout) {
+      return 'Request timed out. Please try again.';
+    }
+
+    final message = ApiErrorFormatter.message(error);
+    final errorLower = message.toLowerCase();
 
     if (errorLower.contains('min amount')) {
       return 'Amount too small. Minimum is 1,000 sats.';
@@ -694,10 +706,7 @@ class SwapController extends ChangeNotifier {
     }
 
     // Clean up error
-    var clean = error;
-    if (clean.contains('AnyhowException(')) {
-      clean = clean.replaceAll('AnyhowException(', '').replaceAll(')', '');
-    }
+    var clean = message;
     if (clean.contains('Stack backtrace:')) {
       clean = clean.split('Stack backtrace:')[0].trim();
     }
@@ -282,7 +282,7 @@ class SendScreenState extends State<SendScreen> {
           amountSats: BigInt.from(amount),
         );
         logger.i(
-            'SDK onchain fee estimate: ${estimate.feeSats} sats (${estimate.feeRate.toStringAsFixed(1)} sat/vB, ${estimate.numInputs} inputs)');
+            'SDK onchain fee estimate: ${estimate.feeSats} sats (network ${estimate.feeRate?.toStringAsFixed(1) ?? '?'} sat/vB, ${estimate.numInputs} inputs)');
       } else if (currentNetwork == 'Arkade') {
         // Arkade transfers are free
         estimate = await estimateArkadeFee(
@@ -0,0 +1,36 @@
+import 'package:ark_flutter/src/rust/error.dart';
+import 'package:flutter_rust_bridge/flutter_rust_bridge_for_generated.dart';
+
+/// Turns errors thrown by the Rust API into messages for the user.
+class ApiErrorFormatter {
+  ApiErrorFormatter._();
+
+  /// User-facing message for an error thrown by a Rust API call.
+  ///
+  /// Typed `ApiError`s get a fixed message, other errors fall back to their
+  /// text without the bridge's exception wrapper.
+  static String message(Object error) {
+    if (error is ApiError) {
+      return switch (error) {
+        ApiError_NotInitialized(:final component) =>
+          '$component is not ready yet. Please try again.',
+        ApiError_InsufficientFunds(:final neededSats, :final availableSats) =>
+          'Insufficient funds: need $neededSats sats, have $availableSats sats.',
+        ApiError_InvalidAddress(:final reason) => 'Invalid address: $reason',
+        ApiError_NetworkMismatch(:final expected, :final actual) =>
+          'Wrong network: expected $expected, got $actual.',
+        ApiError_ServerUnavailable() =>
+          'Server unavailable. Please check your connection.',
+        ApiError_Timeout() => 'Request timed out. Please try again.',
+        ApiError_SwapExpired() => 'This swap has expired.',
+        ApiError_AuthRequired() =>
+          'Session expired. Please go back and try again.',
+        ApiError_Other(:final message) => message,
+      };
+    }
+    if (error is AnyhowException) {
+      return error.message;
+    }
+    return error.toString();
+  }
+}
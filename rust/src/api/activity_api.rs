@@ -0,0 +1,842 @@
+//! Unified activity feed for Flutter.
+//!
+//! Merges the Ark transaction history, LendaSwap swaps and Lendasat contracts
+//! into a single time-ordered list. Ark transactions that belong to a swap or
+//! a loan are folded into that item instead of being listed twice.
+//!
+//! Pages are read from the local history cache with a cursor, so loading the
+//! next page does not contact any server. Swaps and contracts are loaded
+//! together with the first page and reused for [`OWNERS_CACHE_SECS`].
+
+use crate::api::ark_api::{self, Transaction, TxMetadata};
+use crate::api::lendasat_api;
+use crate::ark::history_db::{self, HistoryDb, TxHistoryFilter};
+use crate::ark::tx_metadata_db::DestinationType;
+use crate::error::ApiError;
+use crate::lendasat::models::{Contract, ContractFilters};
+use crate::lendaswap::{self, SwapInfo, SwapStatusSimple};
+use anyhow::{Result, anyhow};
+use lendaswap_core::api::GetSwapResponse;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Arc;
+
+/// Upper bound of contracts loaded per page when building the feed.
+const CONTRACTS_PAGE_LIMIT: i32 = 100;
+
+/// How long loaded swaps and contracts are reused before the first page
+/// loads them again.
+const OWNERS_CACHE_SECS: i64 = 60;
+
+/// Swaps and contracts as last loaded for the feed.
+static OWNERS: tokio::sync::Mutex<Option<Arc<Owners>>> = tokio::sync::Mutex::const_new(None);
+
+/// What an activity item represents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ActivityKind {
+    /// On-chain deposit into the Ark boarding address
+    Boarding,
+    /// Off-chain payment received from another Ark user
+    ArkReceive,
+    /// Off-chain payment sent to another Ark user
+    ArkSend,
+    /// Batch settlement of VTXOs
+    Settlement,
+    /// Collaborative redemption to a Bitcoin address
+    OnchainSend,
+    /// Lightning payment via Boltz submarine swap
+    LightningPayment,
+    /// LendaSwap swap between BTC and a stablecoin
+    Swap,
+    /// Lendasat loan contract
+    Loan,
+}
+
+/// A single entry of the activity feed.
+#[derive(Debug, Clone)]
+pub struct ActivityItem {
+    /// Txid for plain transactions, swap id or contract id otherwise
+    pub id: String,
+    pub kind: ActivityKind,
+    /// Unix timestamp used for ordering
+    pub timestamp: i64,
+    /// Balance change in satoshis (negative for outgoing)
+    pub amount_sats: i64,
+    pub is_pending: bool,
+    /// Ark txids belonging to this item
+    pub txids: Vec<String>,
+    /// Locally recorded metadata of the primary transaction
+    pub metadata: Option<TxMetadata>,
+    pub swap: Option<SwapInfo>,
+    pub contract: Option<Contract>,
+}
+
+/// One page of the activity feed.
+#[derive(Debug, Clone)]
+pub struct ActivityPage {
+    pub items: Vec<ActivityItem>,
+    /// Pass to `activity_feed` to load the next page, `None` at the end
+    pub next_cursor: Option<String>,
+}
+
+/// A swap together with the data needed to match its Ark transactions.
+struct SwapEntry {
+    info: SwapInfo,
+    created_at: i64,
+    /// Ark txids the swap reports, e.g. the VHTLC claim of a BTC→Arkade swap
+    txids: Vec<String>,
+}
+
+/// The swaps and contracts Ark transactions can belong to.
+struct Owners {
+    swaps: Vec<SwapEntry>,
+    contracts: Vec<Contract>,
+    loaded_at: i64,
+}
+
+/// Finds the swap or contract an Ark transaction belongs to.
+struct OwnerIndex<'a> {
+    /// Swap and contract ids by the addresses the wallet pays into
+    by_address: HashMap<&'a str, &'a str>,
+    /// Swap and contract ids by the txids LendaSwap and the Lendasat server report
+    by_txid: HashMap<&'a str, &'a str>,
+    ids: HashSet<&'a str>,
+}
+
+impl<'a> OwnerIndex<'a> {
+    fn new(swaps: &'a [SwapEntry], contracts: &'a [Contract]) -> Self {
+        let mut by_address = HashMap::new();
+        for swap in swaps {
+            if let Some(address) = &swap.info.arkade_htlc_address {
+                by_address.insert(address.as_str(), swap.info.id.as_str());
+            }
+        }
+        for contract in contracts {
+            if let Some(address) = &contract.contract_address {
+                by_address.insert(address.as_str(), contract.id.as_str());
+            }
+        }
+
+        let by_txid = swaps
+            .iter()
+            .flat_map(|s| {
+                s.txids
+                    .iter()
+                    .map(move |t| (t.as_str(), s.info.id.as_str()))
+            })
+            .chain(contracts.iter().flat_map(|c| {
+                c.transactions
+                    .iter()
+                    .map(move |t| (t.txid.as_str(), c.id.as_str()))
+            }))
+            .collect();
+
+        let ids = swaps
+            .iter()
+            .map(|s| s.info.id.as_str())
+            .chain(contracts.iter().map(|c| c.id.as_str()))
+            .collect();
+
+        Self {
+            by_address,
+            by_txid,
+            ids,
+        }
+    }
+
+    /// Id of the swap or contract the transaction belongs to, if any.
+    fn owner_of(&self, txid: &str, metadata: Option<&TxMetadata>) -> Option<&'a str> {
+        self.by_txid
+            .get(txid)
+            .copied()
+            .or_else(|| {
+                metadata
+                    .and_then(|m| m.reference.as_deref())
+                    .and_then(|r| self.ids.get(r).copied())
+            })
+            .or_else(|| {
+                metadata
+                    .and_then(|m| m.counterparty.as_deref())
+                    .and_then(|c| self.by_address.get(c).copied())
+            })
+    }
+}
+
+/// Position in the feed, encoded as `<history cursor>|<timestamp>:<id>`.
+///
+/// Plain transactions and swap/loan items are separate ordered streams, so the
+/// cursor remembers the last item taken from each.
+#[derive(Debug, Default, PartialEq)]
+struct FeedCursor {
+    /// Cursor of the last history entry shown
+    history: Option<String>,
+    /// Timestamp and id of the last swap or loan item shown
+    owner: Option<(i64, String)>,
+}
+
+impl FeedCursor {
+    fn encode(&self) -> String {
+        let owner = self
+            .owner
+            .as_ref()
+            .map(|(timestamp, id)| format!("{timestamp}:{id}"))
+            .unwrap_or_default();
+        format!("{}|{}", self.history.as_deref().unwrap_or_default(), owner)
+    }
+
+    fn decode(cursor: &str) -> Result<Self> {
+        let invalid = || anyhow!("Invalid activity cursor: {}", cursor);
+
+        let (history, owner) = cursor.split_once('|').ok_or_else(invalid)?;
+        let owner = match owner.split_once(':') {
+            Some((timestamp, id)) => Some((timestamp.parse().map_err(|_| invalid())?, id.into())),
+            None if owner.is_empty() => None,
+            None => return Err(invalid()),
+        };
+
+        Ok(Self {
+            history: (!history.is_empty()).then(|| history.to_string()),
+            owner,
+        })
+    }
+
+    /// Whether `item` comes after the last swap or loan item shown.
+    fn is_after_owner(&self, item: &ActivityItem) -> bool {
+        match &self.owner {
+            Some((timestamp, id)) => (item.timestamp, &item.id) < (*timestamp, id),
+            None => true,
+        }
+    }
+}
+
+/// Load one page of the unified activity feed, newest first.
+///
+/// Pass `None` as cursor for the first page. Only the first page syncs the
+/// history cache and reloads swaps and loans when they are out of date; later
+/// pages are read from the cache, so they do not shift while scrolling.
+///
+/// Swaps and loans are only included when LendaSwap respectively Lendasat
+/// are initialized. Failing to load them is logged and does not fail the
+/// feed, so the wallet history is always shown.
+pub async fn activity_feed(cursor: Option<String>, limit: u32) -> Result<ActivityPage, ApiError> {
+    let first_page = cursor.is_none();
+    let cursor = match cursor {
+        Some(cursor) => FeedCursor::decode(&cursor)?,
+        None => FeedCursor::default(),
+    };
+
+    if first_page {
+        ark_api::refresh_tx_history().await?;
+    }
+    let owners = load_owners(first_page).await;
+    let metadata = ark_api::load_tx_metadata().await;
+    let db = history_db::get_db()?;
+
+    Ok(feed_page(&db, &owners, &metadata, cursor, limit).await?)
+}
+
+/// Load the complete activity, newest first.
+pub(crate) async fn load_activity() -> Result<Vec<ActivityItem>> {
+    let txs = ark_api::tx_history().await?;
+    let owners = load_owners(true).await;
+
+    let mut items = merge_activity(txs, &owners);
+    items.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+
+    Ok(items)
+}
+
+/// Build a page of the feed from the history cache and the given owners.
+async fn feed_page(
+    db: &HistoryDb,
+    owners: &Owners,
+    metadata: &HashMap<String, TxMetadata>,
+    cursor: FeedCursor,
+    limit: u32,
+) -> Result<ActivityPage> {
+    let limit = limit.max(1);
+    let index = OwnerIndex::new(&owners.swaps, &owners.contracts);
+
+    // Owned transactions are found through the txids swaps and contracts
+    // report and the local metadata, so building swap and loan items never
+    // walks the whole history
+    let owned: HashMap<&str, &str> = index
+        .by_txid
+        .keys()
+        .copied()
+        .chain(metadata.keys().map(String::as_str))
+        .filter_map(|txid| {
+            index
+                .owner_of(txid, metadata.get(txid))
+                .map(|owner| (txid, owner))
+        })
+        .collect();
+    let owned_txids: Vec<&str> = owned.keys().copied().collect();
+
+    let mut linked: HashMap<String, Vec<Transaction>> = HashMap::new();
+    for tx in db.find(&owned_txids).await? {
+        if let Some(owner) = owned.get(tx.txid.as_str()) {
+            linked
+                .entry(owner.to_string())
+                .or_default()
+                .push(ark_api::to_api_transaction(tx, metadata));
+        }
+    }
+
+    let mut owner_items = owner_items(owners, linked);
+    owner_items.sort_by(|a, b| (b.timestamp, &b.id).cmp(&(a.timestamp, &a.id)));
+    let mut owner_items = owner_items
+        .into_iter()
+        .filter(|item| cursor.is_after_owner(item))
+        .peekable();
+
+    let mut items = Vec::with_capacity(limit as usize);
+    let mut next = cursor;
+    let mut fetch_cursor = next.history.clone();
+    let mut history_done = false;
+    // Plain transactions fetched but not shown yet, with their history cursor
+    let mut pending: VecDeque<(String, ActivityItem)> = VecDeque::new();
+
+    while items.len() < limit as usize {
+        if pending.is_empty() && !history_done {
+            let page = db
+                .page(fetch_cursor.as_deref(), limit, &TxHistoryFilter::default())
+                .await?;
+            history_done = page.next_cursor.is_none();
+            fetch_cursor = page.next_cursor;
+
+            for tx in page.items {
+                if owned.contains_key(tx.txid.as_str()) {
+                    continue;
+                }
+                let tx_cursor = tx.cursor();
+                // Order by the cache's sort time, which is stable for
+                // unconfirmed transactions
+                let sort_time = tx.sort_time;
+                let mut item = tx_item(ark_api::to_api_transaction(tx, metadata));
+                item.timestamp = sort_time;
+                pending.push_back((tx_cursor, item));
+            }
+            continue;
+        }
+
+        let take_tx = match (pending.front(), owner_items.peek()) {
+            (None, None) => break,
+            (Some(_), None) => true,
+            (None, Some(_)) => false,
+            (Some((_, tx)), Some(owner)) => tx.timestamp >= owner.timestamp,
+        };
+
+        if take_tx {
+            if let Some((tx_cursor, item)) = pending.pop_front() {
+                next.history = Some(tx_cursor);
+                items.push(item);
+            }
+        } else if let Some(item) = owner_items.next() {
+            next.owner = Some((item.timestamp, item.id.clone()));
+            items.push(item);
+        }
+    }
+
+    let has_more = !pending.is_empty() || !history_done || owner_items.peek().is_some();
+
+    Ok(ActivityPage {
+        items,
+        next_cursor: has_more.then(|| next.encode()),
+    })
+}
+
+/// Get the swaps and contracts, loading them again if `refresh` is set and
+/// they are older than [`OWNERS_CACHE_SECS`].
+async fn load_owners(refresh: bool) -> Arc<Owners> {
+    let mut cached = OWNERS.lock().await;
+    if let Some(owners) = cached.as_ref() {
+        if !refresh || now_unix() - owners.loaded_at < OWNERS_CACHE_SECS {
+            return Arc::clone(owners);
+        }
+    }
+
+    let owners = Arc::new(Owners {
+        swaps: load_swaps().await,
+        contracts: load_contracts().await,
+        loaded_at: now_unix(),
+    });
+    *cached = Some(Arc::clone(&owners));
+    owners
+}
+
+async fn load_swaps() -> Vec<SwapEntry> {
+    if !lendaswap::is_initialized() {
+        return Vec::new();
+    }
+
+    match lendaswap::list_swaps().await {
+        Ok(swaps) => swaps
+            .iter()
+            .map(|data| {
+                let (created_at, txids) = match &data.response {
+                    GetSwapResponse::BtcToEvm(r) => (r.common.created_at.unix_timestamp(), vec![]),
+                    GetSwapResponse::EvmToBtc(r) => (r.common.created_at.unix_timestamp(), vec![]),
+                    GetSwapResponse::BtcToArkade(r) => (
+                        r.created_at.unix_timestamp(),
+                        r.arkade_claim_txid.iter().cloned().collect(),
+                    ),
+                };
+                SwapEntry {
+                    info: SwapInfo::from_extended_data(data),
+                    created_at,
+                    txids,
+                }
+            })
+            .collect(),
+        Err(e) => {
+            tracing::warn!("Activity feed: failed to load swaps: {e:#}");
+            Vec::new()
+        }
+    }
+}
+
+async fn load_contracts() -> Vec<Contract> {
+    if !lendasat_api::lendasat_is_initialized() || !lendasat_api::lendasat_is_authenticated().await
+    {
+        return Vec::new();
+    }
+
+    let mut contracts = Vec::new();
+    let mut page = 1;
+    loop {
+        let filters = ContractFilters {
+            page: Some(page),
+            limit: Some(CONTRACTS_PAGE_LIMIT),
+            status: None,
+            sort_by: None,
+            sort_order: None,
+        };
+        match lendasat_api::lendasat_get_contracts(Some(filters)).await {
+            Ok(response) => {
+                contracts.extend(response.data);
+                if page >= response.total_pages {
+                    break;
+                }
+                page += 1;
+            }
+            Err(e) => {
+                tracing::warn!("Activity feed: failed to load contracts: {e:#}");
+                break;
+            }
+        }
+    }
+
+    contracts
+}
+
+/// Build activity items, folding swap and loan transactions into their owner.
+fn merge_activity(txs: Vec<Transaction>, owners: &Owners) -> Vec<ActivityItem> {
+    let index = OwnerIndex::new(&owners.swaps, &owners.contracts);
+
+    let mut owned: HashMap<String, Vec<Transaction>> = HashMap::new();
+    let mut items = Vec::new();
+
+    for tx in txs {
+        let (txid, metadata) = tx_parts(&tx);
+        match index.owner_of(txid, metadata) {
+            Some(owner) => owned.entry(owner.to_string()).or_default().push(tx),
+            None => items.push(tx_item(tx)),
+        }
+    }
+
+    items.extend(owner_items(owners, owned));
+    items
+}
+
+/// Items of all swaps and contracts, with their transactions taken from
+/// `linked` by owner id.
+fn owner_items(
+    owners: &Owners,
+    mut linked: HashMap<String, Vec<Transaction>>,
+) -> Vec<ActivityItem> {
+    let mut items = Vec::new();
+
+    for swap in &owners.swaps {
+        let txs = linked.remove(&swap.info.id).unwrap_or_default();
+        let nominal = if swap.info.direction == "btc_to_evm" {
+            -swap.info.source_amount_sats
+        } else {
+            swap.info.source_amount_sats
+        };
+        let is_pending = matches!(
+            swap.info.status,
+            SwapStatusSimple::WaitingForDeposit
+                | SwapStatusSimple::Processing
+                | SwapStatusSimple::Refundable
+        );
+
+        let mut item = owner_item(swap.info.id.clone(), ActivityKind::Swap, txs, nominal);
+        item.timestamp = swap.created_at;
+        item.is_pending = is_pending;
+        item.swap = Some(swap.info.clone());
+        items.push(item);
+    }
+
+    for contract in &owners.contracts {
+        let txs = linked.remove(&contract.id).unwrap_or_default();
+        let mut item = owner_item(contract.id.clone(), ActivityKind::Loan, txs, 0);
+        item.timestamp = parse_timestamp(&contract.created_at).unwrap_or(item.timestamp);
+        item.is_pending = !contract.status.is_closed();
+        item.contract = Some(contract.clone());
+        items.push(item);
+    }
+
+    items
+}
+
+/// Build the item of a swap or contract from its linked transactions.
+///
+/// The amount is the sum of the linked transactions, or `nominal_sats` when
+/// none of them is part of the wallet history yet.
+fn owner_item(
+    id: String,
+    kind: ActivityKind,
+    linked: Vec<Transaction>,
+    nominal_sats: i64,
+) -> ActivityItem {
+    let amount_sats = if linked.is_empty() {
+        nominal_sats
+    } else {
+        linked.iter().map(tx_amount).sum()
+    };
+    let timestamp = linked.iter().map(tx_timestamp).min().unwrap_or(0);
+    let txids = linked.iter().map(|tx| tx_parts(tx).0.to_string()).collect();
+    let metadata = linked.iter().find_map(|tx| tx_parts(tx).1.cloned());
+
+    ActivityItem {
+        id,
+        kind,
+        timestamp,
+        amount_sats,
+        is_pending: false,
+        txids,
+        metadata,
+        swap: None,
+        contract: None,
+    }
+}
+
+fn tx_item(tx: Transaction) -> ActivityItem {
+    let amount_sats = tx_amount(&tx);
+    let timestamp = tx_timestamp(&tx);
+
+    let (kind, id, is_pending, txid, metadata) = match tx {
+        Transaction::Boarding {
+            txid,
+            confirmed_at,
+            metadata,
+            ..
+        } => (
+            ActivityKind::Boarding,
+            txid.clone(),
+            confirmed_at.is_none(),
+            txid,
+            metadata,
+        ),
+        Transaction::Round { txid, metadata, .. } => (
+            ActivityKind::Settlement,
+            txid.clone(),
+            false,
+            txid,
+            metadata,
+        ),
+        Transaction::Redeem {
+            txid,
+            is_settled,
+            metadata,
+            ..
+        } => {
+            let lightning = metadata
+                .as_ref()
+                .filter(|m| m.destination_type == Some(DestinationType::Lightning));
+            match lightning {
+                // Lightning payments are identified by their Boltz swap id
+                Some(m) => (
+                    ActivityKind::LightningPayment,
+                    m.reference.clone().unwrap_or_else(|| txid.clone()),
+                    !is_settled,
+                    txid,
+                    metadata,
+                ),
+                None if amount_sats < 0 => (
+                    ActivityKind::ArkSend,
+                    txid.clone(),
+                    !is_settled,
+                    txid,
+                    metadata,
+                ),
+                None => (
+                    ActivityKind::ArkReceive,
+                    txid.clone(),
+                    !is_settled,
+                    txid,
+                    metadata,
+                ),
+            }
+        }
+        Transaction::Offboard {
+            txid,
+            confirmed_at,
+            metadata,
+            ..
+        } => (
+            ActivityKind::OnchainSend,
+            txid.clone(),
+            confirmed_at.is_none(),
+            txid,
+            metadata,
+        ),
+    };
+
+    ActivityItem {
+        id,
+        kind,
+        timestamp,
+        amount_sats,
+        is_pending,
+        txids: vec![txid],
+        metadata,
+        swap: None,
+        contract: None,
+    }
+}
+
+fn tx_parts(tx: &Transaction) -> (&str, Option<&TxMetadata>) {
+    match tx {
+        Transaction::Boarding { txid, metadata, .. }
+        | Transaction::Round { txid, metadata, .. }
+        | Transaction::Redeem { txid, metadata, .. }
+        | Transaction::Offboard { txid, metadata, .. } => (txid, metadata.as_ref()),
+    }
+}
+
+fn tx_amount(tx: &Transaction) -> i64 {
+    match tx {
+        Transaction::Boarding { amount_sats, .. } => *amount_sats as i64,
+        Transaction::Round { amount_sats, .. }
+        | Transaction::Redeem { amount_sats, .. }
+        | Transaction::Offboard { amount_sats, .. } => *amount_sats,
+    }
+}
+
+/// Timestamp of a transaction; unconfirmed ones sort as the newest.
+fn tx_timestamp(tx: &Transaction) -> i64 {
+    match tx {
+        Transaction::Boarding { confirmed_at, .. } | Transaction::Offboard { confirmed_at, .. } => {
+            confirmed_at.unwrap_or_else(now_unix)
+        }
+        Transaction::Round { created_at, .. } | Transaction::Redeem { created_at, .. } => {
+            *created_at
+        }
+    }
+}
+
+fn parse_timestamp(s: &str) -> Option<i64> {
+    time::OffsetDateTime::parse(s, &time::format_description::well_known::Rfc3339)
+        .map(|t| t.unix_timestamp())
+        .ok()
+}
+
+fn now_unix() -> i64 {
+    time::OffsetDateTime::now_utc().unix_timestamp()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lendasat::models::{LoanTransaction, fixtures};
+    use bitcoin::hashes::Hash;
+    use bitcoin::{Amount, SignedAmount, Txid};
+
+    fn metadata(txid: &str, counterparty: Option<&str>, reference: Option<&str>) -> TxMetadata {
+        TxMetadata {
+            txid: txid.to_string(),
+            destination_type: None,
+            counterparty: counterparty.map(str::to_string),
+            reference: reference.map(str::to_string),
+            note: None,
+            category: None,
+            created_at: 0,
+            updated_at: 0,
+        }
+    }
+
+    fn redeem(txid: &str, amount_sats: i64, metadata: Option<TxMetadata>) -> Transaction {
+        Transaction::Redeem {
+            txid: txid.to_string(),
+            amount_sats,
+            is_settled: true,
+            created_at: 1_000,
+            metadata,
+        }
+    }
+
+    fn swap(id: &str, direction: &str, htlc: Option<&str>, txids: Vec<&str>) -> SwapEntry {
+        SwapEntry {
+            info: SwapInfo {
+                id: id.to_string(),
+                status: SwapStatusSimple::Completed,
+                direction: direction.to_string(),
+                source_token: "btc_arkade".to_string(),
+                target_token: "usdc_pol".to_string(),
+                source_amount_sats: 50_000,
+                target_amount_usd: 50.0,
+                created_at: String::new(),
+                ln_invoice: None,
+                arkade_htlc_address: htlc.map(str::to_string),
+                evm_htlc_address: None,
+                fee_sats: 100,
+                can_claim_gelato: false,
+                can_claim_vhtlc: false,
+                can_refund: false,
+                detailed_status: String::new(),
+                evm_htlc_claim_txid: None,
+                refund_locktime: None,
+            },
+            created_at: 2_000,
+            txids: txids.into_iter().map(str::to_string).collect(),
+        }
+    }
+
+    #[test]
+    fn folds_owned_transactions_into_swaps_and_loans() {
+        let mut contract = fixtures::contract();
+        contract.transactions = vec![LoanTransaction {
+            id: 1,
+            contract_id: contract.id.clone(),
+            transaction_type: "Deposit".to_string(),
+            txid: "loan-deposit".to_string(),
+            timestamp: String::new(),
+        }];
+        let txs = vec![
+            redeem(
+                "swap-fund",
+                -50_100,
+                Some(metadata("swap-fund", Some("htlc"), None)),
+            ),
+            redeem("arkade-claim", 49_900, None),
+            redeem("loan-deposit", -2_000_000, None),
+            redeem(
+                "loan-topup",
+                -100_000,
+                Some(metadata("loan-topup", None, Some("contract-1"))),
+            ),
+            redeem("payment", -700, None),
+            Transaction::Boarding {
+                txid: "boarding".to_string(),
+                amount_sats: 10_000,
+                confirmed_at: Some(500),
+                metadata: None,
+            },
+        ];
+        let swaps = vec![
+            swap("swap-1", "btc_to_evm", Some("htlc"), vec![]),
+            swap("swap-2", "btc_to_arkade", None, vec!["arkade-claim"]),
+            swap("swap-3", "evm_to_btc", None, vec![]),
+        ];
+
+        let owners = Owners {
+            swaps,
+            contracts: vec![contract],
+            loaded_at: 0,
+        };
+
+        let items = merge_activity(txs, &owners);
+
+        let summary: Vec<_> = items
+            .iter()
+            .map(|i| (i.id.as_str(), i.kind, i.amount_sats, i.txids.len()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("payment", ActivityKind::ArkSend, -700, 1),
+                ("boarding", ActivityKind::Boarding, 10_000, 1),
+                ("swap-1", ActivityKind::Swap, -50_100, 1),
+                ("swap-2", ActivityKind::Swap, 49_900, 1),
+                // Not in the wallet history yet, so the nominal amount is used
+                ("swap-3", ActivityKind::Swap, 50_000, 0),
+                ("contract-1", ActivityKind::Loan, -2_100_000, 2),
+            ]
+        );
+        assert_eq!(items[2].timestamp, 2_000);
+        assert!(items[5].is_pending);
+        assert_eq!(items[5].timestamp, 1_767_225_600);
+    }
+
+    #[test]
+    fn cursor_roundtrip() {
+        let cursor = FeedCursor {
+            history: Some("1000:abcd:Redeem".to_string()),
+            owner: Some((2_000, "swap:1".to_string())),
+        };
+        assert_eq!(FeedCursor::decode(&cursor.encode()).unwrap(), cursor);
+        assert_eq!(FeedCursor::decode("|").unwrap(), FeedCursor::default());
+        assert!(FeedCursor::decode("1000:abcd:Redeem").is_err());
+        assert!(FeedCursor::decode("|x:swap").is_err());
+    }
+
+    #[tokio::test]
+    async fn pages_through_cached_history_and_owners() {
+        let dir = std::env::temp_dir().join(format!("activity-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let db = HistoryDb::new(dir.to_str().unwrap()).await.unwrap();
+
+        let txid = |b: u8| Txid::from_byte_array([b; 32]);
+        let ark = |b: u8, sats: i64, created_at: i64| ark_core::history::Transaction::Ark {
+            txid: txid(b),
+            amount: SignedAmount::from_sat(sats),
+            is_settled: true,
+            created_at,
+        };
+        db.sync(&[
+            ark(1, -700, 3_000),
+            ark(2, -100_000, 1_000),
+            ark_core::history::Transaction::Boarding {
+                txid: txid(3),
+                amount: Amount::from_sat(10_000),
+                confirmed_at: Some(500),
+            },
+        ])
+        .await
+        .unwrap();
+
+        let topup = txid(2).to_string();
+        let metadata = HashMap::from([(topup.clone(), metadata(&topup, None, Some("contract-1")))]);
+        let owners = Owners {
+            swaps: vec![swap("swap-1", "btc_to_evm", None, vec![])],
+            contracts: vec![fixtures::contract()],
+            loaded_at: 0,
+        };
+
+        let first = feed_page(&db, &owners, &metadata, FeedCursor::default(), 2)
+            .await
+            .unwrap();
+        let cursor = FeedCursor::decode(first.next_cursor.as_deref().unwrap()).unwrap();
+        let second = feed_page(&db, &owners, &metadata, cursor, 2).await.unwrap();
+
+        let ids: Vec<_> = first
+            .items
+            .iter()
+            .chain(&second.items)
+            .map(|i| i.id.clone())
+            .collect();
+        assert_eq!(
+            ids,
+            vec![
+                "contract-1".to_string(),
+                txid(1).to_string(),
+                "swap-1".to_string(),
+                txid(3).to_string(),
+            ]
+        );
+        assert_eq!(first.items[0].txids, vec![topup]);
+        assert_eq!(first.items[0].amount_sats, -100_000);
+        assert!(second.next_cursor.is_none());
+    }
+}
@@ -1,11 +1,30 @@
-use anyhow::Result;
+use crate::api::offline_api;
+use crate::ark::history_db::{self, CachedTx};
+use crate::ark::psbt_analysis;
+use crate::ark::tx_metadata_db::TxMetadataUpdate;
+use crate::error::ApiError;
+use crate::frb_generated::StreamSink;
+use crate::offline::{self, QueuedAction, Subsystem};
+use anyhow::{Result, anyhow};
 use bitcoin::Network;
-use bitcoin::key::Keypair;
 use nostr::ToBech32;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::str::FromStr;
 
-pub async fn wallet_exists(data_dir: String) -> Result<bool> {
-    crate::ark::wallet_exists(data_dir).await
+pub use crate::ark::client::{RescanProgress, RescanResult};
+pub use crate::ark::esplora::EsploraEndpointHealth;
+pub use crate::ark::history_db::{HistorySyncStats, TxDirection, TxHistoryFilter, TxKind};
+pub use crate::ark::message_signing::IdentitySignature;
+pub use crate::ark::migration::{MigrationState, MigrationStep};
+pub use crate::ark::psbt_analysis::{
+    OutputOwner, PsbtInputSummary, PsbtOutputSummary, PsbtSummary, PsbtWarning,
+};
+pub use crate::ark::signer::SigningKey;
+pub use crate::ark::tx_metadata_db::{DestinationType, TxMetadata};
+
+pub async fn wallet_exists(data_dir: String) -> Result<bool, ApiError> {
+    Ok(crate::ark::wallet_exists(data_dir).await?)
 }
 
 /// Setup a new wallet with a freshly generated 12-word mnemonic.
@@ -16,9 +35,9 @@ pub async fn setup_new_wallet(
     esplora: String,
     server: String,
     boltz_url: String,
-) -> Result<String> {
-    let network = Network::from_str(network.as_str())?;
-    crate::ark::setup_new_wallet(data_dir, network, esplora, server, boltz_url).await
+) -> Result<String, ApiError> {
+    let network = parse_network(&network)?;
+    Ok(crate::ark::setup_new_wallet(data_dir, network, esplora, server, boltz_url).await?)
 }
 
 pub async fn load_existing_wallet(
@@ -27,9 +46,52 @@ pub async fn load_existing_wallet(
     esplora: String,
     server: String,
     boltz_url: String,
-) -> Result<String> {
-    let network = Network::from_str(network.as_str())?;
-    crate::ark::load_existing_wallet(data_dir, network, esplora, server, boltz_url).await
+) -> Result<String, ApiError> {
+    let network = parse_network(&network)?;
+    Ok(crate::ark::load_existing_wallet(data_dir, network, esplora, server, boltz_url).await?)
+}
+
+fn parse_network(network: &str) -> Result<Network> {
+    Network::from_str(network).map_err(|e| anyhow!("Invalid network '{}': {}", network, e))
+}
+
+/// Start or resume moving all funds from `old_server` to `new_server`.
+///
+/// Progress is stored in the data directory, so this can be called again
+/// after a restart or while the offboard transaction is unconfirmed. When the
+/// returned step is `Completed` the wallet uses the new server and the app
+/// should store `new_server` as its Ark server URL.
+///
+/// `old_server` must be the server the wallet is connected to, and pending
+/// boarding outputs on it have to be settled before a migration can start.
+pub async fn migrate_ark_server(
+    data_dir: String,
+    network: String,
+    esplora: String,
+    old_server: String,
+    new_server: String,
+    boltz_url: String,
+) -> Result<MigrationState, ApiError> {
+    let network = parse_network(&network)?;
+    Ok(crate::ark::migration::migrate(
+        &data_dir,
+        network,
+        &esplora,
+        &old_server,
+        &new_server,
+        boltz_url,
+    )
+    .await?)
+}
+
+/// Get the progress of a started server migration, if any.
+pub fn get_ark_server_migration(data_dir: String) -> Result<Option<MigrationState>, ApiError> {
+    Ok(crate::ark::migration::load_state(&data_dir)?)
+}
+
+/// Forget a server migration that has not moved funds yet or has completed.
+pub fn clear_ark_server_migration(data_dir: String) -> Result<(), ApiError> {
+    Ok(crate::ark::migration::clear_state(&data_dir)?)
 }
 
 /// Restore a wallet from a mnemonic phrase (12 or 24 words)
@@ -40,9 +102,9 @@ pub async fn restore_wallet(
     esplora: String,
     server: String,
     boltz_url: String,
-) -> Result<String> {
-    let network = Network::from_str(network.as_str())?;
-    crate::ark::restore_wallet(
+) -> Result<String, ApiError> {
+    let network = parse_network(&network)?;
+    Ok(crate::ark::restore_wallet(
         mnemonic_words,
         data_dir,
         network,
@@ -50,13 +112,15 @@ pub async fn restore_wallet(
         server,
         boltz_url,
     )
-    .await
+    .await?)
 }
 
+#[derive(Serialize, Deserialize)]
 pub struct Balance {
     pub offchain: OffchainBalance,
 }
 
+#[derive(Serialize, Deserialize)]
 pub struct OffchainBalance {
     pub pending_sats: u64,
     pub confirmed_sats: u64,
@@ -65,17 +129,31 @@ pub struct OffchainBalance {
     pub total_sats: u64,
 }
 
-pub async fn balance() -> Result<Balance> {
-    let balance = crate::ark::client::balance().await?;
-    Ok(Balance {
-        offchain: OffchainBalance {
-            pending_sats: balance.offchain.pre_confirmed().to_sat(),
-            confirmed_sats: balance.offchain.confirmed().to_sat(),
-            expired_sats: balance.offchain.expired().to_sat(),
-            recoverable_sats: balance.offchain.recoverable().to_sat(),
-            total_sats: balance.offchain.total().to_sat(),
-        },
-    })
+/// Get the wallet balance.
+///
+/// If the Ark server cannot be reached, the last known balance is returned
+/// and flagged as stale (see `get_cache_status`). A successful refresh after
+/// the wallet was offline also replays actions queued in the meantime.
+pub async fn balance() -> Result<Balance, ApiError> {
+    let result = crate::ark::client::balance()
+        .await
+        .map(|balance| Balance {
+            offchain: OffchainBalance {
+                pending_sats: balance.offchain.pre_confirmed().to_sat(),
+                confirmed_sats: balance.offchain.confirmed().to_sat(),
+                expired_sats: balance.offchain.expired().to_sat(),
+                recoverable_sats: balance.offchain.recoverable().to_sat(),
+                total_sats: balance.offchain.total().to_sat(),
+            },
+        })
+        .map_err(ApiError::from);
+
+    if result.is_ok() {
+        let reconnected = offline::is_stale(Subsystem::Balance).await;
+        offline_api::resume_queued_actions(reconnected);
+    }
+
+    offline::cached(Subsystem::Balance, "", result).await
 }
 
 #[derive(Debug, Clone)]
@@ -93,7 +171,7 @@ pub struct BoltzSwap {
     pub invoice: String,
 }
 
-pub async fn address(amount: Option<u64>) -> Result<Addresses> {
+pub async fn address(amount: Option<u64>) -> Result<Addresses, ApiError> {
     let addresses = crate::ark::client::address(amount.map(bitcoin::Amount::from_sat)).await?;
 
     let boarding = addresses.boarding.to_string();
@@ -139,89 +217,222 @@ pub enum Transaction {
         txid: String,
         amount_sats: u64,
         confirmed_at: Option<i64>,
+        metadata: Option<TxMetadata>,
     },
     Round {
         txid: String,
         amount_sats: i64,
         created_at: i64,
+        metadata: Option<TxMetadata>,
     },
     Redeem {
         txid: String,
         amount_sats: i64,
         is_settled: bool,
         created_at: i64,
+        metadata: Option<TxMetadata>,
     },
     /// On-chain send (collaborative redeem) - funds sent from Ark to on-chain address
     Offboard {
         txid: String,
         amount_sats: i64,
         confirmed_at: Option<i64>,
+        metadata: Option<TxMetadata>,
     },
 }
 
-pub async fn tx_history() -> Result<Vec<Transaction>> {
-    let vec = crate::ark::client::tx_history().await?;
-    let txs = vec
+/// Get the full transaction history, newest first.
+///
+/// Syncs the local history cache first if it is out of date. If the server
+/// cannot be reached, the cached history is returned instead.
+pub async fn tx_history() -> Result<Vec<Transaction>, ApiError> {
+    refresh_tx_history().await?;
+    let cached = history_db::get_db()?.load_all().await?;
+
+    let metadata = load_tx_metadata().await;
+
+    Ok(cached
         .into_iter()
-        .map(|tx| match tx {
-            ark_core::history::Transaction::Boarding {
-                txid,
-                amount,
-                confirmed_at,
-            } => {
-                tracing::debug!("TX HISTORY: Boarding tx {}", txid);
-                Transaction::Boarding {
-                    txid: txid.to_string(),
-                    amount_sats: amount.to_sat(),
-                    confirmed_at,
-                }
-            }
-            ark_core::history::Transaction::Commitment {
-                txid,
-                amount,
-                created_at,
-            } => {
-                tracing::debug!("TX HISTORY: Commitment/Round tx {}", txid);
-                Transaction::Round {
-                    txid: txid.to_string(),
-                    amount_sats: amount.to_sat(),
-                    created_at,
-                }
-            }
-            ark_core::history::Transaction::Ark {
-                txid,
-                amount,
-                is_settled,
-                created_at,
-            } => {
-                tracing::debug!("TX HISTORY: Ark/Redeem tx {}", txid);
-                Transaction::Redeem {
-                    txid: txid.to_string(),
-                    amount_sats: amount.to_sat(),
-                    is_settled,
-                    created_at,
-                }
+        .map(|tx| to_api_transaction(tx, &metadata))
+        .collect())
+}
+
+/// Sync the history cache if it is out of date.
+///
+/// If the server cannot be reached the cache is used as is, unless it was
+/// never synced.
+pub(crate) async fn refresh_tx_history() -> Result<(), ApiError> {
+    match crate::ark::client::sync_tx_history_if_stale().await {
+        Ok(synced) => {
+            if synced.is_some() {
+                offline::record_refresh(Subsystem::TxHistory, true).await;
             }
-            ark_core::history::Transaction::Offboard {
-                commitment_txid,
-                amount,
-                confirmed_at,
-            } => {
-                tracing::debug!("TX HISTORY: Offboard/On-chain send tx {}", commitment_txid);
-                Transaction::Offboard {
-                    txid: commitment_txid.to_string(),
-                    // Offboard is always outgoing, so make amount negative
-                    amount_sats: -(amount.to_sat() as i64),
-                    confirmed_at,
-                }
+        }
+        Err(e) => {
+            if history_db::get_db()?.last_synced_at().await?.is_none() {
+                return Err(e.into());
             }
-        })
-        .collect();
+            tracing::warn!("Failed to sync transaction history, using cache: {e:#}");
+            offline::record_refresh(Subsystem::TxHistory, false).await;
+        }
+    }
+
+    Ok(())
+}
+
+/// A page of the cached transaction history.
+pub struct TxHistoryPage {
+    pub items: Vec<Transaction>,
+    /// Pass to `tx_history_page` to load the next page, `None` at the end
+    pub next_cursor: Option<String>,
+    /// Unix timestamp of the last successful sync with the server
+    pub last_synced_at: Option<i64>,
+}
+
+/// Fetch the transaction history from the server and update the local cache,
+/// even if the cache was synced recently (e.g. on pull to refresh).
+pub async fn sync_tx_history() -> Result<HistorySyncStats, ApiError> {
+    Ok(crate::ark::client::sync_tx_history().await?)
+}
 
-    Ok(txs)
+/// Load a page of the cached transaction history, newest first.
+///
+/// Does not contact the server; call `sync_tx_history` to refresh the cache.
+/// Pass `None` as cursor for the first page.
+pub async fn tx_history_page(
+    cursor: Option<String>,
+    limit: u32,
+    filter: Option<TxHistoryFilter>,
+) -> Result<TxHistoryPage, ApiError> {
+    let db = history_db::get_db()?;
+    let page = db
+        .page(cursor.as_deref(), limit, &filter.unwrap_or_default())
+        .await?;
+    let last_synced_at = db.last_synced_at().await?;
+
+    let metadata = load_tx_metadata().await;
+
+    Ok(TxHistoryPage {
+        items: page
+            .items
+            .into_iter()
+            .map(|tx| to_api_transaction(tx, &metadata))
+            .collect(),
+        next_cursor: page.next_cursor,
+        last_synced_at,
+    })
+}
+
+/// Load all transaction metadata keyed by txid.
+///
+/// Metadata is best-effort: history must still load if the store is
+/// unavailable or cannot be read, so errors are only logged.
+pub(crate) async fn load_tx_metadata() -> HashMap<String, TxMetadata> {
+    let result = match crate::ark::tx_metadata_db::get_db() {
+        Ok(db) => db.get_all().await,
+        Err(e) => Err(e),
+    };
+
+    match result {
+        Ok(metadata) => metadata.into_iter().map(|m| (m.txid.clone(), m)).collect(),
+        Err(e) => {
+            tracing::warn!("Transaction metadata unavailable: {e:#}");
+            HashMap::new()
+        }
+    }
+}
+
+pub(crate) fn to_api_transaction(
+    tx: CachedTx,
+    metadata: &HashMap<String, TxMetadata>,
+) -> Transaction {
+    // A txid can have several entries (e.g. boarding and offboard), each gets
+    // the metadata
+    let metadata = metadata.get(&tx.txid).cloned();
+
+    match tx.kind {
+        TxKind::Boarding => Transaction::Boarding {
+            txid: tx.txid,
+            amount_sats: tx.amount_sats as u64,
+            confirmed_at: tx.confirmed_at,
+            metadata,
+        },
+        TxKind::Round => Transaction::Round {
+            txid: tx.txid,
+            amount_sats: tx.amount_sats,
+            created_at: tx.created_at.unwrap_or(tx.sort_time),
+            metadata,
+        },
+        TxKind::Redeem => Transaction::Redeem {
+            txid: tx.txid,
+            amount_sats: tx.amount_sats,
+            is_settled: tx.is_settled.unwrap_or(false),
+            created_at: tx.created_at.unwrap_or(tx.sort_time),
+            metadata,
+        },
+        TxKind::Offboard => Transaction::Offboard {
+            txid: tx.txid,
+            amount_sats: tx.amount_sats,
+            confirmed_at: tx.confirmed_at,
+            metadata,
+        },
+    }
 }
 
-pub async fn send(address: String, amount_sats: u64, fee_sats: Option<u64>) -> Result<String> {
+/// Set the user's note, category or counterparty for a transaction.
+///
+/// Fields passed as `None` keep their current value, empty strings clear it.
+pub async fn set_tx_metadata(
+    txid: String,
+    note: Option<String>,
+    category: Option<String>,
+    counterparty: Option<String>,
+) -> Result<TxMetadata, ApiError> {
+    let db = crate::ark::tx_metadata_db::get_db()?;
+    let metadata = db
+        .upsert(
+            &txid,
+            TxMetadataUpdate {
+                note,
+                category,
+                counterparty,
+                ..Default::default()
+            },
+        )
+        .await?;
+    Ok(metadata)
+}
+
+/// Get the locally stored metadata for a transaction.
+pub async fn get_tx_metadata(txid: String) -> Result<Option<TxMetadata>, ApiError> {
+    let db = crate::ark::tx_metadata_db::get_db()?;
+    Ok(db.get(&txid).await?)
+}
+
+/// Remove the locally stored metadata for a transaction.
+pub async fn delete_tx_metadata(txid: String) -> Result<(), ApiError> {
+    let db = crate::ark::tx_metadata_db::get_db()?;
+    Ok(db.delete(&txid).await?)
+}
+
+/// Export transaction notes as BIP329 JSON Lines.
+pub async fn export_tx_labels() -> Result<String, ApiError> {
+    let db = crate::ark::tx_metadata_db::get_db()?;
+    Ok(db.export_bip329().await?)
+}
+
+/// Import transaction labels from BIP329 JSON Lines.
+/// Returns the number of imported labels.
+pub async fn import_tx_labels(jsonl: String) -> Result<u32, ApiError> {
+    let db = crate::ark::tx_metadata_db::get_db()?;
+    Ok(db.import_bip329(&jsonl).await?)
+}
+
+pub async fn send(
+    address: String,
+    amount_sats: u64,
+    fee_sats: Option<u64>,
+) -> Result<String, ApiError> {
     let amount = bitcoin::Amount::from_sat(amount_sats);
     let fee = fee_sats.map(bitcoin::Amount::from_sat);
     let txid = crate::ark::client::send(address, amount, fee).await?;
@@ -236,7 +447,7 @@ pub struct LnPaymentResult {
 }
 
 /// Pay a BOLT11 Lightning invoice using Ark funds via Boltz submarine swap
-pub async fn pay_ln_invoice(invoice: String) -> Result<LnPaymentResult> {
+pub async fn pay_ln_invoice(invoice: String) -> Result<LnPaymentResult, ApiError> {
     let result = crate::ark::client::pay_ln_invoice(invoice).await?;
     Ok(LnPaymentResult {
         swap_id: result.swap_id,
@@ -245,11 +456,72 @@ pub async fn pay_ln_invoice(invoice: String) -> Result<LnPaymentResult> {
     })
 }
 
-pub async fn settle() -> Result<()> {
-    crate::ark::client::settle().await?;
+/// Settle all VTXOs and boarding outputs. Queued for later if the Ark server
+/// cannot be reached.
+pub async fn settle() -> Result<(), ApiError> {
+    if let Err(e) = crate::ark::client::settle().await {
+        return Err(offline::defer_if_offline(QueuedAction::Settle, e.into()).await);
+    }
+    Ok(())
+}
+
+/// Re-run key discovery with the given gap limit and scan all boarding addresses.
+///
+/// Progress is streamed to `progress_sink`; the stream ends with `Completed`
+/// or `Failed`. A failed rescan can simply be started again.
+pub async fn rescan_wallet(
+    gap_limit: u32,
+    progress_sink: StreamSink<RescanProgress>,
+) -> Result<RescanResult, ApiError> {
+    let result = crate::ark::client::rescan_wallet(gap_limit, |progress| {
+        if let Err(e) = progress_sink.add(progress) {
+            tracing::warn!("Failed to send rescan progress: {e:?}");
+        }
+    })
+    .await?;
+    Ok(result)
+}
+
+/// Whether key discovery failed while loading the wallet and a rescan is recommended.
+#[flutter_rust_bridge::frb(sync)]
+pub fn wallet_needs_rescan() -> bool {
+    crate::ark::client::needs_rescan()
+}
+
+/// Get the Esplora endpoints in use, the primary endpoint first.
+pub fn get_esplora_endpoints() -> Result<Vec<String>, ApiError> {
+    Ok(crate::ark::esplora::shared_client()?.endpoints())
+}
+
+/// Set additional Esplora endpoints used when the primary one fails.
+///
+/// The endpoints are stored in the data directory and applied immediately to
+/// Ark requests. The on-chain wallet picks its endpoint when the wallet is
+/// loaded and keeps it until the next load.
+pub fn set_esplora_endpoints(
+    data_dir: String,
+    primary: String,
+    fallbacks: Vec<String>,
+) -> Result<(), ApiError> {
+    crate::ark::esplora::save_custom_endpoints(&data_dir, &fallbacks)?;
+
+    let mut endpoints = vec![primary];
+    endpoints.extend(fallbacks);
+    crate::ark::esplora::shared_client()?.set_endpoints(&endpoints)?;
     Ok(())
 }
 
+/// Health of the Esplora endpoints as observed by recent requests.
+pub fn get_esplora_health() -> Result<Vec<EsploraEndpointHealth>, ApiError> {
+    Ok(crate::ark::esplora::shared_client()?.health())
+}
+
+/// Actively probe all Esplora endpoints and return their health.
+pub async fn check_esplora_health() -> Result<Vec<EsploraEndpointHealth>, ApiError> {
+    let esplora = crate::ark::esplora::shared_client()?;
+    Ok(esplora.check_health().await)
+}
+
 /// Represents a pending boarding UTXO (on-chain funds waiting to be settled)
 pub struct BoardingUtxo {
     pub txid: String,
@@ -259,7 +531,7 @@ pub struct BoardingUtxo {
 }
 
 /// Get pending boarding UTXOs (on-chain funds at the boarding address that haven't been settled yet)
-pub async fn get_boarding_utxos() -> Result<Vec<BoardingUtxo>> {
+pub async fn get_boarding_utxos() -> Result<Vec<BoardingUtxo>, ApiError> {
     let utxos = crate::ark::client::get_boarding_utxos().await?;
     Ok(utxos
         .into_iter()
@@ -273,7 +545,7 @@ pub async fn get_boarding_utxos() -> Result<Vec<BoardingUtxo>> {
 }
 
 /// Get the total pending balance in sats (on-chain funds waiting to be settled)
-pub async fn get_pending_balance() -> Result<u64> {
+pub async fn get_pending_balance() -> Result<u64, ApiError> {
     let amount = crate::ark::client::get_pending_balance().await?;
     Ok(amount.to_sat())
 }
@@ -282,18 +554,20 @@ pub async fn get_pending_balance() -> Result<u64> {
 /// This method settles ONLY the confirmed boarding UTXOs without including
 /// any existing VTXOs, avoiding the minExpiryGap rejection from the server.
 /// Use this for completing on-chain boarding when you have existing Ark balance.
-pub async fn settle_boarding() -> Result<()> {
+pub async fn settle_boarding() -> Result<(), ApiError> {
     crate::ark::client::settle_boarding().await?;
     Ok(())
 }
 
 /// Get the Nostr secret key (nsec) derived from the wallet mnemonic
 /// Note: Nostr keys are network-independent, so we use Bitcoin mainnet for derivation
-pub async fn nsec(data_dir: String) -> Result<String> {
+pub async fn nsec(data_dir: String) -> Result<String, ApiError> {
     // Nostr keys are not network-specific, but we need a network for xpriv derivation
     // Using mainnet as the standard (the derived key will be the same regardless)
     let nsec = crate::ark::nsec(data_dir, Network::Bitcoin).await?;
-    Ok(nsec.to_bech32()?)
+    Ok(nsec
+        .to_bech32()
+        .map_err(|e| anyhow!("Failed to encode nsec: {}", e))?)
 }
 
 /// Get the Nostr public key (npub) derived from the wallet mnemonic
@@ -305,19 +579,21 @@ pub async fn nsec(data_dir: String) -> Result<String> {
 /// - Any feature requiring a consistent user ID
 ///
 /// Returns the public key in bech32 format (npub1...)
-pub async fn npub(data_dir: String) -> Result<String> {
+pub async fn npub(data_dir: String) -> Result<String, ApiError> {
     // Nostr keys are not network-specific, but we need a network for xpriv derivation
     // Using mainnet as the standard (the derived key will be the same regardless)
     let npub = crate::ark::npub(data_dir, Network::Bitcoin).await?;
-    Ok(npub.to_bech32()?)
+    Ok(npub
+        .to_bech32()
+        .map_err(|e| anyhow!("Failed to encode npub: {}", e))?)
 }
 
 /// Get the mnemonic words for backup (only available for HD wallets)
-pub fn get_mnemonic(data_dir: String) -> Result<String> {
-    crate::ark::get_mnemonic(data_dir)
+pub fn get_mnemonic(data_dir: String) -> Result<String, ApiError> {
+    Ok(crate::ark::get_mnemonic(data_dir)?)
 }
 
-pub async fn reset_wallet(data_dir: String) -> Result<()> {
+pub async fn reset_wallet(data_dir: String) -> Result<(), ApiError> {
     // First, reset all cached clients to ensure fresh state on next init
     // This is critical for cases where the app doesn't fully restart after reset
     crate::lendaswap::reset_client().await;
@@ -325,128 +601,200 @@ pub async fn reset_wallet(data_dir: String) -> Result<()> {
     tracing::info!("All client caches cleared");
 
     // Then delete the wallet files
-    crate::ark::delete_wallet(data_dir)
+    Ok(crate::ark::delete_wallet(data_dir)?)
 }
 
+/// Parameters of the connected Ark server.
+///
+/// Durations are in seconds. Block-based delays are converted assuming
+/// 10 minutes per block.
 pub struct Info {
     pub server_pk: String,
     pub network: String,
+    /// Server software version
+    pub version: String,
+    pub forfeit_pk: String,
+    /// Address receiving forfeited VTXOs
+    pub forfeit_address: String,
+    /// Duration of a batch session
+    pub session_duration_secs: u64,
+    /// Lifetime of a VTXO before it must be renewed
+    pub vtxo_expiry_secs: Option<u64>,
+    /// Delay before funds can be claimed after a unilateral exit
+    pub unilateral_exit_delay_secs: Option<u64>,
+    /// Delay after which boarding funds can be reclaimed on-chain without the server
+    pub boarding_exit_delay_secs: Option<u64>,
+    /// Smallest output the server accepts
+    pub dust_sats: u64,
+    pub vtxo_min_amount_sats: Option<u64>,
+    pub vtxo_max_amount_sats: Option<u64>,
+    pub utxo_min_amount_sats: Option<u64>,
+    pub utxo_max_amount_sats: Option<u64>,
+    pub fees: Option<ServerFees>,
+    /// Whole days until a freshly settled VTXO expires
+    pub vtxo_expiry_days: Option<u32>,
+    /// Smallest amount that can be sent off-chain
+    pub min_sendable_sats: u64,
+    /// Smallest amount that can be sent to a Bitcoin address
+    pub min_onchain_sats: u64,
+}
+
+/// Fee schedule of the Ark server.
+///
+/// Intent fees are expressions evaluated by the server per input or output.
+pub struct ServerFees {
+    pub tx_fee_rate: String,
+    pub offchain_input: String,
+    pub offchain_output: String,
+    pub onchain_input: String,
+    pub onchain_output: String,
+}
+
+/// Seconds per block used to convert block-based delays.
+const SECS_PER_BLOCK: u64 = 600;
+
+/// Convert a relative timelock to seconds.
+fn sequence_to_secs(sequence: bitcoin::Sequence) -> Option<u64> {
+    match sequence.to_relative_lock_time()? {
+        bitcoin::relative::LockTime::Blocks(h) => Some(h.value() as u64 * SECS_PER_BLOCK),
+        bitcoin::relative::LockTime::Time(t) => Some(t.value() as u64 * 512),
+    }
 }
 
-/// Sign an Ark PSBT using the Ark SDK's key provider and signing functions.
+/// Prove ownership of a boarding address with a BIP-322 signature.
 ///
-/// This uses:
-/// - The SDK's key provider to get the keypair (not manual derivation)
-/// - ark_core::send::sign_ark_transaction for proper script-path signing
+/// Boarding outputs can only be spent by the wallet through their timelocked
+/// exit path, so the signature uses the BIP-322 full format. Returns it base64
+/// encoded.
+pub fn sign_message_bip322(address: String, message: String) -> Result<String, ApiError> {
+    Ok(crate::ark::message_signing::sign_with_boarding_address(
+        &address, &message,
+    )?)
+}
+
+/// Verify a BIP-322 signature (simple or full format) for an address.
+pub fn verify_message_bip322(
+    address: String,
+    message: String,
+    signature: String,
+) -> Result<bool, ApiError> {
+    Ok(crate::ark::message_signing::verify_bip322(
+        &address, &message, &signature,
+    )?)
+}
+
+/// Sign a message with the Ark identity key (BIP-340 Schnorr over SHA256 of the message).
 ///
-/// This matches exactly how Arkade wallet signs PSBTs - using the SDK's
-/// identity.sign() equivalent functionality.
-pub async fn sign_psbt_with_ark_identity(psbt_hex: String) -> Result<String> {
-    use ark_core::send::sign_ark_transaction;
-    use bitcoin::psbt::Psbt;
-    use bitcoin::secp256k1::Secp256k1;
-
-    // Parse PSBT from hex
-    let psbt_bytes =
-        hex::decode(&psbt_hex).map_err(|e| anyhow::anyhow!("Invalid PSBT hex: {}", e))?;
-    let mut psbt = Psbt::deserialize(&psbt_bytes)
-        .map_err(|e| anyhow::anyhow!("Failed to parse PSBT: {}", e))?;
-
-    let num_inputs = psbt.inputs.len();
-    tracing::info!(
-        "Signing Ark PSBT with {} inputs using stable identity key",
-        num_inputs
-    );
-
-    // Log PSBT details for debugging
-    for (idx, input) in psbt.inputs.iter().enumerate() {
-        tracing::debug!(
-            "Input {}: tap_scripts={} entries, tap_internal_key={:?}",
-            idx,
-            input.tap_scripts.len(),
-            input.tap_internal_key.map(|k| k.to_string())
-        );
-    }
-
-    // Get our STABLE identity keypair at path m/83696968'/11811'/0/0 (equivalent to Arkade's SingleKey)
-    // This key NEVER changes, unlike vtxo.owner_pk() which changes with each VTXO
-    // This ensures the signing key matches the borrower_pk used in contract creation
-    use crate::ark::mnemonic_file::{ARK_BASE_DERIVATION_PATH, read_mnemonic_file};
-
-    // Get data_dir from Lendasat state (which must be initialized for LendaSat operations)
-    let (data_dir, network) = {
-        let lock = crate::api::lendasat_api::get_state_lock();
-        let guard = lock.read().await;
-        let state = guard
-            .as_ref()
-            .ok_or_else(|| anyhow::anyhow!("Lendasat not initialized - cannot get identity key"))?;
-        (state.data_dir.clone(), state.network)
-    };
-
-    // Read mnemonic and derive the identity key at index 0
-    let mnemonic = read_mnemonic_file(&data_dir)?
-        .ok_or_else(|| anyhow::anyhow!("No wallet found - mnemonic file missing"))?;
-
-    // Derive at path m/83696968'/11811'/0/0 (Ark base path + index 0)
-    let identity_path = format!("{}/0", ARK_BASE_DERIVATION_PATH);
-    let xpriv =
-        crate::ark::mnemonic_file::derive_xpriv_at_path(&mnemonic, &identity_path, network)?;
-
-    // Create keypair from the derived key
-    let secp_for_key = Secp256k1::new();
-    let ark_keypair = Keypair::from_secret_key(&secp_for_key, &xpriv.private_key);
+/// Uses the same identity as `sign_psbt_with_ark_identity`.
+pub async fn sign_message_with_ark_identity(
+    message: String,
+) -> Result<IdentitySignature, ApiError> {
+    let keypair = crate::ark::signer::identity_keypair().await?;
+    Ok(crate::ark::message_signing::sign_with_identity(
+        &keypair, &message,
+    ))
+}
 
-    let ark_pubkey = ark_keypair.x_only_public_key().0;
-    tracing::info!(
-        "Ark STABLE identity pubkey (path m/83696968'/11811'/0/0): {}",
-        ark_pubkey
-    );
+/// Verify a signature made with `sign_message_with_ark_identity`.
+///
+/// `pubkey` is the x-only public key as hex.
+pub fn verify_ark_identity_signature(
+    pubkey: String,
+    message: String,
+    signature: String,
+) -> Result<bool, ApiError> {
+    Ok(crate::ark::message_signing::verify_identity_signature(
+        &pubkey, &message, &signature,
+    )?)
+}
+
+/// Sign an Ark PSBT with the stable Ark identity key (m/83696968'/11811'/0/0).
+///
+/// The identity never changes, unlike `vtxo.owner_pk()` which changes with each
+/// VTXO, so it matches the borrower key used when a Lendasat contract was
+/// created. This is the equivalent of Arkade's `SingleKey` signing.
+///
+/// Works with either the Ark wallet loaded or Lendasat initialized; without
+/// a loaded wallet the key is derived from the mnemonic in Lendasat's data
+/// directory.
+pub async fn sign_psbt_with_ark_identity(psbt_hex: String) -> Result<String, ApiError> {
+    let mut psbt = psbt_analysis::decode_psbt(&psbt_hex)?;
+    crate::ark::signer::sign_ark_psbt_with_identity(&mut psbt).await?;
+    Ok(hex::encode(psbt.serialize()))
+}
 
-    let secp = Secp256k1::new();
-
-    // Sign each input using the SDK's sign_ark_transaction
-    // This handles script-path signing correctly
-    for input_idx in 0..num_inputs {
-        let kp = ark_keypair;
-        let sign_fn = |_input: &mut bitcoin::psbt::Input,
-                       msg: bitcoin::secp256k1::Message|
-         -> std::result::Result<
-            Vec<(
-                bitcoin::secp256k1::schnorr::Signature,
-                bitcoin::XOnlyPublicKey,
-            )>,
-            ark_core::Error,
-        > {
-            let sig = secp.sign_schnorr_no_aux_rand(&msg, &kp);
-            let pk = kp.x_only_public_key().0;
-            tracing::debug!("Signed with pubkey: {}", pk);
-            Ok(vec![(sig, pk)])
-        };
-
-        sign_ark_transaction(sign_fn, &mut psbt, input_idx)
-            .map_err(|e| anyhow::anyhow!("Failed to sign input {}: {}", input_idx, e))?;
-
-        tracing::info!("Input {}: Signed successfully via SDK", input_idx);
-    }
+/// Sign an Ark PSBT (script-path spends of VTXOs or contracts) with wallet keys.
+///
+/// With `key` set every input is signed with that key, otherwise each input is
+/// signed with the wallet keys referenced by its tapscripts. PSBTs are hex.
+pub async fn sign_ark_psbt(psbt_hex: String, key: Option<SigningKey>) -> Result<String, ApiError> {
+    let mut psbt = psbt_analysis::decode_psbt(&psbt_hex)?;
+    crate::ark::signer::sign_ark_psbt(&mut psbt, key.as_ref())?;
+    Ok(hex::encode(psbt.serialize()))
+}
 
-    tracing::info!(
-        "Ark PSBT signing complete: {}/{} inputs signed via Ark SDK",
-        num_inputs,
-        num_inputs
-    );
+/// Sign an on-chain PSBT (P2WPKH or Taproot inputs) with wallet keys.
+///
+/// Inputs are matched by their key origins or by the public keys they commit
+/// to; with `key` set only that key is used. PSBTs are hex.
+pub async fn sign_onchain_psbt(
+    psbt_hex: String,
+    key: Option<SigningKey>,
+) -> Result<String, ApiError> {
+    let mut psbt = psbt_analysis::decode_psbt(&psbt_hex)?;
+    crate::ark::signer::sign_onchain_psbt(&mut psbt, key.as_ref())?;
+    Ok(hex::encode(psbt.serialize()))
+}
 
-    // Serialize the signed PSBT back to hex
-    let signed_psbt_bytes = psbt.serialize();
-    let signed_psbt_hex = hex::encode(signed_psbt_bytes);
+/// The x-only public key of a wallet key, as hex.
+pub fn get_signing_pubkey(key: SigningKey) -> Result<String, ApiError> {
+    Ok(crate::ark::signer::public_key(&key)?.to_string())
+}
 
-    Ok(signed_psbt_hex)
+/// Decode a PSBT (hex or base64) into a summary of what signing it would do:
+/// spent inputs, outputs paying to the wallet vs third parties, fee, estimated
+/// fee rate and warnings. Show this to the user before signing.
+///
+/// `wallet_addresses` are additionally treated as wallet-owned, e.g. the payout
+/// address registered with a loan contract.
+pub fn analyze_psbt(psbt: String, wallet_addresses: Vec<String>) -> Result<PsbtSummary, ApiError> {
+    let psbt = psbt_analysis::decode_psbt(&psbt)?;
+    Ok(psbt_analysis::analyze(&psbt, &wallet_addresses)?)
 }
 
-pub async fn information() -> Result<Info> {
+pub async fn information() -> Result<Info, ApiError> {
     let info = crate::ark::client::info()?;
+
+    let vtxo_expiry_secs = sequence_to_secs(info.vtxo_tree_expiry);
+    let dust_sats = info.dust.to_sat();
+    let vtxo_min_amount_sats = info.vtxo_min_amount.map(|a| a.to_sat());
+    let utxo_min_amount_sats = info.utxo_min_amount.map(|a| a.to_sat());
+
     Ok(Info {
         server_pk: info.signer_pk.to_string(),
         network: info.network.to_string(),
+        version: info.version.clone(),
+        forfeit_pk: info.forfeit_pk.to_string(),
+        forfeit_address: info.forfeit_address.to_string(),
+        session_duration_secs: info.session_duration,
+        vtxo_expiry_secs,
+        unilateral_exit_delay_secs: sequence_to_secs(info.unilateral_exit_delay),
+        boarding_exit_delay_secs: sequence_to_secs(info.boarding_exit_delay),
+        dust_sats,
+        vtxo_min_amount_sats,
+        vtxo_max_amount_sats: info.vtxo_max_amount.map(|a| a.to_sat()),
+        utxo_min_amount_sats,
+        utxo_max_amount_sats: info.utxo_max_amount.map(|a| a.to_sat()),
+        fees: info.fees.as_ref().map(|f| ServerFees {
+            tx_fee_rate: f.tx_fee_rate.clone(),
+            offchain_input: f.intent_fee.offchain_input.clone(),
+            offchain_output: f.intent_fee.offchain_output.clone(),
+            onchain_input: f.intent_fee.onchain_input.clone(),
+            onchain_output: f.intent_fee.onchain_output.clone(),
+        }),
+        vtxo_expiry_days: vtxo_expiry_secs.map(|secs| (secs / 86_400) as u32),
+        min_sendable_sats: vtxo_min_amount_sats.unwrap_or(0).max(dust_sats),
+        min_onchain_sats: utxo_min_amount_sats.unwrap_or(0).max(dust_sats),
     })
 }
 
@@ -460,15 +808,17 @@ pub async fn wait_for_payment(
     boarding_address: Option<String>,
     boltz_swap_id: Option<String>,
     timeout_seconds: u64,
-) -> Result<PaymentReceived> {
+) -> Result<PaymentReceived, ApiError> {
     use ark_core::ArkAddress;
     use bitcoin::Address;
     use std::str::FromStr;
 
-    let ark_addr = ark_address.map(|s| ArkAddress::decode(&s)).transpose()?;
+    let ark_addr = ark_address
+        .map(|s| ArkAddress::decode(&s).map_err(|e| ApiError::invalid_address(&s, e)))
+        .transpose()?;
 
     let boarding_addr = boarding_address
-        .map(|s| Address::from_str(&s))
+        .map(|s| Address::from_str(&s).map_err(|e| ApiError::invalid_address(&s, e)))
         .transpose()?
         .map(|a| a.assume_checked());
 
@@ -499,10 +849,38 @@ pub async fn wait_for_payment(
 pub struct FeeEstimate {
     /// Estimated fee in satoshis
     pub fee_sats: u64,
-    /// Fee rate used (sat/vB for on-chain, percentage for Lightning)
-    pub fee_rate: f64,
-    /// Number of VTXOs that would be used (for on-chain sends)
+    /// Fee rate used: the network rate (sat/vB) behind the miner fee of
+    /// on-chain sends, the percentage for Lightning. `None` for Arkade sends
+    /// and when the network rate is unavailable.
+    pub fee_rate: Option<f64>,
+    /// Number of VTXOs that would be spent to cover amount and fee
     pub num_inputs: u32,
+    /// Virtual size the send adds on-chain (0 for Arkade and Lightning sends)
+    pub vbytes: u64,
+    /// Part of the fee charged by the Ark server (offboard or batch fee)
+    pub server_fee_sats: u64,
+    /// Part of the fee paying for on-chain block space
+    pub miner_fee_sats: u64,
+    /// Part of the fee charged by Boltz as a percentage of the amount
+    pub service_fee_sats: u64,
+    /// Whether the split above is estimated from the network fee rate rather
+    /// than reported by the server (on-chain sends), show it as approximate
+    pub split_is_estimate: bool,
+}
+
+impl From<crate::ark::client::FeeEstimate> for FeeEstimate {
+    fn from(estimate: crate::ark::client::FeeEstimate) -> Self {
+        FeeEstimate {
+            fee_sats: estimate.fee_sats,
+            fee_rate: estimate.fee_rate,
+            num_inputs: estimate.num_inputs,
+            vbytes: estimate.vbytes,
+            server_fee_sats: estimate.server_fee_sats,
+            miner_fee_sats: estimate.miner_fee_sats,
+            service_fee_sats: estimate.service_fee_sats,
+            split_is_estimate: estimate.split_is_estimate,
+        }
+    }
 }
 
 /// Estimate fee for on-chain send (collaborative redemption)
@@ -510,36 +888,30 @@ pub struct FeeEstimate {
 /// This estimates the fee for sending to a Bitcoin address.
 /// The fee depends on the current network fee rate and the number of VTXOs
 /// that need to be spent to cover the amount.
-pub async fn estimate_onchain_fee(address: String, amount_sats: u64) -> Result<FeeEstimate> {
+pub async fn estimate_onchain_fee(
+    address: String,
+    amount_sats: u64,
+) -> Result<FeeEstimate, ApiError> {
     let estimate = crate::ark::client::estimate_onchain_fee(address, amount_sats).await?;
-    Ok(FeeEstimate {
-        fee_sats: estimate.fee_sats,
-        fee_rate: estimate.fee_rate,
-        num_inputs: estimate.num_inputs,
-    })
+    Ok(estimate.into())
 }
 
 /// Estimate fee for Arkade (off-chain) send
 ///
-/// Ark-to-Ark transfers are essentially free since they happen off-chain.
-/// Returns 0 fee.
-pub async fn estimate_arkade_fee(address: String, amount_sats: u64) -> Result<FeeEstimate> {
+/// Ark-to-Ark transfers happen off-chain, so the only cost is the server's
+/// batch fee.
+pub async fn estimate_arkade_fee(
+    address: String,
+    amount_sats: u64,
+) -> Result<FeeEstimate, ApiError> {
     let estimate = crate::ark::client::estimate_arkade_fee(address, amount_sats).await?;
-    Ok(FeeEstimate {
-        fee_sats: estimate.fee_sats,
-        fee_rate: estimate.fee_rate,
-        num_inputs: estimate.num_inputs,
-    })
+    Ok(estimate.into())
 }
 
 /// Estimate fee for Lightning payment
 ///
 /// Fetches real-time fees from Boltz API including percentage fee and miner fees.
-pub async fn estimate_lightning_fee(amount_sats: u64) -> Result<FeeEstimate> {
+pub async fn estimate_lightning_fee(amount_sats: u64) -> Result<FeeEstimate, ApiError> {
     let estimate = crate::ark::client::estimate_lightning_fee(amount_sats).await?;
-    Ok(FeeEstimate {
-        fee_sats: estimate.fee_sats,
-        fee_rate: estimate.fee_rate,
-        num_inputs: estimate.num_inputs,
-    })
+    Ok(estimate.into())
 }
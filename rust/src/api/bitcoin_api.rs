@@ -1,3 +1,6 @@
+use crate::http;
+use crate::http::RequestExt;
+use crate::http::Service;
 use crate::models::historical_prices::{HistoricalPriceResponse, TimeRange};
 use anyhow::{Context as AnyhowContext, Result, bail};
 
@@ -15,10 +18,10 @@ pub async fn fetch_historical_prices(
         range.to_query_param()
     );
 
-    let client = reqwest::Client::new();
+    let client = http::client(Service::Prices);
     let response = client
         .get(&url)
-        .send()
+        .send_with_retry(Service::Prices)
         .await
         .context("Failed to fetch historical prices")?;
 
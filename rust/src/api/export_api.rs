@@ -0,0 +1,341 @@
+//! Accounting export of the wallet history.
+//!
+//! Exports the unified activity feed as CSV or JSON with the BTC price at the
+//! time of each entry. The CSV columns follow the Koinly universal format,
+//! which CoinTracking, CoinTracker and most other tax tools can import.
+
+use crate::api::activity_api::{self, ActivityItem, ActivityKind};
+use crate::api::bitcoin_api;
+use crate::error::ApiError;
+use crate::models::exchange_rates::{FiatCurrency, fetch_historical_exchange_rates};
+use anyhow::{Result, anyhow, bail};
+use serde::Serialize;
+use time::OffsetDateTime;
+use time::format_description::well_known::Rfc3339;
+
+const SATS_PER_BTC: f64 = 100_000_000.0;
+
+/// Output format of the export.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+/// A single row of the accounting export.
+#[derive(Debug, Clone, Serialize)]
+pub struct ExportRecord {
+    /// Unix timestamp of the entry
+    pub timestamp: i64,
+    /// Date in UTC, RFC3339
+    pub date: String,
+    /// Activity type, e.g. "ArkSend" or "Swap"
+    pub kind: String,
+    /// Txid, swap id or contract id
+    pub id: String,
+    /// Ark txids belonging to the entry
+    pub txids: Vec<String>,
+    /// Balance change in satoshis (negative for outgoing)
+    pub amount_sats: i64,
+    pub sent_amount: Option<f64>,
+    pub sent_currency: Option<String>,
+    pub received_amount: Option<f64>,
+    pub received_currency: Option<String>,
+    pub fee_amount: Option<f64>,
+    pub fee_currency: Option<String>,
+    /// BTC price in the export currency at the time of the entry
+    pub btc_price: Option<f64>,
+    pub fiat_currency: String,
+    /// Fiat value of the BTC moved at the time of the entry
+    pub fiat_value: Option<f64>,
+    /// Fiat value acquired for incoming entries (basis of the received coins)
+    pub cost_basis: Option<f64>,
+    /// Fiat value realized for outgoing entries
+    pub proceeds: Option<f64>,
+    /// Tag understood by tax tools (e.g. "collateral")
+    pub label: Option<String>,
+    pub note: Option<String>,
+    pub category: Option<String>,
+    pub counterparty: Option<String>,
+    pub is_pending: bool,
+}
+
+/// Export the complete wallet history for accounting.
+///
+/// BTC prices are taken from the historical price service at `server_url`
+/// (in USD) and converted to `currency` with the ECB reference rate of the
+/// same day.
+///
+/// # Arguments
+/// * `server_url` - Backend URL serving `/api/historical-prices`
+/// * `format` - CSV (Koinly universal format) or JSON
+/// * `currency` - Fiat currency for prices and values
+pub async fn export_wallet_history(
+    server_url: String,
+    format: ExportFormat,
+    currency: FiatCurrency,
+) -> Result<String, ApiError> {
+    let items = activity_api::load_activity().await?;
+
+    let prices = load_price_history(&server_url).await?;
+    let fx_rates = match items.iter().map(|item| item.timestamp).min() {
+        Some(since) => fetch_historical_exchange_rates(currency, since).await?,
+        None => Vec::new(),
+    };
+
+    // Oldest first, as expected by tax tools
+    let records: Vec<ExportRecord> = items
+        .into_iter()
+        .rev()
+        .map(|item| {
+            let btc_price = price_at(&prices, item.timestamp)
+                .zip(price_at(&fx_rates, item.timestamp))
+                .map(|(price, fx_rate)| price * fx_rate);
+            to_record(item, btc_price, currency)
+        })
+        .collect();
+
+    tracing::info!(
+        records = records.len(),
+        currency = currency.code(),
+        "Exported wallet history"
+    );
+
+    match format {
+        ExportFormat::Csv => Ok(to_csv(&records)),
+        ExportFormat::Json => serde_json::to_string_pretty(&records).map_err(|e| ApiError::Other {
+            message: format!("Failed to serialize export: {}", e),
+        }),
+    }
+}
+
+/// Load historical BTC/USD prices as `(unix timestamp, price)`, oldest first.
+///
+/// The full range only has daily resolution, so the last week is merged in
+/// at its finer resolution.
+async fn load_price_history(server_url: &str) -> Result<Vec<(i64, f64)>> {
+    let mut prices = Vec::new();
+    for range in ["MAX", "1W"] {
+        let response = bitcoin_api::fetch_historical_prices(server_url.to_string(), range.into())
+            .await
+            .map_err(|e| anyhow!("Failed to load historical prices ({}): {}", range, e))?;
+
+        prices.extend(response.prices.iter().filter_map(|p| {
+            let timestamp = OffsetDateTime::parse(&p.timestamp, &Rfc3339).ok()?;
+            let price = p.price.parse::<f64>().ok()?;
+            Some((timestamp.unix_timestamp(), price))
+        }));
+    }
+
+    if prices.is_empty() {
+        bail!("No historical prices available");
+    }
+
+    prices.sort_by_key(|(timestamp, _)| *timestamp);
+    prices.dedup_by_key(|(timestamp, _)| *timestamp);
+
+    Ok(prices)
+}
+
+/// Value of the last data point at or before `timestamp`, used for both BTC
+/// prices and exchange rates.
+///
+/// Entries older than the series use its first data point.
+fn price_at(prices: &[(i64, f64)], timestamp: i64) -> Option<f64> {
+    let idx = prices.partition_point(|(t, _)| *t <= timestamp);
+    prices.get(idx.saturating_sub(1)).map(|(_, price)| *price)
+}
+
+fn to_record(item: ActivityItem, btc_price: Option<f64>, currency: FiatCurrency) -> ExportRecord {
+    let btc = |sats: i64| sats.unsigned_abs() as f64 / SATS_PER_BTC;
+    let fiat_value = btc_price.map(|p| round_fiat(btc(item.amount_sats) * p));
+
+    let mut sent: Option<(f64, String)> = None;
+    let mut received: Option<(f64, String)> = None;
+    if item.amount_sats < 0 {
+        sent = Some((btc(item.amount_sats), "BTC".to_string()));
+    } else if item.amount_sats > 0 {
+        received = Some((btc(item.amount_sats), "BTC".to_string()));
+    }
+
+    let mut fee = None;
+    if let Some(swap) = &item.swap {
+        // A swap is a trade: the stablecoin side is the other leg. BTC→Arkade
+        // swaps move BTC between layers and are a plain receive.
+        let token = token_symbol(if swap.direction == "btc_to_evm" {
+            &swap.target_token
+        } else {
+            &swap.source_token
+        });
+        let is_trade = swap.direction != "btc_to_arkade";
+        if is_trade && item.amount_sats < 0 {
+            received = Some((swap.target_amount_usd, token));
+        } else if is_trade {
+            sent = Some((swap.target_amount_usd, token));
+        }
+        if swap.fee_sats > 0 {
+            fee = Some((btc(swap.fee_sats), "BTC".to_string()));
+        }
+    }
+
+    let label = match item.kind {
+        ActivityKind::Loan => Some("collateral".to_string()),
+        _ => None,
+    };
+
+    let metadata = item.metadata.as_ref();
+    let date = OffsetDateTime::from_unix_timestamp(item.timestamp)
+        .ok()
+        .and_then(|d| d.format(&Rfc3339).ok())
+        .unwrap_or_default();
+
+    ExportRecord {
+        timestamp: item.timestamp,
+        date,
+        kind: format!("{:?}", item.kind),
+        id: item.id,
+        txids: item.txids,
+        amount_sats: item.amount_sats,
+        sent_amount: sent.as_ref().map(|(a, _)| *a),
+        sent_currency: sent.map(|(_, c)| c),
+        received_amount: received.as_ref().map(|(a, _)| *a),
+        received_currency: received.map(|(_, c)| c),
+        fee_amount: fee.as_ref().map(|(a, _)| *a),
+        fee_currency: fee.map(|(_, c)| c),
+        btc_price,
+        fiat_currency: currency.code().to_string(),
+        fiat_value,
+        cost_basis: fiat_value.filter(|_| item.amount_sats > 0),
+        proceeds: fiat_value.filter(|_| item.amount_sats < 0),
+        label,
+        note: metadata.and_then(|m| m.note.clone()),
+        category: metadata.and_then(|m| m.category.clone()),
+        counterparty: metadata.and_then(|m| m.counterparty.clone()),
+        is_pending: item.is_pending,
+    }
+}
+
+/// Ticker of a LendaSwap token id, e.g. "usdc_pol" -> "USDC".
+fn token_symbol(token: &str) -> String {
+    token.split('_').next().unwrap_or(token).to_uppercase()
+}
+
+fn round_fiat(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+/// Render records in the Koinly universal CSV format.
+fn to_csv(records: &[ExportRecord]) -> String {
+    const HEADER: &str = "Date,Sent Amount,Sent Currency,Received Amount,Received Currency,\
+        Fee Amount,Fee Currency,Net Worth Amount,Net Worth Currency,Label,Description,TxHash";
+
+    let mut csv = String::from(HEADER);
+    csv.push('\n');
+
+    for record in records {
+        let date = OffsetDateTime::from_unix_timestamp(record.timestamp)
+            .ok()
+            .and_then(|d| {
+                d.format(time::macros::format_description!(
+                    "[year]-[month]-[day] [hour]:[minute]:[second] UTC"
+                ))
+                .ok()
+            })
+            .unwrap_or_default();
+
+        let mut description = record.kind.clone();
+        if let Some(note) = &record.note {
+            description.push_str(": ");
+            description.push_str(note);
+        }
+        if record.is_pending {
+            description.push_str(" (pending)");
+        }
+
+        let fields = [
+            date,
+            fmt_amount(record.sent_amount),
+            record.sent_currency.clone().unwrap_or_default(),
+            fmt_amount(record.received_amount),
+            record.received_currency.clone().unwrap_or_default(),
+            fmt_amount(record.fee_amount),
+            record.fee_currency.clone().unwrap_or_default(),
+            record
+                .fiat_value
+                .map(|v| format!("{:.2}", v))
+                .unwrap_or_default(),
+            record.fiat_currency.clone(),
+            record.label.clone().unwrap_or_default(),
+            description,
+            record.txids.join(" "),
+        ];
+
+        let line: Vec<String> = fields.iter().map(|f| escape_csv(f)).collect();
+        csv.push_str(&line.join(","));
+        csv.push('\n');
+    }
+
+    csv
+}
+
+fn fmt_amount(amount: Option<f64>) -> String {
+    amount.map(|a| format!("{:.8}", a)).unwrap_or_default()
+}
+
+/// Quote a CSV field if needed.
+///
+/// Fields starting with a formula character are prefixed with `'`, so a note
+/// like `=HYPERLINK(...)` is not evaluated when the export is opened in a
+/// spreadsheet.
+fn escape_csv(field: &str) -> String {
+    let field = if field.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        format!("'{}", field)
+    } else {
+        field.to_string()
+    };
+
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn price_at_uses_last_point_at_or_before() {
+        let prices = [(100, 1.0), (200, 2.0), (300, 3.0)];
+
+        assert_eq!(price_at(&prices, 50), Some(1.0));
+        assert_eq!(price_at(&prices, 100), Some(1.0));
+        assert_eq!(price_at(&prices, 199), Some(1.0));
+        assert_eq!(price_at(&prices, 200), Some(2.0));
+        assert_eq!(price_at(&prices, 1_000), Some(3.0));
+        assert_eq!(price_at(&[], 100), None);
+    }
+
+    #[test]
+    fn escapes_csv_fields() {
+        assert_eq!(escape_csv("ArkSend"), "ArkSend");
+        assert_eq!(escape_csv("0.00010000"), "0.00010000");
+        assert_eq!(escape_csv("a,b"), "\"a,b\"");
+        assert_eq!(escape_csv("say \"hi\"\n"), "\"say \"\"hi\"\"\n\"");
+    }
+
+    #[test]
+    fn neutralizes_formulas() {
+        assert_eq!(escape_csv("=1+1"), "'=1+1");
+        assert_eq!(escape_csv("+49 123"), "'+49 123");
+        assert_eq!(escape_csv("-5"), "'-5");
+        assert_eq!(escape_csv("@SUM(A1)"), "'@SUM(A1)");
+        assert_eq!(escape_csv("\tcmd"), "'\tcmd");
+        assert_eq!(
+            escape_csv("=HYPERLINK(\"x\",\"y\")"),
+            "\"'=HYPERLINK(\"\"x\"\",\"\"y\"\")\""
+        );
+        assert_eq!(escape_csv("a=b"), "a=b");
+    }
+}
@@ -2,18 +2,41 @@
 //!
 //! Provides a Flutter-friendly API for the Lendasat lending platform.
 
+use crate::ark::psbt_analysis::{self, PsbtSummary};
+use crate::ark::signer::{self, SigningKey};
+use crate::error::ApiError;
+use crate::frb_generated::StreamSink;
+use crate::http;
+use crate::http::RequestExt;
+use crate::http::Service;
 use crate::lendasat::auth;
+use crate::lendasat::collateral;
+use crate::lendasat::extension;
+use crate::lendasat::ltv::{self, LtvLevel};
 use crate::lendasat::models::*;
+use crate::lendasat::policy::{self, WalletView};
+use crate::lendasat::repayment::{self, RepaymentStep};
 use crate::lendasat::storage::{self, StoredAuth};
+use crate::lendasat::watch;
+use crate::offline::{self, QueuedAction, Subsystem};
 use anyhow::{Result, anyhow, bail};
 use bitcoin::Network;
+use reqwest::StatusCode;
 use reqwest::header::{AUTHORIZATION, CONTENT_TYPE, HeaderMap, HeaderName, HeaderValue};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::str::FromStr;
 use std::sync::OnceLock;
 use std::sync::atomic::{AtomicBool, Ordering};
 use tokio::sync::RwLock;
 
+pub use crate::lendasat::collateral::CollateralCheck;
+pub use crate::lendasat::extension::ExtensionQuote;
+pub use crate::lendasat::ltv::{LtvAlert, LtvMonitorEvent, LtvStatus, LtvThreshold};
+pub use crate::lendasat::policy::PsbtPolicy;
+pub use crate::lendasat::repayment::RepaymentState;
+pub use crate::lendasat::watch::{ContractUpdate, ContractWatchEvent, PollRound};
+
 // ============================================================================
 // Global State
 // ============================================================================
@@ -21,11 +44,12 @@ use tokio::sync::RwLock;
 static LENDASAT_INITIALIZED: AtomicBool = AtomicBool::new(false);
 
 pub(crate) struct LendasatState {
-    http_client: reqwest::Client,
     base_url: String,
     pub(crate) data_dir: String,
     pub(crate) network: Network,
     jwt_token: Option<String>,
+    /// Unix timestamp after which `jwt_token` is expected to be rejected
+    token_expires_at: Option<i64>,
     api_key: Option<String>,
 }
 
@@ -65,26 +89,19 @@ pub async fn lendasat_init(
     api_url: String,
     network: String,
     api_key: Option<String>,
-) -> Result<()> {
-    let http_client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(30))
-        .build()
-        .map_err(|e| anyhow!("Failed to create HTTP client: {}", e))?;
-
+) -> Result<(), ApiError> {
     let network =
         Network::from_str(&network).map_err(|e| anyhow!("Invalid network '{}': {}", network, e))?;
 
-    // Try to load existing auth
-    let jwt_token = storage::load_auth(&data_dir)?
-        .filter(|auth| !auth.is_potentially_expired())
-        .map(|auth| auth.jwt_token);
+    // Try to load existing auth; an expired token is renewed on the first request
+    let stored_auth = storage::load_auth(&data_dir)?.filter(|auth| !auth.is_potentially_expired());
 
     let state = LendasatState {
-        http_client,
         base_url: api_url,
         data_dir,
         network,
-        jwt_token,
+        token_expires_at: stored_auth.as_ref().map(|auth| auth.expiry()),
+        jwt_token: stored_auth.map(|auth| auth.jwt_token),
         api_key,
     };
 
@@ -118,13 +135,13 @@ pub async fn lendasat_is_authenticated() -> bool {
 // ============================================================================
 
 /// Get the wallet's public key (for display/verification).
-pub async fn lendasat_get_public_key() -> Result<String> {
+pub async fn lendasat_get_public_key() -> Result<String, ApiError> {
     let lock = get_state_lock();
     let guard = lock.read().await;
     let state = guard
         .as_ref()
-        .ok_or_else(|| anyhow!("Lendasat not initialized"))?;
-    auth::get_public_key(&state.data_dir, state.network).await
+        .ok_or_else(|| ApiError::not_initialized("Lendasat"))?;
+    Ok(auth::get_public_key(&state.data_dir, state.network).await?)
 }
 
 /// Get the wallet's derivation path.
@@ -139,7 +156,7 @@ pub fn lendasat_get_derivation_path() -> String {
 /// 2. Request challenge from server
 /// 3. Sign challenge with wallet
 /// 4. Verify signature and get JWT token
-pub async fn lendasat_authenticate() -> Result<AuthResult> {
+pub async fn lendasat_authenticate() -> Result<AuthResult, ApiError> {
     let lock = get_state_lock();
 
     // Get public key from wallet
@@ -147,7 +164,7 @@ pub async fn lendasat_authenticate() -> Result<AuthResult> {
         let guard = lock.read().await;
         let state = guard
             .as_ref()
-            .ok_or_else(|| anyhow!("Lendasat not initialized"))?;
+            .ok_or_else(|| ApiError::not_initialized("Lendasat"))?;
         auth::get_public_key(&state.data_dir, state.network).await?
     };
     tracing::info!("Authenticating with pubkey: {}...", &pubkey[..16]);
@@ -157,24 +174,21 @@ pub async fn lendasat_authenticate() -> Result<AuthResult> {
         let guard = lock.read().await;
         let state = guard
             .as_ref()
-            .ok_or_else(|| anyhow!("Lendasat not initialized"))?;
+            .ok_or_else(|| ApiError::not_initialized("Lendasat"))?;
 
         let url = format!("{}/api/auth/pubkey-challenge", state.base_url);
         let body = serde_json::json!({ "pubkey": pubkey });
 
-        let response = state
-            .http_client
+        let response = http::client(Service::Lendasat)
             .post(&url)
             .header(CONTENT_TYPE, "application/json")
             .json(&body)
-            .send()
+            .send_with_retry(Service::Lendasat)
             .await
-            .map_err(|e| anyhow!("Failed to request challenge: {}", e))?;
+            .map_err(|e| request_error("Failed to request challenge", e))?;
 
         if !response.status().is_success() {
-            let status = response.status();
-            let text = response.text().await.unwrap_or_default();
-            bail!("Challenge request failed ({}): {}", status, text);
+            return Err(response_error("Challenge request failed", response).await);
         }
 
         let challenge_response: PubkeyChallengeResponse = response
@@ -192,7 +206,7 @@ pub async fn lendasat_authenticate() -> Result<AuthResult> {
         let guard = lock.read().await;
         let state = guard
             .as_ref()
-            .ok_or_else(|| anyhow!("Lendasat not initialized"))?;
+            .ok_or_else(|| ApiError::not_initialized("Lendasat"))?;
         auth::sign_message(&challenge, &state.data_dir, state.network).await?
     };
     tracing::debug!("Signed challenge, signature length: {}", signature.len());
@@ -202,7 +216,7 @@ pub async fn lendasat_authenticate() -> Result<AuthResult> {
         let guard = lock.read().await;
         let state = guard
             .as_ref()
-            .ok_or_else(|| anyhow!("Lendasat not initialized"))?;
+            .ok_or_else(|| ApiError::not_initialized("Lendasat"))?;
 
         let url = format!("{}/api/auth/pubkey-verify", state.base_url);
         let body = PubkeyVerifyRequest {
@@ -211,14 +225,13 @@ pub async fn lendasat_authenticate() -> Result<AuthResult> {
             signature,
         };
 
-        let response = state
-            .http_client
+        let response = http::client(Service::Lendasat)
             .post(&url)
             .header(CONTENT_TYPE, "application/json")
             .json(&body)
-            .send()
+            .send_with_retry(Service::Lendasat)
             .await
-            .map_err(|e| anyhow!("Failed to verify signature: {}", e))?;
+            .map_err(|e| request_error("Failed to verify signature", e))?;
 
         if !response.status().is_success() {
             let status = response.status();
@@ -232,7 +245,7 @@ pub async fn lendasat_authenticate() -> Result<AuthResult> {
                 return Ok(AuthResult::NeedsRegistration { pubkey });
             }
 
-            bail!("Signature verification failed ({}): {}", status, text);
+            return Err(status_error("Signature verification failed", status, &text));
         }
 
         let verify_response: PubkeyVerifyResponse = response
@@ -248,7 +261,7 @@ pub async fn lendasat_authenticate() -> Result<AuthResult> {
         let mut guard = lock.write().await;
         let state = guard
             .as_mut()
-            .ok_or_else(|| anyhow!("Lendasat not initialized"))?;
+            .ok_or_else(|| ApiError::not_initialized("Lendasat"))?;
 
         // Save to storage
         let stored_auth = StoredAuth {
@@ -261,11 +274,12 @@ pub async fn lendasat_authenticate() -> Result<AuthResult> {
                 .duration_since(std::time::UNIX_EPOCH)
                 .map(|d| d.as_secs() as i64)
                 .unwrap_or(0),
-            expires_at: None, // JWT expiry is handled server-side
+            expires_at: auth::jwt_expiry(&token),
         };
 
         storage::save_auth(&state.data_dir, &stored_auth)?;
         state.jwt_token = Some(token);
+        state.token_expires_at = Some(stored_auth.expiry());
     }
 
     tracing::info!("Authentication successful for user: {}", user.name);
@@ -282,21 +296,21 @@ pub async fn lendasat_register(
     email: String,
     name: String,
     invite_code: Option<String>,
-) -> Result<String> {
+) -> Result<String, ApiError> {
     let lock = get_state_lock();
 
     let pubkey = {
         let guard = lock.read().await;
         let state = guard
             .as_ref()
-            .ok_or_else(|| anyhow!("Lendasat not initialized"))?;
+            .ok_or_else(|| ApiError::not_initialized("Lendasat"))?;
         auth::get_public_key(&state.data_dir, state.network).await?
     };
 
     let guard = lock.read().await;
     let state = guard
         .as_ref()
-        .ok_or_else(|| anyhow!("Lendasat not initialized"))?;
+        .ok_or_else(|| ApiError::not_initialized("Lendasat"))?;
 
     let url = format!("{}/api/auth/pubkey-register", state.base_url);
     let body = PubkeyRegisterRequest {
@@ -306,19 +320,16 @@ pub async fn lendasat_register(
         invite_code,
     };
 
-    let response = state
-        .http_client
+    let response = http::client(Service::Lendasat)
         .post(&url)
         .header(CONTENT_TYPE, "application/json")
         .json(&body)
-        .send()
+        .send_with_retry(Service::Lendasat)
         .await
-        .map_err(|e| anyhow!("Failed to register: {}", e))?;
+        .map_err(|e| request_error("Failed to register", e))?;
 
     if !response.status().is_success() {
-        let status = response.status();
-        let text = response.text().await.unwrap_or_default();
-        bail!("Registration failed ({}): {}", status, text);
+        return Err(response_error("Registration failed", response).await);
     }
 
     let register_response: PubkeyRegisterResponse = response
@@ -335,15 +346,16 @@ pub async fn lendasat_register(
 }
 
 /// Logout and clear stored credentials.
-pub async fn lendasat_logout() -> Result<()> {
+pub async fn lendasat_logout() -> Result<(), ApiError> {
     let lock = get_state_lock();
     let mut guard = lock.write().await;
     let state = guard
         .as_mut()
-        .ok_or_else(|| anyhow!("Lendasat not initialized"))?;
+        .ok_or_else(|| ApiError::not_initialized("Lendasat"))?;
 
     storage::delete_auth(&state.data_dir)?;
     state.jwt_token = None;
+    state.token_expires_at = None;
 
     tracing::info!("Logged out from Lendasat");
 
@@ -355,12 +367,14 @@ pub async fn lendasat_logout() -> Result<()> {
 // ============================================================================
 
 /// Get available loan offers.
-pub async fn lendasat_get_offers(filters: Option<OfferFilters>) -> Result<Vec<LoanOffer>> {
+pub async fn lendasat_get_offers(
+    filters: Option<OfferFilters>,
+) -> Result<Vec<LoanOffer>, ApiError> {
     let lock = get_state_lock();
     let guard = lock.read().await;
     let state = guard
         .as_ref()
-        .ok_or_else(|| anyhow!("Lendasat not initialized"))?;
+        .ok_or_else(|| ApiError::not_initialized("Lendasat"))?;
 
     let mut url = format!("{}/api/offers", state.base_url);
 
@@ -404,7 +418,7 @@ pub async fn lendasat_get_offers(filters: Option<OfferFilters>) -> Result<Vec<Lo
     }
 
     // Build request with auth headers (JWT or API key)
-    let mut request = state.http_client.get(&url);
+    let mut request = http::client(Service::Lendasat).get(&url);
     if let Some(token) = &state.jwt_token {
         request = request.header(AUTHORIZATION, format!("Bearer {}", token));
     } else if let Some(api_key) = &state.api_key {
@@ -412,14 +426,12 @@ pub async fn lendasat_get_offers(filters: Option<OfferFilters>) -> Result<Vec<Lo
     }
 
     let response = request
-        .send()
+        .send_with_retry(Service::Lendasat)
         .await
-        .map_err(|e| anyhow!("Failed to fetch offers: {}", e))?;
+        .map_err(|e| request_error("Failed to fetch offers", e))?;
 
     if !response.status().is_success() {
-        let status = response.status();
-        let text = response.text().await.unwrap_or_default();
-        bail!("Failed to fetch offers ({}): {}", status, text);
+        return Err(response_error("Failed to fetch offers", response).await);
     }
 
     let offers: Vec<LoanOffer> = response
@@ -431,13 +443,15 @@ pub async fn lendasat_get_offers(filters: Option<OfferFilters>) -> Result<Vec<Lo
 }
 
 /// Get a single offer by ID.
-pub async fn lendasat_get_offer(offer_id: String) -> Result<LoanOffer> {
+pub async fn lendasat_get_offer(offer_id: String) -> Result<LoanOffer, ApiError> {
     let offers = lendasat_get_offers(None).await?;
 
     offers
         .into_iter()
         .find(|o| o.id == offer_id)
-        .ok_or_else(|| anyhow!("Offer not found: {}", offer_id))
+        .ok_or_else(|| ApiError::Other {
+            message: format!("Offer not found: {}", offer_id),
+        })
 }
 
 // ============================================================================
@@ -463,26 +477,132 @@ async fn get_auth_headers(state: &LendasatState) -> Result<HeaderMap> {
             HeaderValue::from_str(api_key).map_err(|e| anyhow!("Invalid API key: {}", e))?,
         );
     } else {
-        bail!("Not authenticated: no JWT token or API key available");
+        return Err(ApiError::AuthRequired.into());
     }
 
     Ok(headers)
 }
 
+/// Renew the JWT this long before it expires.
+const TOKEN_REFRESH_MARGIN_SECS: i64 = 5 * 60;
+
+/// Serializes re-authentication so concurrent requests renew the token once.
+static REAUTH_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+/// Send an authenticated request to the Lendasat API.
+///
+/// `build` creates the request from the API base URL and is called again for
+/// a retry. A JWT about to expire is renewed with a new pubkey challenge
+/// first; if the server still answers 401, the wallet re-authenticates and
+/// the request is retried once. Renewal needs a previous login stored in the
+/// data directory.
+async fn send_authenticated<F>(context: &str, build: F) -> Result<reqwest::Response, ApiError>
+where
+    F: Fn(&str) -> reqwest::RequestBuilder,
+{
+    let lock = get_state_lock();
+
+    let (renewable, expiring_token) = {
+        let guard = lock.read().await;
+        let state = guard
+            .as_ref()
+            .ok_or_else(|| ApiError::not_initialized("Lendasat"))?;
+        let renewable = storage::has_auth(&state.data_dir);
+        let expiring_token = token_expiring(state).then(|| state.jwt_token.clone());
+        (renewable, expiring_token)
+    };
+
+    if renewable {
+        if let Some(token) = expiring_token {
+            if let Err(e) = reauthenticate(token.as_deref()).await {
+                tracing::warn!("Failed to renew Lendasat token: {}", e);
+            }
+        }
+    }
+
+    let mut retried = false;
+    loop {
+        let (request, token) = {
+            let guard = lock.read().await;
+            let state = guard
+                .as_ref()
+                .ok_or_else(|| ApiError::not_initialized("Lendasat"))?;
+            let headers = get_auth_headers(state).await?;
+            (
+                build(&state.base_url).headers(headers),
+                state.jwt_token.clone(),
+            )
+        };
+
+        let response = request
+            .send_with_retry(Service::Lendasat)
+            .await
+            .map_err(|e| request_error(context, e))?;
+
+        if response.status() != StatusCode::UNAUTHORIZED || retried || !renewable {
+            return Ok(response);
+        }
+
+        tracing::info!("Lendasat token rejected, re-authenticating");
+        if let Err(e) = reauthenticate(token.as_deref()).await {
+            tracing::warn!("Lendasat re-authentication failed: {}", e);
+            return Ok(response);
+        }
+        retried = true;
+    }
+}
+
+/// Replace `stale_token` with a fresh JWT from a new pubkey challenge.
+///
+/// Does nothing if another request already renewed it.
+async fn reauthenticate(stale_token: Option<&str>) -> Result<(), ApiError> {
+    let _reauth = REAUTH_LOCK.lock().await;
+
+    {
+        let guard = get_state_lock().read().await;
+        if let Some(state) = guard.as_ref() {
+            if state.jwt_token.as_deref() != stale_token && !token_expiring(state) {
+                return Ok(());
+            }
+        }
+    }
+
+    match lendasat_authenticate().await? {
+        AuthResult::Success { .. } => Ok(()),
+        AuthResult::NeedsRegistration { .. } => Err(ApiError::AuthRequired),
+    }
+}
+
+/// Whether the JWT is missing or about to expire.
+fn token_expiring(state: &LendasatState) -> bool {
+    if state.jwt_token.is_none() {
+        return true;
+    }
+
+    let now = now_unix();
+    state
+        .token_expires_at
+        .is_some_and(|expires_at| now + TOKEN_REFRESH_MARGIN_SECS >= expires_at)
+}
+
 /// Get user's contracts.
+///
+/// If Lendasat cannot be reached, the last known response for the same filters
+/// is returned and flagged as stale (see `get_cache_status`).
 pub async fn lendasat_get_contracts(
     filters: Option<ContractFilters>,
-) -> Result<PaginatedContractsResponse> {
-    let lock = get_state_lock();
-    let guard = lock.read().await;
-    let state = guard
+) -> Result<PaginatedContractsResponse, ApiError> {
+    let scope = filters
         .as_ref()
-        .ok_or_else(|| anyhow!("Lendasat not initialized"))?;
-
-    let headers = get_auth_headers(state).await?;
-
-    let mut url = format!("{}/api/contracts", state.base_url);
+        .map(|filters| format!("{:?}", filters))
+        .unwrap_or_default();
+    let result = fetch_contracts(filters).await;
+    offline::cached(Subsystem::Contracts, &scope, result).await
+}
 
+async fn fetch_contracts(
+    filters: Option<ContractFilters>,
+) -> Result<PaginatedContractsResponse, ApiError> {
     // Add query parameters
     let mut params = Vec::new();
     if let Some(filters) = filters {
@@ -505,22 +625,20 @@ pub async fn lendasat_get_contracts(
         }
     }
 
-    if !params.is_empty() {
-        url = format!("{}?{}", url, params.join("&"));
-    }
+    let query = if params.is_empty() {
+        String::new()
+    } else {
+        format!("?{}", params.join("&"))
+    };
 
-    let response = state
-        .http_client
-        .get(&url)
-        .headers(headers)
-        .send()
-        .await
-        .map_err(|e| anyhow!("Failed to fetch contracts: {}", e))?;
+    let response = send_authenticated("Failed to fetch contracts", |base_url| {
+        let url = format!("{}/api/contracts{}", base_url, query);
+        http::client(Service::Lendasat).get(&url)
+    })
+    .await?;
 
     if !response.status().is_success() {
-        let status = response.status();
-        let text = response.text().await.unwrap_or_default();
-        bail!("Failed to fetch contracts ({}): {}", status, text);
+        return Err(response_error("Failed to fetch contracts", response).await);
     }
 
     let contracts: PaginatedContractsResponse = response
@@ -532,29 +650,15 @@ pub async fn lendasat_get_contracts(
 }
 
 /// Get a single contract by ID.
-pub async fn lendasat_get_contract(contract_id: String) -> Result<Contract> {
-    let lock = get_state_lock();
-    let guard = lock.read().await;
-    let state = guard
-        .as_ref()
-        .ok_or_else(|| anyhow!("Lendasat not initialized"))?;
-
-    let headers = get_auth_headers(state).await?;
-
-    let url = format!("{}/api/contracts/{}", state.base_url, contract_id);
-
-    let response = state
-        .http_client
-        .get(&url)
-        .headers(headers)
-        .send()
-        .await
-        .map_err(|e| anyhow!("Failed to fetch contract: {}", e))?;
+pub async fn lendasat_get_contract(contract_id: String) -> Result<Contract, ApiError> {
+    let response = send_authenticated("Failed to fetch contract", |base_url| {
+        let url = format!("{}/api/contracts/{}", base_url, contract_id);
+        http::client(Service::Lendasat).get(&url)
+    })
+    .await?;
 
     if !response.status().is_success() {
-        let status = response.status();
-        let text = response.text().await.unwrap_or_default();
-        bail!("Failed to fetch contract ({}): {}", status, text);
+        return Err(response_error("Failed to fetch contract", response).await);
     }
 
     let contract: Contract = response
@@ -565,6 +669,96 @@ pub async fn lendasat_get_contract(contract_id: String) -> Result<Contract> {
     Ok(contract)
 }
 
+/// Stream changes to the user's contracts.
+///
+/// Contracts are polled every `interval_secs`. On start every contract is
+/// emitted once; afterwards an update is emitted whenever a contract's
+/// status, installments or transactions change, or a new contract appears.
+/// Every poll ends with a `Polled` event. Runs until the stream is closed.
+pub async fn lendasat_subscribe_contracts(
+    interval_secs: u64,
+    sink: StreamSink<ContractWatchEvent>,
+) -> Result<(), ApiError> {
+    let interval = std::time::Duration::from_secs(interval_secs.max(5));
+    let mut known: HashMap<String, Contract> = HashMap::new();
+
+    loop {
+        let contracts = match fetch_all_contracts(None).await {
+            Ok(contracts) => {
+                let count = contracts.len() as u32;
+                for contract in contracts {
+                    let Some(update) = watch::diff(known.get(&contract.id), &contract) else {
+                        continue;
+                    };
+
+                    tracing::debug!(
+                        "Contract {} changed: {:?} -> {:?}",
+                        update.contract_id,
+                        update.previous_status,
+                        update.status
+                    );
+                    if sink.add(ContractWatchEvent::Update(update)).is_err() {
+                        tracing::debug!("Contract stream closed");
+                        return Ok(());
+                    }
+                    known.insert(contract.id.clone(), contract);
+                }
+                Some(count)
+            }
+            Err(e) => {
+                tracing::warn!("Failed to poll contracts: {}", e);
+                None
+            }
+        };
+
+        if sink
+            .add(ContractWatchEvent::Polled(PollRound::now(contracts)))
+            .is_err()
+        {
+            tracing::debug!("Contract stream closed");
+            return Ok(());
+        }
+
+        tokio::time::sleep(interval).await;
+    }
+}
+
+/// Fetch every page of the user's contracts, optionally only those in `status`.
+async fn fetch_all_contracts(
+    status: Option<Vec<ContractStatus>>,
+) -> Result<Vec<Contract>, ApiError> {
+    let mut contracts = Vec::new();
+    let mut page = 1;
+
+    loop {
+        let filters = ContractFilters {
+            page: Some(page),
+            limit: Some(100),
+            status: status.clone(),
+            ..Default::default()
+        };
+        let response = fetch_contracts(Some(filters)).await?;
+        contracts.extend(response.data);
+
+        if page >= response.total_pages {
+            return Ok(contracts);
+        }
+        page += 1;
+    }
+}
+
+/// Check that a contract's collateral address matches its terms.
+///
+/// The address is rebuilt locally from the borrower and lender keys and the
+/// contract's timelocks. The result is advisory: the collateral tree is not
+/// confirmed against a real contract yet, so funding only refuses contracts
+/// locked to another borrower key.
+pub async fn lendasat_verify_collateral(contract_id: String) -> Result<CollateralCheck, ApiError> {
+    let contract = lendasat_get_contract(contract_id).await?;
+    let server_info = crate::ark::client::info()?;
+    Ok(collateral::check(&contract, &server_info))
+}
+
 /// Create a new loan contract by taking an offer.
 ///
 /// IMPORTANT: This function uses the Ark identity public key as `borrower_pk`.
@@ -581,9 +775,7 @@ pub async fn lendasat_create_contract(
     loan_amount: f64,
     duration_days: i32,
     borrower_loan_address: Option<String>,
-) -> Result<Contract> {
-    let lock = get_state_lock();
-
+) -> Result<Contract, ApiError> {
     // CRITICAL: Use Ark identity pubkey for borrower_pk, NOT the Lendasat derivation path key!
     // The collateral VTXO is locked to the Ark identity, so claim PSBTs expect this key.
     let ark_identity_pubkey = get_ark_identity_pubkey().await?;
@@ -607,15 +799,6 @@ pub async fn lendasat_create_contract(
     tracing::info!("Derivation path (for auth only): {}", derivation_path);
     tracing::info!("============================================");
 
-    let guard = lock.read().await;
-    let state = guard
-        .as_ref()
-        .ok_or_else(|| anyhow!("Lendasat not initialized"))?;
-
-    let headers = get_auth_headers(state).await?;
-
-    let url = format!("{}/api/contracts", state.base_url);
-
     let request = CreateContractRequest {
         id: offer_id,
         borrower_btc_address: borrower_btc_address.clone(),
@@ -635,19 +818,14 @@ pub async fn lendasat_create_contract(
         &request.borrower_btc_address[..20]
     );
 
-    let response = state
-        .http_client
-        .post(&url)
-        .headers(headers)
-        .json(&request)
-        .send()
-        .await
-        .map_err(|e| anyhow!("Failed to create contract: {}", e))?;
+    let response = send_authenticated("Failed to create contract", |base_url| {
+        let url = format!("{}/api/contracts", base_url);
+        http::client(Service::Lendasat).post(&url).json(&request)
+    })
+    .await?;
 
     if !response.status().is_success() {
-        let status = response.status();
-        let text = response.text().await.unwrap_or_default();
-        bail!("Failed to create contract ({}): {}", status, text);
+        return Err(response_error("Failed to create contract", response).await);
     }
 
     let contract: Contract = response
@@ -655,6 +833,29 @@ pub async fn lendasat_create_contract(
         .await
         .map_err(|e| anyhow!("Failed to parse contract: {}", e))?;
 
+    // Never hand out a contract locked to a key the wallet cannot sign with.
+    // The address is usually assigned later and checked again before funding.
+    let verified = match contract.contract_address {
+        Some(_) => crate::ark::client::info().and_then(|server_info| {
+            collateral::funding_address(&contract, &server_info).map(|_| ())
+        }),
+        None => signer::public_key(&SigningKey::Identity)
+            .and_then(|identity| collateral::check_borrower(&contract, &identity)),
+    };
+    if let Err(e) = verified {
+        tracing::error!(contract_id = %contract.id, "Created contract failed verification: {e:#}");
+        return Err(anyhow!(
+            "Contract {} does not match the requested terms, do not fund it: {}",
+            contract.id,
+            e
+        )
+        .into());
+    }
+
+    // The server echoes the address back, but only our copy can be trusted later
+    let data_dir = lendasat_data_dir().await?;
+    storage::save_payout_address(&data_dir, &contract.id, &borrower_btc_address)?;
+
     tracing::info!("=== CONTRACT CREATED SUCCESSFULLY ===");
     tracing::info!("Contract ID: {}", contract.id);
     tracing::info!("borrower_pk used: {} (Ark identity)", &ark_identity_pubkey);
@@ -665,32 +866,626 @@ pub async fn lendasat_create_contract(
 }
 
 /// Cancel a requested contract.
-pub async fn lendasat_cancel_contract(contract_id: String) -> Result<()> {
-    let lock = get_state_lock();
-    let guard = lock.read().await;
-    let state = guard
-        .as_ref()
-        .ok_or_else(|| anyhow!("Lendasat not initialized"))?;
+pub async fn lendasat_cancel_contract(contract_id: String) -> Result<(), ApiError> {
+    let response = send_authenticated("Failed to cancel contract", |base_url| {
+        let url = format!("{}/api/contracts/{}", base_url, contract_id);
+        http::client(Service::Lendasat).delete(&url)
+    })
+    .await?;
+
+    if !response.status().is_success() {
+        return Err(response_error("Failed to cancel contract", response).await);
+    }
+
+    tracing::info!("Cancelled contract: {}", contract_id);
+
+    Ok(())
+}
+
+// ============================================================================
+// Extension
+// ============================================================================
+
+/// Quote extending a contract by `extension_days`.
+///
+/// Uses the contract's extension rate and maximum duration; no request is
+/// sent to the lender. The origination fee is estimated from the original
+/// contract's fee rate, the extended contract holds the final terms.
+pub async fn lendasat_quote_extension(
+    contract_id: String,
+    extension_days: i32,
+) -> Result<ExtensionQuote, ApiError> {
+    let contract = lendasat_get_contract(contract_id).await?;
+    Ok(extension::quote(&contract, extension_days)?)
+}
 
-    let headers = get_auth_headers(state).await?;
+/// Request an extension of a contract by `extension_days`.
+///
+/// Returns the new contract, which references the extended one through
+/// `extends_contract`. Sent as `POST /api/contracts/{id}/extend`; the route is
+/// not documented in any Lendasat API description this repo has, so a server
+/// that does not know it is reported as not supporting extensions.
+pub async fn lendasat_request_extension(
+    contract_id: String,
+    extension_days: i32,
+) -> Result<Contract, ApiError> {
+    let contract = lendasat_get_contract(contract_id.clone()).await?;
+    extension::quote(&contract, extension_days)?;
+
+    let request = ExtendContractRequest {
+        new_duration: extension_days,
+    };
 
-    let url = format!("{}/api/contracts/{}", state.base_url, contract_id);
+    let response = send_authenticated("Failed to extend contract", |base_url| {
+        let url = format!("{}/api/contracts/{}/extend", base_url, contract_id);
+        http::client(Service::Lendasat).post(&url).json(&request)
+    })
+    .await?;
 
-    let response = state
-        .http_client
-        .delete(&url)
-        .headers(headers)
-        .send()
+    if unknown_route(response.status()) {
+        return Err(anyhow!("Lendasat does not support extending contracts").into());
+    }
+    if !response.status().is_success() {
+        return Err(response_error("Failed to extend contract", response).await);
+    }
+
+    let extended: Contract = response
+        .json()
         .await
-        .map_err(|e| anyhow!("Failed to cancel contract: {}", e))?;
+        .map_err(|e| anyhow!("Failed to parse contract: {}", e))?;
+
+    tracing::info!(
+        "Extended contract {} by {} days: {}",
+        contract_id,
+        extension_days,
+        extended.id
+    );
+
+    Ok(extended)
+}
+
+/// Upper bound on contracts followed in each direction of an extension chain.
+const MAX_EXTENSION_CHAIN: usize = 32;
+
+/// Get the chain of extensions a contract belongs to, oldest first.
+pub async fn lendasat_get_contract_chain(
+    contract_id: String,
+) -> Result<Vec<ContractSummary>, ApiError> {
+    let contract = lendasat_get_contract(contract_id).await?;
+
+    let mut chain = vec![ContractSummary::from(&contract)];
+
+    let mut previous = contract.extends_contract.clone();
+    for _ in 0..MAX_EXTENSION_CHAIN {
+        let Some(id) = previous else { break };
+        let c = lendasat_get_contract(id).await?;
+        previous = c.extends_contract.clone();
+        chain.insert(0, ContractSummary::from(&c));
+    }
+
+    let mut next = contract.extended_by_contract.clone();
+    for _ in 0..MAX_EXTENSION_CHAIN {
+        let Some(id) = next else { break };
+        let c = lendasat_get_contract(id).await?;
+        next = c.extended_by_contract.clone();
+        chain.push(ContractSummary::from(&c));
+    }
+
+    Ok(chain)
+}
+
+// ============================================================================
+// LTV Monitoring
+// ============================================================================
+
+/// Current LTV of a contract at the live BTC price.
+pub async fn lendasat_get_ltv(contract_id: String) -> Result<LtvStatus, ApiError> {
+    let contract = lendasat_get_contract(contract_id).await?;
+    let price = btc_price(contract.loan_asset.currency()).await?;
+    Ok(ltv::assess(&contract, price))
+}
+
+/// Watch the LTV of all active contracts and stream alerts.
+///
+/// Every `interval_secs` the active contracts and the BTC price are fetched
+/// and each contract is assessed locally. An alert is emitted whenever a
+/// contract moves into a different band, and on start for contracts that
+/// are already past a threshold. Every check ends with a `Checked` event.
+/// Runs until the stream is closed.
+pub async fn lendasat_monitor_ltv(
+    interval_secs: u64,
+    sink: StreamSink<LtvMonitorEvent>,
+) -> Result<(), ApiError> {
+    let interval = std::time::Duration::from_secs(interval_secs.max(10));
+    let mut levels: HashMap<String, LtvLevel> = HashMap::new();
+
+    loop {
+        let contracts = match assess_active_contracts().await {
+            Ok(statuses) => {
+                let count = statuses.len() as u32;
+                for status in statuses {
+                    let previous = levels.insert(status.contract_id.clone(), status.level);
+                    let Some(alert) = ltv::alert(previous, status) else {
+                        continue;
+                    };
+
+                    tracing::info!(
+                        "Contract {} LTV {:.3} moved to {:?}",
+                        alert.status.contract_id,
+                        alert.status.ltv,
+                        alert.status.level
+                    );
+                    if sink.add(LtvMonitorEvent::Alert(alert)).is_err() {
+                        tracing::debug!("LTV monitor stream closed");
+                        return Ok(());
+                    }
+                }
+                Some(count)
+            }
+            Err(e) => {
+                tracing::warn!("LTV check failed: {}", e);
+                None
+            }
+        };
+
+        if sink
+            .add(LtvMonitorEvent::Checked(PollRound::now(contracts)))
+            .is_err()
+        {
+            tracing::debug!("LTV monitor stream closed");
+            return Ok(());
+        }
+
+        tokio::time::sleep(interval).await;
+    }
+}
+
+async fn assess_active_contracts() -> Result<Vec<LtvStatus>, ApiError> {
+    let contracts = fetch_all_contracts(Some(vec![
+        ContractStatus::CollateralConfirmed,
+        ContractStatus::PrincipalGiven,
+        ContractStatus::RepaymentProvided,
+        ContractStatus::Undercollateralized,
+    ]))
+    .await?;
+
+    let mut prices: HashMap<&'static str, f64> = HashMap::new();
+    let mut statuses = Vec::new();
+    for contract in contracts.iter().filter(|c| c.status.is_active()) {
+        let currency = contract.loan_asset.currency();
+        let price = match prices.get(currency) {
+            Some(price) => *price,
+            None => {
+                let price = btc_price(currency).await?;
+                prices.insert(currency, price);
+                price
+            }
+        };
+        statuses.push(ltv::assess(contract, price));
+    }
+
+    Ok(statuses)
+}
+
+/// Live BTC price in `currency`.
+///
+/// Uses the mempool.space price feed, converting through USD exchange rates
+/// for currencies it does not quote.
+async fn btc_price(currency: &str) -> Result<f64> {
+    let prices = crate::api::mempool_api::get_prices().await?;
+
+    match (currency, prices.eur) {
+        ("USD", _) => Ok(prices.usd),
+        ("EUR", Some(eur)) => Ok(eur),
+        _ => {
+            let rates = crate::models::exchange_rates::fetch_exchange_rates().await?;
+            let rate = rates
+                .rates
+                .get(currency)
+                .ok_or_else(|| anyhow!("No exchange rate for {}", currency))?;
+            Ok(prices.usd * rate)
+        }
+    }
+}
+
+// ============================================================================
+// Collateral Funding
+// ============================================================================
+
+/// How often the contract is polled after funding.
+const FUNDING_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5);
 
+/// How long to wait for the server to see the collateral deposit.
+const FUNDING_POLL_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(180);
+
+/// Serializes collateral deposits so a repeated call cannot fund a contract twice.
+static FUNDING_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+/// Deposit the collateral of an approved contract from the Ark wallet.
+///
+/// Sends the outstanding collateral plus origination fee to the contract
+/// address after checking that it is locked to the wallet's key (see
+/// `lendasat_verify_collateral`).
+/// Arkade collateral is paid from VTXOs, Bitcoin collateral through a
+/// collaborative redemption; confirmed boarding funds are settled into VTXOs
+/// first if the offchain balance falls short. The funding transaction is
+/// labelled with the contract, then the contract is polled until the server
+/// has seen the deposit.
+///
+/// Calling this again for a contract that already has a recorded funding
+/// transaction does not send again; it waits for that deposit instead. The
+/// deposit is recorded before sending, so an interrupted send is looked up
+/// in the wallet history before anything is sent again.
+pub async fn lendasat_fund_collateral(contract_id: String) -> Result<CollateralFunding, ApiError> {
+    use crate::ark::tx_metadata_db::{DestinationType, TxMetadataUpdate, record_send};
+
+    let _guard = FUNDING_LOCK.lock().await;
+
+    let contract = lendasat_get_contract(contract_id.clone()).await?;
+    if contract.status != ContractStatus::Approved {
+        return Err(anyhow!(
+            "Contract {} cannot be funded in status {:?}",
+            contract_id,
+            contract.status
+        )
+        .into());
+    }
+
+    let server_info = crate::ark::client::info()?;
+    let address = collateral::funding_address(&contract, &server_info)
+        .map_err(|e| anyhow!("Refusing to fund contract {}: {}", contract_id, e))?;
+
+    let deposited = contract.deposited_sats.max(0) as u64;
+    let amount_sats = contract
+        .required_collateral_sats()
+        .saturating_sub(deposited);
+    if amount_sats == 0 {
+        return Err(anyhow!("Contract {} is already funded", contract_id).into());
+    }
+
+    let data_dir = lendasat_data_dir().await?;
+    let txid = match recorded_funding(&data_dir, &contract_id).await? {
+        Some(txid) => {
+            tracing::info!(
+                "Contract {} was already funded in {}, waiting for the deposit",
+                contract_id,
+                txid
+            );
+            txid
+        }
+        None => {
+            let fee_sats = match contract.collateral_asset {
+                CollateralAsset::ArkadeBtc => 0,
+                CollateralAsset::BitcoinBtc => {
+                    crate::ark::client::estimate_onchain_fee(address.clone(), amount_sats)
+                        .await?
+                        .fee_sats
+                }
+            };
+            ensure_collateral_funds(&contract, amount_sats + fee_sats).await?;
+
+            tracing::info!(
+                "Funding contract {} with {} sats to {}",
+                contract_id,
+                amount_sats,
+                address
+            );
+
+            // Saved before sending: a retry looks for this payment instead of paying again
+            let mut marker = storage::FundingMarker {
+                address: address.clone(),
+                amount_sats,
+                started_at: now_unix(),
+                txid: None,
+            };
+            storage::save_funding(&data_dir, &contract_id, &marker)?;
+
+            let txid = crate::ark::client::send(
+                address.clone(),
+                bitcoin::Amount::from_sat(amount_sats),
+                None,
+            )
+            .await?
+            .to_string();
+
+            marker.txid = Some(txid.clone());
+            storage::save_funding(&data_dir, &contract_id, &marker)?;
+
+            record_send(
+                &txid,
+                TxMetadataUpdate {
+                    destination_type: Some(DestinationType::LendasatCollateral),
+                    reference: Some(contract_id.clone()),
+                    ..Default::default()
+                },
+            )
+            .await;
+
+            txid
+        }
+    };
+
+    let started = std::time::Instant::now();
+    let status = loop {
+        match lendasat_get_contract(contract_id.clone()).await {
+            Ok(contract) if contract.status.is_collateral_seen() => break contract.status,
+            Ok(contract) if started.elapsed() >= FUNDING_POLL_TIMEOUT => break contract.status,
+            Ok(_) => {}
+            Err(e) if started.elapsed() >= FUNDING_POLL_TIMEOUT => {
+                tracing::warn!("Could not refresh contract {}: {}", contract_id, e);
+                break contract.status;
+            }
+            Err(e) => tracing::debug!("Could not refresh contract {}: {}", contract_id, e),
+        }
+        tokio::time::sleep(FUNDING_POLL_INTERVAL).await;
+    };
+
+    Ok(CollateralFunding {
+        txid,
+        address,
+        amount_sats,
+        collateral_seen: status.is_collateral_seen(),
+        status,
+    })
+}
+
+/// The collateral deposit already sent for an approved contract, if any.
+///
+/// Funding is only possible before the server has seen collateral, so any
+/// collateral transaction recorded for the contract is its funding. A funding
+/// that was started but never got a txid, e.g. because the app stopped during
+/// the send, is looked up in the wallet history and at the contract address.
+async fn recorded_funding(data_dir: &str, contract_id: &str) -> Result<Option<String>> {
+    use crate::ark::tx_metadata_db::{self, DestinationType};
+
+    let mut marker = match storage::load_funding(data_dir, contract_id)? {
+        Some(marker) => marker,
+        None => {
+            let records = tx_metadata_db::get_db()?
+                .find_by_reference(contract_id)
+                .await?;
+            return Ok(records
+                .into_iter()
+                .find(|record| record.destination_type == Some(DestinationType::LendasatCollateral))
+                .map(|record| record.txid));
+        }
+    };
+    if marker.txid.is_some() {
+        return Ok(marker.txid);
+    }
+
+    let amount = bitcoin::Amount::from_sat(marker.amount_sats);
+    match crate::ark::client::find_payment(&marker.address, amount, marker.started_at).await? {
+        Some(txid) => {
+            tracing::info!(
+                "Found interrupted funding {} of contract {}",
+                txid,
+                contract_id
+            );
+            marker.txid = Some(txid.to_string());
+            storage::save_funding(data_dir, contract_id, &marker)?;
+            Ok(marker.txid)
+        }
+        None => {
+            tracing::warn!(
+                "Funding of contract {} never reached {}, sending it again",
+                contract_id,
+                marker.address
+            );
+            Ok(None)
+        }
+    }
+}
+
+/// Make sure the wallet can pay `needed_sats` of collateral.
+///
+/// Bitcoin collateral may also come from confirmed boarding funds: when the
+/// offchain balance falls short but boarding outputs cover the rest, they are
+/// settled into VTXOs before sending.
+async fn ensure_collateral_funds(contract: &Contract, needed_sats: u64) -> Result<(), ApiError> {
+    let balance = crate::ark::client::balance().await?;
+    let offchain_sats = (balance.offchain.confirmed() + balance.offchain.pre_confirmed()).to_sat();
+    if offchain_sats >= needed_sats {
+        return Ok(());
+    }
+
+    let boarding_sats = match contract.collateral_asset {
+        CollateralAsset::ArkadeBtc => 0,
+        CollateralAsset::BitcoinBtc => crate::ark::client::get_boarding_utxos()
+            .await?
+            .iter()
+            .filter(|utxo| utxo.is_confirmed)
+            .map(|utxo| utxo.amount.to_sat())
+            .sum(),
+    };
+    let available_sats = offchain_sats + boarding_sats;
+    if available_sats < needed_sats {
+        return Err(ApiError::InsufficientFunds {
+            needed_sats,
+            available_sats,
+        });
+    }
+
+    tracing::info!(
+        "Settling {} sats of boarding funds to fund contract {}",
+        boarding_sats,
+        contract.id
+    );
+    crate::ark::client::settle_boarding().await?;
+
+    Ok(())
+}
+
+// ============================================================================
+// Collateral Top-up
+// ============================================================================
+
+/// Add collateral to an active contract to bring it back to `target_ltv`.
+///
+/// Computes the sats needed at the live BTC price and, unless `dry_run` is
+/// set, sends them from the Ark wallet to the contract address and
+/// reports the deposit to Lendasat. If the report fails after the send, it is
+/// queued for replay and `reported` is false. A dry run returns the same
+/// figures without sending anything.
+pub async fn lendasat_top_up_collateral(
+    contract_id: String,
+    target_ltv: f64,
+    dry_run: bool,
+) -> Result<CollateralTopUp, ApiError> {
+    use crate::ark::tx_metadata_db::{DestinationType, TxMetadataUpdate, record_send};
+
+    let contract = lendasat_get_contract(contract_id.clone()).await?;
+    if !contract.status.is_active() {
+        return Err(anyhow!(
+            "Contract {} cannot be topped up in status {:?}",
+            contract_id,
+            contract.status
+        )
+        .into());
+    }
+
+    let max_ltv = [
+        contract.ltv_threshold_margin_call_1,
+        contract.ltv_threshold_margin_call_2,
+        contract.ltv_threshold_liquidation,
+    ]
+    .into_iter()
+    .filter(|threshold| *threshold > 0.0)
+    .fold(1.0, f64::min);
+    if target_ltv <= 0.0 || target_ltv >= max_ltv {
+        return Err(anyhow!("Target LTV must be between 0 and {}", max_ltv).into());
+    }
+
+    let btc_price = btc_price(contract.loan_asset.currency()).await?;
+    let current_ltv = ltv::ltv(
+        contract.balance_outstanding,
+        contract.collateral_sats,
+        btc_price,
+    );
+    let amount_sats = ltv::collateral_for_ltv(contract.balance_outstanding, btc_price, target_ltv)
+        .saturating_sub(contract.collateral_sats.max(0) as u64);
+    if amount_sats == 0 {
+        return Err(anyhow!(
+            "Contract {} is already below an LTV of {}",
+            contract_id,
+            target_ltv
+        )
+        .into());
+    }
+    let resulting_ltv = ltv::ltv(
+        contract.balance_outstanding,
+        contract.collateral_sats + amount_sats as i64,
+        btc_price,
+    );
+
+    let server_info = crate::ark::client::info()?;
+    let address = collateral::funding_address(&contract, &server_info)
+        .map_err(|e| anyhow!("Refusing to top up contract {}: {}", contract_id, e))?;
+
+    let fee_sats = match contract.collateral_asset {
+        CollateralAsset::ArkadeBtc => 0,
+        CollateralAsset::BitcoinBtc => {
+            crate::ark::client::estimate_onchain_fee(address.clone(), amount_sats)
+                .await?
+                .fee_sats
+        }
+    };
+
+    let mut top_up = CollateralTopUp {
+        contract_id: contract_id.clone(),
+        dry_run,
+        btc_price,
+        current_ltv,
+        target_ltv,
+        resulting_ltv,
+        amount_sats,
+        fee_sats,
+        address: address.clone(),
+        txid: None,
+        reported: false,
+    };
+    if dry_run {
+        return Ok(top_up);
+    }
+
+    let balance = crate::ark::client::balance().await?;
+    let available_sats = (balance.offchain.confirmed() + balance.offchain.pre_confirmed()).to_sat();
+    if available_sats < amount_sats + fee_sats {
+        return Err(ApiError::InsufficientFunds {
+            needed_sats: amount_sats + fee_sats,
+            available_sats,
+        });
+    }
+
+    tracing::info!(
+        "Topping up contract {} with {} sats to {}",
+        contract_id,
+        amount_sats,
+        address
+    );
+
+    let txid = crate::ark::client::send(address, bitcoin::Amount::from_sat(amount_sats), None)
+        .await?
+        .to_string();
+
+    record_send(
+        &txid,
+        TxMetadataUpdate {
+            destination_type: Some(DestinationType::LendasatCollateral),
+            reference: Some(contract_id.clone()),
+            ..Default::default()
+        },
+    )
+    .await;
+
+    let action = QueuedAction::ReportCollateralTopUp {
+        contract_id: contract_id.clone(),
+        txid: txid.clone(),
+        amount_sats,
+    };
+    match lendasat_report_collateral_top_up(contract_id, txid.clone(), amount_sats).await {
+        Ok(()) => top_up.reported = true,
+        Err(e) => {
+            // The collateral is already sent, so the report must not get lost
+            // whatever the reason it failed
+            offline::defer(&action).await;
+            tracing::warn!("Could not report collateral top-up {}: {}", txid, e);
+        }
+    }
+
+    top_up.txid = Some(txid);
+    Ok(top_up)
+}
+
+/// Report collateral sent to a contract outside the initial deposit.
+///
+/// Sent as `POST /api/contracts/{id}/collateral-top-up`, which, like the
+/// extension route, is not documented in any Lendasat API description this
+/// repo has.
+pub async fn lendasat_report_collateral_top_up(
+    contract_id: String,
+    txid: String,
+    amount_sats: u64,
+) -> Result<(), ApiError> {
+    let request = CollateralTopUpRequest { txid, amount_sats };
+
+    let response = send_authenticated("Failed to report collateral top-up", |base_url| {
+        let url = format!(
+            "{}/api/contracts/{}/collateral-top-up",
+            base_url, contract_id
+        );
+        http::client(Service::Lendasat).post(&url).json(&request)
+    })
+    .await?;
+
+    if unknown_route(response.status()) {
+        return Err(anyhow!("Lendasat does not support reporting collateral top-ups").into());
+    }
     if !response.status().is_success() {
-        let status = response.status();
-        let text = response.text().await.unwrap_or_default();
-        bail!("Failed to cancel contract ({}): {}", status, text);
+        return Err(response_error("Failed to report collateral top-up", response).await);
     }
 
-    tracing::info!("Cancelled contract: {}", contract_id);
+    tracing::info!("Reported collateral top-up for contract: {}", contract_id);
 
     Ok(())
 }
@@ -699,44 +1494,46 @@ pub async fn lendasat_cancel_contract(contract_id: String) -> Result<()> {
 // Repayment
 // ============================================================================
 
-/// Mark an installment as paid.
+/// Mark an installment as paid. Queued for later if Lendasat cannot be reached.
 pub async fn lendasat_mark_installment_paid(
     contract_id: String,
     installment_id: String,
     payment_txid: String,
-) -> Result<()> {
-    let lock = get_state_lock();
-    let guard = lock.read().await;
-    let state = guard
-        .as_ref()
-        .ok_or_else(|| anyhow!("Lendasat not initialized"))?;
-
-    let headers = get_auth_headers(state).await?;
+) -> Result<(), ApiError> {
+    let action = QueuedAction::MarkInstallmentPaid {
+        contract_id: contract_id.clone(),
+        installment_id: installment_id.clone(),
+        payment_txid: payment_txid.clone(),
+    };
 
-    let url = format!(
-        "{}/api/contracts/{}/installment-paid",
-        state.base_url, contract_id
-    );
+    match mark_installment_paid(contract_id, installment_id, payment_txid).await {
+        Ok(()) => Ok(()),
+        Err(e) => Err(offline::defer_if_offline(action, e).await),
+    }
+}
 
+async fn mark_installment_paid(
+    contract_id: String,
+    installment_id: String,
+    payment_txid: String,
+) -> Result<(), ApiError> {
     let request = InstallmentPaidRequest {
         installment_id,
         payment_id: payment_txid,
         amount: None, // Server will verify amount from txid
     };
 
-    let response = state
-        .http_client
-        .put(&url)
-        .headers(headers)
-        .json(&request)
-        .send()
-        .await
-        .map_err(|e| anyhow!("Failed to mark installment paid: {}", e))?;
+    let response = send_authenticated("Failed to mark installment paid", |base_url| {
+        let url = format!(
+            "{}/api/contracts/{}/installment-paid",
+            base_url, contract_id
+        );
+        http::client(Service::Lendasat).put(&url).json(&request)
+    })
+    .await?;
 
     if !response.status().is_success() {
-        let status = response.status();
-        let text = response.text().await.unwrap_or_default();
-        bail!("Failed to mark installment paid ({}): {}", status, text);
+        return Err(response_error("Failed to mark installment paid", response).await);
     }
 
     tracing::info!("Marked installment as paid for contract: {}", contract_id);
@@ -744,40 +1541,108 @@ pub async fn lendasat_mark_installment_paid(
     Ok(())
 }
 
-// ============================================================================
-// Claim Collateral
-// ============================================================================
+/// Repay the next installment of a stablecoin loan from the Ark wallet.
+///
+/// Starts or resumes a repayment: quotes the next installment, swaps BTC to
+/// the loan asset at `loan_repayment_address` through LendaSwap, claims it via
+/// Gelato and marks the installment paid with the EVM claim transaction.
+/// Runs as many steps as possible and returns the reached state; while the
+/// swap is still processing, call again later to finish it. Requires the
+/// LendaSwap client to be initialized.
+pub async fn lendasat_repay_installment(contract_id: String) -> Result<RepaymentState, ApiError> {
+    let data_dir = lendasat_data_dir().await?;
+    let _guard = repayment::lock(&contract_id).await;
+
+    let state = match repayment::load_state(&data_dir, &contract_id)? {
+        Some(state) if state.step == RepaymentStep::Failed => {
+            return Err(anyhow!(
+                "Previous repayment of contract {} failed: {}",
+                contract_id,
+                state.error.as_deref().unwrap_or("unknown error")
+            )
+            .into());
+        }
+        Some(state) if state.step != RepaymentStep::Completed => state,
+        _ => {
+            let contract = lendasat_get_contract(contract_id.clone()).await?;
+            repayment::quote(&contract)?
+        }
+    };
 
-/// Get the PSBT for claiming collateral (standard Bitcoin).
-pub async fn lendasat_get_claim_psbt(
+    let state = repayment::run(&data_dir, state).await?;
+
+    if state.step != RepaymentStep::Claimed {
+        return Ok(state);
+    }
+    let Some(evm_claim_txid) = state.evm_claim_txid.clone() else {
+        return Ok(state);
+    };
+
+    mark_installment_paid(contract_id, state.installment_id.clone(), evm_claim_txid).await?;
+
+    Ok(repayment::complete(&data_dir, state)?)
+}
+
+/// Get the stored repayment progress of a contract, if any.
+pub async fn lendasat_get_repayment(
     contract_id: String,
-    fee_rate: u32,
-) -> Result<ClaimPsbtResponse> {
+) -> Result<Option<RepaymentState>, ApiError> {
+    let data_dir = lendasat_data_dir().await?;
+    Ok(repayment::load_state(&data_dir, &contract_id)?)
+}
+
+/// Fund the swap of an interrupted repayment again.
+///
+/// A repayment whose funding send was interrupted stops with an error if its
+/// payment cannot be found. Call this only after the user has checked the
+/// wallet history and no payment to the swap went out; the next
+/// `lendasat_repay_installment` then funds the swap.
+pub async fn lendasat_retry_repayment_funding(
+    contract_id: String,
+) -> Result<RepaymentState, ApiError> {
+    let data_dir = lendasat_data_dir().await?;
+    let _guard = repayment::lock(&contract_id).await;
+    Ok(repayment::retry_funding(&data_dir, &contract_id)?)
+}
+
+/// Forget the repayment progress of a contract.
+///
+/// Fails while sats sent to the swap have not been paid out yet.
+pub async fn lendasat_clear_repayment(contract_id: String) -> Result<(), ApiError> {
+    let data_dir = lendasat_data_dir().await?;
+    let _guard = repayment::lock(&contract_id).await;
+    Ok(repayment::clear_state(&data_dir, &contract_id)?)
+}
+
+async fn lendasat_data_dir() -> Result<String, ApiError> {
     let lock = get_state_lock();
     let guard = lock.read().await;
     let state = guard
         .as_ref()
-        .ok_or_else(|| anyhow!("Lendasat not initialized"))?;
-
-    let headers = get_auth_headers(state).await?;
+        .ok_or_else(|| ApiError::not_initialized("Lendasat"))?;
+    Ok(state.data_dir.clone())
+}
 
-    let url = format!(
-        "{}/api/contracts/{}/claim?fee_rate={}",
-        state.base_url, contract_id, fee_rate
-    );
+// ============================================================================
+// Claim Collateral
+// ============================================================================
 
-    let response = state
-        .http_client
-        .get(&url)
-        .headers(headers)
-        .send()
-        .await
-        .map_err(|e| anyhow!("Failed to get claim PSBT: {}", e))?;
+/// Get the PSBT for claiming collateral (standard Bitcoin).
+pub async fn lendasat_get_claim_psbt(
+    contract_id: String,
+    fee_rate: u32,
+) -> Result<ClaimPsbtResponse, ApiError> {
+    let response = send_authenticated("Failed to get claim PSBT", |base_url| {
+        let url = format!(
+            "{}/api/contracts/{}/claim?fee_rate={}",
+            base_url, contract_id, fee_rate
+        );
+        http::client(Service::Lendasat).get(&url)
+    })
+    .await?;
 
     if !response.status().is_success() {
-        let status = response.status();
-        let text = response.text().await.unwrap_or_default();
-        bail!("Failed to get claim PSBT ({}): {}", status, text);
+        return Err(response_error("Failed to get claim PSBT", response).await);
     }
 
     let claim_response: ClaimPsbtResponse = response
@@ -787,6 +1652,27 @@ pub async fn lendasat_get_claim_psbt(
 
     Ok(claim_response)
 }
+
+/// Inspect a claim, recovery or settlement PSBT of a contract before signing it.
+///
+/// Like `analyze_psbt`, with the payout address stored when the contract was
+/// created counted as wallet-owned since that is where the collateral is
+/// returned to. The `borrower_btc_address` reported by the server is not
+/// trusted for this.
+pub async fn lendasat_analyze_psbt(
+    contract_id: String,
+    psbt: String,
+) -> Result<PsbtSummary, ApiError> {
+    let wallet = wallet_view(&contract_id).await?;
+    let psbt = psbt_analysis::decode_psbt(&psbt)?;
+    Ok(psbt_analysis::summarize(
+        &psbt,
+        wallet.network,
+        &wallet.scripts,
+        &wallet.is_wallet_key,
+    ))
+}
+
 /// Finalize a signed PSBT and extract the raw transaction.
 ///
 /// CRITICAL: This step was missing and caused broadcast failures!
@@ -795,56 +1681,51 @@ pub async fn lendasat_get_claim_psbt(
 ///
 /// The LendaSat API's broadcast-claim and broadcast-recover endpoints expect
 /// the RAW TRANSACTION hex, not the signed PSBT hex.
-pub fn lendasat_finalize_psbt(signed_psbt_hex: String) -> Result<String> {
-    auth::finalize_psbt_and_extract_tx(&signed_psbt_hex)
+///
+/// Only PSBTs signed with `lendasat_sign_claim_psbt` or
+/// `lendasat_sign_recover_psbt`, which check them against the local policy,
+/// are finalized.
+pub fn lendasat_finalize_psbt(signed_psbt_hex: String) -> Result<String, ApiError> {
+    let psbt = psbt_analysis::decode_psbt(&signed_psbt_hex)?;
+    policy::ensure_approved(psbt.unsigned_tx.compute_txid(), None)?;
+    Ok(auth::finalize_psbt_and_extract_tx(&signed_psbt_hex)?)
 }
 
 /// Convert a PSBT from BASE64 to HEX format.
 ///
 /// Use this for settle-ark PSBTs which come from the API in BASE64 format
 /// but need to be in HEX format for signing.
-pub fn lendasat_psbt_base64_to_hex(base64_psbt: String) -> Result<String> {
-    auth::psbt_base64_to_hex(&base64_psbt)
+pub fn lendasat_psbt_base64_to_hex(base64_psbt: String) -> Result<String, ApiError> {
+    Ok(auth::psbt_base64_to_hex(&base64_psbt)?)
 }
 
 /// Convert a PSBT from HEX to BASE64 format.
 ///
 /// Use this to convert signed PSBTs back to BASE64 format
 /// for the finish-settle-ark API which expects BASE64.
-pub fn lendasat_psbt_hex_to_base64(hex_psbt: String) -> Result<String> {
-    auth::psbt_hex_to_base64(&hex_psbt)
+pub fn lendasat_psbt_hex_to_base64(hex_psbt: String) -> Result<String, ApiError> {
+    Ok(auth::psbt_hex_to_base64(&hex_psbt)?)
 }
 
 /// Broadcast a signed claim transaction.
-pub async fn lendasat_broadcast_claim_tx(contract_id: String, signed_tx: String) -> Result<String> {
-    let lock = get_state_lock();
-    let guard = lock.read().await;
-    let state = guard
-        .as_ref()
-        .ok_or_else(|| anyhow!("Lendasat not initialized"))?;
-
-    let headers = get_auth_headers(state).await?;
-
-    let url = format!(
-        "{}/api/contracts/{}/broadcast-claim",
-        state.base_url, contract_id
-    );
+///
+/// Refuses transactions that were not signed with `lendasat_sign_claim_psbt`.
+pub async fn lendasat_broadcast_claim_tx(
+    contract_id: String,
+    signed_tx: String,
+) -> Result<String, ApiError> {
+    ensure_approved_tx(&contract_id, &signed_tx)?;
 
     let request = BroadcastTxRequest { tx: signed_tx };
 
-    let response = state
-        .http_client
-        .post(&url)
-        .headers(headers)
-        .json(&request)
-        .send()
-        .await
-        .map_err(|e| anyhow!("Failed to broadcast claim tx: {}", e))?;
+    let response = send_authenticated("Failed to broadcast claim tx", |base_url| {
+        let url = format!("{}/api/contracts/{}/broadcast-claim", base_url, contract_id);
+        http::client(Service::Lendasat).post(&url).json(&request)
+    })
+    .await?;
 
     if !response.status().is_success() {
-        let status = response.status();
-        let text = response.text().await.unwrap_or_default();
-        bail!("Failed to broadcast claim tx ({}): {}", status, text);
+        return Err(response_error("Failed to broadcast claim tx", response).await);
     }
 
     let broadcast_response: BroadcastTxResponse = response
@@ -862,31 +1743,20 @@ pub async fn lendasat_broadcast_claim_tx(contract_id: String, signed_tx: String)
 // ============================================================================
 
 /// Get the PSBTs for claiming Ark collateral.
-pub async fn lendasat_get_claim_ark_psbt(contract_id: String) -> Result<ArkClaimPsbtResponse> {
-    let lock = get_state_lock();
-    let guard = lock.read().await;
-    let state = guard
-        .as_ref()
-        .ok_or_else(|| anyhow!("Lendasat not initialized"))?;
-
-    let headers = get_auth_headers(state).await?;
-
-    let url = format!("{}/api/contracts/{}/claim-ark", state.base_url, contract_id);
-
+pub async fn lendasat_get_claim_ark_psbt(
+    contract_id: String,
+) -> Result<ArkClaimPsbtResponse, ApiError> {
     // Use a longer timeout - this involves communication with Ark server
-    let response = state
-        .http_client
-        .get(&url)
-        .headers(headers)
-        .timeout(std::time::Duration::from_secs(120)) // 2 minutes for Ark operations
-        .send()
-        .await
-        .map_err(|e| anyhow!("Failed to get Ark claim PSBTs: {}", e))?;
+    let response = send_authenticated("Failed to get Ark claim PSBTs", |base_url| {
+        let url = format!("{}/api/contracts/{}/claim-ark", base_url, contract_id);
+        http::client(Service::Lendasat)
+            .get(&url)
+            .timeout(std::time::Duration::from_secs(120)) // 2 minutes for Ark operations
+    })
+    .await?;
 
     if !response.status().is_success() {
-        let status = response.status();
-        let text = response.text().await.unwrap_or_default();
-        bail!("Failed to get Ark claim PSBTs ({}): {}", status, text);
+        return Err(response_error("Failed to get Ark claim PSBTs", response).await);
     }
 
     let claim_response: ArkClaimPsbtResponse = response
@@ -902,40 +1772,27 @@ pub async fn lendasat_broadcast_claim_ark_tx(
     contract_id: String,
     signed_ark_psbt: String,
     signed_checkpoint_psbts: Vec<String>,
-) -> Result<String> {
-    let lock = get_state_lock();
-    let guard = lock.read().await;
-    let state = guard
-        .as_ref()
-        .ok_or_else(|| anyhow!("Lendasat not initialized"))?;
-
-    let headers = get_auth_headers(state).await?;
-
-    let url = format!(
-        "{}/api/contracts/{}/broadcast-claim-ark",
-        state.base_url, contract_id
-    );
-
+) -> Result<String, ApiError> {
     let request = BroadcastArkClaimRequest {
         ark_psbt: signed_ark_psbt,
         checkpoint_psbts: signed_checkpoint_psbts,
     };
 
     // Use a longer timeout for Ark broadcast - involves Ark server communication
-    let response = state
-        .http_client
-        .post(&url)
-        .headers(headers)
-        .json(&request)
-        .timeout(std::time::Duration::from_secs(120)) // 2 minutes for Ark operations
-        .send()
-        .await
-        .map_err(|e| anyhow!("Failed to broadcast Ark claim tx: {}", e))?;
+    let response = send_authenticated("Failed to broadcast Ark claim tx", |base_url| {
+        let url = format!(
+            "{}/api/contracts/{}/broadcast-claim-ark",
+            base_url, contract_id
+        );
+        http::client(Service::Lendasat)
+            .post(&url)
+            .json(&request)
+            .timeout(std::time::Duration::from_secs(120)) // 2 minutes for Ark operations
+    })
+    .await?;
 
     if !response.status().is_success() {
-        let status = response.status();
-        let text = response.text().await.unwrap_or_default();
-        bail!("Failed to broadcast Ark claim tx ({}): {}", status, text);
+        return Err(response_error("Failed to broadcast Ark claim tx", response).await);
     }
 
     let broadcast_response: BroadcastTxResponse = response
@@ -954,34 +1811,20 @@ pub async fn lendasat_broadcast_claim_ark_tx(
 
 /// Get the PSBTs for settling Ark collateral when VTXOs are recoverable.
 /// Use this instead of claim-ark when contract.requires_ark_settlement is true.
-pub async fn lendasat_get_settle_ark_psbt(contract_id: String) -> Result<SettleArkPsbtResponse> {
-    let lock = get_state_lock();
-    let guard = lock.read().await;
-    let state = guard
-        .as_ref()
-        .ok_or_else(|| anyhow!("Lendasat not initialized"))?;
-
-    let headers = get_auth_headers(state).await?;
-
-    let url = format!(
-        "{}/api/contracts/{}/settle-ark",
-        state.base_url, contract_id
-    );
-
+pub async fn lendasat_get_settle_ark_psbt(
+    contract_id: String,
+) -> Result<SettleArkPsbtResponse, ApiError> {
     // Use a longer timeout - this involves communication with Ark server
-    let response = state
-        .http_client
-        .get(&url)
-        .headers(headers)
-        .timeout(std::time::Duration::from_secs(120)) // 2 minutes for Ark operations
-        .send()
-        .await
-        .map_err(|e| anyhow!("Failed to get settle Ark PSBTs: {}", e))?;
+    let response = send_authenticated("Failed to get settle Ark PSBTs", |base_url| {
+        let url = format!("{}/api/contracts/{}/settle-ark", base_url, contract_id);
+        http::client(Service::Lendasat)
+            .get(&url)
+            .timeout(std::time::Duration::from_secs(120)) // 2 minutes for Ark operations
+    })
+    .await?;
 
     if !response.status().is_success() {
-        let status = response.status();
-        let text = response.text().await.unwrap_or_default();
-        bail!("Failed to get settle Ark PSBTs ({}): {}", status, text);
+        return Err(response_error("Failed to get settle Ark PSBTs", response).await);
     }
 
     let settle_response: SettleArkPsbtResponse = response
@@ -997,40 +1840,27 @@ pub async fn lendasat_finish_settle_ark(
     contract_id: String,
     signed_intent_psbt: String,
     signed_forfeit_psbts: Vec<String>,
-) -> Result<String> {
-    let lock = get_state_lock();
-    let guard = lock.read().await;
-    let state = guard
-        .as_ref()
-        .ok_or_else(|| anyhow!("Lendasat not initialized"))?;
-
-    let headers = get_auth_headers(state).await?;
-
-    let url = format!(
-        "{}/api/contracts/{}/finish-settle-ark",
-        state.base_url, contract_id
-    );
-
+) -> Result<String, ApiError> {
     let request = FinishSettleArkRequest {
         intent_psbt: signed_intent_psbt,
         forfeit_psbts: signed_forfeit_psbts,
     };
 
     // Use a longer timeout for settlement - the Ark batch protocol can take time
-    let response = state
-        .http_client
-        .post(&url)
-        .headers(headers)
-        .json(&request)
-        .timeout(std::time::Duration::from_secs(120)) // 2 minutes for Ark settlement
-        .send()
-        .await
-        .map_err(|e| anyhow!("Failed to finish settle Ark: {}", e))?;
+    let response = send_authenticated("Failed to finish settle Ark", |base_url| {
+        let url = format!(
+            "{}/api/contracts/{}/finish-settle-ark",
+            base_url, contract_id
+        );
+        http::client(Service::Lendasat)
+            .post(&url)
+            .json(&request)
+            .timeout(std::time::Duration::from_secs(120)) // 2 minutes for Ark settlement
+    })
+    .await?;
 
     if !response.status().is_success() {
-        let status = response.status();
-        let text = response.text().await.unwrap_or_default();
-        bail!("Failed to finish settle Ark ({}): {}", status, text);
+        return Err(response_error("Failed to finish settle Ark", response).await);
     }
 
     let finish_response: FinishSettleArkResponse = response
@@ -1054,32 +1884,18 @@ pub async fn lendasat_finish_settle_ark(
 pub async fn lendasat_get_recover_psbt(
     contract_id: String,
     fee_rate: u32,
-) -> Result<ClaimPsbtResponse> {
-    let lock = get_state_lock();
-    let guard = lock.read().await;
-    let state = guard
-        .as_ref()
-        .ok_or_else(|| anyhow!("Lendasat not initialized"))?;
-
-    let headers = get_auth_headers(state).await?;
-
-    let url = format!(
-        "{}/api/contracts/{}/recover?fee_rate={}",
-        state.base_url, contract_id, fee_rate
-    );
-
-    let response = state
-        .http_client
-        .get(&url)
-        .headers(headers)
-        .send()
-        .await
-        .map_err(|e| anyhow!("Failed to get recover PSBT: {}", e))?;
+) -> Result<ClaimPsbtResponse, ApiError> {
+    let response = send_authenticated("Failed to get recover PSBT", |base_url| {
+        let url = format!(
+            "{}/api/contracts/{}/recover?fee_rate={}",
+            base_url, contract_id, fee_rate
+        );
+        http::client(Service::Lendasat).get(&url)
+    })
+    .await?;
 
     if !response.status().is_success() {
-        let status = response.status();
-        let text = response.text().await.unwrap_or_default();
-        bail!("Failed to get recover PSBT ({}): {}", status, text);
+        return Err(response_error("Failed to get recover PSBT", response).await);
     }
 
     let recover_response: ClaimPsbtResponse = response
@@ -1090,39 +1906,252 @@ pub async fn lendasat_get_recover_psbt(
     Ok(recover_response)
 }
 
-/// Broadcast a signed recovery transaction.
-pub async fn lendasat_broadcast_recover_tx(
+/// Sign a claim PSBT from `lendasat_get_claim_psbt` with the borrower key.
+///
+/// Refuses to sign unless the PSBT passes the local policy: it only spends the
+/// contract's collateral, returns the collateral to the wallet and pays a fee
+/// close to `fee_rate`. Returns the signed PSBT as hex, ready for
+/// `lendasat_finalize_psbt`.
+pub async fn lendasat_sign_claim_psbt(
     contract_id: String,
-    signed_tx: String,
-) -> Result<String> {
-    let lock = get_state_lock();
-    let guard = lock.read().await;
-    let state = guard
-        .as_ref()
-        .ok_or_else(|| anyhow!("Lendasat not initialized"))?;
+    psbt: String,
+    fee_rate: u32,
+) -> Result<String, ApiError> {
+    sign_collateral_spend(contract_id, psbt, fee_rate).await
+}
+
+/// Sign a recovery PSBT from `lendasat_get_recover_psbt` with the borrower key,
+/// under the same policy as `lendasat_sign_claim_psbt`.
+pub async fn lendasat_sign_recover_psbt(
+    contract_id: String,
+    psbt: String,
+    fee_rate: u32,
+) -> Result<String, ApiError> {
+    sign_collateral_spend(contract_id, psbt, fee_rate).await
+}
+
+async fn sign_collateral_spend(
+    contract_id: String,
+    psbt: String,
+    fee_rate: u32,
+) -> Result<String, ApiError> {
+    let contract = lendasat_get_contract(contract_id).await?;
+    let mut psbt = psbt_analysis::decode_psbt(&psbt)?;
+
+    let wallet = wallet_view(&contract.id).await?;
+    if let Err(e) = policy::check_collateral_spend(&contract, &psbt, fee_rate, &wallet) {
+        return Err(refusal(&contract, e).await);
+    }
+
+    let key = SigningKey::Pubkey {
+        pubkey: contract.borrower_pk.clone(),
+    };
+    signer::sign_onchain_psbt(&mut psbt, Some(&key))?;
+    policy::approve(&contract.id, psbt.unsigned_tx.compute_txid());
+
+    tracing::info!("Signed collateral spend of contract {}", contract.id);
+
+    Ok(hex::encode(psbt.serialize()))
+}
+
+/// Sign the PSBTs from `lendasat_get_claim_ark_psbt` with the Ark identity.
+///
+/// Refuses to sign unless the checkpoints spend the contract's collateral and
+/// the Ark transaction returns it to the wallet. Returns the signed PSBTs as
+/// hex, ready for `lendasat_broadcast_claim_ark_tx`.
+pub async fn lendasat_sign_claim_ark_psbt(
+    contract_id: String,
+    ark_psbt: String,
+    checkpoint_psbts: Vec<String>,
+) -> Result<SignedArkClaim, ApiError> {
+    let contract = lendasat_get_contract(contract_id).await?;
+    let mut ark_psbt = psbt_analysis::decode_psbt(&ark_psbt)?;
+    let mut checkpoint_psbts = checkpoint_psbts
+        .iter()
+        .map(|psbt| psbt_analysis::decode_psbt(psbt))
+        .collect::<Result<Vec<_>>>()?;
+
+    let wallet = wallet_view(&contract.id).await?;
+    if let Err(e) = policy::check_ark_claim(&contract, &ark_psbt, &checkpoint_psbts, &wallet) {
+        return Err(refusal(&contract, e).await);
+    }
+
+    signer::sign_ark_psbt_with_identity(&mut ark_psbt).await?;
+    for psbt in checkpoint_psbts.iter_mut() {
+        signer::sign_ark_psbt_with_identity(psbt).await?;
+    }
+
+    tracing::info!("Signed Ark claim of contract {}", contract.id);
+
+    Ok(SignedArkClaim {
+        ark_psbt: hex::encode(ark_psbt.serialize()),
+        checkpoint_psbts: checkpoint_psbts
+            .iter()
+            .map(|psbt| hex::encode(psbt.serialize()))
+            .collect(),
+    })
+}
+
+/// Sign the PSBTs from `lendasat_get_settle_ark_psbt` with the Ark identity.
+///
+/// Refuses to sign unless the intent only spends the contract's collateral and
+/// registers outputs of the wallet. Takes and returns base64, as used by the
+/// settle-ark endpoints.
+pub async fn lendasat_sign_settle_ark_psbt(
+    contract_id: String,
+    intent_psbt: String,
+    forfeit_psbts: Vec<String>,
+) -> Result<SignedArkSettlement, ApiError> {
+    use base64::Engine as _;
+    use base64::engine::general_purpose::STANDARD as BASE64;
+
+    let contract = lendasat_get_contract(contract_id).await?;
+    let mut intent_psbt = psbt_analysis::decode_psbt(&intent_psbt)?;
+    let mut forfeit_psbts = forfeit_psbts
+        .iter()
+        .map(|psbt| psbt_analysis::decode_psbt(psbt))
+        .collect::<Result<Vec<_>>>()?;
+
+    let wallet = wallet_view(&contract.id).await?;
+    if let Err(e) = policy::check_ark_settlement(&contract, &intent_psbt, &forfeit_psbts, &wallet) {
+        return Err(refusal(&contract, e).await);
+    }
+
+    signer::sign_ark_psbt_with_identity(&mut intent_psbt).await?;
+    for psbt in forfeit_psbts.iter_mut() {
+        signer::sign_ark_psbt_with_identity(psbt).await?;
+    }
+
+    tracing::info!("Signed Ark settlement of contract {}", contract.id);
 
-    let headers = get_auth_headers(state).await?;
+    Ok(SignedArkSettlement {
+        intent_psbt: BASE64.encode(intent_psbt.serialize()),
+        forfeit_psbts: forfeit_psbts
+            .iter()
+            .map(|psbt| BASE64.encode(psbt.serialize()))
+            .collect(),
+    })
+}
+
+/// The wallet's scripts plus the payout address stored when this wallet
+/// created the contract. The address in the contract itself comes from the
+/// server and is not trusted.
+///
+/// The wallet's Ark addresses are rebuilt from its keys, so this fails with
+/// `NotInitialized` until the Ark wallet is loaded, even for on-chain claims.
+async fn wallet_view(contract_id: &str) -> Result<WalletView, ApiError> {
+    let data_dir = lendasat_data_dir().await?;
+    let payout_addresses: Vec<String> = storage::load_payout_address(&data_dir, contract_id)?
+        .into_iter()
+        .collect();
+    Ok(WalletView::load(&payout_addresses)?)
+}
+
+/// Turn a policy refusal into an error, pointing to
+/// `lendasat_confirm_payout_address` for contracts created before payout
+/// addresses were stored.
+async fn refusal(contract: &Contract, e: anyhow::Error) -> ApiError {
+    let stored = match lendasat_data_dir().await {
+        Ok(data_dir) => storage::load_payout_address(&data_dir, &contract.id)
+            .map(|address| address.is_some())
+            .unwrap_or(true),
+        Err(_) => true,
+    };
+    if stored {
+        return e.into();
+    }
+
+    anyhow!(
+        "{e:#}. No payout address is stored for contract {}; if the collateral is returned to {}, \
+         confirm that address and try again",
+        contract.id,
+        contract.borrower_btc_address
+    )
+    .into()
+}
+
+/// Whether a payout address is stored for the contract, see
+/// `lendasat_confirm_payout_address`.
+pub async fn lendasat_has_payout_address(contract_id: String) -> Result<bool, ApiError> {
+    let data_dir = lendasat_data_dir().await?;
+    Ok(storage::load_payout_address(&data_dir, &contract_id)?.is_some())
+}
 
-    let url = format!(
-        "{}/api/contracts/{}/broadcast-recover",
-        state.base_url, contract_id
+/// Accept the payout address recorded with a contract that was created
+/// before this wallet stored payout addresses.
+///
+/// The address comes from the server, so only call this after the user has
+/// confirmed it is theirs: claims paying to it are signed from then on. Keeps
+/// an address that is already stored. Returns the stored address.
+pub async fn lendasat_confirm_payout_address(contract_id: String) -> Result<String, ApiError> {
+    let (data_dir, network) = {
+        let lock = get_state_lock();
+        let guard = lock.read().await;
+        let state = guard
+            .as_ref()
+            .ok_or_else(|| ApiError::not_initialized("Lendasat"))?;
+        (state.data_dir.clone(), state.network)
+    };
+
+    if let Some(address) = storage::load_payout_address(&data_dir, &contract_id)? {
+        return Ok(address);
+    }
+
+    let contract = lendasat_get_contract(contract_id).await?;
+    let address = contract.borrower_btc_address;
+    psbt_analysis::address_script(&address, network)?;
+    storage::save_payout_address(&data_dir, &contract.id, &address)?;
+
+    tracing::info!(
+        "Stored confirmed payout address {} for contract {}",
+        address,
+        contract.id
     );
 
+    Ok(address)
+}
+
+fn ensure_approved_tx(contract_id: &str, signed_tx: &str) -> Result<(), ApiError> {
+    let tx: bitcoin::Transaction = bitcoin::consensus::encode::deserialize_hex(signed_tx)
+        .map_err(|e| anyhow!("Invalid transaction: {}", e))?;
+    policy::ensure_approved(tx.compute_txid(), Some(contract_id))?;
+    Ok(())
+}
+
+/// Set the limits checked before signing claim and recovery PSBTs.
+#[flutter_rust_bridge::frb(sync)]
+pub fn lendasat_configure_psbt_policy(policy: PsbtPolicy) {
+    policy::configure(policy)
+}
+
+/// The current claim and recovery PSBT limits.
+#[flutter_rust_bridge::frb(sync)]
+pub fn lendasat_get_psbt_policy() -> PsbtPolicy {
+    policy::policy()
+}
+
+/// Broadcast a signed recovery transaction.
+///
+/// Refuses transactions that were not signed with `lendasat_sign_recover_psbt`.
+pub async fn lendasat_broadcast_recover_tx(
+    contract_id: String,
+    signed_tx: String,
+) -> Result<String, ApiError> {
+    ensure_approved_tx(&contract_id, &signed_tx)?;
+
     let request = BroadcastTxRequest { tx: signed_tx };
 
-    let response = state
-        .http_client
-        .post(&url)
-        .headers(headers)
-        .json(&request)
-        .send()
-        .await
-        .map_err(|e| anyhow!("Failed to broadcast recover tx: {}", e))?;
+    let response = send_authenticated("Failed to broadcast recover tx", |base_url| {
+        let url = format!(
+            "{}/api/contracts/{}/broadcast-recover",
+            base_url, contract_id
+        );
+        http::client(Service::Lendasat).post(&url).json(&request)
+    })
+    .await?;
 
     if !response.status().is_success() {
-        let status = response.status();
-        let text = response.text().await.unwrap_or_default();
-        bail!("Failed to broadcast recover tx ({}): {}", status, text);
+        return Err(response_error("Failed to broadcast recover tx", response).await);
     }
 
     let broadcast_response: BroadcastTxResponse = response
@@ -1139,6 +2168,43 @@ pub async fn lendasat_broadcast_recover_tx(
 // Helpers
 // ============================================================================
 
+/// Error for a request that did not reach the Lendasat API.
+fn request_error(context: &str, error: anyhow::Error) -> ApiError {
+    match ApiError::from(error) {
+        ApiError::Other { message } => ApiError::Other {
+            message: format!("{}: {}", context, message),
+        },
+        error => error,
+    }
+}
+
+/// Error for a Lendasat API response with a failure status.
+async fn response_error(context: &str, response: reqwest::Response) -> ApiError {
+    let status = response.status();
+    let text = response.text().await.unwrap_or_default();
+    status_error(context, status, &text)
+}
+
+fn status_error(context: &str, status: StatusCode, text: &str) -> ApiError {
+    let message = format!("{} ({}): {}", context, status, text);
+
+    if status == StatusCode::UNAUTHORIZED {
+        ApiError::AuthRequired
+    } else if status.is_server_error() {
+        ApiError::ServerUnavailable { message }
+    } else {
+        ApiError::Other { message }
+    }
+}
+
+/// Whether the server does not know the requested route at all.
+fn unknown_route(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::NOT_FOUND | StatusCode::METHOD_NOT_ALLOWED
+    )
+}
+
 /// Get the Ark offchain address for collateral deposit.
 async fn get_ark_address() -> Result<String> {
     use crate::state::ARK_CLIENT;
@@ -1147,7 +2213,7 @@ async fn get_ark_address() -> Result<String> {
     let maybe_client = ARK_CLIENT.try_get();
 
     match maybe_client {
-        None => bail!("Ark client not initialized"),
+        None => bail!(ApiError::not_initialized("Ark client")),
         Some(client) => {
             let client_arc = {
                 let guard = client.read();
@@ -1173,37 +2239,11 @@ async fn get_ark_address() -> Result<String> {
 /// IMPORTANT: This key MUST be used as `borrower_pk` when creating LendaSat contracts
 /// because the collateral is locked to this key, not the Lendasat derivation path key.
 ///
-/// Derives the STABLE identity key at path m/83696968'/11811'/0/0 from the mnemonic.
+/// Uses the STABLE identity key at path m/83696968'/11811'/0/0 of the loaded wallet.
 /// This is equivalent to Arkade wallet's `SingleKey.fromHex(privateKey)` - always
 /// the same key, unlike vtxo.owner_pk() which changes when VTXOs are spent/created.
-pub async fn get_ark_identity_pubkey() -> Result<String> {
-    use crate::ark::mnemonic_file::{ARK_BASE_DERIVATION_PATH, read_mnemonic_file};
-    use bitcoin::key::Keypair;
-    use bitcoin::secp256k1::Secp256k1;
-
-    // Get the data_dir and network from Lendasat state
-    let (data_dir, network) = {
-        let lock = get_state_lock();
-        let guard = lock.read().await;
-        let state = guard
-            .as_ref()
-            .ok_or_else(|| anyhow!("Lendasat not initialized"))?;
-        (state.data_dir.clone(), state.network)
-    };
-
-    // Read mnemonic and derive the identity key at index 0
-    let mnemonic = read_mnemonic_file(&data_dir)?
-        .ok_or_else(|| anyhow!("No wallet found - mnemonic file missing"))?;
-
-    // Derive at path m/83696968'/11811'/0/0 (Ark base path + index 0)
-    let identity_path = format!("{}/0", ARK_BASE_DERIVATION_PATH);
-    let xpriv =
-        crate::ark::mnemonic_file::derive_xpriv_at_path(&mnemonic, &identity_path, network)?;
-
-    // Create keypair from the derived key
-    let secp = Secp256k1::new();
-    let keypair = Keypair::from_secret_key(&secp, &xpriv.private_key);
-    let identity_pk = keypair.x_only_public_key().0;
+pub async fn get_ark_identity_pubkey() -> Result<String, ApiError> {
+    let identity_pk = signer::public_key(&SigningKey::Identity)?;
 
     // Convert x-only pubkey to compressed pubkey (add 02 prefix for even y)
     let x_only_bytes = identity_pk.serialize();
@@ -1213,8 +2253,7 @@ pub async fn get_ark_identity_pubkey() -> Result<String> {
     let pubkey_hex = hex::encode(compressed_bytes);
 
     tracing::info!(
-        "Ark STABLE identity pubkey (path {}): {} (x-only: {})",
-        identity_path,
+        "Ark STABLE identity pubkey: {} (x-only: {})",
         &pubkey_hex[..16],
         identity_pk
     );
@@ -1252,6 +2291,11 @@ pub struct ContractSummary {
     pub expiry: String,
     pub balance_outstanding: f64,
     pub lender_name: String,
+    /// Contract this one extends, if any
+    pub extends_contract: Option<String>,
+    /// Contract that replaced this one through an extension, if any
+    pub extended_by_contract: Option<String>,
+    pub can_extend: bool,
     pub created_at: String,
 }
 
@@ -1268,6 +2312,9 @@ impl From<&Contract> for ContractSummary {
             expiry: c.expiry.clone(),
             balance_outstanding: c.balance_outstanding,
             lender_name: c.lender.name.clone(),
+            extends_contract: c.extends_contract.clone(),
+            extended_by_contract: c.extended_by_contract.clone(),
+            can_extend: c.can_extend,
             created_at: c.created_at.clone(),
         }
     }
@@ -1306,3 +2353,59 @@ impl From<&LoanOffer> for OfferSummary {
         }
     }
 }
+
+/// Result of funding a contract's collateral.
+#[derive(Debug, Clone)]
+pub struct CollateralFunding {
+    pub txid: String,
+    /// The contract address the collateral was sent to
+    pub address: String,
+    pub amount_sats: u64,
+    /// Contract status when polling stopped
+    pub status: ContractStatus,
+    /// Whether the server saw the deposit before polling timed out
+    pub collateral_seen: bool,
+}
+
+/// Result of adding collateral to a contract.
+#[derive(Debug, Clone)]
+pub struct CollateralTopUp {
+    pub contract_id: String,
+    /// Whether this was only a preview and nothing was sent
+    pub dry_run: bool,
+    /// BTC price in the loan currency the amounts were computed with
+    pub btc_price: f64,
+    pub current_ltv: f64,
+    pub target_ltv: f64,
+    /// LTV once the top-up is counted as collateral
+    pub resulting_ltv: f64,
+    pub amount_sats: u64,
+    pub fee_sats: u64,
+    /// The contract address the collateral is sent to
+    pub address: String,
+    pub txid: Option<String>,
+    /// Whether Lendasat was notified; if not, the report is queued while
+    /// offline or can be retried with `lendasat_report_collateral_top_up`
+    pub reported: bool,
+}
+
+/// Ark claim PSBTs signed under the collateral policy, as hex.
+#[derive(Debug, Clone)]
+pub struct SignedArkClaim {
+    pub ark_psbt: String,
+    pub checkpoint_psbts: Vec<String>,
+}
+
+/// Ark settlement PSBTs signed under the collateral policy, as base64.
+#[derive(Debug, Clone)]
+pub struct SignedArkSettlement {
+    pub intent_psbt: String,
+    pub forfeit_psbts: Vec<String>,
+}
+
+fn now_unix() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
@@ -3,7 +3,10 @@
 //! This module exposes the LendaSwap functionality to Flutter via flutter_rust_bridge.
 //! All functions use simple types that can be easily marshalled across the FFI boundary.
 
+use crate::ark::tx_metadata_db::{DestinationType, TxMetadataUpdate, record_send};
+use crate::error::ApiError;
 use crate::lendaswap::{self, SwapInfo};
+use crate::offline::{self, QueuedAction, Subsystem};
 use anyhow::Result;
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
@@ -29,7 +32,7 @@ pub async fn lendaswap_init(
     api_url: String,
     arkade_url: String,
     esplora_url: String,
-) -> Result<()> {
+) -> Result<(), ApiError> {
     tracing::info!(
         "[LendaSwap API] init called - data_dir: {}, network: {}, api_url: {}, arkade_url: {}, esplora_url: {}",
         data_dir,
@@ -44,7 +47,7 @@ pub async fn lendaswap_init(
         Ok(_) => tracing::info!("[LendaSwap API] init SUCCESS"),
         Err(e) => tracing::error!("[LendaSwap API] init FAILED: {:?}", e),
     }
-    result
+    Ok(result?)
 }
 
 /// Check if LendaSwap is initialized.
@@ -75,7 +78,7 @@ pub struct TradingPair {
 }
 
 /// Get all available trading pairs.
-pub async fn lendaswap_get_asset_pairs() -> Result<Vec<TradingPair>> {
+pub async fn lendaswap_get_asset_pairs() -> Result<Vec<TradingPair>, ApiError> {
     let pairs = lendaswap::get_asset_pairs().await?;
 
     Ok(pairs
@@ -126,7 +129,7 @@ pub async fn lendaswap_get_quote(
     from_token: String,
     to_token: String,
     amount_sats: u64,
-) -> Result<SwapQuote> {
+) -> Result<SwapQuote, ApiError> {
     let from = lendaswap::parse_token_id(&from_token)?;
     let to = lendaswap::parse_token_id(&to_token)?;
 
@@ -176,7 +179,7 @@ pub async fn lendaswap_create_btc_to_evm_swap(
     target_token: String,
     target_chain: String,
     referral_code: Option<String>,
-) -> Result<BtcToEvmSwapResult> {
+) -> Result<BtcToEvmSwapResult, ApiError> {
     tracing::info!(
         "[LendaSwap API] create_btc_to_evm_swap called - target_evm_address: {}, target_amount_usd: {}, target_token: {}, target_chain: {}, referral_code: {:?}",
         target_evm_address,
@@ -228,7 +231,7 @@ pub async fn lendaswap_create_btc_to_evm_swap(
                 "[LendaSwap API] create_btc_to_evm_swap SDK call FAILED: {:?}",
                 e
             );
-            return Err(e);
+            return Err(e.into());
         }
     };
 
@@ -290,7 +293,7 @@ pub async fn lendaswap_create_evm_to_btc_swap(
     source_token: String,
     source_chain: String,
     referral_code: Option<String>,
-) -> Result<EvmToBtcSwapResult> {
+) -> Result<EvmToBtcSwapResult, ApiError> {
     let token = lendaswap::parse_token_id(&source_token)?;
     let chain = lendaswap::parse_evm_chain(&source_chain)?;
     let amount = Decimal::from_str(&source_amount_usd.to_string())
@@ -335,7 +338,7 @@ pub async fn lendaswap_create_evm_to_lightning_swap(
     source_token: String,
     source_chain: String,
     referral_code: Option<String>,
-) -> Result<EvmToBtcSwapResult> {
+) -> Result<EvmToBtcSwapResult, ApiError> {
     let token = lendaswap::parse_token_id(&source_token)?;
     let chain = lendaswap::parse_evm_chain(&source_chain)?;
 
@@ -368,7 +371,7 @@ pub async fn lendaswap_create_evm_to_lightning_swap(
 // ============================================================================
 
 /// Get swap details by ID.
-pub async fn lendaswap_get_swap(swap_id: String) -> Result<SwapInfo> {
+pub async fn lendaswap_get_swap(swap_id: String) -> Result<SwapInfo, ApiError> {
     tracing::info!("[LendaSwap API] get_swap called - swap_id: {}", swap_id);
     let data = match lendaswap::get_swap(&swap_id).await {
         Ok(d) => {
@@ -377,7 +380,7 @@ pub async fn lendaswap_get_swap(swap_id: String) -> Result<SwapInfo> {
         }
         Err(e) => {
             tracing::error!("[LendaSwap API] get_swap FAILED for {}: {:?}", swap_id, e);
-            return Err(e);
+            return Err(e.into());
         }
     };
     let info = SwapInfo::from_extended_data(&data);
@@ -394,7 +397,10 @@ pub async fn lendaswap_get_swap(swap_id: String) -> Result<SwapInfo> {
 }
 
 /// List all swaps.
-pub async fn lendaswap_list_swaps() -> Result<Vec<SwapInfo>> {
+///
+/// If LendaSwap cannot be reached, the last known list is returned and flagged
+/// as stale (see `get_cache_status`).
+pub async fn lendaswap_list_swaps() -> Result<Vec<SwapInfo>, ApiError> {
     tracing::info!("[LendaSwap API] list_swaps called");
     let swaps = match lendaswap::list_swaps().await {
         Ok(s) => {
@@ -406,7 +412,7 @@ pub async fn lendaswap_list_swaps() -> Result<Vec<SwapInfo>> {
         }
         Err(e) => {
             tracing::error!("[LendaSwap API] list_swaps FAILED: {:?}", e);
-            return Err(e);
+            return offline::cached(Subsystem::Swaps, "", Err(e.into())).await;
         }
     };
 
@@ -434,11 +440,12 @@ pub async fn lendaswap_list_swaps() -> Result<Vec<SwapInfo>> {
         "[LendaSwap API] list_swaps returning {} SwapInfo objects",
         result.len()
     );
-    Ok(result)
+    offline::cached(Subsystem::Swaps, "", Ok(result)).await
 }
 
-/// Claim a swap via Gelato (gasless).
-pub async fn lendaswap_claim_gelato(swap_id: String) -> Result<()> {
+/// Claim a swap via Gelato (gasless). Queued for later if the network is
+/// unreachable.
+pub async fn lendaswap_claim_gelato(swap_id: String) -> Result<(), ApiError> {
     tracing::info!("[LendaSwap API] claim_gelato called - swap_id: {}", swap_id);
     match lendaswap::claim_gelato(&swap_id, None).await {
         Ok(_) => {
@@ -451,15 +458,17 @@ pub async fn lendaswap_claim_gelato(swap_id: String) -> Result<()> {
                 swap_id,
                 e
             );
-            Err(e)
+            let error = swap_error(&swap_id, e).await;
+            Err(offline::defer_if_offline(QueuedAction::ClaimSwapGelato { swap_id }, error).await)
         }
     }
 }
 
-/// Claim VHTLC for an EVM to BTC swap.
+/// Claim VHTLC for an EVM to BTC swap. Queued for later if the network is
+/// unreachable.
 ///
 /// Returns the transaction ID.
-pub async fn lendaswap_claim_vhtlc(swap_id: String) -> Result<String> {
+pub async fn lendaswap_claim_vhtlc(swap_id: String) -> Result<String, ApiError> {
     tracing::info!("[LendaSwap API] claim_vhtlc called - swap_id: {}", swap_id);
     match lendaswap::claim_vhtlc(&swap_id).await {
         Ok(txid) => {
@@ -468,6 +477,7 @@ pub async fn lendaswap_claim_vhtlc(swap_id: String) -> Result<String> {
                 swap_id,
                 txid
             );
+            record_swap_tx(&txid, &swap_id).await;
             Ok(txid)
         }
         Err(e) => {
@@ -476,7 +486,8 @@ pub async fn lendaswap_claim_vhtlc(swap_id: String) -> Result<String> {
                 swap_id,
                 e
             );
-            Err(e)
+            let error = swap_error(&swap_id, e).await;
+            Err(offline::defer_if_offline(QueuedAction::ClaimSwapVhtlc { swap_id }, error).await)
         }
     }
 }
@@ -484,8 +495,40 @@ pub async fn lendaswap_claim_vhtlc(swap_id: String) -> Result<String> {
 /// Refund VHTLC for a failed BTC to EVM swap.
 ///
 /// Returns the transaction ID.
-pub async fn lendaswap_refund_vhtlc(swap_id: String, refund_address: String) -> Result<String> {
-    lendaswap::refund_vhtlc(&swap_id, &refund_address).await
+pub async fn lendaswap_refund_vhtlc(
+    swap_id: String,
+    refund_address: String,
+) -> Result<String, ApiError> {
+    let txid = lendaswap::refund_vhtlc(&swap_id, &refund_address).await?;
+    record_swap_tx(&txid, &swap_id).await;
+    Ok(txid)
+}
+
+/// Report a failed claim of an expired swap as `SwapExpired`.
+async fn swap_error(swap_id: &str, error: anyhow::Error) -> ApiError {
+    match lendaswap::get_swap(swap_id).await {
+        Ok(data)
+            if matches!(
+                SwapInfo::from_extended_data(&data).status,
+                SwapStatusSimple::Expired
+            ) =>
+        {
+            ApiError::SwapExpired {
+                swap_id: swap_id.to_string(),
+            }
+        }
+        _ => error.into(),
+    }
+}
+
+/// Link an Ark transaction to its swap so the activity feed can group them.
+async fn record_swap_tx(txid: &str, swap_id: &str) {
+    let update = TxMetadataUpdate {
+        destination_type: Some(DestinationType::LendaswapSwap),
+        reference: Some(swap_id.to_string()),
+        ..Default::default()
+    };
+    record_send(txid, update).await;
 }
 
 /// Refund on-chain HTLC for a failed BTC to Arkade swap.
@@ -497,12 +540,12 @@ pub async fn lendaswap_refund_vhtlc(swap_id: String, refund_address: String) ->
 pub async fn lendaswap_refund_onchain_htlc(
     swap_id: String,
     refund_address: String,
-) -> Result<String> {
-    lendaswap::refund_onchain_htlc(&swap_id, &refund_address).await
+) -> Result<String, ApiError> {
+    Ok(lendaswap::refund_onchain_htlc(&swap_id, &refund_address).await?)
 }
 
 /// Recover swaps from server (after mnemonic restore).
-pub async fn lendaswap_recover_swaps() -> Result<Vec<SwapInfo>> {
+pub async fn lendaswap_recover_swaps() -> Result<Vec<SwapInfo>, ApiError> {
     tracing::info!("[LendaSwap API] recover_swaps called");
     let swaps = match lendaswap::recover_swaps().await {
         Ok(s) => {
@@ -514,7 +557,7 @@ pub async fn lendaswap_recover_swaps() -> Result<Vec<SwapInfo>> {
         }
         Err(e) => {
             tracing::error!("[LendaSwap API] recover_swaps SDK FAILED: {:?}", e);
-            return Err(e);
+            return Err(e.into());
         }
     };
 
@@ -545,13 +588,13 @@ pub async fn lendaswap_recover_swaps() -> Result<Vec<SwapInfo>> {
 }
 
 /// Delete a swap from local storage.
-pub async fn lendaswap_delete_swap(swap_id: String) -> Result<()> {
-    lendaswap::delete_swap(&swap_id).await
+pub async fn lendaswap_delete_swap(swap_id: String) -> Result<(), ApiError> {
+    Ok(lendaswap::delete_swap(&swap_id).await?)
 }
 
 /// Clear all local swap storage and recover from server.
 /// Use this when local storage is corrupted.
-pub async fn lendaswap_clear_and_recover() -> Result<Vec<SwapInfo>> {
+pub async fn lendaswap_clear_and_recover() -> Result<Vec<SwapInfo>, ApiError> {
     lendaswap::clear_local_storage().await?;
     let swaps = lendaswap::recover_swaps().await?;
     Ok(swaps.iter().map(SwapInfo::from_extended_data).collect())
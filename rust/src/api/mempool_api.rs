@@ -1,5 +1,8 @@
+use crate::http;
+use crate::http::RequestExt;
+use crate::http::Service;
 use crate::models::mempool::{
-    BitcoinTransaction, Block, FearGreedIndex, HashrateData, RecommendedFees,
+    BitcoinTransaction, Block, Conversions, FearGreedIndex, HashrateData, RecommendedFees,
 };
 use anyhow::{Context, Result, bail};
 
@@ -9,10 +12,10 @@ pub async fn get_blocks() -> Result<Vec<Block>> {
 
     let url = "https://mempool.space/api/blocks";
 
-    let client = reqwest::Client::new();
+    let client = http::client(Service::Mempool);
     let response = client
         .get(url)
-        .send()
+        .send_with_retry(Service::Mempool)
         .await
         .context("Failed to fetch blocks from mempool.space")?;
 
@@ -34,10 +37,10 @@ pub async fn get_blocks_at_height(height: u64) -> Result<Vec<Block>> {
 
     let url = format!("https://mempool.space/api/blocks/{}", height);
 
-    let client = reqwest::Client::new();
+    let client = http::client(Service::Mempool);
     let response = client
         .get(&url)
-        .send()
+        .send_with_retry(Service::Mempool)
         .await
         .context("Failed to fetch blocks at height from mempool.space")?;
 
@@ -64,10 +67,10 @@ pub async fn get_block_by_hash(hash: &str) -> Result<Block> {
     // Use v1 API to get full block details including extras
     let url = format!("https://mempool.space/api/v1/block/{}", hash);
 
-    let client = reqwest::Client::new();
+    let client = http::client(Service::Mempool);
     let response = client
         .get(&url)
-        .send()
+        .send_with_retry(Service::Mempool)
         .await
         .context("Failed to fetch block by hash from mempool.space")?;
 
@@ -103,10 +106,10 @@ pub async fn get_block_transactions(
         hash, start_index
     );
 
-    let client = reqwest::Client::new();
+    let client = http::client(Service::Mempool);
     let response = client
         .get(&url)
-        .send()
+        .send_with_retry(Service::Mempool)
         .await
         .context("Failed to fetch block transactions from mempool.space")?;
 
@@ -131,10 +134,10 @@ pub async fn get_recommended_fees() -> Result<RecommendedFees> {
 
     let url = "https://mempool.space/api/v1/fees/recommended";
 
-    let client = reqwest::Client::new();
+    let client = http::client(Service::Mempool);
     let response = client
         .get(url)
-        .send()
+        .send_with_retry(Service::Mempool)
         .await
         .context("Failed to fetch recommended fees from mempool.space")?;
 
@@ -153,6 +156,29 @@ pub async fn get_recommended_fees() -> Result<RecommendedFees> {
     Ok(fees)
 }
 
+/// Get the current BTC price
+pub async fn get_prices() -> Result<Conversions> {
+    let url = "https://mempool.space/api/v1/prices";
+
+    let client = http::client(Service::Mempool);
+    let response = client
+        .get(url)
+        .send_with_retry(Service::Mempool)
+        .await
+        .context("Failed to fetch prices from mempool.space")?;
+
+    if !response.status().is_success() {
+        bail!("Failed to fetch prices: status {}", response.status());
+    }
+
+    let prices = response
+        .json::<Conversions>()
+        .await
+        .context("Failed to parse prices response")?;
+
+    Ok(prices)
+}
+
 /// Get mining hashrate data for a specific time period
 /// period: "1D", "1W", "1M", "1Y", "3Y"
 pub async fn get_hashrate_data(period: &str) -> Result<HashrateData> {
@@ -163,10 +189,10 @@ pub async fn get_hashrate_data(period: &str) -> Result<HashrateData> {
 
     let url = format!("https://mempool.space/api/v1/mining/hashrate/{}", period);
 
-    let client = reqwest::Client::new();
+    let client = http::client(Service::Mempool);
     let response = client
         .get(&url)
-        .send()
+        .send_with_retry(Service::Mempool)
         .await
         .context("Failed to fetch hashrate data from mempool.space")?;
 
@@ -202,10 +228,10 @@ pub async fn get_transaction(txid: &str, base_url: &str) -> Result<BitcoinTransa
 
     tracing::info!("Fetching transaction {} from {}", txid, url);
 
-    let client = reqwest::Client::new();
+    let client = http::client(Service::Mempool);
     let response = client
         .get(&url)
-        .send()
+        .send_with_retry(Service::Mempool)
         .await
         .with_context(|| format!("Failed to fetch transaction from {}", base))?;
 
@@ -228,12 +254,12 @@ pub async fn get_fear_greed_index(api_key: String) -> Result<FearGreedIndex> {
 
     let url = "https://fear-and-greed-index.p.rapidapi.com/v1/fgi";
 
-    let client = reqwest::Client::new();
+    let client = http::client(Service::FearGreed);
     let response = client
         .get(url)
         .header("X-RapidAPI-Key", api_key)
         .header("X-RapidAPI-Host", "fear-and-greed-index.p.rapidapi.com")
-        .send()
+        .send_with_retry(Service::FearGreed)
         .await
         .context("Failed to fetch Fear & Greed Index from RapidAPI")?;
 
@@ -4,14 +4,20 @@ use crate::models;
 use crate::models::exchange_rates::{ExchangeRates, FiatCurrency};
 use anyhow::Result;
 
+pub mod activity_api;
 pub mod ark_api;
 pub mod bitcoin_api;
+pub mod export_api;
 pub mod lendasat_api;
 pub mod lendaswap_api;
 pub mod mempool_api;
 pub mod mempool_block_tracker;
 pub mod mempool_ws;
 pub mod moonpay_api;
+pub mod offline_api;
+
+pub use crate::error::ApiError;
+pub use crate::http::HttpConfig;
 
 #[flutter_rust_bridge::frb(init)]
 pub fn init_app() {
@@ -111,3 +117,17 @@ pub fn get_supported_currencies() -> Vec<FiatCurrency> {
 pub fn currency_code(currency: FiatCurrency) -> String {
     currency.code().to_string()
 }
+
+// Network settings
+
+/// Configure timeouts, retries and circuit breaking for outbound network calls.
+#[flutter_rust_bridge::frb(sync)]
+pub fn configure_http(config: HttpConfig) {
+    crate::http::configure(config)
+}
+
+/// The current network settings, defaults until `configure_http` is called.
+#[flutter_rust_bridge::frb(sync)]
+pub fn get_http_config() -> HttpConfig {
+    crate::http::config()
+}
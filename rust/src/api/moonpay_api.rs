@@ -1,3 +1,6 @@
+use crate::http;
+use crate::http::RequestExt;
+use crate::http::Service;
 use crate::models::moonpay::{MoonPayCurrencyLimits, MoonPayEncryptedData, MoonPayQuote};
 use anyhow::{Context, Result};
 
@@ -12,10 +15,10 @@ pub async fn moonpay_get_currency_limits(
         server_url, base_currency_code, payment_method
     );
 
-    let http_client = reqwest::Client::new();
+    let http_client = http::client(Service::MoonPay);
     let response = http_client
         .get(&url)
-        .send()
+        .send_with_retry(Service::MoonPay)
         .await
         .context("Failed to fetch MoonPay currency limits")?;
 
@@ -35,10 +38,10 @@ pub async fn moonpay_get_currency_limits(
 pub async fn moonpay_get_quote(server_url: String) -> Result<MoonPayQuote> {
     let url = format!("{}/api/moonpay/quote", server_url);
 
-    let http_client = reqwest::Client::new();
+    let http_client = http::client(Service::MoonPay);
     let response = http_client
         .get(&url)
-        .send()
+        .send_with_retry(Service::MoonPay)
         .await
         .context("Failed to fetch MoonPay quote")?;
 
@@ -61,12 +64,12 @@ pub async fn moonpay_encrypt_data(
 ) -> Result<MoonPayEncryptedData> {
     let url = format!("{}/api/moonpay/encrypt", server_url);
 
-    let http_client = reqwest::Client::new();
+    let http_client = http::client(Service::MoonPay);
     let response = http_client
         .post(&url)
         .header("Content-Type", "application/json")
         .body(format!(r#"{{"data":{}}}"#, data))
-        .send()
+        .send_with_retry(Service::MoonPay)
         .await
         .context("Failed to encrypt data")?;
 
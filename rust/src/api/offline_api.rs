@@ -0,0 +1,230 @@
+//! Offline mode for Flutter.
+//!
+//! Exposes the freshness of the cached state and the queue of actions that
+//! failed for lack of connectivity. Queued actions are replayed automatically
+//! when a balance refresh succeeds after the wallet was offline, at most every
+//! [`AUTO_REPLAY_INTERVAL_SECS`] otherwise, or explicitly via
+//! `process_queued_actions` (e.g. when the OS reports connectivity again).
+
+use crate::api::{ark_api, lendasat_api, lendaswap_api};
+use crate::error::ApiError;
+use crate::lendasat::models::InstallmentStatus;
+use crate::offline::{self, MAX_REPLAY_ATTEMPTS, is_offline_error, is_permanent_error};
+use std::sync::LazyLock;
+use std::sync::atomic::{AtomicI64, Ordering};
+use tokio::sync::Mutex;
+
+pub use crate::offline::{CacheStatus, QueuedAction, QueuedOperation, Subsystem};
+
+/// Held while the queue is replayed, so actions never run twice concurrently.
+static QUEUE_LOCK: LazyLock<Mutex<()>> = LazyLock::new(|| Mutex::new(()));
+
+/// Minimum time between automatic replays while the wallet stays online.
+const AUTO_REPLAY_INTERVAL_SECS: i64 = 5 * 60;
+
+/// Unix timestamp of the last automatic replay.
+static LAST_AUTO_REPLAY: AtomicI64 = AtomicI64::new(0);
+
+/// Outcome of replaying the queue.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct QueueRunResult {
+    /// Actions that succeeded and were removed from the queue
+    pub completed: u32,
+    /// Actions that failed and stay queued for another attempt
+    pub failed: u32,
+    /// Actions that were rejected or failed too often and are no longer
+    /// replayed automatically
+    pub parked: u32,
+    /// Actions still replayed automatically afterwards, including failed ones
+    pub remaining: u32,
+}
+
+/// Freshness of the cached balance, history, swaps and contracts.
+pub async fn get_cache_status() -> Result<Vec<CacheStatus>, ApiError> {
+    Ok(offline::get_db()?.status().await?)
+}
+
+/// Queue an action to run once connectivity returns.
+pub async fn queue_action(action: QueuedAction) -> Result<(), ApiError> {
+    Ok(offline::get_db()?.enqueue(&action).await?)
+}
+
+/// All queued actions, oldest first.
+pub async fn list_queued_actions() -> Result<Vec<QueuedOperation>, ApiError> {
+    Ok(offline::get_db()?.queued().await?)
+}
+
+/// Drop a queued action without running it.
+pub async fn remove_queued_action(id: i64) -> Result<(), ApiError> {
+    Ok(offline::get_db()?.remove(id).await?)
+}
+
+/// Run all queued actions that are not parked, in order.
+///
+/// Stops at the first action that fails because the network is still
+/// unreachable. Actions the backend rejects (e.g. an expired swap) are parked
+/// right away, others after failing [`MAX_REPLAY_ATTEMPTS`] times. Parked
+/// actions keep their error so the user can retry or remove them.
+pub async fn process_queued_actions() -> Result<QueueRunResult, ApiError> {
+    let _guard = QUEUE_LOCK.lock().await;
+    run_queue().await
+}
+
+/// Replay a parked action again, then run the queue.
+pub async fn retry_queued_action(id: i64) -> Result<QueueRunResult, ApiError> {
+    let _guard = QUEUE_LOCK.lock().await;
+    offline::get_db()?.unpark(id).await?;
+    run_queue().await
+}
+
+/// Replay the queue in the background unless it is already being replayed.
+///
+/// Runs right away if `reconnected`, i.e. the backend was unreachable until
+/// now, and otherwise at most every [`AUTO_REPLAY_INTERVAL_SECS`], so regular
+/// balance polling does not replay the queue each time.
+pub(crate) fn resume_queued_actions(reconnected: bool) {
+    let now = now_unix();
+    let last = LAST_AUTO_REPLAY.load(Ordering::Acquire);
+    if !reconnected && now - last < AUTO_REPLAY_INTERVAL_SECS {
+        return;
+    }
+    LAST_AUTO_REPLAY.store(now, Ordering::Release);
+
+    tokio::spawn(async {
+        let Ok(_guard) = QUEUE_LOCK.try_lock() else {
+            return;
+        };
+
+        match run_queue().await {
+            Ok(result) if result.completed > 0 || result.failed > 0 || result.parked > 0 => {
+                tracing::info!(
+                    "Replayed queued actions: {} completed, {} failed, {} parked, {} remaining",
+                    result.completed,
+                    result.failed,
+                    result.parked,
+                    result.remaining
+                );
+            }
+            Ok(_) => {}
+            Err(e) => tracing::warn!("Failed to replay queued actions: {}", e),
+        }
+    });
+}
+
+async fn run_queue() -> Result<QueueRunResult, ApiError> {
+    let db = offline::get_db()?;
+    let queued = db.runnable().await?;
+
+    let mut result = QueueRunResult {
+        remaining: queued.len() as u32,
+        ..Default::default()
+    };
+
+    for operation in queued {
+        match run_action(&operation.action).await {
+            Ok(()) => {
+                db.remove(operation.id).await?;
+                result.completed += 1;
+                result.remaining -= 1;
+            }
+            Err(e) if is_offline_error(&e) => {
+                db.record_offline(operation.id, &e.to_string()).await?;
+                break;
+            }
+            // E.g. Lendasat is not set up yet after a cold start
+            Err(e @ ApiError::NotInitialized { .. }) => {
+                db.record_offline(operation.id, &e.to_string()).await?;
+                result.failed += 1;
+            }
+            Err(e) => {
+                let attempts = db.record_failure(operation.id, &e.to_string()).await?;
+                if is_permanent_error(&e) || attempts >= MAX_REPLAY_ATTEMPTS {
+                    tracing::warn!(
+                        "Queued action {:?} failed after {} attempt(s), parking it: {}",
+                        operation.action,
+                        attempts,
+                        e
+                    );
+                    db.park(operation.id).await?;
+                    result.parked += 1;
+                    result.remaining -= 1;
+                } else {
+                    tracing::warn!("Queued action {:?} failed: {}", operation.action, e);
+                    result.failed += 1;
+                }
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+async fn run_action(action: &QueuedAction) -> Result<(), ApiError> {
+    if already_reported(action).await? {
+        tracing::info!("Queued action {:?} already reached the server", action);
+        return Ok(());
+    }
+
+    match action.clone() {
+        QueuedAction::Settle => ark_api::settle().await,
+        QueuedAction::ClaimSwapGelato { swap_id } => {
+            lendaswap_api::lendaswap_claim_gelato(swap_id).await
+        }
+        QueuedAction::ClaimSwapVhtlc { swap_id } => lendaswap_api::lendaswap_claim_vhtlc(swap_id)
+            .await
+            .map(|_| ()),
+        QueuedAction::MarkInstallmentPaid {
+            contract_id,
+            installment_id,
+            payment_txid,
+        } => {
+            lendasat_api::lendasat_mark_installment_paid(contract_id, installment_id, payment_txid)
+                .await
+        }
+        QueuedAction::ReportCollateralTopUp {
+            contract_id,
+            txid,
+            amount_sats,
+        } => lendasat_api::lendasat_report_collateral_top_up(contract_id, txid, amount_sats).await,
+    }
+}
+
+/// Whether Lendasat already knows about a queued report.
+///
+/// Reports are plain POSTs that are not idempotent. An earlier attempt may
+/// have reached the server even though its response was lost, so the
+/// contract is checked before sending the report again.
+async fn already_reported(action: &QueuedAction) -> Result<bool, ApiError> {
+    match action {
+        QueuedAction::MarkInstallmentPaid {
+            contract_id,
+            installment_id,
+            payment_txid,
+        } => {
+            let contract = lendasat_api::lendasat_get_contract(contract_id.clone()).await?;
+            Ok(contract.installments.iter().any(|i| {
+                i.id == *installment_id
+                    && (matches!(
+                        i.status,
+                        InstallmentStatus::Paid | InstallmentStatus::Confirmed
+                    ) || i.payment_id.as_deref() == Some(payment_txid.as_str()))
+            }))
+        }
+        QueuedAction::ReportCollateralTopUp {
+            contract_id, txid, ..
+        } => {
+            let contract = lendasat_api::lendasat_get_contract(contract_id.clone()).await?;
+            Ok(contract.transactions.iter().any(|t| t.txid == *txid))
+        }
+        QueuedAction::Settle
+        | QueuedAction::ClaimSwapGelato { .. }
+        | QueuedAction::ClaimSwapVhtlc { .. } => Ok(false),
+    }
+}
+
+fn now_unix() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
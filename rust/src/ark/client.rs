@@ -1,20 +1,28 @@
 use crate::ark::address_helper::{decode_bip21, is_ark_address, is_bip21, is_btc_address};
-use crate::ark::esplora::EsploraClient;
-use crate::state::{ARK_CLIENT, ArkClient, ESPLORA_URL};
+use crate::ark::esplora;
+use crate::ark::history_db::{self, HistorySyncStats};
+use crate::ark::tx_metadata_db::{DestinationType, TxMetadataUpdate, record_send};
+use crate::error::ApiError;
+use crate::http;
+use crate::http::Service;
+use crate::state::{ARK_CLIENT, ArkClient, KEY_DISCOVERY_INCOMPLETE, KEY_PROVIDER};
 use anyhow::Result;
 use anyhow::{anyhow, bail};
-use ark_client::Blockchain;
 use ark_client::lightning_invoice::Bolt11Invoice;
+use ark_client::{Blockchain, KeyProvider};
 use ark_client::{OffChainBalance, SwapAmount};
 use ark_core::ArkAddress;
 use ark_core::history::Transaction;
 use ark_core::server::{Info, SubscriptionResponse};
-use bitcoin::{Address, Amount, OutPoint, Txid};
+use bitcoin::address::NetworkUnchecked;
+use bitcoin::{Address, Amount, Network, OutPoint, Txid, XOnlyPublicKey};
 use futures::StreamExt;
 use rand::SeedableRng;
 use rand::rngs::StdRng;
+use std::collections::HashSet;
 use std::str::FromStr;
 use std::sync::Arc;
+use std::sync::atomic::Ordering;
 use std::time::Duration;
 
 pub struct Balance {
@@ -26,7 +34,7 @@ pub async fn balance() -> Result<Balance> {
 
     match maybe_client {
         None => {
-            bail!("Ark client not initialized");
+            bail!(ApiError::not_initialized("Ark client"));
         }
         Some(client) => {
             // Clone the Arc<Client> so we can drop the lock guard
@@ -50,10 +58,13 @@ pub async fn balance() -> Result<Balance> {
             }
 
             // Now we can use the cloned Arc safely across await
-            let offchain_balance = client_arc
-                .offchain_balance()
-                .await
-                .map_err(|error| anyhow!("Could not fetch balance {error}"))?;
+            let offchain_balance = http::retry(Service::Ark, || async {
+                client_arc
+                    .offchain_balance()
+                    .await
+                    .map_err(|error| sdk_error("Could not fetch balance", &error))
+            })
+            .await?;
 
             Ok(Balance {
                 // TODO: would be good to also get the on-chain balance here
@@ -85,7 +96,7 @@ pub async fn address(amount: Option<Amount>) -> Result<Addresses> {
 
     match maybe_client {
         None => {
-            bail!("Ark client not initialized");
+            bail!(ApiError::not_initialized("Ark client"));
         }
         Some(client) => {
             // Clone the Arc<Client> so we can drop the lock guard
@@ -138,7 +149,7 @@ pub async fn tx_history() -> Result<Vec<Transaction>> {
 
     match maybe_client {
         None => {
-            bail!("Ark client not initialized");
+            bail!(ApiError::not_initialized("Ark client"));
         }
         Some(client) => {
             // Clone the Arc<Client> so we can drop the lock guard
@@ -147,10 +158,13 @@ pub async fn tx_history() -> Result<Vec<Transaction>> {
                 Arc::clone(&*guard)
             };
 
-            let mut txs = client
-                .transaction_history()
-                .await
-                .map_err(|error| anyhow!("Failed getting transaction history {error:#}"))?;
+            let mut txs = http::retry(Service::Ark, || async {
+                client
+                    .transaction_history()
+                    .await
+                    .map_err(|error| sdk_error("Failed getting transaction history", &error))
+            })
+            .await?;
 
             // sort desc, i.e. newest transactions first
             txs.sort_by_key(|b| std::cmp::Reverse(b.created_at()));
@@ -159,12 +173,40 @@ pub async fn tx_history() -> Result<Vec<Transaction>> {
     }
 }
 
+/// Fetch the transaction history from the server and update the local cache.
+pub async fn sync_tx_history() -> Result<HistorySyncStats> {
+    let db = history_db::get_db()?;
+    db.clear_stale();
+
+    let result = match tx_history().await {
+        Ok(txs) => db.sync(&txs).await,
+        Err(e) => Err(e),
+    };
+    if result.is_err() {
+        db.mark_stale();
+    }
+
+    result
+}
+
+/// Like [`sync_tx_history`], but only if the cache is out of date.
+///
+/// Returns `None` without contacting the server if the cache was synced
+/// recently and the wallet has not sent funds since.
+pub async fn sync_tx_history_if_stale() -> Result<Option<HistorySyncStats>> {
+    if !history_db::get_db()?.needs_sync().await? {
+        return Ok(None);
+    }
+
+    sync_tx_history().await.map(Some)
+}
+
 pub async fn send(address: String, amount: Amount, fee: Option<Amount>) -> Result<Txid> {
     let maybe_client = ARK_CLIENT.try_get();
 
     match maybe_client {
         None => {
-            bail!("Ark client not initialized");
+            bail!(ApiError::not_initialized("Ark client"));
         }
         Some(client) => {
             let client = {
@@ -172,35 +214,46 @@ pub async fn send(address: String, amount: Amount, fee: Option<Amount>) -> Resul
                 Arc::clone(&*guard)
             };
 
-            if is_bip21(address.as_str()) {
+            let (txid, destination_type, counterparty) = if is_bip21(address.as_str()) {
                 let uri = decode_bip21(address.as_str())?;
                 let amount = uri.amount.unwrap_or(amount);
 
-                if let Some(address) = uri.btc_address {
+                if let Some(btc_address) = uri.btc_address {
                     // TODO: there seems to be a bug sending on-chain
+                    let address =
+                        require_network(btc_address, &address, client.server_info.network)?;
                     let txid = client
-                        .send_on_chain(address.assume_checked(), amount)
+                        .send_on_chain(address.clone(), amount)
                         .await
-                        .map_err(|e| anyhow!("Failed sending onchain {e:#}"))?;
-                    Ok(txid)
+                        .map_err(|e| sdk_error("Failed sending onchain", &e))?;
+                    (txid, DestinationType::Onchain, address.to_string())
                 } else if let Some(address) = uri.ark_address {
+                    require_offchain_funds(&client, amount).await?;
                     let txid = client
                         .send_vtxo(address, amount)
                         .await
-                        .map_err(|e| anyhow!("Failed sending offchain {e:#}"))?;
-                    Ok(txid)
+                        .map_err(|e| sdk_error("Failed sending offchain", &e))?;
+                    (txid, DestinationType::Ark, address.encode())
                 } else {
-                    bail!("Unknown bip21 format. We only support bitcoin: and ark: addresses");
+                    bail!(ApiError::invalid_address(
+                        &address,
+                        "Unknown bip21 format. We only support bitcoin: and ark: addresses"
+                    ));
                 }
             } else if is_ark_address(address.as_str()) {
-                let address = ArkAddress::decode(address.as_str())?;
+                let ark_address = ArkAddress::decode(address.as_str())
+                    .map_err(|e| ApiError::invalid_address(&address, e))?;
+                require_offchain_funds(&client, amount).await?;
                 let txid = client
-                    .send_vtxo(address, amount)
+                    .send_vtxo(ark_address, amount)
                     .await
-                    .map_err(|e| anyhow!("Failed sending offchain {e:#}"))?;
-                Ok(txid)
+                    .map_err(|e| sdk_error("Failed sending offchain", &e))?;
+                (txid, DestinationType::Ark, address)
             } else if is_btc_address(address.as_str()) {
-                let address = Address::from_str(address.as_str())?;
+                let btc_address = Address::from_str(address.as_str())
+                    .map_err(|e| ApiError::invalid_address(&address, e))?;
+                let btc_address =
+                    require_network(btc_address, &address, client.server_info.network)?;
                 let rng = &mut StdRng::from_entropy();
 
                 // Select VTXOs sorted by expiry (soonest first) like Arkade wallet
@@ -212,26 +265,90 @@ pub async fn send(address: String, amount: Amount, fee: Option<Amount>) -> Resul
                     .collaborative_redeem_vtxo_selection(
                         rng,
                         vtxo_outpoints.into_iter(),
-                        address.assume_checked(),
+                        btc_address,
                         amount,
                         fee,
                     )
                     .await
-                    .map_err(|e| anyhow!("Failed sending onchain {e:#}"))?;
-                Ok(txid)
+                    .map_err(|e| sdk_error("Failed sending onchain", &e))?;
+                (txid, DestinationType::Onchain, address)
             } else {
-                bail!("Address format not supported")
-            }
+                bail!(ApiError::invalid_address(
+                    &address,
+                    "Address format not supported"
+                ))
+            };
+
+            record_send(
+                &txid.to_string(),
+                TxMetadataUpdate {
+                    destination_type: Some(destination_type),
+                    counterparty: Some(counterparty),
+                    ..Default::default()
+                },
+            )
+            .await;
+
+            Ok(txid)
         }
     }
 }
 
+/// How far the Ark server's timestamps may be behind the wallet's clock.
+const PAYMENT_CLOCK_SKEW_SECS: i64 = 5 * 60;
+
+/// A payment of `amount` from the wallet to `address` made since `since`
+/// (unix seconds), if one can be seen.
+///
+/// Used before sending again after an interrupted send, which may have
+/// reached the server anyway. The Ark history does not name the receiver of
+/// an offchain payment or offboard, so those are matched by amount and time;
+/// outputs at an on-chain address are also looked up on Esplora.
+pub(crate) async fn find_payment(
+    address: &str,
+    amount: Amount,
+    since: i64,
+) -> Result<Option<Txid>> {
+    let since = since - PAYMENT_CLOCK_SKEW_SECS;
+    let sent = tx_history().await?.into_iter().find_map(|tx| match tx {
+        Transaction::Ark {
+            txid,
+            amount: sent,
+            created_at,
+            ..
+        } if created_at >= since && sent.to_sat() == -(amount.to_sat() as i64) => Some(txid),
+        Transaction::Offboard {
+            commitment_txid,
+            amount: sent,
+            confirmed_at,
+        } if confirmed_at.is_none_or(|t| t >= since) && sent == amount => Some(commitment_txid),
+        _ => None,
+    });
+    if sent.is_some() || is_ark_address(address) {
+        return Ok(sent);
+    }
+
+    let network = info()?.network;
+    let unchecked =
+        Address::from_str(address).map_err(|e| ApiError::invalid_address(address, e))?;
+    let onchain_address = require_network(unchecked, address, network)?;
+    let outputs = esplora::shared_client()?
+        .find_outpoints(&onchain_address)
+        .await
+        .map_err(|e| sdk_error("Could not find outpoints", &e))?;
+
+    Ok(outputs
+        .into_iter()
+        .find(|output| output.amount == amount)
+        .map(|output| output.outpoint.txid))
+}
+
 pub async fn settle() -> Result<()> {
     let maybe_client = ARK_CLIENT.try_get();
 
     match maybe_client {
         None => {
-            bail!("Ark client not initialized");
+            bail!(ApiError::not_initialized("Ark client"));
         }
         Some(client) => {
             let client = {
@@ -242,36 +359,121 @@ pub async fn settle() -> Result<()> {
             client
                 .settle(&mut rng)
                 .await
-                .map_err(|e| anyhow!("Failed settling {e:#}"))?;
+                .map_err(|e| sdk_error("Failed settling", &e))?;
+            history_db::mark_stale();
         }
     }
 
     Ok(())
 }
 
+/// Wrap an error of the Ark SDK (or the Esplora client) with `context`.
+///
+/// The SDK hides transport failures in its own error type. They are surfaced
+/// as `ServerUnavailable` or `Timeout`, so callers can fall back to cached
+/// state or queue the action instead of reporting a generic failure.
+pub(crate) fn sdk_error(context: &str, error: &(dyn std::error::Error + 'static)) -> anyhow::Error {
+    let mut message = context.to_string();
+    let mut cause = Some(error);
+    while let Some(current) = cause {
+        let text = current.to_string();
+        // Many errors repeat their source in their own message
+        if !message.contains(&text) {
+            message = format!("{message}: {text}");
+        }
+        cause = current.source();
+    }
+
+    match ApiError::from_sdk(error) {
+        Some(ApiError::Timeout) => anyhow::Error::new(ApiError::Timeout).context(message),
+        Some(ApiError::ServerUnavailable { .. }) => ApiError::ServerUnavailable { message }.into(),
+        Some(api_error) => anyhow::Error::new(api_error).context(message),
+        None => anyhow!(message),
+    }
+}
+
+/// Check that a Bitcoin address belongs to the wallet's network.
+pub(crate) fn require_network(
+    address: Address<NetworkUnchecked>,
+    raw: &str,
+    network: Network,
+) -> Result<Address> {
+    if address.is_valid_for_network(network) {
+        return Ok(address.assume_checked());
+    }
+
+    let actual = [Network::Bitcoin, Network::Testnet, Network::Regtest]
+        .into_iter()
+        .find(|n| address.is_valid_for_network(*n))
+        .map(|n| n.to_string())
+        .unwrap_or_else(|| format!("unknown network ({})", raw));
+
+    bail!(ApiError::NetworkMismatch {
+        expected: network.to_string(),
+        actual,
+    })
+}
+
 /// Select VTXOs for an onchain send, sorted by expiry (soonest first).
 /// This matches Arkade wallet behavior - uses VTXOs expiring soonest first,
 /// leaving fresher ones available for later use.
 async fn select_vtxos_for_amount(client: &ArkClient, amount: Amount) -> Result<Vec<OutPoint>> {
+    let (selected, total_available) = plan_vtxo_selection(client, amount).await?;
+
+    if total_available < amount {
+        bail!(ApiError::InsufficientFunds {
+            needed_sats: amount.to_sat(),
+            available_sats: total_available.to_sat(),
+        });
+    }
+
+    let selected_amount: Amount = selected.iter().map(|(_, amount)| *amount).sum();
+
+    tracing::info!(
+        vtxos_selected = selected.len(),
+        total_selected = %selected_amount,
+        requested = %amount,
+        "Selected VTXOs for onchain send"
+    );
+
+    Ok(selected.into_iter().map(|(outpoint, _)| outpoint).collect())
+}
+
+/// Fail with `InsufficientFunds` before an offchain send the spendable VTXOs
+/// cannot cover, instead of relying on the SDK's coin selection error.
+async fn require_offchain_funds(client: &ArkClient, amount: Amount) -> Result<()> {
+    let (_, total_available) = plan_vtxo_selection(client, amount).await?;
+
+    if total_available < amount {
+        bail!(ApiError::InsufficientFunds {
+            needed_sats: amount.to_sat(),
+            available_sats: total_available.to_sat(),
+        });
+    }
+
+    Ok(())
+}
+
+/// Plan which VTXOs would be spent to cover `amount`, without failing on
+/// insufficient balance.
+///
+/// Returns the selected outpoints with their amounts and the total spendable
+/// offchain balance, so callers can decide how to handle a shortfall.
+async fn plan_vtxo_selection(
+    client: &ArkClient,
+    amount: Amount,
+) -> Result<(Vec<(OutPoint, Amount)>, Amount)> {
     // Get all VTXOs
     let (vtxo_list, _) = client
         .list_vtxos()
         .await
-        .map_err(|e| anyhow!("Failed to list VTXOs: {e}"))?;
+        .map_err(|e| sdk_error("Failed to list VTXOs", &e))?;
 
     // Get all spendable VTXOs (no expiry filtering - let the server validate)
     let mut vtxos: Vec<_> = vtxo_list.spendable_offchain().collect();
 
     let total_available: Amount = vtxos.iter().map(|v| v.amount).sum();
 
-    if total_available < amount {
-        bail!(
-            "Insufficient balance for onchain send: need {}, have {}",
-            amount,
-            total_available
-        );
-    }
-
     // Sort by expiry ascending (soonest first) - like Arkade wallet
     // This uses up older VTXOs first, preserving fresher ones
     vtxos.sort_by_key(|v| v.expires_at);
@@ -284,25 +486,18 @@ async fn select_vtxos_for_amount(client: &ArkClient, amount: Amount) -> Result<V
         if selected_amount >= amount {
             break;
         }
-        selected.push(vtxo.outpoint);
+        selected.push((vtxo.outpoint, vtxo.amount));
         selected_amount += vtxo.amount;
 
         tracing::debug!(
             outpoint = %vtxo.outpoint,
             amount = %vtxo.amount,
             expires_at = vtxo.expires_at,
-            "Selected VTXO for onchain send"
+            "Selected VTXO"
         );
     }
 
-    tracing::info!(
-        vtxos_selected = selected.len(),
-        total_selected = %selected_amount,
-        requested = %amount,
-        "Selected VTXOs for onchain send"
-    );
-
-    Ok(selected)
+    Ok((selected, total_available))
 }
 
 /// Represents a pending boarding UTXO (on-chain funds waiting to be settled)
@@ -319,7 +514,7 @@ pub async fn get_boarding_utxos() -> Result<Vec<BoardingUtxo>> {
 
     match maybe_client {
         None => {
-            bail!("Ark client not initialized");
+            bail!(ApiError::not_initialized("Ark client"));
         }
         Some(client) => {
             let client = {
@@ -327,15 +522,7 @@ pub async fn get_boarding_utxos() -> Result<Vec<BoardingUtxo>> {
                 Arc::clone(&*guard)
             };
 
-            // Get the stored esplora URL to create a separate esplora client
-            let esplora_url = ESPLORA_URL
-                .try_get()
-                .ok_or_else(|| anyhow!("Esplora URL not initialized"))?
-                .read()
-                .clone();
-
-            let esplora = EsploraClient::new(&esplora_url)
-                .map_err(|e| anyhow!("Could not create esplora client: {e:#}"))?;
+            let esplora = esplora::shared_client()?;
 
             // Get all boarding addresses
             let boarding_addresses = client
@@ -349,7 +536,7 @@ pub async fn get_boarding_utxos() -> Result<Vec<BoardingUtxo>> {
                 let address_utxos = esplora
                     .find_outpoints(&address)
                     .await
-                    .map_err(|e| anyhow!("Could not find outpoints: {e:#}"))?;
+                    .map_err(|e| sdk_error("Could not find outpoints", &e))?;
 
                 for utxo in address_utxos {
                     utxos.push(BoardingUtxo {
@@ -379,27 +566,171 @@ pub async fn get_pending_balance() -> Result<Amount> {
     Ok(total)
 }
 
+/// Progress of a wallet rescan.
+#[derive(Debug, Clone)]
+pub enum RescanProgress {
+    /// Key discovery started with the given gap limit
+    Started {
+        gap_limit: u32,
+    },
+    /// Key discovery finished; `new_keys` were not known before the rescan
+    KeysDiscovered {
+        total_keys: u32,
+        new_keys: Vec<String>,
+    },
+    /// Checking a boarding address for on-chain funds
+    ScanningBoardingAddress {
+        index: u32,
+        total: u32,
+        address: String,
+    },
+    Completed(RescanResult),
+    /// The rescan failed and can be retried
+    Failed {
+        error: String,
+    },
+}
+
+/// Outcome of a wallet rescan.
+#[derive(Debug, Clone)]
+pub struct RescanResult {
+    pub total_keys: u32,
+    /// Public keys (x-only, hex) found by this rescan
+    pub new_keys: Vec<String>,
+    pub boarding_utxos: u32,
+    pub boarding_sats: u64,
+}
+
+/// Re-run key discovery for the Ark keychain and scan all boarding addresses.
+///
+/// Unlike the discovery during setup, failures are returned so the caller
+/// can retry, e.g. with a larger gap limit for heavily used wallets.
+pub async fn rescan_wallet(
+    gap_limit: u32,
+    on_progress: impl Fn(RescanProgress),
+) -> Result<RescanResult> {
+    match run_rescan(gap_limit, &on_progress).await {
+        Ok(result) => {
+            KEY_DISCOVERY_INCOMPLETE.store(false, Ordering::SeqCst);
+            on_progress(RescanProgress::Completed(result.clone()));
+            Ok(result)
+        }
+        Err(e) => {
+            tracing::warn!("Wallet rescan failed: {e:#}");
+            on_progress(RescanProgress::Failed {
+                error: format!("{e:#}"),
+            });
+            Err(e)
+        }
+    }
+}
+
+async fn run_rescan(gap_limit: u32, on_progress: &impl Fn(RescanProgress)) -> Result<RescanResult> {
+    let client = {
+        let lock = ARK_CLIENT
+            .try_get()
+            .ok_or_else(|| ApiError::not_initialized("Ark client"))?;
+        let guard = lock.read();
+        Arc::clone(&*guard)
+    };
+    let key_provider = {
+        let lock = KEY_PROVIDER
+            .try_get()
+            .ok_or_else(|| ApiError::not_initialized("Key provider"))?;
+        let guard = lock.read();
+        Arc::clone(&*guard)
+    };
+
+    let known_keys: HashSet<XOnlyPublicKey> = key_provider
+        .get_cached_pks()
+        .map_err(|e| anyhow!("Could not read cached keys: {e:#}"))?
+        .into_iter()
+        .collect();
+
+    tracing::info!(
+        gap_limit,
+        known_keys = known_keys.len(),
+        "Rescanning wallet"
+    );
+    on_progress(RescanProgress::Started { gap_limit });
+
+    client
+        .discover_keys(gap_limit)
+        .await
+        .map_err(|e| anyhow!("Key discovery failed: {e:#}"))?;
+
+    let all_keys = key_provider
+        .get_cached_pks()
+        .map_err(|e| anyhow!("Could not read cached keys: {e:#}"))?;
+    let new_keys: Vec<String> = all_keys
+        .iter()
+        .filter(|pk| !known_keys.contains(pk))
+        .map(|pk| pk.to_string())
+        .collect();
+    let total_keys = all_keys.len() as u32;
+
+    tracing::info!(
+        total_keys,
+        new_keys = new_keys.len(),
+        "Key discovery finished"
+    );
+    on_progress(RescanProgress::KeysDiscovered {
+        total_keys,
+        new_keys: new_keys.clone(),
+    });
+
+    let esplora = esplora::shared_client()?;
+
+    let boarding_addresses = client
+        .get_boarding_addresses()
+        .map_err(|e| anyhow!("Could not get boarding addresses: {e:#}"))?;
+    let total = boarding_addresses.len() as u32;
+
+    let mut boarding_utxos = 0;
+    let mut boarding_sats = 0;
+    for (index, address) in boarding_addresses.iter().enumerate() {
+        on_progress(RescanProgress::ScanningBoardingAddress {
+            index: index as u32,
+            total,
+            address: address.to_string(),
+        });
+
+        let utxos = esplora
+            .find_outpoints(address)
+            .await
+            .map_err(|e| anyhow!("Could not find outpoints for {address}: {e:#}"))?;
+
+        boarding_utxos += utxos.len() as u32;
+        boarding_sats += utxos.iter().map(|u| u.amount.to_sat()).sum::<u64>();
+    }
+
+    Ok(RescanResult {
+        total_keys,
+        new_keys,
+        boarding_utxos,
+        boarding_sats,
+    })
+}
+
+/// Whether key discovery failed during setup and `rescan_wallet` should be run.
+pub fn needs_rescan() -> bool {
+    KEY_DISCOVERY_INCOMPLETE.load(Ordering::SeqCst)
+}
+
 /// Auto-settle confirmed boarding UTXOs silently.
 /// Returns Ok(true) if settled, Ok(false) if nothing to settle.
 async fn auto_settle_boarding() -> Result<bool> {
     let maybe_client = ARK_CLIENT.try_get();
 
     match maybe_client {
-        None => bail!("Ark client not initialized"),
+        None => bail!(ApiError::not_initialized("Ark client")),
         Some(client) => {
             let client = {
                 let guard = client.read();
                 Arc::clone(&*guard)
             };
 
-            let esplora_url = ESPLORA_URL
-                .try_get()
-                .ok_or_else(|| anyhow!("Esplora URL not initialized"))?
-                .read()
-                .clone();
-
-            let esplora = EsploraClient::new(&esplora_url)
-                .map_err(|e| anyhow!("Could not create esplora client: {e:#}"))?;
+            let esplora = esplora::shared_client()?;
 
             let boarding_addresses = client
                 .get_boarding_addresses()
@@ -411,7 +742,7 @@ async fn auto_settle_boarding() -> Result<bool> {
                 let address_utxos = esplora
                     .find_outpoints(&address)
                     .await
-                    .map_err(|e| anyhow!("Could not find outpoints: {e:#}"))?;
+                    .map_err(|e| sdk_error("Could not find outpoints", &e))?;
 
                 for utxo in address_utxos {
                     if utxo.confirmation_blocktime.is_some() {
@@ -430,7 +761,7 @@ async fn auto_settle_boarding() -> Result<bool> {
             client
                 .settle_vtxos(&mut rng, &[], &boarding_outpoints)
                 .await
-                .map_err(|e| anyhow!("Failed auto-settling: {e:#}"))?;
+                .map_err(|e| sdk_error("Failed auto-settling", &e))?;
 
             Ok(true)
         }
@@ -445,7 +776,7 @@ pub async fn settle_boarding() -> Result<()> {
 
     match maybe_client {
         None => {
-            bail!("Ark client not initialized");
+            bail!(ApiError::not_initialized("Ark client"));
         }
         Some(client) => {
             let client = {
@@ -453,15 +784,7 @@ pub async fn settle_boarding() -> Result<()> {
                 Arc::clone(&*guard)
             };
 
-            // Get the stored esplora URL
-            let esplora_url = ESPLORA_URL
-                .try_get()
-                .ok_or_else(|| anyhow!("Esplora URL not initialized"))?
-                .read()
-                .clone();
-
-            let esplora = EsploraClient::new(&esplora_url)
-                .map_err(|e| anyhow!("Could not create esplora client: {e:#}"))?;
+            let esplora = esplora::shared_client()?;
 
             // Get all boarding addresses
             let boarding_addresses = client
@@ -475,7 +798,7 @@ pub async fn settle_boarding() -> Result<()> {
                 let address_utxos = esplora
                     .find_outpoints(&address)
                     .await
-                    .map_err(|e| anyhow!("Could not find outpoints: {e:#}"))?;
+                    .map_err(|e| sdk_error("Could not find outpoints", &e))?;
 
                 for utxo in address_utxos {
                     // Only include confirmed UTXOs
@@ -505,7 +828,8 @@ pub async fn settle_boarding() -> Result<()> {
                     &boarding_outpoints, // Only boarding UTXOs
                 )
                 .await
-                .map_err(|e| anyhow!("Failed settling boarding UTXOs: {e:#}"))?;
+                .map_err(|e| sdk_error("Failed settling boarding UTXOs", &e))?;
+            history_db::mark_stale();
 
             tracing::info!("Successfully settled boarding UTXOs");
         }
@@ -530,7 +854,7 @@ pub async fn pay_ln_invoice(invoice: String) -> Result<LnPaymentResult> {
 
     match maybe_client {
         None => {
-            bail!("Ark client not initialized");
+            bail!(ApiError::not_initialized("Ark client"));
         }
         Some(client) => {
             let client = {
@@ -558,6 +882,8 @@ pub async fn pay_ln_invoice(invoice: String) -> Result<LnPaymentResult> {
                 invoice_amount_msats / 1000
             );
 
+            let payee = bolt11.recover_payee_pub_key().to_string();
+
             // Pay the invoice via submarine swap
             let result = client
                 .pay_ln_invoice(bolt11)
@@ -570,6 +896,17 @@ pub async fn pay_ln_invoice(invoice: String) -> Result<LnPaymentResult> {
                 result.txid
             );
 
+            record_send(
+                &result.txid.to_string(),
+                TxMetadataUpdate {
+                    destination_type: Some(DestinationType::Lightning),
+                    counterparty: Some(payee),
+                    reference: Some(result.swap_id.clone()),
+                    ..Default::default()
+                },
+            )
+            .await;
+
             Ok(LnPaymentResult {
                 swap_id: result.swap_id,
                 txid: result.txid,
@@ -588,7 +925,7 @@ pub(crate) async fn wait_for_payment(
     let maybe_client = ARK_CLIENT.try_get();
     match maybe_client {
         None => {
-            bail!("Ark client not initialized")
+            bail!(ApiError::not_initialized("Ark client"))
         }
         Some(client) => {
             let client = {
@@ -622,7 +959,8 @@ pub(crate) async fn wait_for_payment(
 
                 // Timeout
                 _ = tokio::time::sleep(timeout_duration) => {
-                    bail!("Payment waiting timed out after {} seconds", timeout_seconds)
+                    tracing::info!("Payment waiting timed out after {} seconds", timeout_seconds);
+                    bail!(ApiError::Timeout)
                 }
             }
         }
@@ -726,7 +1064,7 @@ pub(crate) fn info() -> Result<Info> {
 
     match maybe_client {
         None => {
-            bail!("Ark client not initialized")
+            bail!(ApiError::not_initialized("Ark client"))
         }
         Some(client) => {
             let client = {
@@ -743,10 +1081,52 @@ pub(crate) fn info() -> Result<Info> {
 pub struct FeeEstimate {
     /// Estimated fee in satoshis
     pub fee_sats: u64,
-    /// Fee rate used (sat/vB for on-chain, percentage for Lightning)
-    pub fee_rate: f64,
+    /// Fee rate used: the network rate (sat/vB) behind the miner fee of
+    /// on-chain sends, the percentage for Lightning. `None` for Arkade sends
+    /// and when the network rate is unavailable.
+    pub fee_rate: Option<f64>,
     /// Number of VTXOs that would be used
     pub num_inputs: u32,
+    /// Virtual size the send adds on-chain (0 for off-chain sends)
+    pub vbytes: u64,
+    /// Part of the fee charged by the Ark server (offboard or batch fee)
+    pub server_fee_sats: u64,
+    /// Part of the fee paying for on-chain block space
+    pub miner_fee_sats: u64,
+    /// Part of the fee charged by a swap provider (Boltz percentage fee)
+    pub service_fee_sats: u64,
+    /// Whether the split into server, miner and service fee is our own
+    /// estimate rather than reported by the server or swap provider
+    pub split_is_estimate: bool,
+}
+
+/// Confirmation target used to look up the network fee rate for breakdowns
+const FEE_ESTIMATE_TARGET_BLOCKS: u16 = 1;
+
+/// Virtual size of a transaction output paying to `script_pubkey`.
+///
+/// A collaborative redemption adds exactly one output to the commitment
+/// transaction, so this is the block space the user pays for when offboarding.
+fn output_vbytes(script_pubkey: bitcoin::ScriptBuf) -> u64 {
+    let output = bitcoin::TxOut {
+        value: Amount::ZERO,
+        script_pubkey,
+    };
+    output.size() as u64
+}
+
+/// Count the VTXOs needed to cover `amount`, falling back to all spendable
+/// VTXOs when the balance is insufficient.
+async fn count_inputs_for_amount(client: &ArkClient, amount: Amount) -> Result<u32> {
+    let (selected, _) = plan_vtxo_selection(client, amount).await?;
+    Ok(selected.len() as u32)
+}
+
+/// Fetch the current network fee rate (sat/vB) from Esplora.
+async fn network_fee_rate() -> Result<f64> {
+    let esplora = esplora::shared_client()?;
+
+    esplora.fee_rate(FEE_ESTIMATE_TARGET_BLOCKS).await
 }
 
 /// Estimate fee for on-chain send (collaborative redemption)
@@ -759,7 +1139,7 @@ pub async fn estimate_onchain_fee(address: String, amount_sats: u64) -> Result<F
 
     match maybe_client {
         None => {
-            bail!("Ark client not initialized");
+            bail!(ApiError::not_initialized("Ark client"));
         }
         Some(client) => {
             let client = {
@@ -769,16 +1149,21 @@ pub async fn estimate_onchain_fee(address: String, amount_sats: u64) -> Result<F
 
             // Validate address
             if !is_btc_address(&address) {
-                bail!("Not a valid Bitcoin address");
+                bail!(ApiError::invalid_address(
+                    &address,
+                    "Not a valid Bitcoin address"
+                ));
             }
 
             let amount = Amount::from_sat(amount_sats);
 
             // Parse the Bitcoin address
             let to_address = Address::from_str(&address)
-                .map_err(|e| anyhow!("Invalid Bitcoin address: {e}"))?
+                .map_err(|e| ApiError::invalid_address(&address, e))?
                 .assume_checked();
 
+            let vbytes = output_vbytes(to_address.script_pubkey());
+
             // Use SDK's fee estimation API
             let mut rng = StdRng::from_entropy();
             let fee_signed = client
@@ -789,17 +1174,47 @@ pub async fn estimate_onchain_fee(address: String, amount_sats: u64) -> Result<F
             // Convert SignedAmount to u64 (fee should always be positive)
             let fee_sats = fee_signed.to_sat().unsigned_abs();
 
+            // The fee has to be covered by the same VTXOs as the amount
+            let num_inputs =
+                count_inputs_for_amount(&client, amount + Amount::from_sat(fee_sats)).await?;
+
+            // The server only reports the total offboard fee. Estimate the part
+            // paying for block space from the current network fee rate and
+            // attribute the rest to the server; if the fee rate is unavailable
+            // everything is attributed to the server.
+            let fee_rate = match network_fee_rate().await {
+                Ok(rate) => Some(rate),
+                Err(e) => {
+                    tracing::warn!("Could not fetch network fee rate: {e:#}");
+                    None
+                }
+            };
+            let miner_fee_sats = fee_rate
+                .map(|rate| ((rate * vbytes as f64).ceil() as u64).min(fee_sats))
+                .unwrap_or(0);
+            let server_fee_sats = fee_sats - miner_fee_sats;
+
             tracing::info!(
-                "Estimated onchain fee from SDK: {} sats for {} sats to {}",
                 fee_sats,
+                ?fee_rate,
+                vbytes,
+                num_inputs,
+                miner_fee_sats,
+                server_fee_sats,
+                "Estimated onchain fee from SDK for {} sats to {}",
                 amount_sats,
                 address
             );
 
             Ok(FeeEstimate {
                 fee_sats,
-                fee_rate: 0.0, // SDK doesn't return fee rate directly
-                num_inputs: 0, // SDK doesn't return input count
+                fee_rate,
+                num_inputs,
+                vbytes,
+                server_fee_sats,
+                miner_fee_sats,
+                service_fee_sats: 0,
+                split_is_estimate: true,
             })
         }
     }
@@ -809,12 +1224,13 @@ pub async fn estimate_onchain_fee(address: String, amount_sats: u64) -> Result<F
 ///
 /// Ark-to-Ark transfers happen off-chain via batch settlement.
 /// Uses the Arkade SDK's estimate_batch_fees API for accurate fee estimation.
-pub async fn estimate_arkade_fee(address: String, _amount_sats: u64) -> Result<FeeEstimate> {
+/// Nothing is written on-chain, so the whole fee is the server's batch fee.
+pub async fn estimate_arkade_fee(address: String, amount_sats: u64) -> Result<FeeEstimate> {
     let maybe_client = ARK_CLIENT.try_get();
 
     match maybe_client {
         None => {
-            bail!("Ark client not initialized");
+            bail!(ApiError::not_initialized("Ark client"));
         }
         Some(client) => {
             let client = {
@@ -824,11 +1240,14 @@ pub async fn estimate_arkade_fee(address: String, _amount_sats: u64) -> Result<F
 
             // Validate and parse Ark address
             if !is_ark_address(&address) {
-                bail!("Not a valid Ark address");
+                bail!(ApiError::invalid_address(
+                    &address,
+                    "Not a valid Ark address"
+                ));
             }
 
-            let ark_address =
-                ArkAddress::from_str(&address).map_err(|e| anyhow!("Invalid Ark address: {e}"))?;
+            let ark_address = ArkAddress::from_str(&address)
+                .map_err(|e| ApiError::invalid_address(&address, e))?;
 
             // Use SDK's fee estimation API
             let mut rng = StdRng::from_entropy();
@@ -840,16 +1259,25 @@ pub async fn estimate_arkade_fee(address: String, _amount_sats: u64) -> Result<F
             // Convert SignedAmount to u64 (fee should always be positive)
             let fee_sats = fee_signed.to_sat().unsigned_abs();
 
+            let num_inputs =
+                count_inputs_for_amount(&client, Amount::from_sat(amount_sats + fee_sats)).await?;
+
             tracing::info!(
-                "Estimated Arkade batch fee from SDK: {} sats to {}",
                 fee_sats,
+                num_inputs,
+                "Estimated Arkade batch fee from SDK to {}",
                 address
             );
 
             Ok(FeeEstimate {
                 fee_sats,
-                fee_rate: 0.0,
-                num_inputs: 0,
+                fee_rate: None,
+                num_inputs,
+                vbytes: 0,
+                server_fee_sats: fee_sats,
+                miner_fee_sats: 0,
+                service_fee_sats: 0,
+                split_is_estimate: false,
             })
         }
     }
@@ -864,7 +1292,7 @@ pub async fn estimate_lightning_fee(amount_sats: u64) -> Result<FeeEstimate> {
 
     match maybe_client {
         None => {
-            bail!("Ark client not initialized");
+            bail!(ApiError::not_initialized("Ark client"));
         }
         Some(client) => {
             let client = {
@@ -873,10 +1301,13 @@ pub async fn estimate_lightning_fee(amount_sats: u64) -> Result<FeeEstimate> {
             };
 
             // Fetch real-time fees from Boltz via SDK
-            let boltz_fees = client
-                .get_fees()
-                .await
-                .map_err(|e| anyhow!("Failed to fetch Boltz fees: {e}"))?;
+            let boltz_fees = http::retry(Service::Boltz, || async {
+                client
+                    .get_fees()
+                    .await
+                    .map_err(|e| sdk_error("Failed to fetch Boltz fees", &e))
+            })
+            .await?;
 
             // Submarine swap fees (Ark -> Lightning)
             let percentage = boltz_fees.submarine.percentage;
@@ -886,19 +1317,29 @@ pub async fn estimate_lightning_fee(amount_sats: u64) -> Result<FeeEstimate> {
             let percentage_fee = ((amount_sats as f64) * percentage / 100.0).ceil() as u64;
             let total_fee = percentage_fee + miner_fee;
 
+            // The swap is funded with an Ark send covering amount + fees
+            let num_inputs =
+                count_inputs_for_amount(&client, Amount::from_sat(amount_sats + total_fee)).await?;
+
             tracing::info!(
-                "Estimated Lightning fee from Boltz API: {} sats ({}% = {} sats + {} sats miner fee) for {} sats",
+                "Estimated Lightning fee from Boltz API: {} sats ({}% = {} sats + {} sats miner fee) for {} sats using {} VTXOs",
                 total_fee,
                 percentage,
                 percentage_fee,
                 miner_fee,
-                amount_sats
+                amount_sats,
+                num_inputs
             );
 
             Ok(FeeEstimate {
                 fee_sats: total_fee,
-                fee_rate: percentage,
-                num_inputs: 0,
+                fee_rate: Some(percentage),
+                num_inputs,
+                vbytes: 0,
+                server_fee_sats: 0,
+                miner_fee_sats: miner_fee,
+                service_fee_sats: percentage_fee,
+                split_is_estimate: false,
             })
         }
     }
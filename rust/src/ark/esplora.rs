@@ -1,82 +1,180 @@
+use crate::error::ApiError;
+use crate::state::ESPLORA_CLIENT;
+use anyhow::{anyhow, bail};
 use ark_client::error::IntoError;
 use ark_client::{Blockchain, Error, SpendStatus, TxStatus};
 use ark_core::ExplorerUtxo;
 use bitcoin::OutPoint;
-use bitcoin::{Address, Amount, Transaction, Txid};
+use bitcoin::{Address, Amount, Script, Transaction, Txid};
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashSet, VecDeque};
+use std::future::Future;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
+/// File with the user's additional Esplora endpoints inside the data directory.
+pub const ESPLORA_ENDPOINTS_FILE: &str = "esplora_endpoints.json";
+
+/// Consecutive failures after which an endpoint is put into cooldown.
+const MAX_CONSECUTIVE_FAILURES: u32 = 3;
+
+/// How long a failing endpoint is only used as a last resort.
+const FAILURE_COOLDOWN: Duration = Duration::from_secs(60);
+
+/// Blocks an endpoint's tip may lag behind the others before it is deprioritized.
+const MAX_TIP_LAG: u32 = 2;
+
+/// Weight of the newest sample in the latency moving average.
+const LATENCY_SMOOTHING: f64 = 0.3;
+
+/// Latency assumed for endpoints that have not been measured yet.
+const DEFAULT_LATENCY_MS: f64 = 500.0;
+
+/// Confirmed transactions per page of the Esplora history endpoints.
+const HISTORY_PAGE_SIZE: usize = 25;
+
+/// Esplora client with failover across several endpoints.
+///
+/// Requests go to the best scoring endpoint first and fall back to the next
+/// one on transport or server errors. Latency and failures of every request
+/// feed into the score. Before a fallback endpoint is used its tip is
+/// compared with the best tip seen so far, so that failover does not switch
+/// to an endpoint that is behind.
 pub struct EsploraClient {
-    esplora_client: esplora_client::AsyncClient,
+    backends: RwLock<Vec<Arc<Backend>>>,
 }
 
-impl Blockchain for EsploraClient {
-    async fn find_outpoints(&self, address: &Address) -> Result<Vec<ExplorerUtxo>, Error> {
-        let script_pubkey = address.script_pubkey();
-        let txs = self
-            .esplora_client
-            .scripthash_txs(&script_pubkey, None)
-            .await
-            .map_err(|e| format!("Could not fetch tx {e:#}").into_error())?;
+struct Backend {
+    url: String,
+    client: esplora_client::AsyncClient,
+    health: Mutex<BackendHealth>,
+}
 
-        let outputs = txs
-            .into_iter()
-            .flat_map(|tx| {
-                let txid = tx.txid;
-                tx.vout
-                    .iter()
-                    .enumerate()
-                    .filter(|(_, v)| v.scriptpubkey == script_pubkey)
-                    .map(|(i, v)| ExplorerUtxo {
-                        outpoint: OutPoint {
-                            txid,
-                            vout: i as u32,
-                        },
-                        amount: Amount::from_sat(v.value),
-                        confirmation_blocktime: tx.status.block_time,
-                        is_spent: false,
-                    })
-                    .collect::<Vec<_>>()
-            })
-            .collect::<Vec<_>>();
-
-        let mut utxos = Vec::new();
-        for output in outputs.iter() {
-            let outpoint = output.outpoint;
-            let status = self
-                .esplora_client
-                .get_output_status(&outpoint.txid, outpoint.vout as u64)
-                .await
-                .map_err(|e| format!("Could not get status {e:#}").into_error())?;
-
-            match status {
-                Some(esplora_client::OutputStatus { spent: false, .. }) | None => {
-                    utxos.push(*output);
-                }
-                // Ignore spent transaction outputs
-                Some(esplora_client::OutputStatus { spent: true, .. }) => {}
-            }
+#[derive(Default)]
+struct BackendHealth {
+    latency_ms: Option<f64>,
+    consecutive_failures: u32,
+    last_failure: Option<Instant>,
+    last_error: Option<String>,
+    tip_height: Option<u32>,
+    is_lagging: bool,
+}
+
+/// Health of a single Esplora endpoint.
+#[derive(Debug, Clone)]
+pub struct EsploraEndpointHealth {
+    pub url: String,
+    /// Smoothed request latency in milliseconds, if measured
+    pub latency_ms: Option<f64>,
+    pub consecutive_failures: u32,
+    pub last_error: Option<String>,
+    pub tip_height: Option<u32>,
+    /// Tip is more than a few blocks behind the other endpoints
+    pub is_lagging: bool,
+    /// Endpoint is currently skipped in favour of healthy ones
+    pub in_cooldown: bool,
+}
+
+/// Entry of the Esplora `/address/:address/utxo` endpoint.
+#[derive(Debug, Deserialize)]
+struct AddressUtxo {
+    txid: Txid,
+    vout: u32,
+    value: u64,
+    status: esplora_client::TxStatus,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct StoredEndpoints {
+    urls: Vec<String>,
+}
+
+impl Backend {
+    fn new(url: &str) -> anyhow::Result<Self> {
+        let client = esplora_client::Builder::new(url).build_async()?;
+
+        Ok(Self {
+            url: url.to_string(),
+            client,
+            health: Mutex::new(BackendHealth::default()),
+        })
+    }
+
+    fn in_cooldown(health: &BackendHealth) -> bool {
+        health.consecutive_failures >= MAX_CONSECUTIVE_FAILURES
+            && health
+                .last_failure
+                .is_some_and(|t| t.elapsed() < FAILURE_COOLDOWN)
+    }
+
+    /// Lower is better.
+    fn score(&self) -> f64 {
+        let health = self.health.lock();
+        let mut score = health.latency_ms.unwrap_or(DEFAULT_LATENCY_MS);
+        score *= 1.0 + health.consecutive_failures as f64;
+        if health.is_lagging {
+            score += 10_000.0;
+        }
+        if Self::in_cooldown(&health) {
+            score += 100_000.0;
         }
+        score
+    }
 
-        Ok(utxos)
+    fn record_success(&self, latency: Duration) {
+        let mut health = self.health.lock();
+        let sample = latency.as_secs_f64() * 1000.0;
+        health.latency_ms = Some(match health.latency_ms {
+            Some(avg) => avg + LATENCY_SMOOTHING * (sample - avg),
+            None => sample,
+        });
+        health.consecutive_failures = 0;
+    }
+
+    fn record_failure(&self, error: &esplora_client::Error) {
+        let mut health = self.health.lock();
+        health.consecutive_failures += 1;
+        health.last_failure = Some(Instant::now());
+        health.last_error = Some(error.to_string());
+    }
+}
+
+/// Whether a tip at `height` is too far behind the best known tip.
+fn lags_behind(height: u32, best_height: u32) -> bool {
+    height + MAX_TIP_LAG < best_height
+}
+
+/// Whether an error is caused by the request itself, so trying another
+/// endpoint would not help (e.g. broadcasting an invalid transaction).
+fn is_request_error(error: &esplora_client::Error) -> bool {
+    matches!(error, esplora_client::Error::HttpResponse { status, .. } if (400..500).contains(status))
+}
+
+impl Blockchain for EsploraClient {
+    async fn find_outpoints(&self, address: &Address) -> Result<Vec<ExplorerUtxo>, Error> {
+        // The whole lookup runs against one endpoint so that pages stay consistent
+        self.call(|c| async move { find_unspent_outputs(&c, address).await })
+            .await
+            .map_err(|e| format!("Could not fetch outpoints {e:#}").into_error())
     }
 
     async fn find_tx(&self, txid: &Txid) -> Result<Option<Transaction>, Error> {
-        let res = self.esplora_client.get_tx(txid).await;
+        let res = self.call(|c| async move { c.get_tx(txid).await }).await;
         match res {
             Ok(Some(tx)) => Ok(Some(tx)),
             Ok(None) => Ok(None),
             Err(e) => {
                 tracing::error!("Could not fetch transaction {e:#}");
-                // TODO: I can't instantiate `Error`, hence, we don't fail here.
-                // This needs some changes in the library
-                Ok(None)
+                Err(format!("Could not fetch transaction {e:#}").into_error())
             }
         }
     }
 
     async fn get_output_status(&self, txid: &Txid, vout: u32) -> Result<SpendStatus, Error> {
         let res = self
-            .esplora_client
-            .get_output_status(txid, vout as u64)
+            .call(|c| async move { c.get_output_status(txid, vout as u64).await })
             .await;
 
         match res {
@@ -86,16 +184,13 @@ impl Blockchain for EsploraClient {
             Ok(None) => Ok(SpendStatus { spend_txid: None }),
             Err(e) => {
                 tracing::error!("Could not fetch status {e:#}");
-                // TODO: I can't instantiate `Error`, hence, we don't fail here.
-                // This needs some changes in the library
                 Err(format!("Could not fetch status {e:#}").into_error())
             }
         }
     }
 
     async fn broadcast(&self, tx: &Transaction) -> Result<(), Error> {
-        self.esplora_client
-            .broadcast(tx)
+        self.call(|c| async move { c.broadcast(tx).await })
             .await
             .map_err(|err| format!("Could not broadcast tx {err:#}").into_error())?;
         Ok(())
@@ -114,7 +209,9 @@ impl Blockchain for EsploraClient {
     }
 
     async fn get_tx_status(&self, txid: &Txid) -> Result<TxStatus, Error> {
-        let res = self.esplora_client.get_tx_status(txid).await;
+        let res = self
+            .call(|c| async move { c.get_tx_status(txid).await })
+            .await;
 
         match res {
             Ok(status) => Ok(TxStatus {
@@ -130,15 +227,823 @@ impl Blockchain for EsploraClient {
 
 impl EsploraClient {
     pub fn new(url: &str) -> anyhow::Result<Self> {
-        let builder = esplora_client::Builder::new(url);
-        let esplora_client = builder.build_async()?;
+        Self::with_endpoints(&[url.to_string()])
+    }
 
-        Ok(Self { esplora_client })
+    /// Create a client for several endpoints. The first one is tried first
+    /// until latency measurements are available.
+    pub fn with_endpoints(urls: &[String]) -> anyhow::Result<Self> {
+        let backends = build_backends(urls)?;
+
+        Ok(Self {
+            backends: RwLock::new(backends),
+        })
+    }
+
+    /// Replace the endpoints, keeping the health of those that stay.
+    pub fn set_endpoints(&self, urls: &[String]) -> anyhow::Result<()> {
+        let mut new_backends = Vec::new();
+        {
+            let current = self.backends.read();
+            for url in dedup_urls(urls) {
+                match current.iter().find(|b| b.url == url) {
+                    Some(backend) => new_backends.push(Arc::clone(backend)),
+                    None => new_backends.push(Arc::new(Backend::new(&url)?)),
+                }
+            }
+        }
+
+        if new_backends.is_empty() {
+            bail!("At least one Esplora endpoint is required");
+        }
+
+        *self.backends.write() = new_backends;
+        Ok(())
+    }
+
+    /// Configured endpoint URLs in configuration order.
+    pub fn endpoints(&self) -> Vec<String> {
+        self.backends.read().iter().map(|b| b.url.clone()).collect()
+    }
+
+    /// Current health of all endpoints.
+    pub fn health(&self) -> Vec<EsploraEndpointHealth> {
+        self.backends
+            .read()
+            .iter()
+            .map(|b| {
+                let health = b.health.lock();
+                EsploraEndpointHealth {
+                    url: b.url.clone(),
+                    latency_ms: health.latency_ms,
+                    consecutive_failures: health.consecutive_failures,
+                    last_error: health.last_error.clone(),
+                    tip_height: health.tip_height,
+                    is_lagging: health.is_lagging,
+                    in_cooldown: Backend::in_cooldown(&health),
+                }
+            })
+            .collect()
+    }
+
+    /// Query the tip of every endpoint, update their scores and flag
+    /// endpoints whose tip lags behind the others.
+    pub async fn check_health(&self) -> Vec<EsploraEndpointHealth> {
+        let backends = self.backends.read().clone();
+
+        let results = futures::future::join_all(backends.iter().map(|backend| async move {
+            let started = Instant::now();
+            let result = backend.client.get_height().await;
+            (backend, started.elapsed(), result)
+        }))
+        .await;
+
+        let mut best_height = 0;
+        for (backend, latency, result) in &results {
+            match result {
+                Ok(height) => {
+                    backend.record_success(*latency);
+                    backend.health.lock().tip_height = Some(*height);
+                    best_height = best_height.max(*height);
+                }
+                Err(e) => {
+                    tracing::warn!(url = %backend.url, "Esplora health check failed: {e}");
+                    backend.record_failure(e);
+                }
+            }
+        }
+
+        for backend in &backends {
+            let mut health = backend.health.lock();
+            health.is_lagging = health
+                .tip_height
+                .is_some_and(|h| lags_behind(h, best_height));
+            if health.is_lagging {
+                tracing::warn!(
+                    url = %backend.url,
+                    tip = ?health.tip_height,
+                    best_height,
+                    "Esplora endpoint is behind"
+                );
+            }
+        }
+
+        self.health()
     }
 
     pub async fn check_connection(&self) -> anyhow::Result<()> {
-        let height = self.esplora_client.get_height().await?;
+        let height = self.call(|c| async move { c.get_height().await }).await?;
         tracing::debug!(latest_height = height, "Fetched latest height");
         Ok(())
     }
+
+    /// URL of the best scoring endpoint.
+    pub fn best_endpoint(&self) -> String {
+        self.backends
+            .read()
+            .iter()
+            .min_by(|a, b| a.score().total_cmp(&b.score()))
+            .map(|b| b.url.clone())
+            .expect("at least one Esplora endpoint is configured")
+    }
+
+    /// Highest tip reported by any endpoint so far.
+    fn best_tip(&self) -> Option<u32> {
+        self.backends
+            .read()
+            .iter()
+            .filter_map(|b| b.health.lock().tip_height)
+            .max()
+    }
+
+    /// Refresh the tip of `backend` and compare it with the best known tip.
+    ///
+    /// Returns `false` if the endpoint lags behind.
+    async fn check_tip(&self, backend: &Backend) -> Result<bool, esplora_client::Error> {
+        let best_known = self.best_tip();
+
+        let started = Instant::now();
+        let height = backend.client.get_height().await?;
+        backend.record_success(started.elapsed());
+
+        let best_height = best_known.map_or(height, |best| best.max(height));
+        let mut health = backend.health.lock();
+        health.tip_height = Some(height);
+        health.is_lagging = lags_behind(height, best_height);
+        if health.is_lagging {
+            tracing::warn!(
+                url = %backend.url,
+                tip = height,
+                best_height,
+                "Esplora fallback endpoint is behind"
+            );
+        }
+
+        Ok(!health.is_lagging)
+    }
+
+    /// Run `op` against the best endpoint, failing over to the others.
+    ///
+    /// Fallback endpoints have their tip checked first; those that lag
+    /// behind are only tried once all others failed.
+    async fn call<T, F, Fut>(&self, op: F) -> Result<T, esplora_client::Error>
+    where
+        F: Fn(esplora_client::AsyncClient) -> Fut,
+        Fut: Future<Output = Result<T, esplora_client::Error>>,
+    {
+        let mut backends = self.backends.read().clone();
+        backends.sort_by(|a, b| a.score().total_cmp(&b.score()));
+
+        // The preferred endpoint is vetted by its score
+        let mut queue: VecDeque<(Arc<Backend>, bool)> = backends
+            .into_iter()
+            .enumerate()
+            .map(|(index, backend)| (backend, index == 0))
+            .collect();
+
+        let mut last_error = None;
+        while let Some((backend, vetted)) = queue.pop_front() {
+            if !vetted {
+                match self.check_tip(&backend).await {
+                    Ok(true) => {}
+                    Ok(false) => {
+                        queue.push_back((backend, true));
+                        continue;
+                    }
+                    Err(e) => {
+                        tracing::warn!(url = %backend.url, "Esplora fallback endpoint unreachable: {e}");
+                        backend.record_failure(&e);
+                        last_error = Some(e);
+                        continue;
+                    }
+                }
+            }
+
+            let started = Instant::now();
+            match op(backend.client.clone()).await {
+                Ok(value) => {
+                    backend.record_success(started.elapsed());
+                    return Ok(value);
+                }
+                Err(e) if is_request_error(&e) => return Err(e),
+                Err(e) => {
+                    tracing::warn!(url = %backend.url, "Esplora request failed, trying next endpoint: {e}");
+                    backend.record_failure(&e);
+                    last_error = Some(e);
+                }
+            }
+        }
+
+        Err(last_error.expect("at least one Esplora endpoint is configured"))
+    }
+
+    /// Get the fee rate (sat/vB) for confirmation within `target_blocks`.
+    ///
+    /// Falls back to the next higher target Esplora knows about, and to 1 sat/vB
+    /// if it returns no estimates at all.
+    pub async fn fee_rate(&self, target_blocks: u16) -> anyhow::Result<f64> {
+        let estimates = self
+            .call(|c| async move { c.get_fee_estimates().await })
+            .await?;
+
+        let fee_rate = estimates
+            .iter()
+            .filter(|(target, _)| **target >= target_blocks)
+            .min_by_key(|(target, _)| **target)
+            .map(|(_, rate)| *rate)
+            .unwrap_or(1.0);
+
+        tracing::debug!(target_blocks, fee_rate, "Fetched fee rate");
+        Ok(fee_rate)
+    }
+}
+
+fn dedup_urls(urls: &[String]) -> Vec<String> {
+    let mut unique: Vec<String> = Vec::new();
+    for url in urls {
+        let url = url.trim().trim_end_matches('/').to_string();
+        if !url.is_empty() && !unique.contains(&url) {
+            unique.push(url);
+        }
+    }
+    unique
+}
+
+fn build_backends(urls: &[String]) -> anyhow::Result<Vec<Arc<Backend>>> {
+    let backends = dedup_urls(urls)
+        .iter()
+        .map(|url| Backend::new(url).map(Arc::new))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    if backends.is_empty() {
+        bail!("At least one Esplora endpoint is required");
+    }
+
+    Ok(backends)
+}
+
+/// Find the unspent outputs of `address`.
+///
+/// Uses the UTXO endpoint, which needs a single request. Servers reject it for
+/// addresses with too many outputs, in which case the unspent outputs are
+/// derived from the complete transaction history instead. Other errors, e.g.
+/// rate limiting or server errors, are returned as they are.
+async fn find_unspent_outputs(
+    client: &esplora_client::AsyncClient,
+    address: &Address,
+) -> Result<Vec<ExplorerUtxo>, esplora_client::Error> {
+    match address_utxos(client, address).await {
+        Ok(utxos) => Ok(utxos),
+        Err(esplora_client::Error::HttpResponse { status, message })
+            if utxo_endpoint_unavailable(status) =>
+        {
+            tracing::debug!(
+                %address,
+                status,
+                message,
+                "UTXO endpoint not available, scanning address history"
+            );
+            let txs = address_history(client, address).await?;
+            Ok(unspent_outputs(&txs, &address.script_pubkey()))
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Whether the UTXO endpoint answered with `status` because it does not
+/// serve this address: Electrs rejects addresses with too many entries with
+/// 400, servers without the endpoint answer 404 or 405.
+fn utxo_endpoint_unavailable(status: u16) -> bool {
+    matches!(status, 400 | 404 | 405)
+}
+
+async fn address_utxos(
+    client: &esplora_client::AsyncClient,
+    address: &Address,
+) -> Result<Vec<ExplorerUtxo>, esplora_client::Error> {
+    let url = format!("{}/address/{}/utxo", client.url(), address);
+    let response = client.client().get(&url).send().await?;
+
+    if !response.status().is_success() {
+        return Err(esplora_client::Error::HttpResponse {
+            status: response.status().as_u16(),
+            message: response.text().await?,
+        });
+    }
+
+    let body = response.text().await?;
+    let utxos: Vec<AddressUtxo> =
+        serde_json::from_str(&body).map_err(|_| esplora_client::Error::InvalidResponse)?;
+
+    Ok(utxos
+        .into_iter()
+        .map(|u| ExplorerUtxo {
+            outpoint: OutPoint {
+                txid: u.txid,
+                vout: u.vout,
+            },
+            amount: Amount::from_sat(u.value),
+            confirmation_blocktime: u.status.block_time,
+            is_spent: false,
+        })
+        .collect())
+}
+
+/// Load the complete history of `address`, newest first.
+///
+/// The first page holds the mempool transactions and up to
+/// `HISTORY_PAGE_SIZE` confirmed ones; further confirmed transactions are
+/// requested after the last one seen.
+async fn address_history(
+    client: &esplora_client::AsyncClient,
+    address: &Address,
+) -> Result<Vec<esplora_client::Tx>, esplora_client::Error> {
+    let mut txs = client.get_address_txs(address, None).await?;
+    let mut page_confirmed = txs.iter().filter(|tx| tx.status.confirmed).count();
+
+    while page_confirmed >= HISTORY_PAGE_SIZE {
+        let Some(last_seen) = txs.iter().rev().find(|tx| tx.status.confirmed) else {
+            break;
+        };
+
+        let page = client
+            .get_address_txs(address, Some(last_seen.txid))
+            .await?;
+        page_confirmed = page.len();
+        txs.extend(page);
+    }
+
+    Ok(txs)
+}
+
+/// Outputs to `script_pubkey` in `txs` that no transaction in `txs` spends.
+///
+/// The history of an address contains the transactions spending from it, so
+/// no further requests are needed.
+fn unspent_outputs(txs: &[esplora_client::Tx], script_pubkey: &Script) -> Vec<ExplorerUtxo> {
+    let spent: HashSet<OutPoint> = txs
+        .iter()
+        .flat_map(|tx| &tx.vin)
+        .map(|vin| OutPoint {
+            txid: vin.txid,
+            vout: vin.vout,
+        })
+        .collect();
+
+    let mut seen = HashSet::new();
+    txs.iter()
+        .flat_map(|tx| {
+            tx.vout
+                .iter()
+                .enumerate()
+                .filter(|(_, v)| v.scriptpubkey.as_script() == script_pubkey)
+                .map(|(i, v)| ExplorerUtxo {
+                    outpoint: OutPoint {
+                        txid: tx.txid,
+                        vout: i as u32,
+                    },
+                    amount: Amount::from_sat(v.value),
+                    confirmation_blocktime: tx.status.block_time,
+                    is_spent: false,
+                })
+        })
+        .filter(|u| !spent.contains(&u.outpoint) && seen.insert(u.outpoint))
+        .collect()
+}
+
+/// Get the Esplora client shared with the Ark client.
+pub(crate) fn shared_client() -> anyhow::Result<Arc<EsploraClient>> {
+    let lock = ESPLORA_CLIENT
+        .try_get()
+        .ok_or_else(|| ApiError::not_initialized("Esplora client"))?;
+    let guard = lock.read();
+    Ok(Arc::clone(&*guard))
+}
+
+/// Load the user's additional endpoints.
+pub fn load_custom_endpoints(data_dir: &str) -> anyhow::Result<Vec<String>> {
+    let path = Path::new(data_dir).join(ESPLORA_ENDPOINTS_FILE);
+
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let content = std::fs::read_to_string(&path)
+        .map_err(|e| anyhow!("Failed to read Esplora endpoints file: {}", e))?;
+
+    let stored: StoredEndpoints = serde_json::from_str(&content)
+        .map_err(|e| anyhow!("Failed to parse Esplora endpoints file: {}", e))?;
+
+    Ok(stored.urls)
+}
+
+/// Store the user's additional endpoints.
+pub fn save_custom_endpoints(data_dir: &str, urls: &[String]) -> anyhow::Result<()> {
+    let path = Path::new(data_dir).join(ESPLORA_ENDPOINTS_FILE);
+
+    let stored = StoredEndpoints {
+        urls: dedup_urls(urls),
+    };
+    let content = serde_json::to_string_pretty(&stored)
+        .map_err(|e| anyhow!("Failed to serialize Esplora endpoints: {}", e))?;
+
+    std::fs::write(&path, content)
+        .map_err(|e| anyhow!("Failed to write Esplora endpoints file: {}", e))?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitcoin::hashes::Hash;
+    use bitcoin::{Network, ScriptBuf, WPubkeyHash};
+    use serde_json::{Value, json};
+    use std::sync::atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    const FUNDING_TXS: usize = 50;
+    const SPENDING_TXS: usize = 10;
+
+    /// Minimal Esplora serving an address with 60 transactions: 50 paying to
+    /// it and 10 spending one of those outputs each.
+    struct MockEsplora {
+        url: String,
+        requests: Arc<AtomicUsize>,
+        utxo_endpoint: Arc<AtomicBool>,
+        tip_height: Arc<AtomicU32>,
+        /// Answer every request with a server error
+        failing: Arc<AtomicBool>,
+    }
+
+    fn address() -> Address {
+        let spk = ScriptBuf::new_p2wpkh(&WPubkeyHash::all_zeros());
+        Address::from_script(&spk, Network::Regtest).unwrap()
+    }
+
+    fn txid(i: usize) -> String {
+        format!("{:064x}", i + 1)
+    }
+
+    /// History of the address, newest first.
+    fn history(address: &Address) -> Vec<Value> {
+        let own = address.script_pubkey().to_hex_string();
+        let other = ScriptBuf::new_p2wpkh(&WPubkeyHash::from_byte_array([1; 20])).to_hex_string();
+
+        let tx = |i: usize, input: (String, u32), script: &str| {
+            json!({
+                "txid": txid(i),
+                "version": 2,
+                "locktime": 0,
+                "vin": [{
+                    "txid": input.0,
+                    "vout": input.1,
+                    "prevout": null,
+                    "scriptsig": "",
+                    "sequence": 0xffffffffu32,
+                    "is_coinbase": false,
+                }],
+                "vout": [{ "value": 1_000 + i as u64, "scriptpubkey": script }],
+                "size": 200,
+                "weight": 800,
+                "status": {
+                    "confirmed": true,
+                    "block_height": 100 + i,
+                    "block_hash": null,
+                    "block_time": 1_700_000_000 + i as u64,
+                },
+                "fee": 200,
+            })
+        };
+
+        let mut txs: Vec<Value> = (0..FUNDING_TXS)
+            .map(|i| tx(i, (txid(10_000 + i), 0), &own))
+            .collect();
+        txs.extend((0..SPENDING_TXS).map(|i| tx(FUNDING_TXS + i, (txid(i * 2), 0), &other)));
+        txs.reverse();
+        txs
+    }
+
+    fn unspent() -> Vec<Value> {
+        let spent: Vec<String> = (0..SPENDING_TXS).map(|i| txid(i * 2)).collect();
+        (0..FUNDING_TXS)
+            .map(txid)
+            .filter(|t| !spent.contains(t))
+            .map(|t| {
+                json!({
+                    "txid": t,
+                    "vout": 0,
+                    "value": 1_000,
+                    "status": { "confirmed": true, "block_time": 1_700_000_000u64 },
+                })
+            })
+            .collect()
+    }
+
+    fn route(path: &str, utxo_endpoint: bool, tip_height: u32) -> (u16, String) {
+        if path == "/blocks/tip/height" {
+            return (200, tip_height.to_string());
+        }
+
+        let address = address();
+        let base = format!("/address/{address}");
+        let txs = history(&address);
+
+        if path == format!("{base}/utxo") {
+            return if utxo_endpoint {
+                (200, Value::Array(unspent()).to_string())
+            } else {
+                (400, "Too many history entries".to_string())
+            };
+        }
+
+        let page = if path == format!("{base}/txs") {
+            txs.iter().take(HISTORY_PAGE_SIZE).cloned().collect()
+        } else if let Some(last_seen) = path.strip_prefix(&format!("{base}/txs/chain/")) {
+            txs.iter()
+                .skip_while(|tx| tx["txid"] != last_seen)
+                .skip(1)
+                .take(HISTORY_PAGE_SIZE)
+                .cloned()
+                .collect()
+        } else {
+            return (404, "Not found".to_string());
+        };
+
+        (200, Value::Array(page).to_string())
+    }
+
+    async fn serve() -> MockEsplora {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(AtomicUsize::new(0));
+        let utxo_endpoint = Arc::new(AtomicBool::new(true));
+        let tip_height = Arc::new(AtomicU32::new(100));
+        let failing = Arc::new(AtomicBool::new(false));
+
+        let counter = Arc::clone(&requests);
+        let utxo = Arc::clone(&utxo_endpoint);
+        let tip = Arc::clone(&tip_height);
+        let fail = Arc::clone(&failing);
+        tokio::spawn(async move {
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut buf = vec![0; 4096];
+                let n = socket.read(&mut buf).await.unwrap();
+                let request = String::from_utf8_lossy(&buf[..n]);
+                let path = request.split_whitespace().nth(1).unwrap_or("/").to_string();
+
+                counter.fetch_add(1, Ordering::SeqCst);
+                let (status, body) = if fail.load(Ordering::SeqCst) {
+                    (503, "Service unavailable".to_string())
+                } else {
+                    route(
+                        &path,
+                        utxo.load(Ordering::SeqCst),
+                        tip.load(Ordering::SeqCst),
+                    )
+                };
+
+                let response = format!(
+                    "HTTP/1.1 {status} OK\r\nContent-Type: application/json\r\n\
+                     Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                socket.write_all(response.as_bytes()).await.unwrap();
+                socket.shutdown().await.ok();
+            }
+        });
+
+        MockEsplora {
+            url,
+            requests,
+            utxo_endpoint,
+            tip_height,
+            failing,
+        }
+    }
+
+    // The previous implementation needed 1 + 50 requests (one status request
+    // per output) and missed every transaction after the first page.
+
+    #[tokio::test]
+    async fn find_outpoints_uses_utxo_endpoint() {
+        let mock = serve().await;
+        let client = EsploraClient::new(&mock.url).unwrap();
+
+        let utxos = client.find_outpoints(&address()).await.unwrap();
+
+        assert_eq!(utxos.len(), FUNDING_TXS - SPENDING_TXS);
+        assert_eq!(mock.requests.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn find_outpoints_pages_history_without_utxo_endpoint() {
+        let mock = serve().await;
+        mock.utxo_endpoint.store(false, Ordering::SeqCst);
+        let client = EsploraClient::new(&mock.url).unwrap();
+
+        let utxos = client.find_outpoints(&address()).await.unwrap();
+
+        // 1 rejected UTXO request + 3 history pages for 60 transactions
+        assert_eq!(mock.requests.load(Ordering::SeqCst), 4);
+        assert_eq!(utxos.len(), FUNDING_TXS - SPENDING_TXS);
+
+        let spent: Vec<String> = (0..SPENDING_TXS).map(|i| txid(i * 2)).collect();
+        assert!(
+            utxos
+                .iter()
+                .all(|u| !spent.contains(&u.outpoint.txid.to_string()))
+        );
+        // The oldest funding transaction is only on the last page
+        assert!(utxos.iter().any(|u| u.outpoint.txid.to_string() == txid(1)));
+    }
+
+    #[tokio::test]
+    async fn find_outpoints_does_not_scan_history_on_other_errors() {
+        let mock = serve().await;
+        mock.failing.store(true, Ordering::SeqCst);
+        let client = EsploraClient::new(&mock.url).unwrap();
+
+        assert!(client.find_outpoints(&address()).await.is_err());
+        // Only the UTXO request, no history pages
+        assert_eq!(mock.requests.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn only_unsupported_utxo_requests_fall_back() {
+        assert!(utxo_endpoint_unavailable(400));
+        assert!(utxo_endpoint_unavailable(404));
+        assert!(!utxo_endpoint_unavailable(429));
+        assert!(!utxo_endpoint_unavailable(500));
+        assert!(!utxo_endpoint_unavailable(503));
+    }
+
+    fn backend(latency_ms: f64) -> Backend {
+        let backend = Backend::new("http://127.0.0.1:1").unwrap();
+        backend.health.lock().latency_ms = Some(latency_ms);
+        backend
+    }
+
+    /// Make `backends` preferred in the given order.
+    fn rank(client: &EsploraClient, latencies: &[f64]) {
+        for (backend, latency) in client.backends.read().iter().zip(latencies) {
+            backend.health.lock().latency_ms = Some(*latency);
+        }
+    }
+
+    fn failures(client: &EsploraClient, url: &str) -> u32 {
+        client
+            .health()
+            .into_iter()
+            .find(|h| h.url == url)
+            .unwrap()
+            .consecutive_failures
+    }
+
+    #[test]
+    fn scores_faster_endpoints_better() {
+        assert!(backend(50.0).score() < backend(200.0).score());
+        assert_eq!(
+            Backend::new("http://127.0.0.1:1").unwrap().score(),
+            DEFAULT_LATENCY_MS
+        );
+    }
+
+    #[test]
+    fn failures_and_lag_worsen_score() {
+        let failing = backend(50.0);
+        failing.health.lock().consecutive_failures = 2;
+        assert_eq!(failing.score(), 150.0);
+
+        let lagging = backend(50.0);
+        lagging.health.lock().is_lagging = true;
+        assert!(lagging.score() > backend(5_000.0).score());
+
+        let cooling_down = backend(50.0);
+        {
+            let mut health = cooling_down.health.lock();
+            health.consecutive_failures = MAX_CONSECUTIVE_FAILURES;
+            health.last_failure = Some(Instant::now());
+        }
+        assert!(cooling_down.score() > lagging.score());
+    }
+
+    #[test]
+    fn cooldown_expires() {
+        let recovered = backend(50.0);
+        {
+            let mut health = recovered.health.lock();
+            health.consecutive_failures = MAX_CONSECUTIVE_FAILURES;
+            health.last_failure = Instant::now().checked_sub(FAILURE_COOLDOWN * 2);
+        }
+
+        assert!(!Backend::in_cooldown(&recovered.health.lock()));
+    }
+
+    #[test]
+    fn only_client_errors_skip_failover() {
+        let error = |status| esplora_client::Error::HttpResponse {
+            status,
+            message: String::new(),
+        };
+
+        assert!(is_request_error(&error(400)));
+        assert!(!is_request_error(&error(500)));
+        assert!(!is_request_error(&error(503)));
+    }
+
+    #[test]
+    fn detects_lagging_tips() {
+        assert!(!lags_behind(100, 100));
+        assert!(!lags_behind(100 - MAX_TIP_LAG, 100));
+        assert!(lags_behind(100 - MAX_TIP_LAG - 1, 100));
+        assert!(!lags_behind(110, 100));
+    }
+
+    #[tokio::test]
+    async fn fails_over_to_next_endpoint() {
+        let primary = serve().await;
+        let fallback = serve().await;
+        primary.failing.store(true, Ordering::SeqCst);
+        let client =
+            EsploraClient::with_endpoints(&[primary.url.clone(), fallback.url.clone()]).unwrap();
+
+        let utxos = client.find_outpoints(&address()).await.unwrap();
+
+        assert_eq!(utxos.len(), FUNDING_TXS - SPENDING_TXS);
+        assert_eq!(failures(&client, &primary.url), 1);
+        assert_eq!(failures(&client, &fallback.url), 0);
+        // Tip check and UTXO request
+        assert_eq!(fallback.requests.load(Ordering::SeqCst), 2);
+        // The fallback now scores better than the failing endpoint
+        assert_eq!(client.best_endpoint(), fallback.url);
+    }
+
+    #[tokio::test]
+    async fn skips_lagging_endpoint_during_failover() {
+        let primary = serve().await;
+        let lagging = serve().await;
+        let synced = serve().await;
+        let client = EsploraClient::with_endpoints(&[
+            primary.url.clone(),
+            lagging.url.clone(),
+            synced.url.clone(),
+        ])
+        .unwrap();
+        client.check_health().await;
+        rank(&client, &[1.0, 2.0, 3.0]);
+
+        primary.failing.store(true, Ordering::SeqCst);
+        lagging.tip_height.store(90, Ordering::SeqCst);
+        let lagging_before = lagging.requests.load(Ordering::SeqCst);
+        let synced_before = synced.requests.load(Ordering::SeqCst);
+
+        let utxos = client.find_outpoints(&address()).await.unwrap();
+
+        assert_eq!(utxos.len(), FUNDING_TXS - SPENDING_TXS);
+        // Only the tip was queried on the lagging endpoint
+        assert_eq!(lagging.requests.load(Ordering::SeqCst), lagging_before + 1);
+        assert_eq!(synced.requests.load(Ordering::SeqCst), synced_before + 2);
+        let health = client.health();
+        assert!(
+            health
+                .iter()
+                .find(|h| h.url == lagging.url)
+                .unwrap()
+                .is_lagging
+        );
+    }
+
+    #[tokio::test]
+    async fn uses_lagging_endpoint_as_last_resort() {
+        let primary = serve().await;
+        let lagging = serve().await;
+        let client =
+            EsploraClient::with_endpoints(&[primary.url.clone(), lagging.url.clone()]).unwrap();
+        client.check_health().await;
+        rank(&client, &[1.0, 2.0]);
+
+        primary.failing.store(true, Ordering::SeqCst);
+        lagging.tip_height.store(90, Ordering::SeqCst);
+
+        let utxos = client.find_outpoints(&address()).await.unwrap();
+
+        assert_eq!(utxos.len(), FUNDING_TXS - SPENDING_TXS);
+    }
+
+    #[tokio::test]
+    async fn reports_last_error_when_all_endpoints_fail() {
+        let primary = serve().await;
+        let fallback = serve().await;
+        primary.failing.store(true, Ordering::SeqCst);
+        fallback.failing.store(true, Ordering::SeqCst);
+        let client =
+            EsploraClient::with_endpoints(&[primary.url.clone(), fallback.url.clone()]).unwrap();
+
+        assert!(client.find_outpoints(&address()).await.is_err());
+        assert_eq!(failures(&client, &primary.url), 1);
+        assert_eq!(failures(&client, &fallback.url), 1);
+    }
 }
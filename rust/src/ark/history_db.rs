@@ -0,0 +1,965 @@
+//! SQLite-based cache of the Ark transaction history.
+//!
+//! The Ark server returns the complete history on every request. This cache
+//! keeps the last synced state locally so the UI can page through it with a
+//! cursor and show it while offline. The full history is only fetched again
+//! once the cache is older than [`SYNC_INTERVAL_SECS`] or the wallet sent
+//! funds since. A sync compares the server's history with the entries that
+//! can still change and only writes entries that are new or have changed
+//! (e.g. a boarding transaction that got confirmed).
+//!
+//! Entries are keyed on `(txid, kind)`: the commitment transaction of a round
+//! can show up both as `Round` and as `Offboard`.
+
+use crate::error::ApiError;
+use crate::state::HISTORY_DB;
+use anyhow::{Result, anyhow, bail};
+use ark_core::history::Transaction;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// File name of the history cache inside the data directory.
+pub const HISTORY_DB_FILE: &str = "tx_history.sqlite";
+
+/// Maximum number of entries returned per page.
+const MAX_PAGE_SIZE: u32 = 200;
+
+/// Age after which the cache is synced with the server again.
+pub const SYNC_INTERVAL_SECS: i64 = 30;
+
+/// Final entries younger than this relative to the newest final entry are
+/// still compared on sync, so a reorg that changes a confirmation time is
+/// picked up.
+const SYNC_OVERLAP_SECS: i64 = 6 * 60 * 60;
+
+/// SQL condition for entries that no longer change: confirmed on-chain
+/// transactions, rounds and settled redeems.
+const FINAL_CONDITION: &str = "(kind = 'Round' OR confirmed_at IS NOT NULL OR is_settled = 1)";
+
+/// Type of a cached transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TxKind {
+    Boarding,
+    Round,
+    Redeem,
+    Offboard,
+}
+
+impl TxKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            TxKind::Boarding => "Boarding",
+            TxKind::Round => "Round",
+            TxKind::Redeem => "Redeem",
+            TxKind::Offboard => "Offboard",
+        }
+    }
+
+    fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "Boarding" => Some(TxKind::Boarding),
+            "Round" => Some(TxKind::Round),
+            "Redeem" => Some(TxKind::Redeem),
+            "Offboard" => Some(TxKind::Offboard),
+            _ => None,
+        }
+    }
+}
+
+/// Direction of funds relative to the wallet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TxDirection {
+    Incoming,
+    Outgoing,
+}
+
+/// Filter for paging through the cached history. Unset fields match everything.
+#[derive(Debug, Clone, Default)]
+pub struct TxHistoryFilter {
+    pub direction: Option<TxDirection>,
+    pub kinds: Option<Vec<TxKind>>,
+    /// Only transactions at or after this unix timestamp
+    pub from: Option<i64>,
+    /// Only transactions before this unix timestamp
+    pub to: Option<i64>,
+}
+
+/// A transaction as stored in the cache.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CachedTx {
+    pub txid: String,
+    pub kind: TxKind,
+    /// Balance change in satoshis (negative for outgoing)
+    pub amount_sats: i64,
+    /// Only set for Redeem transactions
+    pub is_settled: Option<bool>,
+    /// Confirmation time of Boarding and Offboard transactions
+    pub confirmed_at: Option<i64>,
+    /// Creation time of Round and Redeem transactions
+    pub created_at: Option<i64>,
+    /// Time used for ordering; unconfirmed transactions use the time they were first seen
+    pub sort_time: i64,
+}
+
+/// One page of cached history.
+#[derive(Debug, Clone)]
+pub struct CachedTxPage {
+    pub items: Vec<CachedTx>,
+    /// Pass to the next call to continue after the last item, `None` at the end
+    pub next_cursor: Option<String>,
+}
+
+/// Result of syncing the cache with the server.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HistorySyncStats {
+    pub added: u32,
+    pub updated: u32,
+    /// Unconfirmed entries the server no longer reports (dropped or replaced)
+    pub removed: u32,
+    pub total: u32,
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+struct CachedTxRow {
+    txid: String,
+    kind: String,
+    amount_sats: i64,
+    is_settled: Option<bool>,
+    confirmed_at: Option<i64>,
+    created_at: Option<i64>,
+    sort_time: i64,
+}
+
+impl TryFrom<CachedTxRow> for CachedTx {
+    type Error = anyhow::Error;
+
+    fn try_from(row: CachedTxRow) -> Result<Self> {
+        let kind = TxKind::from_str(&row.kind)
+            .ok_or_else(|| anyhow!("Unknown transaction kind: {}", row.kind))?;
+
+        Ok(CachedTx {
+            txid: row.txid,
+            kind,
+            amount_sats: row.amount_sats,
+            is_settled: row.is_settled,
+            confirmed_at: row.confirmed_at,
+            created_at: row.created_at,
+            sort_time: row.sort_time,
+        })
+    }
+}
+
+impl CachedTx {
+    /// Convert a transaction from the Ark SDK. `now` is used as sort time
+    /// for transactions that have no timestamp yet.
+    fn from_sdk(tx: &Transaction, now: i64) -> Self {
+        match tx {
+            Transaction::Boarding {
+                txid,
+                amount,
+                confirmed_at,
+            } => CachedTx {
+                txid: txid.to_string(),
+                kind: TxKind::Boarding,
+                amount_sats: amount.to_sat() as i64,
+                is_settled: None,
+                confirmed_at: *confirmed_at,
+                created_at: None,
+                sort_time: confirmed_at.unwrap_or(now),
+            },
+            Transaction::Commitment {
+                txid,
+                amount,
+                created_at,
+            } => CachedTx {
+                txid: txid.to_string(),
+                kind: TxKind::Round,
+                amount_sats: amount.to_sat(),
+                is_settled: None,
+                confirmed_at: None,
+                created_at: Some(*created_at),
+                sort_time: *created_at,
+            },
+            Transaction::Ark {
+                txid,
+                amount,
+                is_settled,
+                created_at,
+            } => CachedTx {
+                txid: txid.to_string(),
+                kind: TxKind::Redeem,
+                amount_sats: amount.to_sat(),
+                is_settled: Some(*is_settled),
+                confirmed_at: None,
+                created_at: Some(*created_at),
+                sort_time: *created_at,
+            },
+            Transaction::Offboard {
+                commitment_txid,
+                amount,
+                confirmed_at,
+            } => CachedTx {
+                txid: commitment_txid.to_string(),
+                kind: TxKind::Offboard,
+                // Offboard is always outgoing
+                amount_sats: -(amount.to_sat() as i64),
+                is_settled: None,
+                confirmed_at: *confirmed_at,
+                created_at: None,
+                sort_time: confirmed_at.unwrap_or(now),
+            },
+        }
+    }
+
+    /// Cursor that continues paging after this entry.
+    pub fn cursor(&self) -> String {
+        encode_cursor(self.sort_time, &self.txid, self.kind)
+    }
+
+    fn key(&self) -> (String, TxKind) {
+        (self.txid.clone(), self.kind)
+    }
+
+    /// An on-chain transaction without a confirmation time. Only these can be
+    /// dropped from the mempool or replaced.
+    fn is_unconfirmed(&self) -> bool {
+        self.confirmed_at.is_none() && self.created_at.is_none()
+    }
+
+    /// Whether `self` differs from the cached `old` entry in anything but the
+    /// sort time of a still unconfirmed transaction.
+    fn differs_from(&self, old: &CachedTx) -> bool {
+        let sort_time_changed = !self.is_unconfirmed() && self.sort_time != old.sort_time;
+
+        self.amount_sats != old.amount_sats
+            || self.is_settled != old.is_settled
+            || self.confirmed_at != old.confirmed_at
+            || self.created_at != old.created_at
+            || sort_time_changed
+    }
+}
+
+/// SQLite-based cache of the transaction history.
+pub struct HistoryDb {
+    pool: sqlx::SqlitePool,
+    /// Set when the wallet changed the history since the last sync
+    stale: AtomicBool,
+}
+
+impl HistoryDb {
+    /// Create a new HistoryDb with the given data directory.
+    /// The SQLite database will be created at `{data_dir}/tx_history.sqlite`.
+    pub async fn new(data_dir: &str) -> Result<Self> {
+        let db_path = Path::new(data_dir).join(HISTORY_DB_FILE);
+        let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
+
+        let pool = sqlx::SqlitePool::connect(&db_url)
+            .await
+            .map_err(|e| anyhow!("Failed to connect to SQLite database: {}", e))?;
+
+        Self::run_migrations(&pool).await?;
+
+        Ok(Self {
+            pool,
+            stale: AtomicBool::new(true),
+        })
+    }
+
+    /// Run database migrations to create/update tables.
+    async fn run_migrations(pool: &sqlx::SqlitePool) -> Result<()> {
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS tx_history (
+                txid TEXT NOT NULL,
+                kind TEXT NOT NULL,
+                amount_sats INTEGER NOT NULL,
+                is_settled INTEGER,
+                confirmed_at INTEGER,
+                created_at INTEGER,
+                sort_time INTEGER NOT NULL,
+                PRIMARY KEY (txid, kind)
+            )
+            "#,
+        )
+        .execute(pool)
+        .await
+        .map_err(|e| anyhow!("Failed to create tx_history table: {}", e))?;
+
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS sync_state (
+                id INTEGER PRIMARY KEY CHECK (id = 0),
+                last_synced_at INTEGER NOT NULL
+            )
+            "#,
+        )
+        .execute(pool)
+        .await
+        .map_err(|e| anyhow!("Failed to create sync_state table: {}", e))?;
+
+        sqlx::query(
+            "CREATE INDEX IF NOT EXISTS idx_tx_history_order ON tx_history(sort_time DESC, txid DESC, kind DESC)",
+        )
+        .execute(pool)
+        .await
+        .ok();
+
+        Ok(())
+    }
+
+    /// Bring the cache up to date with the history returned by the server.
+    ///
+    /// Only new and changed entries are written. Unconfirmed entries the
+    /// server no longer reports were dropped or replaced and are removed.
+    /// Other entries are kept, so history stays available even if the server
+    /// prunes old data.
+    ///
+    /// The SDK has no way to ask for the history since a point in time, so
+    /// `txs` is always the complete history. The sync picks up from the last
+    /// known point instead: entries that were already final (confirmed or
+    /// settled) more than [`SYNC_OVERLAP_SECS`] before the newest final entry
+    /// are neither loaded nor compared again.
+    pub async fn sync(&self, txs: &[Transaction]) -> Result<HistorySyncStats> {
+        let now = now_unix();
+
+        let since = self.final_since().await?;
+        let cached: HashMap<(String, TxKind), CachedTx> = self
+            .load_open(since)
+            .await?
+            .into_iter()
+            .map(|tx| (tx.key(), tx))
+            .collect();
+        let done = self.final_keys_before(since).await?;
+
+        let mut stats = HistorySyncStats::default();
+        let mut seen = HashSet::with_capacity(txs.len());
+        let mut db_tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| anyhow!("Failed to start history sync: {}", e))?;
+
+        for tx in txs {
+            let mut entry = CachedTx::from_sdk(tx, now);
+            if !seen.insert(entry.key()) || done.contains(&entry.key()) {
+                continue;
+            }
+
+            match cached.get(&entry.key()) {
+                Some(old) if !entry.differs_from(old) => continue,
+                Some(old) => {
+                    if entry.is_unconfirmed() {
+                        entry.sort_time = old.sort_time;
+                    }
+                    stats.updated += 1;
+                }
+                None => stats.added += 1,
+            }
+
+            sqlx::query(
+                r#"
+                INSERT INTO tx_history
+                    (txid, kind, amount_sats, is_settled, confirmed_at, created_at, sort_time)
+                VALUES (?, ?, ?, ?, ?, ?, ?)
+                ON CONFLICT(txid, kind) DO UPDATE SET
+                    amount_sats = excluded.amount_sats,
+                    is_settled = excluded.is_settled,
+                    confirmed_at = excluded.confirmed_at,
+                    created_at = excluded.created_at,
+                    sort_time = excluded.sort_time
+                "#,
+            )
+            .bind(&entry.txid)
+            .bind(entry.kind.as_str())
+            .bind(entry.amount_sats)
+            .bind(entry.is_settled)
+            .bind(entry.confirmed_at)
+            .bind(entry.created_at)
+            .bind(entry.sort_time)
+            .execute(&mut *db_tx)
+            .await
+            .map_err(|e| anyhow!("Failed to store transaction {}: {}", entry.txid, e))?;
+        }
+
+        for (key, old) in &cached {
+            if seen.contains(key) || !old.is_unconfirmed() {
+                continue;
+            }
+
+            sqlx::query("DELETE FROM tx_history WHERE txid = ? AND kind = ?")
+                .bind(&old.txid)
+                .bind(old.kind.as_str())
+                .execute(&mut *db_tx)
+                .await
+                .map_err(|e| anyhow!("Failed to remove transaction {}: {}", old.txid, e))?;
+            stats.removed += 1;
+        }
+
+        sqlx::query("INSERT OR REPLACE INTO sync_state (id, last_synced_at) VALUES (0, ?)")
+            .bind(now)
+            .execute(&mut *db_tx)
+            .await
+            .map_err(|e| anyhow!("Failed to store sync state: {}", e))?;
+
+        db_tx
+            .commit()
+            .await
+            .map_err(|e| anyhow!("Failed to commit history sync: {}", e))?;
+
+        stats.total = (cached.len() + done.len()) as u32 + stats.added - stats.removed;
+
+        tracing::debug!(
+            added = stats.added,
+            updated = stats.updated,
+            removed = stats.removed,
+            total = stats.total,
+            "Synced transaction history cache"
+        );
+
+        Ok(stats)
+    }
+
+    /// Remove all cached transactions, e.g. after the wallet moved to another
+    /// Ark server whose history does not include the old server's.
+    pub async fn clear(&self) -> Result<()> {
+        let mut db_tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| anyhow!("Failed to start transaction: {}", e))?;
+
+        sqlx::query("DELETE FROM tx_history")
+            .execute(&mut *db_tx)
+            .await
+            .map_err(|e| anyhow!("Failed to clear transaction history: {}", e))?;
+        sqlx::query("DELETE FROM sync_state")
+            .execute(&mut *db_tx)
+            .await
+            .map_err(|e| anyhow!("Failed to clear sync state: {}", e))?;
+
+        db_tx
+            .commit()
+            .await
+            .map_err(|e| anyhow!("Failed to commit history clear: {}", e))?;
+
+        self.mark_stale();
+        Ok(())
+    }
+
+    /// Whether the history should be fetched from the server again.
+    pub async fn needs_sync(&self) -> Result<bool> {
+        if self.stale.load(Ordering::Acquire) {
+            return Ok(true);
+        }
+
+        Ok(match self.last_synced_at().await? {
+            Some(last) => now_unix() - last >= SYNC_INTERVAL_SECS,
+            None => true,
+        })
+    }
+
+    /// Force the next `needs_sync` check to fetch the history again.
+    pub fn mark_stale(&self) {
+        self.stale.store(true, Ordering::Release);
+    }
+
+    /// Called before fetching the history, so changes made while the fetch is
+    /// in flight mark the cache stale again.
+    pub fn clear_stale(&self) {
+        self.stale.store(false, Ordering::Release);
+    }
+
+    /// Unix timestamp of the last successful sync, if any.
+    pub async fn last_synced_at(&self) -> Result<Option<i64>> {
+        let last: Option<(i64,)> =
+            sqlx::query_as("SELECT last_synced_at FROM sync_state WHERE id = 0")
+                .fetch_optional(&self.pool)
+                .await
+                .map_err(|e| anyhow!("Failed to load sync state: {}", e))?;
+
+        Ok(last.map(|(t,)| t))
+    }
+
+    /// Sort time from which final entries are still compared on sync, `None`
+    /// if every entry is.
+    async fn final_since(&self) -> Result<Option<i64>> {
+        let newest: Option<(Option<i64>,)> = sqlx::query_as(&format!(
+            "SELECT MAX(sort_time) FROM tx_history WHERE {FINAL_CONDITION}"
+        ))
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| anyhow!("Failed to load transaction history: {}", e))?;
+
+        Ok(newest
+            .and_then(|(t,)| t)
+            .map(|t| t.saturating_sub(SYNC_OVERLAP_SECS)))
+    }
+
+    /// Entries that can still change: not yet final, or final after `since`.
+    async fn load_open(&self, since: Option<i64>) -> Result<Vec<CachedTx>> {
+        let Some(since) = since else {
+            return self.load_all().await;
+        };
+
+        let rows: Vec<CachedTxRow> = sqlx::query_as(&format!(
+            "SELECT * FROM tx_history WHERE NOT ({FINAL_CONDITION}) OR sort_time >= ?"
+        ))
+        .bind(since)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| anyhow!("Failed to load transaction history: {}", e))?;
+
+        rows.into_iter().map(CachedTx::try_from).collect()
+    }
+
+    /// Keys of the entries that were final before `since`.
+    async fn final_keys_before(&self, since: Option<i64>) -> Result<HashSet<(String, TxKind)>> {
+        let Some(since) = since else {
+            return Ok(HashSet::new());
+        };
+
+        let rows: Vec<(String, String)> = sqlx::query_as(&format!(
+            "SELECT txid, kind FROM tx_history WHERE {FINAL_CONDITION} AND sort_time < ?"
+        ))
+        .bind(since)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| anyhow!("Failed to load transaction history: {}", e))?;
+
+        rows.into_iter()
+            .map(|(txid, kind)| {
+                let kind = TxKind::from_str(&kind)
+                    .ok_or_else(|| anyhow!("Unknown transaction kind: {}", kind))?;
+                Ok((txid, kind))
+            })
+            .collect()
+    }
+
+    /// Get all cached transactions, newest first.
+    pub async fn load_all(&self) -> Result<Vec<CachedTx>> {
+        let rows: Vec<CachedTxRow> = sqlx::query_as(
+            "SELECT * FROM tx_history ORDER BY sort_time DESC, txid DESC, kind DESC",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| anyhow!("Failed to load transaction history: {}", e))?;
+
+        rows.into_iter().map(CachedTx::try_from).collect()
+    }
+
+    /// Get the cached entries of the given txids, newest first.
+    pub async fn find(&self, txids: &[&str]) -> Result<Vec<CachedTx>> {
+        if txids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut query =
+            sqlx::QueryBuilder::<sqlx::Sqlite>::new("SELECT * FROM tx_history WHERE txid IN (");
+        let mut separated = query.separated(", ");
+        for txid in txids {
+            separated.push_bind(*txid);
+        }
+        separated.push_unseparated(") ORDER BY sort_time DESC, txid DESC, kind DESC");
+
+        let rows: Vec<CachedTxRow> = query
+            .build_query_as()
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| anyhow!("Failed to load transaction history: {}", e))?;
+
+        rows.into_iter().map(CachedTx::try_from).collect()
+    }
+
+    /// Get a page of cached transactions, newest first.
+    ///
+    /// `cursor` is the `next_cursor` of the previous page, or `None` for the
+    /// first page.
+    pub async fn page(
+        &self,
+        cursor: Option<&str>,
+        limit: u32,
+        filter: &TxHistoryFilter,
+    ) -> Result<CachedTxPage> {
+        let limit = limit.clamp(1, MAX_PAGE_SIZE);
+
+        let mut query =
+            sqlx::QueryBuilder::<sqlx::Sqlite>::new("SELECT * FROM tx_history WHERE 1=1");
+
+        if let Some(cursor) = cursor {
+            let (sort_time, txid, kind) = decode_cursor(cursor)?;
+            query
+                .push(" AND (sort_time, txid, kind) < (")
+                .push_bind(sort_time)
+                .push(", ")
+                .push_bind(txid)
+                .push(", ")
+                .push_bind(kind.as_str())
+                .push(")");
+        }
+
+        match filter.direction {
+            Some(TxDirection::Incoming) => {
+                query.push(" AND amount_sats > 0");
+            }
+            Some(TxDirection::Outgoing) => {
+                query.push(" AND amount_sats < 0");
+            }
+            None => {}
+        }
+
+        if let Some(kinds) = &filter.kinds {
+            if kinds.is_empty() {
+                return Ok(CachedTxPage {
+                    items: Vec::new(),
+                    next_cursor: None,
+                });
+            }
+
+            query.push(" AND kind IN (");
+            let mut separated = query.separated(", ");
+            for kind in kinds {
+                separated.push_bind(kind.as_str());
+            }
+            separated.push_unseparated(")");
+        }
+
+        if let Some(from) = filter.from {
+            query.push(" AND sort_time >= ").push_bind(from);
+        }
+
+        if let Some(to) = filter.to {
+            query.push(" AND sort_time < ").push_bind(to);
+        }
+
+        // Fetch one extra row to know whether another page follows
+        query
+            .push(" ORDER BY sort_time DESC, txid DESC, kind DESC LIMIT ")
+            .push_bind(limit as i64 + 1);
+
+        let rows: Vec<CachedTxRow> = query
+            .build_query_as()
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| anyhow!("Failed to load transaction history: {}", e))?;
+
+        let mut items = rows
+            .into_iter()
+            .map(CachedTx::try_from)
+            .collect::<Result<Vec<_>>>()?;
+
+        let next_cursor = if items.len() > limit as usize {
+            items.truncate(limit as usize);
+            items.last().map(CachedTx::cursor)
+        } else {
+            None
+        };
+
+        Ok(CachedTxPage { items, next_cursor })
+    }
+}
+
+/// Get the history cache of the loaded wallet.
+pub(crate) fn get_db() -> Result<Arc<HistoryDb>> {
+    let lock = HISTORY_DB
+        .try_get()
+        .ok_or_else(|| ApiError::not_initialized("Transaction history cache"))?;
+    let guard = lock.read();
+    Ok(Arc::clone(&*guard))
+}
+
+/// Mark the history cache of the loaded wallet stale after the wallet sent
+/// funds, so the next load fetches the new transaction.
+pub(crate) fn mark_stale() {
+    if let Ok(db) = get_db() {
+        db.mark_stale();
+    }
+}
+
+fn encode_cursor(sort_time: i64, txid: &str, kind: TxKind) -> String {
+    format!("{}:{}:{}", sort_time, txid, kind.as_str())
+}
+
+fn decode_cursor(cursor: &str) -> Result<(i64, String, TxKind)> {
+    let mut parts = cursor.splitn(3, ':');
+    let (Some(sort_time), Some(txid), Some(kind)) = (parts.next(), parts.next(), parts.next())
+    else {
+        bail!("Invalid history cursor: {}", cursor);
+    };
+
+    let sort_time = sort_time
+        .parse()
+        .map_err(|_| anyhow!("Invalid history cursor: {}", cursor))?;
+    let kind =
+        TxKind::from_str(kind).ok_or_else(|| anyhow!("Invalid history cursor: {}", cursor))?;
+
+    Ok((sort_time, txid.to_string(), kind))
+}
+
+fn now_unix() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitcoin::hashes::Hash;
+    use bitcoin::{Amount, SignedAmount, Txid};
+
+    async fn open_db() -> HistoryDb {
+        let dir = std::env::temp_dir().join(format!("history-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        HistoryDb::new(dir.to_str().unwrap()).await.unwrap()
+    }
+
+    fn txid(b: u8) -> Txid {
+        Txid::from_byte_array([b; 32])
+    }
+
+    fn boarding(b: u8, confirmed_at: Option<i64>) -> Transaction {
+        Transaction::Boarding {
+            txid: txid(b),
+            amount: Amount::from_sat(10_000),
+            confirmed_at,
+        }
+    }
+
+    fn redeem(b: u8, sats: i64, created_at: i64) -> Transaction {
+        Transaction::Ark {
+            txid: txid(b),
+            amount: SignedAmount::from_sat(sats),
+            is_settled: false,
+            created_at,
+        }
+    }
+
+    #[test]
+    fn cursor_roundtrip() {
+        let cursor = encode_cursor(1_700_000_000, "abcd", TxKind::Offboard);
+        let (sort_time, txid, kind) = decode_cursor(&cursor).unwrap();
+
+        assert_eq!(sort_time, 1_700_000_000);
+        assert_eq!(txid, "abcd");
+        assert_eq!(kind, TxKind::Offboard);
+    }
+
+    #[test]
+    fn invalid_cursor_is_rejected() {
+        assert!(decode_cursor("abcd").is_err());
+        assert!(decode_cursor("x:abcd:Round").is_err());
+        assert!(decode_cursor("1:abcd").is_err());
+        assert!(decode_cursor("1:abcd:Unknown").is_err());
+    }
+
+    #[tokio::test]
+    async fn sync_only_writes_changes() {
+        let db = open_db().await;
+
+        let stats = db
+            .sync(&[boarding(1, None), redeem(2, -500, 1_000)])
+            .await
+            .unwrap();
+        assert_eq!((stats.added, stats.updated, stats.total), (2, 0, 2));
+
+        let stats = db
+            .sync(&[boarding(1, None), redeem(2, -500, 1_000)])
+            .await
+            .unwrap();
+        assert_eq!((stats.added, stats.updated, stats.total), (0, 0, 2));
+
+        let stats = db
+            .sync(&[boarding(1, Some(2_000)), redeem(2, -500, 1_000)])
+            .await
+            .unwrap();
+        assert_eq!((stats.added, stats.updated, stats.total), (0, 1, 2));
+
+        let all = db.load_all().await.unwrap();
+        assert_eq!(all[0].txid, txid(1).to_string());
+        assert_eq!(all[0].confirmed_at, Some(2_000));
+        assert_eq!(all[0].sort_time, 2_000);
+        assert!(db.last_synced_at().await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn sync_skips_entries_final_before_last_known_point() {
+        let db = open_db().await;
+        let newest = 100_000;
+        db.sync(&[boarding(1, Some(1_000)), boarding(2, Some(newest))])
+            .await
+            .unwrap();
+
+        // Changes to entries final long before the newest one are not compared
+        let stats = db
+            .sync(&[
+                boarding(1, Some(1_500)),
+                boarding(2, Some(newest + 10)),
+                boarding(3, None),
+            ])
+            .await
+            .unwrap();
+
+        assert_eq!(
+            (stats.added, stats.updated, stats.removed, stats.total),
+            (1, 1, 0, 3)
+        );
+        let old = db
+            .load_all()
+            .await
+            .unwrap()
+            .into_iter()
+            .find(|tx| tx.txid == txid(1).to_string())
+            .unwrap();
+        assert_eq!(old.confirmed_at, Some(1_000));
+    }
+
+    #[tokio::test]
+    async fn clear_removes_entries_and_sync_state() {
+        let db = open_db().await;
+        db.sync(&[boarding(1, Some(2_000)), redeem(2, -500, 1_000)])
+            .await
+            .unwrap();
+        db.clear_stale();
+
+        db.clear().await.unwrap();
+
+        assert!(db.load_all().await.unwrap().is_empty());
+        assert_eq!(db.last_synced_at().await.unwrap(), None);
+        assert!(db.needs_sync().await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn keys_entries_on_txid_and_kind() {
+        let db = open_db().await;
+        let commitment = txid(3);
+        let round = Transaction::Commitment {
+            txid: commitment,
+            amount: SignedAmount::from_sat(-20_000),
+            created_at: 1_000,
+        };
+        let offboard = Transaction::Offboard {
+            commitment_txid: commitment,
+            amount: Amount::from_sat(20_000),
+            confirmed_at: Some(1_100),
+        };
+
+        let stats = db.sync(&[round, offboard]).await.unwrap();
+
+        assert_eq!(stats.added, 2);
+        let kinds: Vec<_> = db
+            .load_all()
+            .await
+            .unwrap()
+            .iter()
+            .map(|tx| tx.kind)
+            .collect();
+        assert_eq!(kinds, vec![TxKind::Offboard, TxKind::Round]);
+    }
+
+    #[tokio::test]
+    async fn prunes_dropped_unconfirmed_entries() {
+        let db = open_db().await;
+        db.sync(&[
+            boarding(1, None),
+            boarding(2, Some(1_000)),
+            redeem(3, 700, 900),
+        ])
+        .await
+        .unwrap();
+
+        // The unconfirmed boarding transaction was replaced, the server pruned
+        // the other two
+        let stats = db.sync(&[boarding(4, None)]).await.unwrap();
+
+        assert_eq!((stats.added, stats.removed, stats.total), (1, 1, 3));
+        let mut txids: Vec<_> = db
+            .load_all()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|tx| tx.txid)
+            .collect();
+        txids.sort();
+        let mut expected = vec![txid(2), txid(3), txid(4)]
+            .into_iter()
+            .map(|t| t.to_string())
+            .collect::<Vec<_>>();
+        expected.sort();
+        assert_eq!(txids, expected);
+    }
+
+    #[tokio::test]
+    async fn pages_with_cursor_and_filter() {
+        let db = open_db().await;
+        db.sync(&[
+            redeem(1, 100, 1_000),
+            redeem(2, -200, 1_000),
+            redeem(3, 300, 2_000),
+            boarding(4, Some(500)),
+        ])
+        .await
+        .unwrap();
+        let filter = TxHistoryFilter::default();
+
+        let first = db.page(None, 2, &filter).await.unwrap();
+        let second = db
+            .page(first.next_cursor.as_deref(), 2, &filter)
+            .await
+            .unwrap();
+
+        let order: Vec<_> = first
+            .items
+            .iter()
+            .chain(&second.items)
+            .map(|tx| tx.txid.clone())
+            .collect();
+        assert_eq!(
+            order,
+            vec![txid(3), txid(2), txid(1), txid(4)]
+                .into_iter()
+                .map(|t| t.to_string())
+                .collect::<Vec<_>>()
+        );
+        assert!(second.next_cursor.is_none());
+
+        let incoming_redeems = TxHistoryFilter {
+            direction: Some(TxDirection::Incoming),
+            kinds: Some(vec![TxKind::Redeem]),
+            from: Some(1_000),
+            to: Some(2_000),
+        };
+        let page = db.page(None, 10, &incoming_redeems).await.unwrap();
+        assert_eq!(page.items.len(), 1);
+        assert_eq!(page.items[0].txid, txid(1).to_string());
+
+        let none = TxHistoryFilter {
+            kinds: Some(Vec::new()),
+            ..Default::default()
+        };
+        assert!(db.page(None, 10, &none).await.unwrap().items.is_empty());
+    }
+
+    #[tokio::test]
+    async fn sync_is_needed_when_stale_or_old() {
+        let db = open_db().await;
+        assert!(db.needs_sync().await.unwrap());
+
+        db.clear_stale();
+        db.sync(&[]).await.unwrap();
+        assert!(!db.needs_sync().await.unwrap());
+
+        db.mark_stale();
+        assert!(db.needs_sync().await.unwrap());
+
+        db.clear_stale();
+        sqlx::query("UPDATE sync_state SET last_synced_at = last_synced_at - ?")
+            .bind(SYNC_INTERVAL_SECS)
+            .execute(&db.pool)
+            .await
+            .unwrap();
+        assert!(db.needs_sync().await.unwrap());
+    }
+}
@@ -0,0 +1,533 @@
+//! Message signing with wallet keys.
+//!
+//! Boarding addresses are proven with BIP-322 signatures. A boarding output
+//! can only be spent by the wallet alone through its timelocked exit script,
+//! so the proof is a script-path spend of that leaf. Because `OP_CSV` needs a
+//! transaction version of at least 2 and a matching sequence, such proofs use
+//! the BIP-322 "full" format; the "simple" format (witness only) is used
+//! whenever the default `to_sign` transaction suffices.
+//!
+//! The Ark identity key signs plain BIP-340 Schnorr signatures over the
+//! SHA256 of the message.
+
+use crate::ark::client::require_network;
+use crate::error::ApiError;
+use crate::state::WALLET_DB;
+use anyhow::{Result, anyhow, bail};
+use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
+use bitcoin::absolute::LockTime;
+use bitcoin::address::NetworkUnchecked;
+use bitcoin::consensus::{deserialize, serialize};
+use bitcoin::hashes::{Hash, HashEngine, sha256};
+use bitcoin::key::{Keypair, Secp256k1, XOnlyPublicKey};
+use bitcoin::opcodes::all::{OP_CHECKSIG, OP_CSV, OP_DROP, OP_RETURN};
+use bitcoin::opcodes::{Class, ClassifyContext};
+use bitcoin::script::{Builder, Instruction, read_scriptint};
+use bitcoin::secp256k1::{Message, schnorr};
+use bitcoin::sighash::{Prevouts, SighashCache};
+use bitcoin::taproot::{ControlBlock, TapLeafHash};
+use bitcoin::transaction::Version;
+use bitcoin::{
+    Address, Amount, CompressedPublicKey, OutPoint, Script, ScriptBuf, Sequence, TapSighashType,
+    Transaction, TxIn, TxOut, Witness, ecdsa, relative, taproot,
+};
+use std::str::FromStr;
+
+/// Tag of the BIP-322 message hash.
+const BIP322_TAG: &[u8] = b"BIP0322-signed-message";
+
+/// A Schnorr signature made with the Ark identity key.
+#[derive(Debug, Clone)]
+pub struct IdentitySignature {
+    /// X-only public key of the Ark identity, hex encoded
+    pub pubkey: String,
+    /// BIP-340 signature over SHA256(message), hex encoded
+    pub signature: String,
+}
+
+/// Sign `message` with the boarding address `address` of the loaded wallet.
+///
+/// Returns the base64 encoded BIP-322 signature.
+pub(crate) fn sign_with_boarding_address(address: &str, message: &str) -> Result<String> {
+    let network = crate::ark::client::info()?.network;
+    let unchecked = Address::<NetworkUnchecked>::from_str(address)
+        .map_err(|e| ApiError::invalid_address(address, e))?;
+    let checked = require_network(unchecked, address, network)?;
+
+    let wallet_db = {
+        let lock = WALLET_DB
+            .try_get()
+            .ok_or_else(|| ApiError::not_initialized("Ark wallet"))?;
+        lock.read().clone()
+    };
+
+    let (boarding_output, secret_key) = wallet_db
+        .find_boarding_output(&checked.script_pubkey())
+        .map_err(|e| anyhow!("Failed to load boarding outputs: {}", e))?
+        .ok_or_else(|| {
+            ApiError::invalid_address(address, "not a boarding address of this wallet")
+        })?;
+
+    let secp = Secp256k1::new();
+    let keypair = Keypair::from_secret_key(&secp, &secret_key);
+    let (exit_script, control_block) = boarding_output.exit_spend_info();
+
+    let to_sign = sign_script_path(
+        &checked.script_pubkey(),
+        &exit_script,
+        &control_block,
+        boarding_output.exit_delay(),
+        &keypair,
+        message.as_bytes(),
+    )?;
+
+    Ok(encode_signature(&to_sign))
+}
+
+/// Verify a BIP-322 signature (simple or full format) for `address`.
+///
+/// Supports P2WPKH, P2TR key-path spends and P2TR script-path spends of
+/// `<pk> CHECKSIG` and `<delay> CSV DROP <pk> CHECKSIG` leaves, which covers
+/// Ark boarding addresses. Returns `false` for a well-formed but invalid
+/// signature.
+pub(crate) fn verify_bip322(address: &str, message: &str, signature: &str) -> Result<bool> {
+    let address = Address::<NetworkUnchecked>::from_str(address)
+        .map_err(|e| ApiError::invalid_address(address, e))?
+        .assume_checked();
+
+    verify(&address.script_pubkey(), message.as_bytes(), signature)
+}
+
+/// Sign `message` with the Ark identity key.
+pub(crate) fn sign_with_identity(keypair: &Keypair, message: &str) -> IdentitySignature {
+    let secp = Secp256k1::new();
+    let digest = sha256::Hash::hash(message.as_bytes());
+    let signature =
+        secp.sign_schnorr_no_aux_rand(&Message::from_digest(digest.to_byte_array()), keypair);
+
+    IdentitySignature {
+        pubkey: keypair.x_only_public_key().0.to_string(),
+        signature: hex::encode(signature.serialize()),
+    }
+}
+
+/// Verify a Schnorr signature made with `sign_with_identity`.
+pub(crate) fn verify_identity_signature(
+    pubkey: &str,
+    message: &str,
+    signature: &str,
+) -> Result<bool> {
+    let pubkey =
+        XOnlyPublicKey::from_str(pubkey).map_err(|e| anyhow!("Invalid public key: {}", e))?;
+    let signature_bytes =
+        hex::decode(signature).map_err(|e| anyhow!("Invalid signature hex: {}", e))?;
+    let signature = schnorr::Signature::from_slice(&signature_bytes)
+        .map_err(|e| anyhow!("Invalid signature: {}", e))?;
+
+    let digest = sha256::Hash::hash(message.as_bytes());
+    let secp = Secp256k1::verification_only();
+    Ok(secp
+        .verify_schnorr(
+            &signature,
+            &Message::from_digest(digest.to_byte_array()),
+            &pubkey,
+        )
+        .is_ok())
+}
+
+/// BIP-322 tagged hash of the message.
+fn message_hash(message: &[u8]) -> sha256::Hash {
+    let tag = sha256::Hash::hash(BIP322_TAG);
+    let mut engine = sha256::Hash::engine();
+    engine.input(tag.as_ref());
+    engine.input(tag.as_ref());
+    engine.input(message);
+    sha256::Hash::from_engine(engine)
+}
+
+/// The virtual transaction committing to the message and the address.
+fn to_spend(script_pubkey: &Script, message: &[u8]) -> Transaction {
+    let script_sig = Builder::new()
+        .push_int(0)
+        .push_slice(message_hash(message).to_byte_array())
+        .into_script();
+
+    Transaction {
+        version: Version(0),
+        lock_time: LockTime::ZERO,
+        input: vec![TxIn {
+            previous_output: OutPoint::null(),
+            script_sig,
+            sequence: Sequence::ZERO,
+            witness: Witness::new(),
+        }],
+        output: vec![TxOut {
+            value: Amount::ZERO,
+            script_pubkey: script_pubkey.to_owned(),
+        }],
+    }
+}
+
+/// The virtual transaction spending `to_spend`, carrying the signature.
+fn to_sign(to_spend: &Transaction, version: Version, sequence: Sequence) -> Transaction {
+    Transaction {
+        version,
+        lock_time: LockTime::ZERO,
+        input: vec![TxIn {
+            previous_output: OutPoint {
+                txid: to_spend.compute_txid(),
+                vout: 0,
+            },
+            script_sig: ScriptBuf::new(),
+            sequence,
+            witness: Witness::new(),
+        }],
+        output: vec![TxOut {
+            value: Amount::ZERO,
+            script_pubkey: Builder::new().push_opcode(OP_RETURN).into_script(),
+        }],
+    }
+}
+
+/// Sign by spending the taproot leaf `leaf_script` of `script_pubkey`.
+///
+/// A relative timelock in `sequence` is honoured by using version 2.
+fn sign_script_path(
+    script_pubkey: &Script,
+    leaf_script: &Script,
+    control_block: &ControlBlock,
+    sequence: Sequence,
+    keypair: &Keypair,
+    message: &[u8],
+) -> Result<Transaction> {
+    let to_spend = to_spend(script_pubkey, message);
+    let version = if sequence.is_relative_lock_time() && sequence != Sequence::ZERO {
+        Version::TWO
+    } else {
+        Version(0)
+    };
+    let mut to_sign = to_sign(&to_spend, version, sequence);
+
+    let leaf_hash = TapLeafHash::from_script(leaf_script, control_block.leaf_version);
+    let sighash = SighashCache::new(&to_sign)
+        .taproot_script_spend_signature_hash(
+            0,
+            &Prevouts::All(&to_spend.output),
+            leaf_hash,
+            TapSighashType::Default,
+        )
+        .map_err(|e| anyhow!("Failed to compute sighash: {}", e))?;
+
+    let secp = Secp256k1::new();
+    let signature = taproot::Signature {
+        signature: secp.sign_schnorr_no_aux_rand(&Message::from(sighash), keypair),
+        sighash_type: TapSighashType::Default,
+    };
+
+    to_sign.input[0].witness = Witness::from_slice(&[
+        signature.to_vec(),
+        leaf_script.to_bytes(),
+        control_block.serialize(),
+    ]);
+
+    Ok(to_sign)
+}
+
+/// Simple format if the default `to_sign` transaction was used, full otherwise.
+fn encode_signature(to_sign: &Transaction) -> String {
+    let is_default = to_sign.version == Version(0)
+        && to_sign.lock_time == LockTime::ZERO
+        && to_sign.input[0].sequence == Sequence::ZERO;
+
+    if is_default {
+        BASE64.encode(serialize(&to_sign.input[0].witness))
+    } else {
+        BASE64.encode(serialize(to_sign))
+    }
+}
+
+fn verify(script_pubkey: &Script, message: &[u8], signature: &str) -> Result<bool> {
+    let bytes = BASE64
+        .decode(signature.trim())
+        .map_err(|e| anyhow!("Invalid signature encoding: {}", e))?;
+
+    let to_spend = to_spend(script_pubkey, message);
+    let to_sign = match deserialize::<Transaction>(&bytes) {
+        Ok(tx) => {
+            let spends_to_spend = tx.input.len() == 1
+                && tx.input[0].previous_output
+                    == OutPoint {
+                        txid: to_spend.compute_txid(),
+                        vout: 0,
+                    };
+            let is_empty_op_return = tx.output.len() == 1
+                && tx.output[0].value == Amount::ZERO
+                && tx.output[0].script_pubkey.as_bytes() == [OP_RETURN.to_u8()];
+            if !spends_to_spend || !is_empty_op_return {
+                return Ok(false);
+            }
+            tx
+        }
+        Err(_) => {
+            let witness: Witness =
+                deserialize(&bytes).map_err(|e| anyhow!("Invalid BIP-322 signature: {}", e))?;
+            let mut tx = to_sign(&to_spend, Version(0), Sequence::ZERO);
+            tx.input[0].witness = witness;
+            tx
+        }
+    };
+
+    verify_spend(&to_sign, &to_spend.output[0])
+}
+
+/// Check the witness of the only input of `to_sign` against `prevout`.
+fn verify_spend(to_sign: &Transaction, prevout: &TxOut) -> Result<bool> {
+    let witness = &to_sign.input[0].witness;
+    let script_pubkey = &prevout.script_pubkey;
+    let prevouts = [prevout];
+    let mut cache = SighashCache::new(to_sign);
+    let secp = Secp256k1::verification_only();
+
+    if script_pubkey.is_p2wpkh() {
+        let (Some(signature), Some(pubkey), 2) = (witness.nth(0), witness.nth(1), witness.len())
+        else {
+            return Ok(false);
+        };
+        let (Ok(signature), Ok(pubkey)) = (
+            ecdsa::Signature::from_slice(signature),
+            CompressedPublicKey::from_slice(pubkey),
+        ) else {
+            return Ok(false);
+        };
+        if ScriptBuf::new_p2wpkh(&pubkey.wpubkey_hash()) != *script_pubkey {
+            return Ok(false);
+        }
+
+        let sighash = cache
+            .p2wpkh_signature_hash(0, script_pubkey, prevout.value, signature.sighash_type)
+            .map_err(|e| anyhow!("Failed to compute sighash: {}", e))?;
+        return Ok(secp
+            .verify_ecdsa(&Message::from(sighash), &signature.signature, &pubkey.0)
+            .is_ok());
+    }
+
+    if script_pubkey.is_p2tr() {
+        let output_key = XOnlyPublicKey::from_slice(&script_pubkey.as_bytes()[2..34])
+            .map_err(|e| anyhow!("Invalid taproot output key: {}", e))?;
+
+        return match witness.len() {
+            1 => {
+                let Some(Ok(signature)) = witness.nth(0).map(taproot::Signature::from_slice) else {
+                    return Ok(false);
+                };
+                let sighash = cache
+                    .taproot_key_spend_signature_hash(
+                        0,
+                        &Prevouts::All(&prevouts),
+                        signature.sighash_type,
+                    )
+                    .map_err(|e| anyhow!("Failed to compute sighash: {}", e))?;
+                Ok(secp
+                    .verify_schnorr(&signature.signature, &Message::from(sighash), &output_key)
+                    .is_ok())
+            }
+            3 => {
+                let (Some(signature), Some(leaf_script), Some(control_block)) =
+                    (witness.nth(0), witness.nth(1), witness.nth(2))
+                else {
+                    return Ok(false);
+                };
+                let (Ok(signature), Ok(control_block)) = (
+                    taproot::Signature::from_slice(signature),
+                    ControlBlock::decode(control_block),
+                ) else {
+                    return Ok(false);
+                };
+                let leaf_script = Script::from_bytes(leaf_script);
+                if !control_block.verify_taproot_commitment(&secp, output_key, leaf_script) {
+                    return Ok(false);
+                }
+
+                let Some((pubkey, delay)) = parse_leaf(leaf_script) else {
+                    bail!("Unsupported taproot leaf script: {}", leaf_script);
+                };
+                if let Some(delay) = delay {
+                    let sequence = to_sign.input[0].sequence;
+                    if to_sign.version < Version::TWO || !delay.is_implied_by_sequence(sequence) {
+                        return Ok(false);
+                    }
+                }
+
+                let leaf_hash = TapLeafHash::from_script(leaf_script, control_block.leaf_version);
+                let sighash = cache
+                    .taproot_script_spend_signature_hash(
+                        0,
+                        &Prevouts::All(&prevouts),
+                        leaf_hash,
+                        signature.sighash_type,
+                    )
+                    .map_err(|e| anyhow!("Failed to compute sighash: {}", e))?;
+                Ok(secp
+                    .verify_schnorr(&signature.signature, &Message::from(sighash), &pubkey)
+                    .is_ok())
+            }
+            _ => Ok(false),
+        };
+    }
+
+    bail!("Unsupported address type for BIP-322 verification")
+}
+
+/// Recognize `<pk> CHECKSIG` and `<delay> CSV DROP <pk> CHECKSIG` leaves.
+fn parse_leaf(script: &Script) -> Option<(XOnlyPublicKey, Option<relative::LockTime>)> {
+    let instructions = script.instructions().collect::<Result<Vec<_>, _>>().ok()?;
+
+    match instructions.as_slice() {
+        [Instruction::PushBytes(pk), Instruction::Op(OP_CHECKSIG)] => {
+            Some((XOnlyPublicKey::from_slice(pk.as_bytes()).ok()?, None))
+        }
+        [
+            delay,
+            Instruction::Op(OP_CSV),
+            Instruction::Op(OP_DROP),
+            Instruction::PushBytes(pk),
+            Instruction::Op(OP_CHECKSIG),
+        ] => {
+            let delay = match delay {
+                Instruction::PushBytes(bytes) => read_scriptint(bytes.as_bytes()).ok()?,
+                Instruction::Op(op) => match op.classify(ClassifyContext::TapScript) {
+                    Class::PushNum(n) => n as i64,
+                    _ => return None,
+                },
+            };
+            let delay = relative::LockTime::from_consensus(u32::try_from(delay).ok()?).ok()?;
+            Some((XOnlyPublicKey::from_slice(pk.as_bytes()).ok()?, Some(delay)))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitcoin::secp256k1::rand::rngs::OsRng;
+    use bitcoin::taproot::{LeafVersion, TaprootBuilder};
+
+    /// Taproot output with a single leaf, like a boarding output's exit path.
+    fn single_leaf_output(leaf_script: &ScriptBuf) -> (ScriptBuf, ControlBlock) {
+        let secp = Secp256k1::new();
+        let internal_key = Keypair::new(&secp, &mut OsRng).x_only_public_key().0;
+        let spend_info = TaprootBuilder::new()
+            .add_leaf(0, leaf_script.clone())
+            .unwrap()
+            .finalize(&secp, internal_key)
+            .unwrap();
+        let control_block = spend_info
+            .control_block(&(leaf_script.clone(), LeafVersion::TapScript))
+            .unwrap();
+        let script_pubkey = ScriptBuf::new_p2tr_tweaked(spend_info.output_key());
+        (script_pubkey, control_block)
+    }
+
+    fn exit_leaf(keypair: &Keypair, delay: Sequence) -> ScriptBuf {
+        Builder::new()
+            .push_int(delay.to_consensus_u32() as i64)
+            .push_opcode(OP_CSV)
+            .push_opcode(OP_DROP)
+            .push_x_only_key(&keypair.x_only_public_key().0)
+            .push_opcode(OP_CHECKSIG)
+            .into_script()
+    }
+
+    #[test]
+    fn message_hash_matches_bip322_vectors() {
+        assert_eq!(
+            hex::encode(message_hash(b"").to_byte_array()),
+            "c90c269c4f8fcbe6880f72a721ddfbf1914268a794cbb21cfafee13770ae19f1"
+        );
+        assert_eq!(
+            hex::encode(message_hash(b"Hello World").to_byte_array()),
+            "f0eb03b1a75ac6d9847f55c624a99169b5dccba2a31f5b23bea77ba270de0a7a"
+        );
+    }
+
+    #[test]
+    fn timelocked_leaf_uses_full_format() {
+        let keypair = Keypair::new(&Secp256k1::new(), &mut OsRng);
+        let delay = Sequence::from_height(144);
+        let leaf = exit_leaf(&keypair, delay);
+        let (script_pubkey, control_block) = single_leaf_output(&leaf);
+
+        let to_sign = sign_script_path(
+            &script_pubkey,
+            &leaf,
+            &control_block,
+            delay,
+            &keypair,
+            b"proof of funds",
+        )
+        .unwrap();
+        let signature = encode_signature(&to_sign);
+
+        assert!(deserialize::<Transaction>(&BASE64.decode(&signature).unwrap()).is_ok());
+        assert!(verify(&script_pubkey, b"proof of funds", &signature).unwrap());
+        assert!(!verify(&script_pubkey, b"other message", &signature).unwrap());
+    }
+
+    #[test]
+    fn timelock_must_be_satisfied() {
+        let keypair = Keypair::new(&Secp256k1::new(), &mut OsRng);
+        let leaf = exit_leaf(&keypair, Sequence::from_height(144));
+        let (script_pubkey, control_block) = single_leaf_output(&leaf);
+
+        let to_sign = sign_script_path(
+            &script_pubkey,
+            &leaf,
+            &control_block,
+            Sequence::from_height(10),
+            &keypair,
+            b"hello",
+        )
+        .unwrap();
+
+        assert!(!verify(&script_pubkey, b"hello", &encode_signature(&to_sign)).unwrap());
+    }
+
+    #[test]
+    fn plain_leaf_uses_simple_format() {
+        let keypair = Keypair::new(&Secp256k1::new(), &mut OsRng);
+        let leaf = Builder::new()
+            .push_x_only_key(&keypair.x_only_public_key().0)
+            .push_opcode(OP_CHECKSIG)
+            .into_script();
+        let (script_pubkey, control_block) = single_leaf_output(&leaf);
+
+        let to_sign = sign_script_path(
+            &script_pubkey,
+            &leaf,
+            &control_block,
+            Sequence::ZERO,
+            &keypair,
+            b"hello",
+        )
+        .unwrap();
+        let signature = encode_signature(&to_sign);
+
+        assert!(deserialize::<Witness>(&BASE64.decode(&signature).unwrap()).is_ok());
+        assert!(verify(&script_pubkey, b"hello", &signature).unwrap());
+    }
+
+    #[test]
+    fn identity_signature_roundtrip() {
+        let keypair = Keypair::new(&Secp256k1::new(), &mut OsRng);
+        let signed = sign_with_identity(&keypair, "I own this key");
+
+        assert!(
+            verify_identity_signature(&signed.pubkey, "I own this key", &signed.signature).unwrap()
+        );
+        assert!(
+            !verify_identity_signature(&signed.pubkey, "something else", &signed.signature)
+                .unwrap()
+        );
+    }
+}
@@ -0,0 +1,557 @@
+//! Migration of all funds to a different Ark server.
+//!
+//! A migration connects to the new server with the same keys, offboards the
+//! whole off-chain balance from the old server to a boarding address of the
+//! new server and boards it there once confirmed. Progress is stored on disk
+//! after every step, and the offboard amount before it is sent, so an
+//! interrupted migration resumes where it stopped instead of sending funds
+//! twice.
+//!
+//! Boarding outputs of the old server cannot be offboarded, so a migration
+//! only starts once there are none. The cached history belongs to the old
+//! server and is cleared when the migration completes.
+
+use crate::ark::client;
+use crate::ark::history_db;
+use crate::ark::mnemonic_file::{derive_master_xpriv, read_mnemonic_file};
+use crate::ark::{active_server, connect_client, install_client};
+use crate::error::ApiError;
+use crate::state::ARK_CLIENT;
+use anyhow::{Result, anyhow, bail};
+use ark_core::ExplorerUtxo;
+use bitcoin::key::Secp256k1;
+use bitcoin::{Address, Amount, Network, OutPoint};
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+/// File name of the migration progress inside the data directory.
+pub const MIGRATION_FILE: &str = "ark_migration.json";
+
+/// Step a migration has reached.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MigrationStep {
+    /// Connected to the new server, nothing moved yet
+    Started,
+    /// Offboard to the new server's boarding address was requested
+    Offboarding,
+    /// Offboard transaction broadcast, waiting for confirmation
+    Offboarded,
+    /// Funds boarded on the new server; the wallet now uses it
+    Completed,
+}
+
+/// Persisted progress of a server migration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MigrationState {
+    pub old_server: String,
+    pub new_server: String,
+    pub step: MigrationStep,
+    /// Boarding address on the new server receiving the funds
+    pub boarding_address: Option<String>,
+    pub offboard_txid: Option<String>,
+    pub amount_sats: Option<u64>,
+    pub started_at: i64,
+    pub updated_at: i64,
+}
+
+/// Load the stored migration progress, if a migration was started.
+pub fn load_state(data_dir: &str) -> Result<Option<MigrationState>> {
+    let path = Path::new(data_dir).join(MIGRATION_FILE);
+
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let content =
+        fs::read_to_string(&path).map_err(|e| anyhow!("Failed to read migration file: {}", e))?;
+
+    let state: MigrationState = serde_json::from_str(&content)
+        .map_err(|e| anyhow!("Failed to parse migration file: {}", e))?;
+
+    Ok(Some(state))
+}
+
+fn save_state(data_dir: &str, state: &mut MigrationState) -> Result<()> {
+    state.updated_at = now_unix();
+
+    let path = Path::new(data_dir).join(MIGRATION_FILE);
+
+    let content = serde_json::to_string_pretty(state)
+        .map_err(|e| anyhow!("Failed to serialize migration state: {}", e))?;
+
+    fs::write(&path, content).map_err(|e| anyhow!("Failed to write migration file: {}", e))?;
+
+    Ok(())
+}
+
+/// Delete the stored migration progress.
+///
+/// Only allowed before funds were moved or after the migration completed.
+pub fn clear_state(data_dir: &str) -> Result<()> {
+    if let Some(state) = load_state(data_dir)? {
+        if matches!(
+            state.step,
+            MigrationStep::Offboarding | MigrationStep::Offboarded
+        ) {
+            bail!("Cannot cancel a migration while funds are in transit");
+        }
+    }
+
+    let path = Path::new(data_dir).join(MIGRATION_FILE);
+    if path.exists() {
+        fs::remove_file(&path).map_err(|e| anyhow!("Failed to delete migration file: {}", e))?;
+    }
+
+    Ok(())
+}
+
+/// Start or resume a migration to `new_server`.
+///
+/// `old_server` must be the server the wallet is connected to. Runs as many
+/// steps as possible and returns the reached state. While the offboard
+/// transaction is unconfirmed the migration stays `Offboarded`; call again
+/// later to finish it. Once `Completed`, the new server is the wallet's
+/// active Ark client and the app should persist `new_server`.
+pub async fn migrate(
+    data_dir: &str,
+    network: Network,
+    esplora_url: &str,
+    old_server: &str,
+    new_server: &str,
+    boltz_url: String,
+) -> Result<MigrationState> {
+    let mut state = resume_or_start(load_state(data_dir)?, old_server, new_server)?;
+
+    if state.step == MigrationStep::Completed {
+        return Ok(state);
+    }
+
+    // Until the migration completes the active client is the old server's
+    let active = active_server().ok_or_else(|| ApiError::not_initialized("Ark client"))?;
+    if !same_server(&active, old_server) {
+        bail!(
+            "The wallet is connected to '{}', not to '{}'",
+            active,
+            old_server
+        );
+    }
+
+    let mnemonic = read_mnemonic_file(data_dir)
+        .map_err(|e| anyhow!("Failed to read mnemonic file: {}", e))?
+        .ok_or_else(|| anyhow!("No wallet found"))?;
+    let master_xpriv = derive_master_xpriv(&mnemonic, network)
+        .map_err(|e| anyhow!("Failed to derive master key: {}", e))?;
+
+    tracing::info!(new_server, step = ?state.step, "Running Ark server migration");
+
+    let (new_client, key_provider, esplora, wallet_db) = connect_client(
+        master_xpriv,
+        Secp256k1::new(),
+        network,
+        esplora_url,
+        new_server,
+        boltz_url,
+        data_dir,
+    )
+    .await?;
+
+    if new_client.server_info.network != network {
+        bail!(ApiError::NetworkMismatch {
+            expected: network.to_string(),
+            actual: new_client.server_info.network.to_string(),
+        });
+    }
+
+    if state.step == MigrationStep::Started {
+        ensure_no_boarding_funds().await?;
+
+        let boarding_address = new_client
+            .get_boarding_address()
+            .map_err(|e| anyhow!("Could not get boarding address on new server: {e:#}"))?;
+
+        state.boarding_address = Some(boarding_address.to_string());
+        state.step = MigrationStep::Offboarding;
+        save_state(data_dir, &mut state)?;
+    }
+
+    let boarding_address = state
+        .boarding_address
+        .as_deref()
+        .ok_or_else(|| anyhow!("Migration state is missing the boarding address"))?;
+    let boarding_address = Address::from_str(boarding_address)?.require_network(network)?;
+
+    if state.step == MigrationStep::Offboarding {
+        // A previous attempt may have sent the funds before the state was saved
+        let existing = esplora
+            .find_outpoints(&boarding_address)
+            .await
+            .map_err(|e| anyhow!("Could not check boarding address: {e:#}"))?;
+
+        if let Some(output) = offboard_output(&state, &existing) {
+            tracing::info!("Offboard already reached the new server");
+            state.offboard_txid = Some(output.outpoint.txid.to_string());
+        } else {
+            let balance = old_offchain_balance().await?;
+            if state.amount_sats.is_some_and(|a| a > 0) && balance == Amount::ZERO {
+                // The funds left the old server, but the offboard transaction
+                // is not visible yet. Sending again would fail or pay twice.
+                tracing::info!("Waiting for offboard transaction to show up");
+                return Ok(state);
+            }
+
+            match offboard_amount(&boarding_address, balance).await? {
+                Some(amount) => {
+                    // Stored before sending, so a resumed migration can
+                    // recognize the offboard output by its amount
+                    state.amount_sats = Some(amount.to_sat());
+                    save_state(data_dir, &mut state)?;
+
+                    tracing::info!(%amount, "Offboarding to new server");
+                    let txid = client::send(boarding_address.to_string(), amount, None).await?;
+                    state.offboard_txid = Some(txid.to_string());
+                    save_state(data_dir, &mut state)?;
+                }
+                None => {
+                    tracing::info!("No off-chain funds on old server, switching directly");
+                    state.amount_sats = Some(0);
+                }
+            }
+        }
+
+        state.step = MigrationStep::Offboarded;
+        save_state(data_dir, &mut state)?;
+    }
+
+    if state.step == MigrationStep::Offboarded {
+        let utxos = esplora
+            .find_outpoints(&boarding_address)
+            .await
+            .map_err(|e| anyhow!("Could not check boarding address: {e:#}"))?;
+
+        let Some(confirmed) = boardable_outpoints(&state, &utxos) else {
+            tracing::info!("Waiting for offboard transaction to confirm");
+            return Ok(state);
+        };
+
+        // Funds received on the old server during the migration would be
+        // left behind once the wallet switches
+        let remaining = old_offchain_balance().await?;
+        if remaining > Amount::ZERO {
+            bail!(
+                "{} arrived on the current server during the migration, \
+                 send them to yourself on the new server before it can finish",
+                remaining
+            );
+        }
+
+        if !confirmed.is_empty() {
+            let mut rng = StdRng::from_entropy();
+            new_client
+                .settle_vtxos(&mut rng, &[], &confirmed)
+                .await
+                .map_err(|e| anyhow!("Failed boarding funds on new server: {e:#}"))?;
+        }
+
+        install_client(new_client, new_server, key_provider, esplora, wallet_db);
+
+        state.step = MigrationStep::Completed;
+        save_state(data_dir, &mut state)?;
+
+        // The new server's history does not contain the old server's transactions
+        match history_db::get_db() {
+            Ok(db) => {
+                if let Err(e) = db.clear().await {
+                    tracing::warn!("Could not clear the old server's history: {e:#}");
+                }
+            }
+            Err(e) => tracing::warn!("Transaction history cache unavailable: {e:#}"),
+        }
+
+        tracing::info!(new_server, "Ark server migration completed");
+    }
+
+    Ok(state)
+}
+
+/// Continue the stored migration, or start a new one if there is none.
+fn resume_or_start(
+    existing: Option<MigrationState>,
+    old_server: &str,
+    new_server: &str,
+) -> Result<MigrationState> {
+    match existing {
+        Some(state) if !same_server(&state.new_server, new_server) => {
+            bail!(
+                "A migration to '{}' is already in progress",
+                state.new_server
+            );
+        }
+        Some(state) if !same_server(&state.old_server, old_server) => {
+            bail!(
+                "The migration in progress moves funds from '{}', not from '{}'",
+                state.old_server,
+                old_server
+            );
+        }
+        Some(state) => Ok(state),
+        None => {
+            if same_server(old_server, new_server) {
+                bail!("The wallet already uses '{}'", new_server);
+            }
+
+            let now = now_unix();
+            Ok(MigrationState {
+                old_server: old_server.to_string(),
+                new_server: new_server.to_string(),
+                step: MigrationStep::Started,
+                boarding_address: None,
+                offboard_txid: None,
+                amount_sats: None,
+                started_at: now,
+                updated_at: now,
+            })
+        }
+    }
+}
+
+/// Compare server URLs, ignoring surrounding whitespace and trailing slashes.
+fn same_server(a: &str, b: &str) -> bool {
+    a.trim().trim_end_matches('/') == b.trim().trim_end_matches('/')
+}
+
+/// Outputs at the new server's boarding address that can be boarded, or
+/// `None` while the offboarded funds are still unconfirmed.
+fn boardable_outpoints(state: &MigrationState, utxos: &[ExplorerUtxo]) -> Option<Vec<OutPoint>> {
+    let confirmed: Vec<_> = utxos
+        .iter()
+        .filter(|u| u.confirmation_blocktime.is_some())
+        .map(|u| u.outpoint)
+        .collect();
+
+    let expects_funds = state.amount_sats.unwrap_or(0) > 0;
+    if expects_funds && (confirmed.is_empty() || confirmed.len() < utxos.len()) {
+        return None;
+    }
+
+    Some(confirmed)
+}
+
+/// Refuse to migrate while the old server's boarding address holds funds.
+///
+/// Those outputs are not part of the off-chain balance and would be left
+/// behind, so they have to be settled first.
+async fn ensure_no_boarding_funds() -> Result<()> {
+    let utxos = client::get_boarding_utxos().await?;
+    if utxos.is_empty() {
+        return Ok(());
+    }
+
+    let total: u64 = utxos.iter().map(|u| u.amount.to_sat()).sum();
+    bail!(
+        "{} boarding output(s) with {} sats are still pending on the current server, \
+         settle them before migrating",
+        utxos.len(),
+        total
+    );
+}
+
+/// The output at the new server's boarding address created by the offboard.
+///
+/// Matched on the offboard txid once it is known, otherwise on the amount
+/// stored before sending. Unrelated outputs at the address do not count.
+fn offboard_output<'a>(
+    state: &MigrationState,
+    utxos: &'a [ExplorerUtxo],
+) -> Option<&'a ExplorerUtxo> {
+    match (&state.offboard_txid, state.amount_sats) {
+        (Some(txid), _) => utxos.iter().find(|u| u.outpoint.txid.to_string() == *txid),
+        (None, Some(amount)) if amount > 0 => utxos.iter().find(|u| u.amount.to_sat() == amount),
+        (None, _) => None,
+    }
+}
+
+/// Off-chain balance on the active (old) server.
+async fn old_offchain_balance() -> Result<Amount> {
+    if ARK_CLIENT.try_get().is_none() {
+        bail!(ApiError::not_initialized("Ark client"));
+    }
+
+    Ok(client::balance().await?.offchain.total())
+}
+
+/// Amount that reaches `address` when offboarding `total`, after the fee.
+///
+/// Returns `None` if there is nothing to send.
+async fn offboard_amount(address: &Address, total: Amount) -> Result<Option<Amount>> {
+    if total == Amount::ZERO {
+        return Ok(None);
+    }
+
+    let fee = client::estimate_onchain_fee(address.to_string(), total.to_sat()).await?;
+    let amount = total
+        .checked_sub(Amount::from_sat(fee.fee_sats))
+        .filter(|a| *a > Amount::ZERO)
+        .ok_or_else(|| anyhow!("Balance of {} does not cover the offboard fee", total))?;
+
+    Ok(Some(amount))
+}
+
+fn now_unix() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitcoin::Txid;
+    use bitcoin::hashes::Hash;
+
+    const OLD: &str = "https://old.example.com";
+    const NEW: &str = "https://new.example.com";
+
+    fn temp_dir() -> String {
+        let dir = std::env::temp_dir().join(format!("migration-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir.to_str().unwrap().to_string()
+    }
+
+    fn at_step(step: MigrationStep, amount_sats: Option<u64>) -> MigrationState {
+        let mut state = resume_or_start(None, OLD, NEW).unwrap();
+        state.step = step;
+        state.amount_sats = amount_sats;
+        state
+    }
+
+    fn utxo(vout: u32, confirmed: bool) -> ExplorerUtxo {
+        ExplorerUtxo {
+            outpoint: OutPoint {
+                txid: Txid::from_byte_array([1; 32]),
+                vout,
+            },
+            amount: Amount::from_sat(50_000),
+            confirmation_blocktime: confirmed.then_some(1_700_000_000),
+            is_spent: false,
+        }
+    }
+
+    #[test]
+    fn starts_new_migration() {
+        let state = resume_or_start(None, OLD, NEW).unwrap();
+
+        assert_eq!(state.step, MigrationStep::Started);
+        assert_eq!(state.old_server, OLD);
+        assert_eq!(state.new_server, NEW);
+    }
+
+    #[test]
+    fn refuses_migration_to_current_server() {
+        assert!(resume_or_start(None, OLD, "https://old.example.com/").is_err());
+    }
+
+    #[test]
+    fn resumes_matching_migration() {
+        let existing = at_step(MigrationStep::Offboarded, Some(10_000));
+
+        let state = resume_or_start(Some(existing), OLD, &format!("{NEW}/")).unwrap();
+
+        assert_eq!(state.step, MigrationStep::Offboarded);
+        assert_eq!(state.amount_sats, Some(10_000));
+    }
+
+    #[test]
+    fn refuses_other_migration_in_progress() {
+        let existing = at_step(MigrationStep::Offboarding, None);
+
+        assert!(resume_or_start(Some(existing.clone()), OLD, "https://other.example.com").is_err());
+        assert!(resume_or_start(Some(existing), "https://other.example.com", NEW).is_err());
+    }
+
+    #[test]
+    fn waits_for_offboard_confirmation() {
+        let state = at_step(MigrationStep::Offboarded, Some(100_000));
+
+        assert_eq!(boardable_outpoints(&state, &[]), None);
+        assert_eq!(boardable_outpoints(&state, &[utxo(0, false)]), None);
+        // All outputs have to confirm, they are boarded together
+        assert_eq!(
+            boardable_outpoints(&state, &[utxo(0, true), utxo(1, false)]),
+            None
+        );
+    }
+
+    #[test]
+    fn boards_confirmed_outputs() {
+        let state = at_step(MigrationStep::Offboarded, Some(100_000));
+
+        let outpoints = boardable_outpoints(&state, &[utxo(0, true), utxo(1, true)]).unwrap();
+
+        assert_eq!(outpoints.len(), 2);
+    }
+
+    #[test]
+    fn completes_without_funds() {
+        let state = at_step(MigrationStep::Offboarded, Some(0));
+
+        assert_eq!(boardable_outpoints(&state, &[]), Some(Vec::new()));
+    }
+
+    #[test]
+    fn matches_offboard_output_by_txid_or_amount() {
+        let mut other = utxo(0, true);
+        other.outpoint.txid = Txid::from_byte_array([2; 32]);
+        other.amount = Amount::from_sat(7_000);
+        let utxos = [other, utxo(1, false)];
+        let matched = |state: &MigrationState| offboard_output(state, &utxos).map(|u| u.outpoint);
+
+        // Nothing planned yet, so no output proves the offboard
+        assert_eq!(matched(&at_step(MigrationStep::Offboarding, None)), None);
+
+        let planned = at_step(MigrationStep::Offboarding, Some(50_000));
+        assert_eq!(matched(&planned), Some(utxos[1].outpoint));
+
+        let other_amount = at_step(MigrationStep::Offboarding, Some(60_000));
+        assert_eq!(matched(&other_amount), None);
+
+        let mut sent = at_step(MigrationStep::Offboarding, Some(60_000));
+        sent.offboard_txid = Some(Txid::from_byte_array([2; 32]).to_string());
+        assert_eq!(matched(&sent), Some(utxos[0].outpoint));
+    }
+
+    #[test]
+    fn persists_progress() {
+        let data_dir = temp_dir();
+        assert!(load_state(&data_dir).unwrap().is_none());
+
+        let mut state = at_step(MigrationStep::Offboarding, None);
+        state.boarding_address = Some("bc1qboarding".to_string());
+        save_state(&data_dir, &mut state).unwrap();
+
+        let loaded = load_state(&data_dir).unwrap().unwrap();
+        assert_eq!(loaded.step, MigrationStep::Offboarding);
+        assert_eq!(loaded.boarding_address.as_deref(), Some("bc1qboarding"));
+    }
+
+    #[test]
+    fn cannot_cancel_while_funds_are_in_transit() {
+        let data_dir = temp_dir();
+
+        for step in [MigrationStep::Offboarding, MigrationStep::Offboarded] {
+            save_state(&data_dir, &mut at_step(step, Some(10_000))).unwrap();
+            assert!(clear_state(&data_dir).is_err());
+            assert!(load_state(&data_dir).unwrap().is_some());
+        }
+
+        for step in [MigrationStep::Started, MigrationStep::Completed] {
+            save_state(&data_dir, &mut at_step(step, None)).unwrap();
+            clear_state(&data_dir).unwrap();
+            assert!(load_state(&data_dir).unwrap().is_none());
+        }
+    }
+}
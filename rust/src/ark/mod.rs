@@ -1,18 +1,31 @@
 mod address_helper;
 pub mod client;
 pub mod esplora;
+pub mod history_db;
+pub mod message_signing;
+pub mod migration;
 pub mod mnemonic_file;
+pub mod psbt_analysis;
+pub mod signer;
 pub mod storage;
+pub mod tx_metadata_db;
 
-use crate::ark::esplora::EsploraClient;
+use crate::ark::esplora::{ESPLORA_ENDPOINTS_FILE, EsploraClient, load_custom_endpoints};
+use crate::ark::history_db::{HISTORY_DB_FILE, HistoryDb};
+use crate::ark::migration::MIGRATION_FILE;
 use crate::ark::mnemonic_file::{
     ARK_BASE_DERIVATION_PATH, NOSTR_DERIVATION_PATH, delete_mnemonic_file, derive_master_xpriv,
     derive_xpriv_at_path, generate_mnemonic, mnemonic_exists, parse_mnemonic, read_mnemonic_file,
     write_mnemonic_file,
 };
 use crate::ark::storage::InMemoryDb;
-use crate::state::{ARK_CLIENT, ESPLORA_URL, UnifiedKeyProvider};
-use anyhow::{Result, anyhow};
+use crate::ark::tx_metadata_db::{TX_METADATA_DB_FILE, TxMetadataDb};
+use crate::offline::{OFFLINE_DB_FILE, OfflineDb};
+use crate::state::{
+    ARK_CLIENT, ARK_SERVER_URL, ArkClient, ESPLORA_CLIENT, HISTORY_DB, KEY_DISCOVERY_INCOMPLETE,
+    KEY_PROVIDER, OFFLINE_DB, TX_METADATA_DB, UnifiedKeyProvider, WALLET_DB,
+};
+use anyhow::{Context, Result, anyhow};
 use ark_client::{Bip32KeyProvider, DEFAULT_GAP_LIMIT, OfflineClient, SqliteSwapStorage};
 use bitcoin::Network;
 use bitcoin::bip32::{DerivationPath, Xpriv};
@@ -22,6 +35,7 @@ use parking_lot::RwLock;
 use std::path::Path;
 use std::str::FromStr;
 use std::sync::Arc;
+use std::sync::atomic::Ordering;
 use std::time::Duration;
 
 /// Setup a new wallet with a freshly generated mnemonic
@@ -59,14 +73,10 @@ pub async fn setup_new_wallet(
         data_dir,
     )
     .await
-    .map_err(|e| {
-        anyhow!(
-            "Failed to setup client - Network: {:?}, Esplora: {}, Server: {}, Boltz: {} - Error: {}",
-            network,
-            esplora,
-            server,
-            boltz_url,
-            e
+    .with_context(|| {
+        format!(
+            "Failed to setup client - Network: {:?}, Esplora: {}, Server: {}, Boltz: {}",
+            network, esplora, server, boltz_url
         )
     })?;
 
@@ -109,13 +119,10 @@ pub async fn restore_wallet(
         data_dir,
     )
     .await
-    .map_err(|e| {
-        anyhow!(
-            "Failed to setup client after restore - Network: {:?}, Esplora: {}, Server: {} - Error: {}",
-            network,
-            esplora,
-            server,
-            e
+    .with_context(|| {
+        format!(
+            "Failed to setup client after restore - Network: {:?}, Esplora: {}, Server: {}",
+            network, esplora, server
         )
     })?;
     Ok(server_pk)
@@ -153,13 +160,10 @@ pub(crate) async fn load_existing_wallet(
         data_dir,
     )
     .await
-    .map_err(|e| {
-        anyhow!(
-            "Failed to setup client from existing wallet - Network: {:?}, Esplora: {}, Server: {} - Error: {}",
-            network,
-            esplora,
-            server,
-            e
+    .with_context(|| {
+        format!(
+            "Failed to setup client from existing wallet - Network: {:?}, Esplora: {}, Server: {}",
+            network, esplora, server
         )
     })?;
 
@@ -177,32 +181,90 @@ pub async fn setup_client_hd(
     boltz_url: String,
     data_dir: String,
 ) -> Result<String> {
-    let db = InMemoryDb::default();
+    // Open local stores before connecting so cached data is available offline
+    open_local_stores(&data_dir).await?;
 
-    // Create keypair from master xpriv for the BDK wallet
-    let kp = Keypair::from_secret_key(&secp, &master_xpriv.private_key);
+    let (client, key_provider, esplora, wallet_db) = connect_client(
+        master_xpriv,
+        secp,
+        network,
+        &esplora_url,
+        &server,
+        boltz_url,
+        &data_dir,
+    )
+    .await?;
 
-    let wallet = ark_bdk_wallet::Wallet::new(kp, secp, network, esplora_url.as_str(), db)
-        .map_err(|e| anyhow!("Failed to create wallet: {}", e))?;
+    let info = client.server_info.clone();
 
-    let wallet = Arc::new(wallet);
-    let esplora = EsploraClient::new(esplora_url.as_str()).map_err(|e| {
+    install_client(client, &server, key_provider, esplora, wallet_db);
+
+    tracing::info!(server_pk = ?info.signer_pk, "Connected to server with HD wallet");
+
+    Ok(info.signer_pk.to_string())
+}
+
+/// Build an Ark client for the given server and discover its keys.
+///
+/// The client is not installed globally; see `install_client`.
+pub(crate) async fn connect_client(
+    master_xpriv: Xpriv,
+    secp: Secp256k1<All>,
+    network: Network,
+    esplora_url: &str,
+    server: &str,
+    boltz_url: String,
+    data_dir: &str,
+) -> Result<(
+    ArkClient,
+    Arc<UnifiedKeyProvider>,
+    Arc<EsploraClient>,
+    InMemoryDb,
+)> {
+    let db = InMemoryDb::default();
+    let wallet_db = db.clone();
+
+    // The configured endpoint comes first, user-added endpoints act as fallbacks
+    let mut endpoints = vec![esplora_url.to_string()];
+    match load_custom_endpoints(data_dir) {
+        Ok(custom) => endpoints.extend(custom),
+        Err(error) => tracing::warn!(?error, "Failed to load custom Esplora endpoints"),
+    }
+
+    let esplora = EsploraClient::with_endpoints(&endpoints).map_err(|e| {
         anyhow!(
             "Failed to create Esplora client for URL '{}': {}",
             esplora_url,
             e
         )
     })?;
+    let esplora = Arc::new(esplora);
     tracing::info!("Checking esplora connection");
 
-    esplora
-        .check_connection()
-        .await
-        .map_err(|e| anyhow!("Failed to connect to Esplora at '{}': {}", esplora_url, e))?;
+    esplora.check_connection().await.map_err(|e| {
+        client::sdk_error(
+            &format!("Failed to connect to Esplora at '{}'", esplora_url),
+            &*e,
+        )
+    })?;
+
+    // The BDK wallet only takes a single URL and has no failover, so give it
+    // the endpoint that currently scores best
+    esplora.check_health().await;
+    let wallet_esplora_url = esplora.best_endpoint();
+    tracing::info!(url = %wallet_esplora_url, "Using Esplora endpoint for on-chain wallet");
+
+    // Create keypair from master xpriv for the BDK wallet
+    let kp = Keypair::from_secret_key(&secp, &master_xpriv.private_key);
+
+    let wallet = ark_bdk_wallet::Wallet::new(kp, secp, network, &wallet_esplora_url, db)
+        .map_err(|e| anyhow!("Failed to create wallet: {}", e))?;
+
+    let wallet = Arc::new(wallet);
 
     tracing::info!("Connecting to Ark");
 
-    let data_path = Path::new(data_dir.as_str());
+    let data_path = Path::new(data_dir);
     let swap_storage = data_path.join("boltz_swap_storage.sqlite");
 
     let sqlite_storage = SqliteSwapStorage::new(swap_storage)
@@ -214,30 +276,49 @@ pub async fn setup_client_hd(
     let base_path = DerivationPath::from_str(ARK_BASE_DERIVATION_PATH)
         .map_err(|e| anyhow!("Invalid base derivation path: {}", e))?;
     let bip32_provider = Bip32KeyProvider::new(master_xpriv, base_path);
-    let key_provider = UnifiedKeyProvider::Hd(bip32_provider);
+    let key_provider = Arc::new(UnifiedKeyProvider::Hd(bip32_provider));
 
     let client = OfflineClient::new(
         "lenda-mobile".to_string(),
-        Arc::new(key_provider),
-        Arc::new(esplora),
+        Arc::clone(&key_provider),
+        Arc::clone(&esplora),
         wallet,
-        server.clone(),
+        server.to_string(),
         Arc::new(sqlite_storage),
         boltz_url,
         Duration::from_secs(30),
     )
     .connect()
     .await
-    .map_err(|err| anyhow!("Failed to connect to Ark server at '{}': {}", server, err))?;
+    .map_err(|err| {
+        client::sdk_error(
+            &format!("Failed to connect to Ark server at '{}'", server),
+            &err,
+        )
+    })?;
 
     // Discover existing keys/VTXOs using BIP44-style gap limit scan
     // This is critical for restoring balance after app restart
-    if let Err(error) = client.discover_keys(DEFAULT_GAP_LIMIT).await {
-        tracing::warn!(?error, "Failed during key discovery");
+    // If this fails, `rescan_wallet` can be used to retry
+    match client.discover_keys(DEFAULT_GAP_LIMIT).await {
+        Ok(_) => KEY_DISCOVERY_INCOMPLETE.store(false, Ordering::SeqCst),
+        Err(error) => {
+            tracing::warn!(?error, "Failed during key discovery, wallet needs a rescan");
+            KEY_DISCOVERY_INCOMPLETE.store(true, Ordering::SeqCst);
+        }
     }
 
-    let info = client.server_info.clone();
+    Ok((client, key_provider, esplora, wallet_db))
+}
 
+/// Make `client` the wallet's active Ark client.
+pub(crate) fn install_client(
+    client: ArkClient,
+    server: &str,
+    key_provider: Arc<UnifiedKeyProvider>,
+    esplora: Arc<EsploraClient>,
+    wallet_db: InMemoryDb,
+) {
     // Check if ARK_CLIENT is already initialized (e.g., after wallet reset without app restart)
     // If so, overwrite the RwLock content instead of calling set() which only works once
     if let Some(existing_lock) = ARK_CLIENT.try_get() {
@@ -249,17 +330,78 @@ pub async fn setup_client_hd(
         tracing::info!("Initialized new ARK_CLIENT");
     }
 
-    // Store esplora URL for later use (e.g., querying boarding UTXOs)
-    if let Some(existing_lock) = ESPLORA_URL.try_get() {
+    if let Some(existing_lock) = ARK_SERVER_URL.try_get() {
         let mut guard = existing_lock.write();
-        *guard = esplora_url;
+        *guard = server.to_string();
     } else {
-        ESPLORA_URL.set(RwLock::new(esplora_url));
+        ARK_SERVER_URL.set(RwLock::new(server.to_string()));
     }
 
-    tracing::info!(server_pk = ?info.signer_pk, "Connected to server with HD wallet");
+    if let Some(existing_lock) = KEY_PROVIDER.try_get() {
+        let mut guard = existing_lock.write();
+        *guard = key_provider;
+    } else {
+        KEY_PROVIDER.set(RwLock::new(key_provider));
+    }
 
-    Ok(info.signer_pk.to_string())
+    // Share the esplora client for later use (e.g., querying boarding UTXOs)
+    if let Some(existing_lock) = ESPLORA_CLIENT.try_get() {
+        let mut guard = existing_lock.write();
+        *guard = esplora;
+    } else {
+        ESPLORA_CLIENT.set(RwLock::new(esplora));
+    }
+
+    // Keep access to the boarding outputs (e.g. for signing messages)
+    if let Some(existing_lock) = WALLET_DB.try_get() {
+        let mut guard = existing_lock.write();
+        *guard = wallet_db;
+    } else {
+        WALLET_DB.set(RwLock::new(wallet_db));
+    }
+}
+
+/// URL of the Ark server the active client is connected to.
+pub(crate) fn active_server() -> Option<String> {
+    ARK_SERVER_URL.try_get().map(|lock| lock.read().clone())
+}
+
+/// Open the wallet's local SQLite stores and make them globally available.
+async fn open_local_stores(data_dir: &str) -> Result<()> {
+    let tx_metadata_db = TxMetadataDb::new(data_dir)
+        .await
+        .map_err(|e| anyhow!("Failed to open transaction metadata store: {}", e))?;
+
+    if let Some(existing_lock) = TX_METADATA_DB.try_get() {
+        let mut guard = existing_lock.write();
+        *guard = Arc::new(tx_metadata_db);
+    } else {
+        TX_METADATA_DB.set(RwLock::new(Arc::new(tx_metadata_db)));
+    }
+
+    let history_db = HistoryDb::new(data_dir)
+        .await
+        .map_err(|e| anyhow!("Failed to open transaction history cache: {}", e))?;
+
+    if let Some(existing_lock) = HISTORY_DB.try_get() {
+        let mut guard = existing_lock.write();
+        *guard = Arc::new(history_db);
+    } else {
+        HISTORY_DB.set(RwLock::new(Arc::new(history_db)));
+    }
+
+    let offline_db = OfflineDb::new(data_dir)
+        .await
+        .map_err(|e| anyhow!("Failed to open offline store: {}", e))?;
+
+    if let Some(existing_lock) = OFFLINE_DB.try_get() {
+        let mut guard = existing_lock.write();
+        *guard = Arc::new(offline_db);
+    } else {
+        OFFLINE_DB.set(RwLock::new(Arc::new(offline_db)));
+    }
+
+    Ok(())
 }
 
 /// Check if a wallet exists (mnemonic file)
@@ -298,12 +440,34 @@ pub(crate) async fn npub(data_dir: String, network: Network) -> Result<nostr::Pu
     Ok(keys.public_key())
 }
 
+/// Derivation path of the stable Ark identity, m/83696968'/11811'/0/0, the
+/// first key of the Ark keychain.
+///
+/// Both the mnemonic and the key provider of the loaded wallet derive the
+/// identity at this path.
+pub(crate) fn ark_identity_path() -> String {
+    format!("{}/0", ARK_BASE_DERIVATION_PATH)
+}
+
+/// Derive the stable Ark identity keypair from the mnemonic in `data_dir`.
+pub(crate) fn ark_identity_keypair(data_dir: &str, network: Network) -> Result<Keypair> {
+    let mnemonic = read_mnemonic_file(data_dir)?.ok_or_else(|| anyhow!("No wallet found"))?;
+    let xpriv = derive_xpriv_at_path(&mnemonic, &ark_identity_path(), network)?;
+    Ok(Keypair::from_secret_key(
+        &Secp256k1::new(),
+        &xpriv.private_key,
+    ))
+}
+
 /// Delete the wallet mnemonic file and associated user data
 ///
 /// This includes:
 /// - The mnemonic file
 /// - LendaSwap swap storage (lendaswap_swaps/ and lendaswap_key_index)
 /// - LendaSat auth tokens (lendasat_auth.json)
+/// - Transaction labels and notes (tx_metadata.sqlite)
+/// - Cached transaction history (tx_history.sqlite)
+/// - Server migration progress (ark_migration.json)
 ///
 /// Note: Swaps can be recovered from the server using `recover_swaps()` after
 /// restoring the wallet with the same mnemonic, as they are associated with
@@ -346,5 +510,45 @@ pub fn delete_wallet(data_dir: String) -> Result<()> {
         tracing::info!("Deleted lendasat_auth.json file");
     }
 
+    // Delete transaction metadata
+    let tx_metadata_file = Path::new(&data_dir).join(TX_METADATA_DB_FILE);
+    if tx_metadata_file.exists() {
+        fs::remove_file(&tx_metadata_file)
+            .map_err(|e| anyhow!("Failed to delete {} file: {}", TX_METADATA_DB_FILE, e))?;
+        tracing::info!("Deleted {} file", TX_METADATA_DB_FILE);
+    }
+
+    // Delete server migration progress
+    let migration_file = Path::new(&data_dir).join(MIGRATION_FILE);
+    if migration_file.exists() {
+        fs::remove_file(&migration_file)
+            .map_err(|e| anyhow!("Failed to delete {} file: {}", MIGRATION_FILE, e))?;
+        tracing::info!("Deleted {} file", MIGRATION_FILE);
+    }
+
+    // Delete custom Esplora endpoints
+    let endpoints_file = Path::new(&data_dir).join(ESPLORA_ENDPOINTS_FILE);
+    if endpoints_file.exists() {
+        fs::remove_file(&endpoints_file)
+            .map_err(|e| anyhow!("Failed to delete {} file: {}", ESPLORA_ENDPOINTS_FILE, e))?;
+        tracing::info!("Deleted {} file", ESPLORA_ENDPOINTS_FILE);
+    }
+
+    // Delete transaction history cache
+    let history_file = Path::new(&data_dir).join(HISTORY_DB_FILE);
+    if history_file.exists() {
+        fs::remove_file(&history_file)
+            .map_err(|e| anyhow!("Failed to delete {} file: {}", HISTORY_DB_FILE, e))?;
+        tracing::info!("Deleted {} file", HISTORY_DB_FILE);
+    }
+
+    // Delete cached state and queued actions
+    let offline_file = Path::new(&data_dir).join(OFFLINE_DB_FILE);
+    if offline_file.exists() {
+        fs::remove_file(&offline_file)
+            .map_err(|e| anyhow!("Failed to delete {} file: {}", OFFLINE_DB_FILE, e))?;
+        tracing::info!("Deleted {} file", OFFLINE_DB_FILE);
+    }
+
     Ok(())
 }
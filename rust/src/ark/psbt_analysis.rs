@@ -0,0 +1,710 @@
+//! Human-readable inspection of PSBTs before signing.
+//!
+//! Server-built PSBTs (Lendasat claims, recoveries and settlements) are decoded
+//! into a summary of what the wallet is about to sign: where the funds come
+//! from, which outputs return to the wallet and which go to third parties, the
+//! fee and an estimated fee rate, plus anything that looks off.
+
+use crate::ark::signer;
+use crate::error::ApiError;
+use crate::state::ARK_CLIENT;
+use anyhow::{Result, anyhow};
+use ark_core::{ArkAddress, Vtxo};
+use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
+use bitcoin::key::Secp256k1;
+use bitcoin::opcodes::all::{OP_CHECKSIG, OP_CHECKSIGADD, OP_CHECKSIGVERIFY};
+use bitcoin::psbt::{Input, Psbt};
+use bitcoin::script::Instruction;
+use bitcoin::{
+    Address, CompressedPublicKey, Network, Script, ScriptBuf, TapSighashType, TxOut, VarInt,
+};
+use std::collections::HashSet;
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// Fees above this share of the spent amount are flagged.
+const HIGH_FEE_PERCENT: u64 = 10;
+
+/// Fee rates above this are flagged, no sane claim needs more.
+const HIGH_FEE_RATE_SAT_VB: f64 = 1_000.0;
+
+/// Decoded view of a PSBT.
+#[derive(Debug, Clone)]
+pub struct PsbtSummary {
+    pub txid: String,
+    pub version: i32,
+    pub lock_time: u32,
+    pub inputs: Vec<PsbtInputSummary>,
+    pub outputs: Vec<PsbtOutputSummary>,
+    /// Sum of the spent amounts, `None` if an input amount is unknown or the
+    /// sum overflows
+    pub total_input_sats: Option<u64>,
+    pub total_output_sats: u64,
+    /// Amount paid to wallet-owned outputs
+    pub to_wallet_sats: u64,
+    /// Amount paid to third-party outputs
+    pub to_external_sats: u64,
+    pub fee_sats: Option<u64>,
+    /// Estimated virtual size once all inputs are signed
+    pub estimated_vsize: u64,
+    pub fee_rate_sat_vb: Option<f64>,
+    pub warnings: Vec<PsbtWarning>,
+}
+
+#[derive(Debug, Clone)]
+pub struct PsbtInputSummary {
+    /// Spent outpoint as `txid:vout`
+    pub outpoint: String,
+    pub amount_sats: Option<u64>,
+    pub address: Option<String>,
+    /// The spent output pays to a wallet script
+    pub is_mine: bool,
+    /// A wallet key can sign this input
+    pub wallet_can_sign: bool,
+    pub sequence: u32,
+    pub sighash: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct PsbtOutputSummary {
+    pub index: u32,
+    pub amount_sats: u64,
+    pub address: Option<String>,
+    pub script_pubkey: String,
+    pub owner: OutputOwner,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputOwner {
+    /// Pays to the wallet
+    Wallet,
+    /// Pays to a third party
+    External,
+    /// Zero-value P2A anchor used to bump Ark transactions
+    Anchor,
+    /// OP_RETURN data carrier
+    Data,
+}
+
+/// Something about a PSBT the user should know before signing.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PsbtWarning {
+    /// The spent amount of an input is unknown, so the fee cannot be computed
+    MissingInputAmount { input: u32 },
+    /// The outputs spend more than the inputs provide
+    OutputsExceedInputs { input_sats: u64, output_sats: u64 },
+    /// The fee is unusually high in absolute or relative terms
+    HighFee { fee_sats: u64, fee_rate_sat_vb: f64 },
+    /// No output pays back to the wallet
+    NoWalletOutput,
+    /// An output is below the dust limit of its script type
+    DustOutput { output: u32, amount_sats: u64 },
+    /// An input commits to less than the whole transaction
+    UnusualSighash { input: u32, sighash: String },
+    /// No input can be signed by the wallet
+    NothingToSign,
+}
+
+/// Decode a PSBT given as hex or base64.
+pub(crate) fn decode_psbt(psbt: &str) -> Result<Psbt> {
+    let psbt = psbt.trim();
+    let bytes = match hex::decode(psbt) {
+        Ok(bytes) => bytes,
+        Err(_) => BASE64
+            .decode(psbt)
+            .map_err(|_| anyhow!("Invalid PSBT: neither hex nor base64"))?,
+    };
+    Psbt::deserialize(&bytes).map_err(|e| anyhow!("Failed to parse PSBT: {}", e))
+}
+
+/// Parse an on-chain or Ark address into the script it pays to.
+pub(crate) fn address_script(address: &str, network: Network) -> Result<ScriptBuf> {
+    if let Ok(ark_address) = ArkAddress::decode(address) {
+        return Ok(ark_address.to_p2tr_script_pubkey());
+    }
+
+    let unchecked =
+        Address::from_str(address).map_err(|e| anyhow!("Invalid address '{}': {}", address, e))?;
+    let address = crate::ark::client::require_network(unchecked, address, network)?;
+    Ok(address.script_pubkey())
+}
+
+/// Scripts the wallet receives on: its Ark and boarding addresses and the
+/// single-key outputs of its keys.
+///
+/// Ark addresses are rebuilt for every derived HD key, so VTXOs received on an
+/// earlier address still count as the wallet's.
+pub(crate) fn wallet_scripts() -> Result<(Network, HashSet<ScriptBuf>)> {
+    let client = {
+        let lock = ARK_CLIENT
+            .try_get()
+            .ok_or_else(|| ApiError::not_initialized("Ark client"))?;
+        let guard = lock.read();
+        Arc::clone(&*guard)
+    };
+    let network = client.server_info.network;
+
+    let mut scripts = HashSet::new();
+
+    let (offchain_address, _) = client
+        .get_offchain_address()
+        .map_err(|e| anyhow!("Could not get offchain address: {e:#}"))?;
+    scripts.insert(offchain_address.to_p2tr_script_pubkey());
+
+    let boarding_addresses = client
+        .get_boarding_addresses()
+        .map_err(|e| anyhow!("Could not get boarding addresses: {e:#}"))?;
+    scripts.extend(boarding_addresses.iter().map(|a| a.script_pubkey()));
+
+    let secp = Secp256k1::verification_only();
+    let server_pk = client.server_info.signer_pk.x_only_public_key().0;
+    let exit_delay = client.server_info.unilateral_exit_delay;
+    for full_pk in signer::wallet_full_pubkeys()? {
+        let pk = full_pk.x_only_public_key().0;
+        scripts.insert(ScriptBuf::new_p2tr(&secp, pk, None));
+        let compressed = CompressedPublicKey(full_pk);
+        scripts.insert(ScriptBuf::new_p2wpkh(&compressed.wpubkey_hash()));
+
+        match Vtxo::new_default(&secp, server_pk, pk, exit_delay, network) {
+            Ok(vtxo) => {
+                scripts.insert(vtxo.to_ark_address().to_p2tr_script_pubkey());
+            }
+            Err(e) => tracing::warn!(%pk, "Could not build Ark address: {e:#}"),
+        }
+    }
+
+    Ok((network, scripts))
+}
+
+/// Summarize `psbt`. `extra_addresses` are treated as wallet-owned, e.g. the
+/// payout address registered with a loan contract.
+pub(crate) fn analyze(psbt: &Psbt, extra_addresses: &[String]) -> Result<PsbtSummary> {
+    let (network, mut wallet_scripts) = wallet_scripts()?;
+    for address in extra_addresses {
+        wallet_scripts.insert(address_script(address, network)?);
+    }
+
+    Ok(summarize(
+        psbt,
+        network,
+        &wallet_scripts,
+        &signer::is_wallet_key,
+    ))
+}
+
+pub(crate) fn summarize(
+    psbt: &Psbt,
+    network: Network,
+    wallet_scripts: &HashSet<ScriptBuf>,
+    is_wallet_key: &dyn Fn(&bitcoin::XOnlyPublicKey) -> bool,
+) -> PsbtSummary {
+    let tx = &psbt.unsigned_tx;
+    let mut warnings = Vec::new();
+
+    let mut inputs = Vec::with_capacity(tx.input.len());
+    let mut total_input_sats = Some(0u64);
+    for (idx, (txin, input)) in tx.input.iter().zip(&psbt.inputs).enumerate() {
+        let spent = spent_output(input, txin.previous_output.vout);
+        let amount_sats = spent.map(|utxo| utxo.value.to_sat());
+        match amount_sats {
+            Some(amount) => {
+                total_input_sats = total_input_sats.and_then(|total| total.checked_add(amount))
+            }
+            None => {
+                total_input_sats = None;
+                warnings.push(PsbtWarning::MissingInputAmount { input: idx as u32 });
+            }
+        }
+
+        let sighash = input.sighash_type.map(|ty| ty.to_string());
+        if let Some(ty) = input.sighash_type {
+            let whole_tx = matches!(
+                ty.taproot_hash_ty(),
+                Ok(TapSighashType::Default | TapSighashType::All)
+            );
+            if !whole_tx {
+                warnings.push(PsbtWarning::UnusualSighash {
+                    input: idx as u32,
+                    sighash: ty.to_string(),
+                });
+            }
+        }
+
+        let wallet_can_sign = input_keys(input).iter().any(is_wallet_key);
+
+        inputs.push(PsbtInputSummary {
+            outpoint: txin.previous_output.to_string(),
+            amount_sats,
+            address: spent.and_then(|utxo| address_of(&utxo.script_pubkey, network)),
+            is_mine: spent.is_some_and(|utxo| wallet_scripts.contains(&utxo.script_pubkey)),
+            wallet_can_sign,
+            sequence: txin.sequence.to_consensus_u32(),
+            sighash,
+        });
+    }
+
+    if !inputs.iter().any(|input| input.wallet_can_sign) {
+        warnings.push(PsbtWarning::NothingToSign);
+    }
+
+    let mut outputs = Vec::with_capacity(tx.output.len());
+    let mut to_wallet_sats = 0;
+    let mut to_external_sats = 0;
+    for (idx, (txout, output)) in tx.output.iter().zip(&psbt.outputs).enumerate() {
+        let script = &txout.script_pubkey;
+        let amount_sats = txout.value.to_sat();
+
+        let owner = if *script == ScriptBuf::new_p2a() {
+            OutputOwner::Anchor
+        } else if script.is_op_return() {
+            OutputOwner::Data
+        } else if wallet_scripts.contains(script)
+            || (output.tap_tree.is_none()
+                && output.tap_internal_key.is_some_and(|pk| is_wallet_key(&pk)))
+        {
+            OutputOwner::Wallet
+        } else {
+            OutputOwner::External
+        };
+
+        match owner {
+            OutputOwner::Wallet => to_wallet_sats = amount_sats.saturating_add(to_wallet_sats),
+            OutputOwner::External => {
+                to_external_sats = amount_sats.saturating_add(to_external_sats)
+            }
+            OutputOwner::Anchor | OutputOwner::Data => {}
+        }
+        if matches!(owner, OutputOwner::Wallet | OutputOwner::External)
+            && txout.value < script.minimal_non_dust()
+        {
+            warnings.push(PsbtWarning::DustOutput {
+                output: idx as u32,
+                amount_sats,
+            });
+        }
+
+        outputs.push(PsbtOutputSummary {
+            index: idx as u32,
+            amount_sats,
+            address: address_of(script, network),
+            script_pubkey: script.to_hex_string(),
+            owner,
+        });
+    }
+
+    if !outputs
+        .iter()
+        .any(|output| output.owner == OutputOwner::Wallet)
+    {
+        warnings.push(PsbtWarning::NoWalletOutput);
+    }
+
+    let total_output_sats = tx
+        .output
+        .iter()
+        .fold(0u64, |total, o| total.saturating_add(o.value.to_sat()));
+    let estimated_vsize = estimated_vsize(psbt, is_wallet_key);
+
+    let fee_sats = match total_input_sats {
+        Some(input_sats) if input_sats < total_output_sats => {
+            warnings.push(PsbtWarning::OutputsExceedInputs {
+                input_sats,
+                output_sats: total_output_sats,
+            });
+            None
+        }
+        Some(input_sats) => Some(input_sats - total_output_sats),
+        None => None,
+    };
+    let fee_rate_sat_vb = fee_sats.map(|fee| fee as f64 / estimated_vsize.max(1) as f64);
+
+    if let (Some(fee_sats), Some(fee_rate), Some(input_sats)) =
+        (fee_sats, fee_rate_sat_vb, total_input_sats)
+    {
+        let high_share = fee_sats as u128 * 100 > input_sats as u128 * HIGH_FEE_PERCENT as u128;
+        if high_share || fee_rate > HIGH_FEE_RATE_SAT_VB {
+            warnings.push(PsbtWarning::HighFee {
+                fee_sats,
+                fee_rate_sat_vb: fee_rate,
+            });
+        }
+    }
+
+    PsbtSummary {
+        txid: tx.compute_txid().to_string(),
+        version: tx.version.0,
+        lock_time: tx.lock_time.to_consensus_u32(),
+        inputs,
+        outputs,
+        total_input_sats,
+        total_output_sats,
+        to_wallet_sats,
+        to_external_sats,
+        fee_sats,
+        estimated_vsize,
+        fee_rate_sat_vb,
+        warnings,
+    }
+}
+
+pub(crate) fn spent_output(input: &Input, vout: u32) -> Option<&TxOut> {
+    input.witness_utxo.as_ref().or_else(|| {
+        input
+            .non_witness_utxo
+            .as_ref()
+            .and_then(|tx| tx.output.get(vout as usize))
+    })
+}
+
+fn address_of(script: &Script, network: Network) -> Option<String> {
+    Address::from_script(script, network)
+        .ok()
+        .map(|address| address.to_string())
+}
+
+/// Keys an input can be signed with, as far as the PSBT tells.
+fn input_keys(input: &Input) -> Vec<bitcoin::XOnlyPublicKey> {
+    let mut keys = input
+        .tap_scripts
+        .values()
+        .flat_map(|(script, _)| signer::script_pubkeys(script))
+        .collect::<Vec<_>>();
+    keys.extend(input.tap_internal_key);
+    keys.extend(input.tap_key_origins.keys());
+    keys.extend(
+        input
+            .bip32_derivation
+            .keys()
+            .map(|pk| pk.x_only_public_key().0),
+    );
+    keys
+}
+
+/// Virtual size of the transaction once every input carries its witness.
+///
+/// Unsigned inputs are estimated from their type: P2WPKH and Taproot key-path
+/// spends have a fixed witness size, script-path spends use the leaf the wallet
+/// signs (or the first one) with one signature per signature opcode.
+fn estimated_vsize(psbt: &Psbt, is_wallet_key: &dyn Fn(&bitcoin::XOnlyPublicKey) -> bool) -> u64 {
+    let tx = &psbt.unsigned_tx;
+    let mut weight = tx.weight().to_wu();
+    let mut has_witness = false;
+
+    for (txin, input) in tx.input.iter().zip(&psbt.inputs) {
+        let witness_size = if let Some(witness) = &input.final_script_witness {
+            witness.size()
+        } else if let Some(utxo) = spent_output(input, txin.previous_output.vout) {
+            let script = &utxo.script_pubkey;
+            if script.is_p2wpkh() {
+                // items, signature with sighash byte, compressed key
+                1 + 1 + 72 + 1 + 33
+            } else if script.is_p2tr() {
+                taproot_witness_size(input, is_wallet_key)
+            } else {
+                0
+            }
+        } else {
+            0
+        };
+
+        if witness_size > 0 {
+            has_witness = true;
+            weight += witness_size as u64;
+        }
+    }
+
+    if has_witness {
+        // Segwit marker and flag
+        weight += 2;
+    }
+
+    weight.div_ceil(4)
+}
+
+fn taproot_witness_size(
+    input: &Input,
+    is_wallet_key: &dyn Fn(&bitcoin::XOnlyPublicKey) -> bool,
+) -> usize {
+    let leaf = input
+        .tap_scripts
+        .iter()
+        .find(|(_, (script, _))| signer::script_pubkeys(script).iter().any(is_wallet_key))
+        .or_else(|| input.tap_scripts.iter().next());
+
+    match leaf {
+        // Key path: items, one 64 byte signature
+        None => 1 + 1 + 64,
+        Some((control_block, (script, _))) => {
+            let signatures = signature_count(script).max(1);
+            let control_block_len = control_block.size();
+            1 + signatures * (1 + 64)
+                + VarInt(script.len() as u64).size()
+                + script.len()
+                + VarInt(control_block_len as u64).size()
+                + control_block_len
+        }
+    }
+}
+
+fn signature_count(script: &Script) -> usize {
+    script
+        .instructions()
+        .filter(|instruction| {
+            matches!(
+                instruction,
+                Ok(Instruction::Op(op))
+                    if *op == OP_CHECKSIG || *op == OP_CHECKSIGVERIFY || *op == OP_CHECKSIGADD
+            )
+        })
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitcoin::absolute::LockTime;
+    use bitcoin::hashes::Hash;
+    use bitcoin::key::Keypair;
+    use bitcoin::psbt::PsbtSighashType;
+    use bitcoin::secp256k1::SecretKey;
+    use bitcoin::transaction::Version;
+    use bitcoin::{Amount, OutPoint, Sequence, Transaction, TxIn, Witness, XOnlyPublicKey};
+
+    fn p2wpkh(secret: u8) -> ScriptBuf {
+        let secp = Secp256k1::new();
+        let sk = SecretKey::from_slice(&[secret; 32]).unwrap();
+        let pk = CompressedPublicKey(sk.public_key(&secp));
+        ScriptBuf::new_p2wpkh(&pk.wpubkey_hash())
+    }
+
+    fn p2tr(secret: u8) -> ScriptBuf {
+        let secp = Secp256k1::new();
+        let sk = SecretKey::from_slice(&[secret; 32]).unwrap();
+        let pk = Keypair::from_secret_key(&secp, &sk).x_only_public_key().0;
+        ScriptBuf::new_p2tr(&secp, pk, None)
+    }
+
+    /// One P2WPKH input of `input_sats` paying to the given outputs.
+    fn psbt(input_sats: u64, outputs: Vec<(ScriptBuf, u64)>) -> Psbt {
+        let tx = Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: vec![TxIn {
+                previous_output: OutPoint::null(),
+                script_sig: ScriptBuf::new(),
+                sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+                witness: Witness::new(),
+            }],
+            output: outputs
+                .into_iter()
+                .map(|(script_pubkey, sats)| TxOut {
+                    value: Amount::from_sat(sats),
+                    script_pubkey,
+                })
+                .collect(),
+        };
+        let mut psbt = Psbt::from_unsigned_tx(tx).unwrap();
+        psbt.inputs[0].witness_utxo = Some(TxOut {
+            value: Amount::from_sat(input_sats),
+            script_pubkey: p2wpkh(1),
+        });
+        psbt
+    }
+
+    fn no_keys(_: &XOnlyPublicKey) -> bool {
+        false
+    }
+
+    fn x_only(secret: u8) -> XOnlyPublicKey {
+        let secp = Secp256k1::new();
+        let sk = SecretKey::from_slice(&[secret; 32]).unwrap();
+        Keypair::from_secret_key(&secp, &sk).x_only_public_key().0
+    }
+
+    #[test]
+    fn computes_fee_and_ownership() {
+        let psbt = psbt(100_000, vec![(p2wpkh(2), 60_000), (p2tr(3), 38_470)]);
+        let wallet_scripts = HashSet::from([p2wpkh(1), p2tr(3)]);
+
+        let summary = summarize(&psbt, Network::Regtest, &wallet_scripts, &no_keys);
+
+        assert!(summary.inputs[0].is_mine);
+        assert_eq!(summary.outputs[0].owner, OutputOwner::External);
+        assert_eq!(summary.outputs[1].owner, OutputOwner::Wallet);
+        assert_eq!(summary.to_wallet_sats, 38_470);
+        assert_eq!(summary.to_external_sats, 60_000);
+        assert_eq!(summary.fee_sats, Some(1_530));
+        // 125 bytes without witness, 108 witness bytes plus marker and flag
+        assert_eq!(summary.estimated_vsize, 153);
+        assert_eq!(summary.fee_rate_sat_vb, Some(10.0));
+        assert_eq!(summary.warnings, vec![PsbtWarning::NothingToSign]);
+    }
+
+    #[test]
+    fn flags_anomalies() {
+        let mut psbt = psbt(100_000, vec![(p2wpkh(2), 50_000), (p2tr(3), 100)]);
+        psbt.inputs[0].sighash_type = Some(PsbtSighashType::from_u32(0x83));
+
+        let summary = summarize(&psbt, Network::Regtest, &HashSet::new(), &no_keys);
+
+        assert!(summary.warnings.contains(&PsbtWarning::NoWalletOutput));
+        assert!(summary.warnings.contains(&PsbtWarning::DustOutput {
+            output: 1,
+            amount_sats: 100,
+        }));
+        assert!(
+            summary
+                .warnings
+                .iter()
+                .any(|w| matches!(w, PsbtWarning::UnusualSighash { input: 0, .. }))
+        );
+        assert!(summary.warnings.iter().any(|w| matches!(
+            w,
+            PsbtWarning::HighFee {
+                fee_sats: 49_900,
+                ..
+            }
+        )));
+    }
+
+    #[test]
+    fn rejects_overspending_outputs() {
+        let psbt = psbt(10_000, vec![(p2wpkh(2), 20_000)]);
+
+        let summary = summarize(&psbt, Network::Regtest, &HashSet::new(), &no_keys);
+
+        assert_eq!(summary.fee_sats, None);
+        assert!(
+            summary
+                .warnings
+                .contains(&PsbtWarning::OutputsExceedInputs {
+                    input_sats: 10_000,
+                    output_sats: 20_000,
+                })
+        );
+    }
+
+    #[test]
+    fn classifies_output_owners() {
+        let op_return = ScriptBuf::builder()
+            .push_opcode(bitcoin::opcodes::all::OP_RETURN)
+            .push_slice([1, 2, 3])
+            .into_script();
+        let mut psbt = psbt(
+            100_000,
+            vec![
+                (ScriptBuf::new_p2a(), 0),
+                (op_return, 0),
+                (p2tr(4), 40_000),
+                (p2tr(5), 50_000),
+            ],
+        );
+        psbt.inputs[0].tap_internal_key = Some(x_only(6));
+        // Only the internal key tells that this output belongs to the wallet
+        psbt.outputs[2].tap_internal_key = Some(x_only(6));
+        psbt.outputs[3].tap_internal_key = Some(x_only(7));
+        let is_wallet_key = |pk: &XOnlyPublicKey| *pk == x_only(6);
+
+        let summary = summarize(&psbt, Network::Regtest, &HashSet::new(), &is_wallet_key);
+
+        let owners: Vec<_> = summary.outputs.iter().map(|o| o.owner).collect();
+        assert_eq!(
+            owners,
+            vec![
+                OutputOwner::Anchor,
+                OutputOwner::Data,
+                OutputOwner::Wallet,
+                OutputOwner::External,
+            ]
+        );
+        assert_eq!(summary.to_wallet_sats, 40_000);
+        assert_eq!(summary.to_external_sats, 50_000);
+        assert!(summary.inputs[0].wallet_can_sign);
+        assert!(!summary.inputs[0].is_mine);
+        // Zero-value anchors and data carriers are not dust
+        assert!(
+            !summary
+                .warnings
+                .iter()
+                .any(|w| matches!(w, PsbtWarning::DustOutput { .. }))
+        );
+        assert!(!summary.warnings.contains(&PsbtWarning::NothingToSign));
+        assert!(!summary.warnings.contains(&PsbtWarning::NoWalletOutput));
+    }
+
+    #[test]
+    fn accepts_normal_fee() {
+        let psbt = psbt(100_000, vec![(p2wpkh(2), 99_000)]);
+
+        let summary = summarize(&psbt, Network::Regtest, &HashSet::new(), &no_keys);
+
+        assert_eq!(summary.fee_sats, Some(1_000));
+        // 82 bytes without witness, 108 witness bytes plus marker and flag
+        assert_eq!(summary.estimated_vsize, 110);
+        assert!(
+            !summary
+                .warnings
+                .iter()
+                .any(|w| matches!(w, PsbtWarning::HighFee { .. }))
+        );
+    }
+
+    #[test]
+    fn flags_high_fee_rate() {
+        // 0.2% of the input, but 1818 sat/vB
+        let psbt = psbt(100_000_000, vec![(p2wpkh(2), 99_800_000)]);
+
+        let summary = summarize(&psbt, Network::Regtest, &HashSet::new(), &no_keys);
+
+        assert!(summary.warnings.iter().any(|w| matches!(
+            w,
+            PsbtWarning::HighFee {
+                fee_sats: 200_000,
+                fee_rate_sat_vb,
+            } if *fee_rate_sat_vb > HIGH_FEE_RATE_SAT_VB
+        )));
+    }
+
+    #[test]
+    fn flags_high_fee_share() {
+        // 60 sat/vB, but 12% of the input
+        let psbt = psbt(55_000, vec![(p2wpkh(2), 48_400)]);
+
+        let summary = summarize(&psbt, Network::Regtest, &HashSet::new(), &no_keys);
+
+        assert!(summary.warnings.iter().any(|w| matches!(
+            w,
+            PsbtWarning::HighFee {
+                fee_sats: 6_600,
+                fee_rate_sat_vb,
+            } if *fee_rate_sat_vb < HIGH_FEE_RATE_SAT_VB
+        )));
+    }
+
+    #[test]
+    fn overflowing_inputs_leave_fee_unknown() {
+        let mut psbt = psbt(u64::MAX, vec![(p2wpkh(2), 1_000)]);
+        psbt.unsigned_tx.input.push(TxIn {
+            previous_output: OutPoint {
+                txid: bitcoin::Txid::from_byte_array([1; 32]),
+                vout: 0,
+            },
+            script_sig: ScriptBuf::new(),
+            sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+            witness: Witness::new(),
+        });
+        psbt.inputs.push(Input {
+            witness_utxo: Some(TxOut {
+                value: Amount::from_sat(1),
+                script_pubkey: p2wpkh(1),
+            }),
+            ..Default::default()
+        });
+
+        let summary = summarize(&psbt, Network::Regtest, &HashSet::new(), &no_keys);
+
+        assert_eq!(summary.total_input_sats, None);
+        assert_eq!(summary.fee_sats, None);
+        assert_eq!(summary.fee_rate_sat_vb, None);
+    }
+}
@@ -0,0 +1,644 @@
+//! Wallet-level PSBT signing.
+//!
+//! Signs Ark and on-chain PSBTs with the keys of the loaded wallet: the stable
+//! Ark identity, the HD keys of the key provider and the keys of boarding
+//! outputs. Keys are picked explicitly by derivation path or public key, or
+//! looked up from the public keys the PSBT itself references, so any
+//! integration can have the wallet co-sign without keeping key material of its
+//! own.
+
+use crate::ark::storage::InMemoryDb;
+use crate::error::ApiError;
+use crate::state::{KEY_PROVIDER, UnifiedKeyProvider, WALLET_DB};
+use anyhow::{Result, anyhow, bail};
+use ark_client::KeyProvider;
+use ark_client::wallet::Persistence;
+use ark_core::send::sign_ark_transaction;
+use bitcoin::bip32::{DerivationPath, Fingerprint};
+use bitcoin::key::{Keypair, Secp256k1, XOnlyPublicKey};
+use bitcoin::psbt::{GetKey, Input, KeyRequest, Psbt, SigningKeys};
+use bitcoin::script::Instruction;
+use bitcoin::secp256k1::{Message, Signing, schnorr};
+use bitcoin::taproot::TapLeafHash;
+use bitcoin::{CompressedPublicKey, PrivateKey, Script, ScriptBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// The wallet key a PSBT is signed with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SigningKey {
+    /// The stable Ark identity at m/83696968'/11811'/0/0
+    Identity,
+    /// The HD key at a full derivation path, e.g. "m/83696968'/11811'/0/3"
+    Path { path: String },
+    /// The wallet key (HD or boarding) with this public key, as x-only or compressed hex
+    Pubkey { pubkey: String },
+}
+
+/// Keys of the loaded wallet.
+struct WalletKeys {
+    key_provider: Arc<UnifiedKeyProvider>,
+    wallet_db: InMemoryDb,
+    /// Restricts signing to this key if set
+    only: Option<Keypair>,
+}
+
+impl WalletKeys {
+    fn load(key: Option<&SigningKey>) -> Result<Self> {
+        let key_provider = KEY_PROVIDER
+            .try_get()
+            .ok_or_else(|| ApiError::not_initialized("Key provider"))?
+            .read()
+            .clone();
+        let wallet_db = WALLET_DB
+            .try_get()
+            .ok_or_else(|| ApiError::not_initialized("Wallet"))?
+            .read()
+            .clone();
+
+        Self::new(key_provider, wallet_db, key)
+    }
+
+    fn new(
+        key_provider: Arc<UnifiedKeyProvider>,
+        wallet_db: InMemoryDb,
+        key: Option<&SigningKey>,
+    ) -> Result<Self> {
+        let mut keys = Self {
+            key_provider,
+            wallet_db,
+            only: None,
+        };
+        keys.only = key.map(|key| keys.resolve(key)).transpose()?;
+        Ok(keys)
+    }
+
+    fn resolve(&self, key: &SigningKey) -> Result<Keypair> {
+        match key {
+            SigningKey::Identity => self.identity(),
+            SigningKey::Path { path } => {
+                let path = DerivationPath::from_str(path)
+                    .map_err(|e| anyhow!("Invalid derivation path '{}': {}", path, e))?;
+                self.key_provider
+                    .get_keypair_for_path(&path.to_u32_vec())
+                    .map_err(|e| anyhow!("Failed to derive key: {}", e))
+            }
+            SigningKey::Pubkey { pubkey } => {
+                let pk = parse_pubkey(pubkey)?;
+                self.lookup(&pk)
+                    .ok_or_else(|| anyhow!("Key {} does not belong to this wallet", pk))
+            }
+        }
+    }
+
+    fn identity(&self) -> Result<Keypair> {
+        provider_identity(&self.key_provider)
+    }
+
+    fn pubkeys(&self) -> Result<Vec<XOnlyPublicKey>> {
+        let mut pks = self
+            .key_provider
+            .get_cached_pks()
+            .map_err(|e| anyhow!("Failed to get wallet keys: {}", e))?;
+        let identity = self.identity()?.x_only_public_key().0;
+        if !pks.contains(&identity) {
+            pks.push(identity);
+        }
+        Ok(pks)
+    }
+
+    /// The keys of [`Self::pubkeys`] with their parity, from the key provider.
+    fn full_pubkeys(&self) -> Result<Vec<bitcoin::secp256k1::PublicKey>> {
+        self.pubkeys()?
+            .iter()
+            .map(|pk| {
+                self.lookup(pk)
+                    .map(|kp| kp.public_key())
+                    .ok_or_else(|| anyhow!("Key {} does not belong to this wallet", pk))
+            })
+            .collect()
+    }
+
+    /// The wallet key for `pk`, honouring the restriction to a single key.
+    fn lookup(&self, pk: &XOnlyPublicKey) -> Option<Keypair> {
+        if let Some(only) = self.only {
+            return (only.x_only_public_key().0 == *pk).then_some(only);
+        }
+
+        if let Ok(kp) = self.key_provider.get_keypair_for_pk(pk) {
+            return Some(kp);
+        }
+        if let Ok(identity) = self.identity() {
+            if identity.x_only_public_key().0 == *pk {
+                return Some(identity);
+            }
+        }
+        self.wallet_db
+            .sk_for_pk(pk)
+            .ok()
+            .map(|sk| Keypair::from_secret_key(&Secp256k1::new(), &sk))
+    }
+
+    /// Declare the wallet keys an input can be signed with, for inputs that do
+    /// not carry key origins themselves.
+    fn add_key_origins(&self, input: &mut Input) {
+        // The fingerprint is unknown to us, keys are looked up by public key
+        let origin = (Fingerprint::default(), DerivationPath::master());
+
+        if let Some(internal_key) = input.tap_internal_key {
+            if self.lookup(&internal_key).is_some() {
+                input
+                    .tap_key_origins
+                    .entry(internal_key)
+                    .or_insert_with(|| (Vec::new(), origin.clone()));
+            }
+        }
+
+        for (script, version) in input.tap_scripts.values() {
+            let leaf_hash = TapLeafHash::from_script(script, *version);
+            for pk in script_pubkeys(script) {
+                if self.lookup(&pk).is_none() {
+                    continue;
+                }
+                let (leaf_hashes, _) = input
+                    .tap_key_origins
+                    .entry(pk)
+                    .or_insert_with(|| (Vec::new(), origin.clone()));
+                if !leaf_hashes.contains(&leaf_hash) {
+                    leaf_hashes.push(leaf_hash);
+                }
+            }
+        }
+
+        // A P2WPKH output only commits to the key hash, so only the selected key can be matched
+        let spent_script = input.witness_utxo.as_ref().map(|utxo| &utxo.script_pubkey);
+        if let (Some(only), Some(spent_script)) = (self.only, spent_script) {
+            let pk = CompressedPublicKey(only.public_key());
+            if *spent_script == ScriptBuf::new_p2wpkh(&pk.wpubkey_hash()) {
+                input.bip32_derivation.entry(pk.0).or_insert(origin);
+            }
+        }
+    }
+}
+
+impl GetKey for WalletKeys {
+    type Error = std::convert::Infallible;
+
+    fn get_key<C: Signing>(
+        &self,
+        key_request: KeyRequest,
+        secp: &Secp256k1<C>,
+    ) -> Result<Option<PrivateKey>, Self::Error> {
+        let key = match key_request {
+            KeyRequest::XOnlyPubkey(pk) => self
+                .lookup(&pk)
+                .map(|kp| PrivateKey::new(kp.secret_key(), bitcoin::NetworkKind::Main)),
+            KeyRequest::Pubkey(pk) => self
+                .lookup(&pk.inner.x_only_public_key().0)
+                .map(|kp| PrivateKey::new(kp.secret_key(), bitcoin::NetworkKind::Main))
+                // ECDSA signatures need the exact key, not just the same x coordinate
+                .filter(|sk| sk.public_key(secp) == pk),
+            _ => None,
+        };
+        Ok(key)
+    }
+}
+
+/// The Ark identity as derived by the key provider, at the same path as
+/// [`crate::ark::ark_identity_keypair`] derives it from the mnemonic.
+fn provider_identity(key_provider: &UnifiedKeyProvider) -> Result<Keypair> {
+    let path = DerivationPath::from_str(&crate::ark::ark_identity_path())
+        .map_err(|e| anyhow!("Invalid identity derivation path: {}", e))?;
+    key_provider
+        .get_keypair_for_path(&path.to_u32_vec())
+        .map_err(|e| anyhow!("Failed to derive identity key: {}", e))
+}
+
+/// The stable Ark identity keypair.
+///
+/// Taken from the loaded wallet if there is one. Lendasat can sign with the
+/// identity before the Ark wallet is loaded, so otherwise it is derived from
+/// the mnemonic in Lendasat's data directory.
+pub(crate) async fn identity_keypair() -> Result<Keypair> {
+    if let Some(key_provider) = KEY_PROVIDER.try_get() {
+        let key_provider = key_provider.read().clone();
+        return provider_identity(&key_provider);
+    }
+
+    let (data_dir, network) = {
+        let guard = crate::api::lendasat_api::get_state_lock().read().await;
+        let state = guard
+            .as_ref()
+            .ok_or_else(|| ApiError::not_initialized("Wallet"))?;
+        (state.data_dir.clone(), state.network)
+    };
+    crate::ark::ark_identity_keypair(&data_dir, network)
+}
+
+/// X-only public keys pushed by a tapscript.
+pub(crate) fn script_pubkeys(script: &Script) -> Vec<XOnlyPublicKey> {
+    script
+        .instructions()
+        .filter_map(|instruction| match instruction {
+            Ok(Instruction::PushBytes(bytes)) => XOnlyPublicKey::from_slice(bytes.as_bytes()).ok(),
+            _ => None,
+        })
+        .collect()
+}
+
+pub(crate) fn parse_pubkey(pubkey: &str) -> Result<XOnlyPublicKey> {
+    let bytes = hex::decode(pubkey).map_err(|e| anyhow!("Invalid public key hex: {}", e))?;
+    match bytes.len() {
+        32 => XOnlyPublicKey::from_slice(&bytes),
+        33 => bitcoin::secp256k1::PublicKey::from_slice(&bytes).map(|pk| pk.x_only_public_key().0),
+        len => bail!("Invalid public key length: {} bytes", len),
+    }
+    .map_err(|e| anyhow!("Invalid public key: {}", e))
+}
+
+/// Public keys of the loaded wallet: the identity and every derived HD key.
+pub(crate) fn wallet_pubkeys() -> Result<Vec<XOnlyPublicKey>> {
+    WalletKeys::load(None)?.pubkeys()
+}
+
+/// Full public keys of the loaded wallet, see [`wallet_pubkeys`].
+///
+/// Single-key outputs like P2WPKH commit to the parity of the key, which an
+/// x-only key does not carry.
+pub(crate) fn wallet_full_pubkeys() -> Result<Vec<bitcoin::secp256k1::PublicKey>> {
+    WalletKeys::load(None)?.full_pubkeys()
+}
+
+/// Whether `pk` is a key of the loaded wallet, including boarding keys.
+pub(crate) fn is_wallet_key(pk: &XOnlyPublicKey) -> bool {
+    WalletKeys::load(None)
+        .map(|keys| keys.lookup(pk).is_some())
+        .unwrap_or(false)
+}
+
+/// The x-only public key of `key`.
+pub(crate) fn public_key(key: &SigningKey) -> Result<XOnlyPublicKey> {
+    let keys = WalletKeys::load(None)?;
+    Ok(keys.resolve(key)?.x_only_public_key().0)
+}
+
+/// Sign the script-path inputs of an Ark transaction (e.g. an offchain spend
+/// of a VTXO or a collateral contract).
+///
+/// With `key` set, every input is signed with that key. Otherwise each input is
+/// signed with the wallet keys found in its tapscripts and inputs without any
+/// are left alone. Returns the number of signed inputs.
+pub(crate) fn sign_ark_psbt(psbt: &mut Psbt, key: Option<&SigningKey>) -> Result<usize> {
+    sign_ark_psbt_with_keys(psbt, &WalletKeys::load(key)?)
+}
+
+fn sign_ark_psbt_with_keys(psbt: &mut Psbt, keys: &WalletKeys) -> Result<usize> {
+    sign_ark_inputs(psbt, |input| match keys.only {
+        Some(only) => vec![only],
+        None => input
+            .tap_scripts
+            .values()
+            .flat_map(|(script, _)| script_pubkeys(script))
+            .filter_map(|pk| keys.lookup(&pk))
+            .collect(),
+    })
+}
+
+/// Sign every input of an Ark transaction with the Ark identity.
+///
+/// Unlike `sign_ark_psbt` with [`SigningKey::Identity`], this does not need
+/// the Ark wallet to be loaded, see [`identity_keypair`].
+pub(crate) async fn sign_ark_psbt_with_identity(psbt: &mut Psbt) -> Result<usize> {
+    let identity = identity_keypair().await?;
+    sign_ark_inputs(psbt, |_| vec![identity])
+}
+
+/// Sign each input with the keys `signers_for` returns for it, skipping
+/// inputs without any.
+fn sign_ark_inputs(psbt: &mut Psbt, signers_for: impl Fn(&Input) -> Vec<Keypair>) -> Result<usize> {
+    let secp = Secp256k1::new();
+
+    let mut signed = 0;
+    for input_idx in 0..psbt.inputs.len() {
+        let signers = signers_for(&psbt.inputs[input_idx]);
+        if signers.is_empty() {
+            tracing::debug!("Input {}: no wallet key involved, skipping", input_idx);
+            continue;
+        }
+
+        let sign_fn = |_input: &mut Input,
+                       msg: Message|
+         -> std::result::Result<
+            Vec<(schnorr::Signature, XOnlyPublicKey)>,
+            ark_core::Error,
+        > {
+            Ok(signers
+                .iter()
+                .map(|kp| {
+                    (
+                        secp.sign_schnorr_no_aux_rand(&msg, kp),
+                        kp.x_only_public_key().0,
+                    )
+                })
+                .collect())
+        };
+
+        sign_ark_transaction(sign_fn, psbt, input_idx)
+            .map_err(|e| anyhow!("Failed to sign input {}: {}", input_idx, e))?;
+        signed += 1;
+    }
+
+    if signed == 0 {
+        bail!("No input of the PSBT can be signed with the wallet keys");
+    }
+
+    tracing::info!("Signed {}/{} Ark PSBT inputs", signed, psbt.inputs.len());
+
+    Ok(signed)
+}
+
+/// Sign the inputs of an on-chain transaction, both P2WPKH and Taproot key-
+/// and script-path spends.
+///
+/// Inputs are matched through their key origins, or through their internal key,
+/// tapscripts or (with `key` set) P2WPKH script. Returns the number of signed
+/// inputs.
+pub(crate) fn sign_onchain_psbt(psbt: &mut Psbt, key: Option<&SigningKey>) -> Result<usize> {
+    sign_onchain_psbt_with_keys(psbt, &WalletKeys::load(key)?)
+}
+
+fn sign_onchain_psbt_with_keys(psbt: &mut Psbt, keys: &WalletKeys) -> Result<usize> {
+    let secp = Secp256k1::new();
+
+    // Our placeholder origins are only needed for signing, restore the original ones afterwards
+    let origins = psbt
+        .inputs
+        .iter()
+        .map(|input| {
+            (
+                input.bip32_derivation.clone(),
+                input.tap_key_origins.clone(),
+            )
+        })
+        .collect::<Vec<_>>();
+    for input in psbt.inputs.iter_mut() {
+        keys.add_key_origins(input);
+    }
+
+    let result = psbt.sign(keys, &secp);
+
+    for (input, (bip32_derivation, tap_key_origins)) in psbt.inputs.iter_mut().zip(origins) {
+        input.bip32_derivation = bip32_derivation;
+        input.tap_key_origins = tap_key_origins;
+    }
+
+    let (used, errors) = match result {
+        Ok(used) => (used, Default::default()),
+        Err((used, errors)) => (used, errors),
+    };
+    let signed = used
+        .values()
+        .filter(|keys| match keys {
+            SigningKeys::Ecdsa(pks) => !pks.is_empty(),
+            SigningKeys::Schnorr(pks) => !pks.is_empty(),
+        })
+        .count();
+
+    if signed == 0 {
+        match errors.first_key_value() {
+            Some((idx, e)) => bail!("Failed to sign input {}: {}", idx, e),
+            None => bail!("No input of the PSBT can be signed with the wallet keys"),
+        }
+    }
+    for (idx, e) in errors {
+        tracing::warn!("Input {} was not signed: {}", idx, e);
+    }
+
+    tracing::info!(
+        "Signed {}/{} on-chain PSBT inputs",
+        signed,
+        psbt.inputs.len()
+    );
+
+    Ok(signed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ark::mnemonic_file::{
+        ARK_BASE_DERIVATION_PATH, derive_master_xpriv, parse_mnemonic, write_mnemonic_file,
+    };
+    use ark_client::Bip32KeyProvider;
+    use bitcoin::absolute::LockTime;
+    use bitcoin::opcodes::all::OP_CHECKSIG;
+    use bitcoin::taproot::{LeafVersion, TaprootBuilder};
+    use bitcoin::transaction::Version;
+    use bitcoin::{
+        Amount, Network, OutPoint, Sequence, Transaction, TxIn, TxOut, Witness, secp256k1,
+    };
+
+    const MNEMONIC: &str = "abandon abandon abandon abandon abandon abandon abandon abandon \
+        abandon abandon abandon about";
+
+    fn key_provider() -> Arc<UnifiedKeyProvider> {
+        let mnemonic = parse_mnemonic(MNEMONIC).unwrap();
+        let master = derive_master_xpriv(&mnemonic, Network::Regtest).unwrap();
+        let base_path = DerivationPath::from_str(ARK_BASE_DERIVATION_PATH).unwrap();
+        Arc::new(UnifiedKeyProvider::Hd(Bip32KeyProvider::new(
+            master, base_path,
+        )))
+    }
+
+    fn wallet_keys(key: Option<&SigningKey>) -> WalletKeys {
+        WalletKeys::new(key_provider(), InMemoryDb::default(), key).unwrap()
+    }
+
+    fn foreign_key() -> Keypair {
+        let sk = secp256k1::SecretKey::from_slice(&[7; 32]).unwrap();
+        Keypair::from_secret_key(&Secp256k1::new(), &sk)
+    }
+
+    fn psbt(inputs: Vec<Input>) -> Psbt {
+        let tx = Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: (0..inputs.len())
+                .map(|vout| TxIn {
+                    previous_output: OutPoint {
+                        txid: bitcoin::Txid::from_str(&"11".repeat(32)).unwrap(),
+                        vout: vout as u32,
+                    },
+                    script_sig: ScriptBuf::new(),
+                    sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+                    witness: Witness::new(),
+                })
+                .collect(),
+            output: vec![TxOut {
+                value: Amount::from_sat(1_000),
+                script_pubkey: ScriptBuf::new_p2a(),
+            }],
+        };
+        let mut psbt = Psbt::from_unsigned_tx(tx).unwrap();
+        psbt.inputs = inputs;
+        psbt
+    }
+
+    /// A Taproot input spendable by a single `<pk> CHECKSIG` leaf.
+    fn script_path_input(pk: XOnlyPublicKey) -> Input {
+        let secp = Secp256k1::new();
+        let script = ScriptBuf::builder()
+            .push_x_only_key(&pk)
+            .push_opcode(OP_CHECKSIG)
+            .into_script();
+        let spend_info = TaprootBuilder::new()
+            .add_leaf(0, script.clone())
+            .unwrap()
+            .finalize(&secp, foreign_key().x_only_public_key().0)
+            .unwrap();
+        let control_block = spend_info
+            .control_block(&(script.clone(), LeafVersion::TapScript))
+            .unwrap();
+
+        let mut input = Input {
+            witness_utxo: Some(TxOut {
+                value: Amount::from_sat(10_000),
+                script_pubkey: ScriptBuf::new_p2tr_tweaked(spend_info.output_key()),
+            }),
+            ..Default::default()
+        };
+        input
+            .tap_scripts
+            .insert(control_block, (script, LeafVersion::TapScript));
+        input
+    }
+
+    fn p2wpkh_input(keypair: &Keypair) -> Input {
+        let pk = CompressedPublicKey(keypair.public_key());
+        Input {
+            witness_utxo: Some(TxOut {
+                value: Amount::from_sat(10_000),
+                script_pubkey: ScriptBuf::new_p2wpkh(&pk.wpubkey_hash()),
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn identity_matches_mnemonic_derivation() {
+        let dir = std::env::temp_dir().join(format!("signer-test-{}", uuid::Uuid::new_v4()));
+        let mnemonic = parse_mnemonic(MNEMONIC).unwrap();
+        write_mnemonic_file(&mnemonic, dir.to_str().unwrap()).unwrap();
+
+        let from_mnemonic =
+            crate::ark::ark_identity_keypair(dir.to_str().unwrap(), Network::Regtest).unwrap();
+        let from_provider = wallet_keys(None).identity().unwrap();
+        let by_path = wallet_keys(Some(&SigningKey::Path {
+            path: format!("{}/0", ARK_BASE_DERIVATION_PATH),
+        }))
+        .only
+        .unwrap();
+
+        assert_eq!(from_provider, from_mnemonic);
+        assert_eq!(by_path, from_mnemonic);
+    }
+
+    #[test]
+    fn full_pubkeys_keep_the_parity() {
+        let keys = wallet_keys(None);
+        let identity = keys.identity().unwrap();
+
+        let full = keys.full_pubkeys().unwrap();
+
+        assert!(full.contains(&identity.public_key()));
+        assert_eq!(full.len(), keys.pubkeys().unwrap().len());
+    }
+
+    #[test]
+    fn signs_ark_inputs_of_wallet_keys() {
+        let keys = wallet_keys(None);
+        let identity = keys.identity().unwrap();
+        let identity_pk = identity.x_only_public_key().0;
+        let mut psbt = psbt(vec![
+            script_path_input(identity_pk),
+            script_path_input(foreign_key().x_only_public_key().0),
+        ]);
+
+        let signed = sign_ark_psbt_with_keys(&mut psbt, &keys).unwrap();
+
+        assert_eq!(signed, 1);
+        assert!(
+            psbt.inputs[0]
+                .tap_script_sigs
+                .keys()
+                .any(|(pk, _)| *pk == identity_pk)
+        );
+        assert!(psbt.inputs[1].tap_script_sigs.is_empty());
+    }
+
+    #[test]
+    fn rejects_ark_psbt_without_wallet_keys() {
+        let mut psbt = psbt(vec![script_path_input(foreign_key().x_only_public_key().0)]);
+
+        assert!(sign_ark_psbt_with_keys(&mut psbt, &wallet_keys(None)).is_err());
+    }
+
+    #[test]
+    fn signs_onchain_inputs_and_restores_origins() {
+        let path = format!("{}/3", ARK_BASE_DERIVATION_PATH);
+        let keys = wallet_keys(Some(&SigningKey::Path { path }));
+        let key = keys.only.unwrap();
+
+        let foreign = foreign_key();
+        let mut foreign_input = p2wpkh_input(&foreign);
+        // A real origin supplied by the creator of the PSBT must survive signing
+        let foreign_origin = (
+            Fingerprint::from([1, 2, 3, 4]),
+            DerivationPath::from_str("m/84'/0'/0'/0/0").unwrap(),
+        );
+        foreign_input
+            .bip32_derivation
+            .insert(foreign.public_key(), foreign_origin.clone());
+        let mut psbt = psbt(vec![p2wpkh_input(&key), foreign_input]);
+
+        let signed = sign_onchain_psbt_with_keys(&mut psbt, &keys).unwrap();
+
+        assert_eq!(signed, 1);
+        assert!(
+            psbt.inputs[0]
+                .partial_sigs
+                .contains_key(&bitcoin::PublicKey::new(key.public_key()))
+        );
+        assert!(psbt.inputs[1].partial_sigs.is_empty());
+        // The placeholder origin used to find the key is removed again
+        assert!(psbt.inputs[0].bip32_derivation.is_empty());
+        assert!(psbt.inputs[0].tap_key_origins.is_empty());
+        assert_eq!(
+            psbt.inputs[1].bip32_derivation.get(&foreign.public_key()),
+            Some(&foreign_origin)
+        );
+    }
+
+    #[test]
+    fn signs_taproot_key_spend_of_identity() {
+        let keys = wallet_keys(None);
+        let identity = keys.identity().unwrap();
+        let internal_key = identity.x_only_public_key().0;
+        let secp = Secp256k1::new();
+        let mut input = Input {
+            witness_utxo: Some(TxOut {
+                value: Amount::from_sat(10_000),
+                script_pubkey: ScriptBuf::new_p2tr(&secp, internal_key, None),
+            }),
+            ..Default::default()
+        };
+        input.tap_internal_key = Some(internal_key);
+        let mut psbt = psbt(vec![input]);
+
+        let signed = sign_onchain_psbt_with_keys(&mut psbt, &keys).unwrap();
+
+        assert_eq!(signed, 1);
+        assert!(psbt.inputs[0].tap_key_sig.is_some());
+        assert!(psbt.inputs[0].tap_key_origins.is_empty());
+    }
+}
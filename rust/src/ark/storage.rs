@@ -2,14 +2,33 @@ use ark_client::Error;
 use ark_client::error::IntoError;
 use ark_client::wallet::Persistence;
 use ark_core::BoardingOutput;
+use bitcoin::Script;
 use bitcoin::XOnlyPublicKey;
 use bitcoin::secp256k1::SecretKey;
 use std::collections::HashMap;
-use std::sync::RwLock;
+use std::sync::{Arc, RwLock};
 
-#[derive(Default)]
+/// Boarding outputs of the wallet. Clones share the same outputs, so a clone
+/// kept next to the BDK wallet sees every boarding address it derives.
+#[derive(Default, Clone)]
 pub struct InMemoryDb {
-    boarding_outputs: RwLock<HashMap<BoardingOutput, SecretKey>>,
+    boarding_outputs: Arc<RwLock<HashMap<BoardingOutput, SecretKey>>>,
+}
+
+impl InMemoryDb {
+    /// Find the boarding output paying to `script_pubkey` and its owner key.
+    pub fn find_boarding_output(
+        &self,
+        script_pubkey: &Script,
+    ) -> Result<Option<(BoardingOutput, SecretKey)>, Error> {
+        let guard = self.boarding_outputs.read().map_err(|e| {
+            format!("Failed to acquire read lock for boarding outputs: {e}").into_error()
+        })?;
+        Ok(guard
+            .iter()
+            .find(|(b, _)| b.address().script_pubkey().as_script() == script_pubkey)
+            .map(|(b, sk)| (b.clone(), *sk)))
+    }
 }
 
 impl Persistence for InMemoryDb {
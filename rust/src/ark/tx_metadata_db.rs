@@ -0,0 +1,575 @@
+//! SQLite-based storage for local transaction metadata.
+//!
+//! The Ark server only knows about amounts and txids. Everything the user
+//! knows about a payment (where it went, who it was for, why it was made)
+//! is recorded here at send time, keyed by txid, and joined into the
+//! transaction history.
+//!
+//! Labels can be exchanged with other wallets using the BIP329 JSON Lines format.
+
+use crate::error::ApiError;
+use crate::state::TX_METADATA_DB;
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::Arc;
+
+/// File name of the metadata database inside the data directory.
+pub const TX_METADATA_DB_FILE: &str = "tx_metadata.sqlite";
+
+/// Where the funds of a transaction were sent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DestinationType {
+    /// Off-chain send to another Ark address
+    Ark,
+    /// Collaborative redemption to a Bitcoin address
+    Onchain,
+    /// Lightning payment via Boltz submarine swap
+    Lightning,
+    /// Funding, claim or refund of a LendaSwap swap
+    LendaswapSwap,
+    /// Collateral deposit for a Lendasat loan
+    LendasatCollateral,
+    /// Repayment of a Lendasat loan
+    LendasatRepayment,
+}
+
+impl DestinationType {
+    fn as_str(&self) -> &'static str {
+        match self {
+            DestinationType::Ark => "Ark",
+            DestinationType::Onchain => "Onchain",
+            DestinationType::Lightning => "Lightning",
+            DestinationType::LendaswapSwap => "LendaswapSwap",
+            DestinationType::LendasatCollateral => "LendasatCollateral",
+            DestinationType::LendasatRepayment => "LendasatRepayment",
+        }
+    }
+
+    fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "Ark" => Some(DestinationType::Ark),
+            "Onchain" => Some(DestinationType::Onchain),
+            "Lightning" => Some(DestinationType::Lightning),
+            "LendaswapSwap" => Some(DestinationType::LendaswapSwap),
+            "LendasatCollateral" => Some(DestinationType::LendasatCollateral),
+            "LendasatRepayment" => Some(DestinationType::LendasatRepayment),
+            _ => None,
+        }
+    }
+}
+
+/// User-facing context for a single transaction.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TxMetadata {
+    pub txid: String,
+    pub destination_type: Option<DestinationType>,
+    /// Address or node the funds went to
+    pub counterparty: Option<String>,
+    /// Id of the related Boltz swap, LendaSwap swap or Lendasat contract
+    pub reference: Option<String>,
+    /// Free-form note entered by the user
+    pub note: Option<String>,
+    /// User-chosen category (e.g. "groceries", "savings")
+    pub category: Option<String>,
+    /// Unix timestamp of when the metadata was first recorded
+    pub created_at: i64,
+    /// Unix timestamp of the last change
+    pub updated_at: i64,
+}
+
+/// Fields to set on a transaction's metadata. `None` leaves a field unchanged,
+/// an empty note, category or counterparty clears it.
+#[derive(Debug, Clone, Default)]
+pub struct TxMetadataUpdate {
+    pub destination_type: Option<DestinationType>,
+    pub counterparty: Option<String>,
+    pub reference: Option<String>,
+    pub note: Option<String>,
+    pub category: Option<String>,
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+struct TxMetadataRow {
+    txid: String,
+    destination_type: Option<String>,
+    counterparty: Option<String>,
+    reference: Option<String>,
+    note: Option<String>,
+    category: Option<String>,
+    created_at: i64,
+    updated_at: i64,
+}
+
+impl From<TxMetadataRow> for TxMetadata {
+    fn from(row: TxMetadataRow) -> Self {
+        TxMetadata {
+            txid: row.txid,
+            destination_type: row
+                .destination_type
+                .as_deref()
+                .and_then(DestinationType::from_str),
+            counterparty: row.counterparty,
+            reference: row.reference,
+            note: row.note,
+            category: row.category,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        }
+    }
+}
+
+/// A single BIP329 label record.
+///
+/// Only `tx` records are imported; other types are skipped.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Bip329Label {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(rename = "ref")]
+    reference: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    label: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    origin: Option<String>,
+}
+
+/// SQLite-based storage for transaction metadata.
+pub struct TxMetadataDb {
+    pool: sqlx::SqlitePool,
+}
+
+impl TxMetadataDb {
+    /// Create a new TxMetadataDb with the given data directory.
+    /// The SQLite database will be created at `{data_dir}/tx_metadata.sqlite`.
+    pub async fn new(data_dir: &str) -> Result<Self> {
+        let db_path = Path::new(data_dir).join(TX_METADATA_DB_FILE);
+        let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
+
+        let pool = sqlx::SqlitePool::connect(&db_url)
+            .await
+            .map_err(|e| anyhow!("Failed to connect to SQLite database: {}", e))?;
+
+        Self::run_migrations(&pool).await?;
+
+        Ok(Self { pool })
+    }
+
+    /// Run database migrations to create/update tables.
+    async fn run_migrations(pool: &sqlx::SqlitePool) -> Result<()> {
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS tx_metadata (
+                txid TEXT PRIMARY KEY NOT NULL,
+                destination_type TEXT,
+                counterparty TEXT,
+                reference TEXT,
+                note TEXT,
+                category TEXT,
+                created_at INTEGER NOT NULL,
+                updated_at INTEGER NOT NULL
+            )
+            "#,
+        )
+        .execute(pool)
+        .await
+        .map_err(|e| anyhow!("Failed to create tx_metadata table: {}", e))?;
+
+        sqlx::query("CREATE INDEX IF NOT EXISTS idx_tx_metadata_category ON tx_metadata(category)")
+            .execute(pool)
+            .await
+            .ok();
+
+        sqlx::query(
+            "CREATE INDEX IF NOT EXISTS idx_tx_metadata_reference ON tx_metadata(reference)",
+        )
+        .execute(pool)
+        .await
+        .ok();
+
+        Ok(())
+    }
+
+    /// Insert or update metadata for a transaction.
+    ///
+    /// Fields passed as `None` keep their stored value, so the send flow can
+    /// record the destination and the UI can add a note later without one
+    /// overwriting the other. An empty note, category or counterparty clears
+    /// the stored value.
+    pub async fn upsert(&self, txid: &str, update: TxMetadataUpdate) -> Result<TxMetadata> {
+        let now = now_unix();
+
+        sqlx::query(
+            r#"
+            INSERT INTO tx_metadata
+                (txid, destination_type, counterparty, reference, note, category,
+                 created_at, updated_at)
+            VALUES (?1, ?2, NULLIF(?3, ''), ?4, NULLIF(?5, ''), NULLIF(?6, ''), ?7, ?7)
+            ON CONFLICT(txid) DO UPDATE SET
+                destination_type = COALESCE(?2, destination_type),
+                counterparty = CASE WHEN ?3 IS NULL THEN counterparty ELSE NULLIF(?3, '') END,
+                reference = COALESCE(?4, reference),
+                note = CASE WHEN ?5 IS NULL THEN note ELSE NULLIF(?5, '') END,
+                category = CASE WHEN ?6 IS NULL THEN category ELSE NULLIF(?6, '') END,
+                updated_at = ?7
+            "#,
+        )
+        .bind(txid)
+        .bind(update.destination_type.map(|d| d.as_str()))
+        .bind(update.counterparty)
+        .bind(update.reference)
+        .bind(update.note)
+        .bind(update.category)
+        .bind(now)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| anyhow!("Failed to store tx metadata: {}", e))?;
+
+        self.get(txid)
+            .await?
+            .ok_or_else(|| anyhow!("Tx metadata for {} missing after insert", txid))
+    }
+
+    /// Get the metadata for a single transaction.
+    pub async fn get(&self, txid: &str) -> Result<Option<TxMetadata>> {
+        let row: Option<TxMetadataRow> = sqlx::query_as("SELECT * FROM tx_metadata WHERE txid = ?")
+            .bind(txid)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| anyhow!("Failed to load tx metadata: {}", e))?;
+
+        Ok(row.map(TxMetadata::from))
+    }
+
+    /// Find the transactions recorded for a swap or contract id.
+    pub async fn find_by_reference(&self, reference: &str) -> Result<Vec<TxMetadata>> {
+        let rows: Vec<TxMetadataRow> =
+            sqlx::query_as("SELECT * FROM tx_metadata WHERE reference = ? ORDER BY created_at")
+                .bind(reference)
+                .fetch_all(&self.pool)
+                .await
+                .map_err(|e| anyhow!("Failed to load tx metadata: {}", e))?;
+
+        Ok(rows.into_iter().map(TxMetadata::from).collect())
+    }
+
+    /// Find the transactions recorded as paying to an address.
+    pub async fn find_by_counterparty(&self, counterparty: &str) -> Result<Vec<TxMetadata>> {
+        let rows: Vec<TxMetadataRow> =
+            sqlx::query_as("SELECT * FROM tx_metadata WHERE counterparty = ? ORDER BY created_at")
+                .bind(counterparty)
+                .fetch_all(&self.pool)
+                .await
+                .map_err(|e| anyhow!("Failed to load tx metadata: {}", e))?;
+
+        Ok(rows.into_iter().map(TxMetadata::from).collect())
+    }
+
+    /// Get the metadata for all transactions.
+    pub async fn get_all(&self) -> Result<Vec<TxMetadata>> {
+        let rows: Vec<TxMetadataRow> =
+            sqlx::query_as("SELECT * FROM tx_metadata ORDER BY created_at DESC")
+                .fetch_all(&self.pool)
+                .await
+                .map_err(|e| anyhow!("Failed to load tx metadata: {}", e))?;
+
+        Ok(rows.into_iter().map(TxMetadata::from).collect())
+    }
+
+    /// Delete the metadata for a transaction.
+    pub async fn delete(&self, txid: &str) -> Result<()> {
+        sqlx::query("DELETE FROM tx_metadata WHERE txid = ?")
+            .bind(txid)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| anyhow!("Failed to delete tx metadata: {}", e))?;
+
+        Ok(())
+    }
+
+    /// Export all transaction labels in BIP329 JSON Lines format.
+    ///
+    /// The label is the user's note; transactions without a note are skipped,
+    /// so that importing the export restores exactly the notes.
+    pub async fn export_bip329(&self) -> Result<String> {
+        let lines = self
+            .get_all()
+            .await?
+            .into_iter()
+            .filter_map(|m| {
+                let label = m.note?;
+                Some(Bip329Label {
+                    kind: "tx".to_string(),
+                    reference: m.txid,
+                    label: Some(label),
+                    origin: None,
+                })
+            })
+            .map(|label| serde_json::to_string(&label))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| anyhow!("Failed to serialize BIP329 label: {}", e))?;
+
+        Ok(lines.join("\n"))
+    }
+
+    /// Import transaction labels from BIP329 JSON Lines.
+    ///
+    /// Labels become the note of the transaction. Records of other types
+    /// (addresses, outputs, xpubs) and malformed lines are skipped.
+    /// Returns the number of imported labels.
+    pub async fn import_bip329(&self, jsonl: &str) -> Result<u32> {
+        let mut imported = 0;
+
+        for line in jsonl.lines().map(str::trim).filter(|l| !l.is_empty()) {
+            let record = match serde_json::from_str::<Bip329Label>(line) {
+                Ok(record) => record,
+                Err(e) => {
+                    tracing::warn!("Skipping malformed BIP329 line: {}", e);
+                    continue;
+                }
+            };
+
+            if record.kind != "tx" {
+                continue;
+            }
+
+            let Some(label) = record.label.filter(|l| !l.is_empty()) else {
+                continue;
+            };
+
+            let update = TxMetadataUpdate {
+                note: Some(label),
+                ..Default::default()
+            };
+            self.upsert(&record.reference, update).await?;
+            imported += 1;
+        }
+
+        tracing::info!(imported, "Imported BIP329 transaction labels");
+
+        Ok(imported)
+    }
+}
+
+/// Get the metadata store of the loaded wallet.
+pub(crate) fn get_db() -> Result<Arc<TxMetadataDb>> {
+    let lock = TX_METADATA_DB
+        .try_get()
+        .ok_or_else(|| ApiError::not_initialized("Transaction metadata store"))?;
+    let guard = lock.read();
+    Ok(Arc::clone(&*guard))
+}
+
+/// Record metadata for a transaction the wallet just sent.
+///
+/// This never fails the caller: the payment already happened, so a storage
+/// error is only logged. Also marks the history cache stale so the send
+/// shows up on the next load.
+pub(crate) async fn record_send(txid: &str, update: TxMetadataUpdate) {
+    crate::ark::history_db::mark_stale();
+
+    let result = match get_db() {
+        Ok(db) => db.upsert(txid, update).await.map(|_| ()),
+        Err(e) => Err(e),
+    };
+
+    if let Err(e) = result {
+        tracing::warn!(txid, "Could not record transaction metadata: {e:#}");
+    }
+}
+
+fn now_unix() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn open_db() -> TxMetadataDb {
+        let dir = std::env::temp_dir().join(format!("tx-metadata-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        TxMetadataDb::new(dir.to_str().unwrap()).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn upsert_keeps_fields_not_in_update() {
+        let db = open_db().await;
+
+        let sent = db
+            .upsert(
+                "tx1",
+                TxMetadataUpdate {
+                    destination_type: Some(DestinationType::Onchain),
+                    counterparty: Some("bc1qcounterparty".to_string()),
+                    reference: Some("swap-1".to_string()),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+
+        let noted = db
+            .upsert(
+                "tx1",
+                TxMetadataUpdate {
+                    note: Some("rent".to_string()),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(noted.destination_type, Some(DestinationType::Onchain));
+        assert_eq!(noted.counterparty.as_deref(), Some("bc1qcounterparty"));
+        assert_eq!(noted.reference.as_deref(), Some("swap-1"));
+        assert_eq!(noted.note.as_deref(), Some("rent"));
+        assert_eq!(noted.category, None);
+        assert_eq!(noted.created_at, sent.created_at);
+    }
+
+    #[tokio::test]
+    async fn upsert_overwrites_fields_in_update() {
+        let db = open_db().await;
+        let note = |note: &str| TxMetadataUpdate {
+            note: Some(note.to_string()),
+            category: Some("bills".to_string()),
+            ..Default::default()
+        };
+
+        db.upsert("tx1", note("rent")).await.unwrap();
+        let updated = db.upsert("tx1", note("rent for May")).await.unwrap();
+
+        assert_eq!(updated.note.as_deref(), Some("rent for May"));
+        assert_eq!(updated.category.as_deref(), Some("bills"));
+        assert_eq!(db.get_all().await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn empty_values_clear_fields() {
+        let db = open_db().await;
+        let update = |note: &str, category: &str| TxMetadataUpdate {
+            note: Some(note.to_string()),
+            category: Some(category.to_string()),
+            ..Default::default()
+        };
+
+        db.upsert("tx1", update("rent", "bills")).await.unwrap();
+        let cleared = db.upsert("tx1", update("", "")).await.unwrap();
+        assert_eq!(cleared.note, None);
+        assert_eq!(cleared.category, None);
+
+        // Never stored as empty strings either
+        let inserted = db.upsert("tx2", update("", "")).await.unwrap();
+        assert_eq!(inserted.note, None);
+        assert_eq!(inserted.category, None);
+    }
+
+    #[tokio::test]
+    async fn bip329_round_trip() {
+        let source = open_db().await;
+        source
+            .upsert(
+                "tx1",
+                TxMetadataUpdate {
+                    note: Some("rent".to_string()),
+                    counterparty: Some("bc1qlandlord".to_string()),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        source
+            .upsert(
+                "tx2",
+                TxMetadataUpdate {
+                    note: Some("dinner with \"friends\"".to_string()),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        // No note, nothing to export
+        source
+            .upsert(
+                "tx3",
+                TxMetadataUpdate {
+                    counterparty: Some("ark1qfriend".to_string()),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+
+        let exported = source.export_bip329().await.unwrap();
+        assert_eq!(exported.lines().count(), 2);
+        assert!(!exported.contains("bc1qlandlord"));
+
+        let target = open_db().await;
+        assert_eq!(target.import_bip329(&exported).await.unwrap(), 2);
+
+        let tx1 = target.get("tx1").await.unwrap().unwrap();
+        assert_eq!(tx1.note.as_deref(), Some("rent"));
+        assert_eq!(tx1.counterparty, None);
+        let tx2 = target.get("tx2").await.unwrap().unwrap();
+        assert_eq!(tx2.note.as_deref(), Some("dinner with \"friends\""));
+        assert!(target.get("tx3").await.unwrap().is_none());
+
+        // Exporting the imported labels yields the same records
+        let sorted = |jsonl: &str| {
+            let mut lines: Vec<String> = jsonl.lines().map(str::to_string).collect();
+            lines.sort();
+            lines
+        };
+        let reexported = target.export_bip329().await.unwrap();
+        assert_eq!(sorted(&reexported), sorted(&exported));
+    }
+
+    #[tokio::test]
+    async fn bip329_import_skips_other_records() {
+        let db = open_db().await;
+        let jsonl = [
+            r#"{"type":"tx","ref":"tx1","label":"coffee"}"#,
+            r#"{"type":"addr","ref":"bc1qaddress","label":"savings"}"#,
+            r#"{"type":"tx","ref":"tx2","label":""}"#,
+            r#"{"type":"tx","ref":"tx3"}"#,
+            "not json",
+            "",
+        ]
+        .join("\n");
+
+        assert_eq!(db.import_bip329(&jsonl).await.unwrap(), 1);
+
+        let all = db.get_all().await.unwrap();
+        assert_eq!(all.len(), 1);
+        assert_eq!(all[0].txid, "tx1");
+        assert_eq!(all[0].note.as_deref(), Some("coffee"));
+    }
+
+    #[tokio::test]
+    async fn bip329_import_keeps_existing_metadata() {
+        let db = open_db().await;
+        db.upsert(
+            "tx1",
+            TxMetadataUpdate {
+                destination_type: Some(DestinationType::Ark),
+                counterparty: Some("ark1qfriend".to_string()),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        db.import_bip329(r#"{"type":"tx","ref":"tx1","label":"dinner"}"#)
+            .await
+            .unwrap();
+
+        let tx1 = db.get("tx1").await.unwrap().unwrap();
+        assert_eq!(tx1.destination_type, Some(DestinationType::Ark));
+        assert_eq!(tx1.counterparty.as_deref(), Some("ark1qfriend"));
+        assert_eq!(tx1.note.as_deref(), Some("dinner"));
+    }
+}
@@ -0,0 +1,279 @@
+//! Errors returned across the Flutter FFI boundary.
+//!
+//! Internal code keeps using `anyhow`. Failures the UI reacts to are raised as
+//! an `ApiError` inside the `anyhow::Error` (e.g. `bail!(ApiError::Timeout)`)
+//! and recovered by the API functions when converting the error.
+
+use std::fmt;
+
+/// Error returned by the public API.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ApiError {
+    /// A client or store was used before it was set up, e.g. "Ark client"
+    NotInitialized {
+        component: String,
+    },
+    /// The wallet cannot cover the amount including fees
+    InsufficientFunds {
+        needed_sats: u64,
+        available_sats: u64,
+    },
+    InvalidAddress {
+        address: String,
+        reason: String,
+    },
+    /// An address or server belongs to a different Bitcoin network
+    NetworkMismatch {
+        expected: String,
+        actual: String,
+    },
+    /// A backend could not be reached or answered with a server error
+    ServerUnavailable {
+        message: String,
+    },
+    Timeout,
+    /// The swap can no longer be completed
+    SwapExpired {
+        swap_id: String,
+    },
+    /// Lendasat requires a (new) login
+    AuthRequired,
+    /// Any other failure, with a human readable description
+    Other {
+        message: String,
+    },
+}
+
+impl ApiError {
+    pub fn not_initialized(component: &str) -> Self {
+        ApiError::NotInitialized {
+            component: component.to_string(),
+        }
+    }
+
+    pub fn invalid_address(address: &str, reason: impl fmt::Display) -> Self {
+        ApiError::InvalidAddress {
+            address: address.to_string(),
+            reason: reason.to_string(),
+        }
+    }
+
+    /// Classify a failed HTTP request.
+    pub fn from_reqwest(error: &reqwest::Error) -> Option<Self> {
+        if error.is_timeout() {
+            return Some(ApiError::Timeout);
+        }
+        if error.is_connect() || error.status().is_some_and(|s| s.is_server_error()) {
+            return Some(ApiError::ServerUnavailable {
+                message: error.to_string(),
+            });
+        }
+        None
+    }
+
+    /// Classify a failure reported by an SDK that wraps transport errors in
+    /// its own error type, e.g. the Ark client.
+    ///
+    /// The source chain is searched for the underlying HTTP or socket error.
+    /// Errors that only carry the transport failure in their message are
+    /// recognised by it.
+    pub fn from_sdk(error: &(dyn std::error::Error + 'static)) -> Option<Self> {
+        let mut messages = Vec::new();
+        let mut cause = Some(error);
+        while let Some(current) = cause {
+            if let Some(api_error) = current.downcast_ref::<ApiError>() {
+                return Some(api_error.clone());
+            }
+            if let Some(api_error) = current
+                .downcast_ref::<reqwest::Error>()
+                .and_then(ApiError::from_reqwest)
+            {
+                return Some(api_error);
+            }
+            if let Some(api_error) = current
+                .downcast_ref::<std::io::Error>()
+                .and_then(ApiError::from_io)
+            {
+                return Some(api_error);
+            }
+            messages.push(current.to_string());
+            cause = current.source();
+        }
+
+        ApiError::from_message(&messages.join(": "))
+    }
+
+    fn from_io(error: &std::io::Error) -> Option<Self> {
+        use std::io::ErrorKind;
+
+        match error.kind() {
+            ErrorKind::TimedOut => Some(ApiError::Timeout),
+            ErrorKind::ConnectionRefused
+            | ErrorKind::ConnectionReset
+            | ErrorKind::ConnectionAborted
+            | ErrorKind::NotConnected
+            | ErrorKind::HostUnreachable
+            | ErrorKind::NetworkUnreachable => Some(ApiError::ServerUnavailable {
+                message: error.to_string(),
+            }),
+            _ => None,
+        }
+    }
+
+    /// Recognise a transport failure by its message, for errors that were
+    /// formatted before they reached us.
+    fn from_message(message: &str) -> Option<Self> {
+        const TIMEOUT: [&str; 3] = ["timed out", "deadline exceeded", "deadline has elapsed"];
+        const UNAVAILABLE: [&str; 9] = [
+            "error sending request",
+            "connection refused",
+            "connection reset",
+            "tcp connect error",
+            "dns error",
+            "failed to lookup address",
+            "network is unreachable",
+            "transport error",
+            "status: unavailable",
+        ];
+
+        let lower = message.to_lowercase();
+        if TIMEOUT.iter().any(|marker| lower.contains(marker)) {
+            return Some(ApiError::Timeout);
+        }
+        if UNAVAILABLE.iter().any(|marker| lower.contains(marker)) {
+            return Some(ApiError::ServerUnavailable {
+                message: message.to_string(),
+            });
+        }
+        None
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ApiError::NotInitialized { component } => write!(f, "{} not initialized", component),
+            ApiError::InsufficientFunds {
+                needed_sats,
+                available_sats,
+            } => write!(
+                f,
+                "Insufficient funds: need {} sats, have {} sats",
+                needed_sats, available_sats
+            ),
+            ApiError::InvalidAddress { address, reason } => {
+                write!(f, "Invalid address '{}': {}", address, reason)
+            }
+            ApiError::NetworkMismatch { expected, actual } => {
+                write!(f, "Network mismatch: expected {}, got {}", expected, actual)
+            }
+            ApiError::ServerUnavailable { message } => write!(f, "Server unavailable: {}", message),
+            ApiError::Timeout => write!(f, "Request timed out"),
+            ApiError::SwapExpired { swap_id } => write!(f, "Swap {} has expired", swap_id),
+            ApiError::AuthRequired => write!(f, "Not authenticated"),
+            ApiError::Other { message } => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+impl From<anyhow::Error> for ApiError {
+    fn from(error: anyhow::Error) -> Self {
+        ApiError::from(&error)
+    }
+}
+
+impl From<&anyhow::Error> for ApiError {
+    fn from(error: &anyhow::Error) -> Self {
+        for cause in error.chain() {
+            if let Some(api_error) = cause.downcast_ref::<ApiError>() {
+                return api_error.clone();
+            }
+            if let Some(api_error) = cause
+                .downcast_ref::<reqwest::Error>()
+                .and_then(ApiError::from_reqwest)
+            {
+                return api_error;
+            }
+        }
+
+        ApiError::Other {
+            message: format!("{:#}", error),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug)]
+    struct SdkError {
+        message: &'static str,
+        source: Option<std::io::Error>,
+    }
+
+    impl fmt::Display for SdkError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "{}", self.message)
+        }
+    }
+
+    impl std::error::Error for SdkError {
+        fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+            self.source.as_ref().map(|e| e as _)
+        }
+    }
+
+    #[test]
+    fn sdk_error_with_io_source() {
+        let error = SdkError {
+            message: "request failed",
+            source: Some(std::io::Error::from(std::io::ErrorKind::ConnectionRefused)),
+        };
+        assert!(matches!(
+            ApiError::from_sdk(&error),
+            Some(ApiError::ServerUnavailable { .. })
+        ));
+
+        let error = SdkError {
+            message: "request failed",
+            source: Some(std::io::Error::from(std::io::ErrorKind::TimedOut)),
+        };
+        assert_eq!(ApiError::from_sdk(&error), Some(ApiError::Timeout));
+    }
+
+    #[test]
+    fn sdk_error_by_message() {
+        let error = SdkError {
+            message: "failed to get info: error sending request for url (https://ark.example)",
+            source: None,
+        };
+        assert!(matches!(
+            ApiError::from_sdk(&error),
+            Some(ApiError::ServerUnavailable { .. })
+        ));
+
+        let error = SdkError {
+            message: "status: DeadlineExceeded, message: \"Deadline exceeded\"",
+            source: None,
+        };
+        assert_eq!(ApiError::from_sdk(&error), Some(ApiError::Timeout));
+    }
+
+    #[test]
+    fn sdk_error_rejected_by_server() {
+        let error = SdkError {
+            message: "invalid intent: VTXO already spent",
+            source: Some(std::io::Error::other("bad data")),
+        };
+        assert_eq!(ApiError::from_sdk(&error), None);
+    }
+
+    #[test]
+    fn anyhow_chain_keeps_api_error() {
+        let error = anyhow::Error::new(ApiError::Timeout).context("Failed settling");
+        assert_eq!(ApiError::from(error), ApiError::Timeout);
+    }
+}
@@ -0,0 +1,491 @@
+//! Shared HTTP layer for outbound network calls.
+//!
+//! Every backend gets a pooled `reqwest::Client` with its own timeout. Requests
+//! go through [`RequestExt::send_with_retry`] (or [`retry`] for SDK calls), which
+//! retries transient failures with exponential backoff and jitter when it is
+//! safe to do so, and trips a per-service circuit breaker once a backend keeps
+//! failing so that callers fail fast instead of piling up on timeouts.
+
+use crate::error::ApiError;
+use anyhow::Result;
+use parking_lot::Mutex;
+use rand::Rng;
+use reqwest::Method;
+use reqwest::RequestBuilder;
+use reqwest::Response;
+use reqwest::StatusCode;
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::sync::LazyLock;
+use std::time::Duration;
+use std::time::Instant;
+
+/// Backends the app talks to, each with its own client and circuit breaker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum Service {
+    Mempool,
+    MoonPay,
+    Prices,
+    Lendasat,
+    Ark,
+    Boltz,
+    FearGreed,
+}
+
+impl fmt::Display for Service {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Service::Mempool => "mempool.space",
+            Service::MoonPay => "MoonPay",
+            Service::Prices => "price service",
+            Service::Lendasat => "Lendasat",
+            Service::Ark => "Ark server",
+            Service::Boltz => "Boltz",
+            Service::FearGreed => "Fear & Greed index",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Retry, timeout and circuit breaker settings for outbound network calls.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpConfig {
+    /// Retries after the first attempt, 0 disables retrying
+    pub max_retries: u32,
+    /// Delay before the first retry, doubled on every further attempt
+    pub base_delay_ms: u64,
+    /// Upper bound for a single backoff delay
+    pub max_delay_ms: u64,
+    /// Consecutive failures after which a service is considered down, 0 disables the breaker
+    pub circuit_failure_threshold: u32,
+    /// How long calls to a down service fail fast before a trial call is let through
+    pub circuit_open_secs: u64,
+    pub mempool_timeout_secs: u64,
+    pub moonpay_timeout_secs: u64,
+    pub prices_timeout_secs: u64,
+    pub lendasat_timeout_secs: u64,
+    pub ark_timeout_secs: u64,
+    pub boltz_timeout_secs: u64,
+    pub fear_greed_timeout_secs: u64,
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            base_delay_ms: 250,
+            max_delay_ms: 5_000,
+            circuit_failure_threshold: 5,
+            circuit_open_secs: 30,
+            mempool_timeout_secs: 15,
+            moonpay_timeout_secs: 20,
+            prices_timeout_secs: 15,
+            lendasat_timeout_secs: 30,
+            ark_timeout_secs: 30,
+            boltz_timeout_secs: 30,
+            fear_greed_timeout_secs: 15,
+        }
+    }
+}
+
+impl HttpConfig {
+    fn timeout(&self, service: Service) -> Duration {
+        let secs = match service {
+            Service::Mempool => self.mempool_timeout_secs,
+            Service::MoonPay => self.moonpay_timeout_secs,
+            Service::Prices => self.prices_timeout_secs,
+            Service::Lendasat => self.lendasat_timeout_secs,
+            Service::Ark => self.ark_timeout_secs,
+            Service::Boltz => self.boltz_timeout_secs,
+            Service::FearGreed => self.fear_greed_timeout_secs,
+        };
+        Duration::from_secs(secs.max(1))
+    }
+
+    /// Full jitter: a random delay between zero and the capped exponential backoff.
+    fn backoff(&self, attempt: u32) -> Duration {
+        let exponential = self
+            .base_delay_ms
+            .saturating_mul(1u64 << attempt.min(16))
+            .min(self.max_delay_ms);
+        Duration::from_millis(rand::thread_rng().gen_range(0..=exponential))
+    }
+}
+
+#[derive(Debug, Default)]
+struct CircuitBreaker {
+    consecutive_failures: u32,
+    open_until: Option<Instant>,
+}
+
+impl CircuitBreaker {
+    /// Whether a call may go through at `now`. Once the open period has passed
+    /// a single trial call is let through and the breaker stays open for
+    /// everyone else until that call reports back.
+    fn admit(&mut self, now: Instant, open_for: Duration) -> bool {
+        match self.open_until {
+            Some(open_until) if now < open_until => false,
+            Some(_) => {
+                self.open_until = Some(now + open_for);
+                true
+            }
+            None => true,
+        }
+    }
+
+    /// Count a failed call, returns `true` if this opened the breaker.
+    fn record_failure(&mut self, now: Instant, threshold: u32, open_for: Duration) -> bool {
+        self.consecutive_failures = self.consecutive_failures.saturating_add(1);
+        if threshold == 0 || self.consecutive_failures < threshold {
+            return false;
+        }
+
+        let opened = self.open_until.is_none();
+        self.open_until = Some(now + open_for);
+        opened
+    }
+}
+
+#[derive(Default)]
+struct HttpState {
+    config: HttpConfig,
+    clients: HashMap<Service, reqwest::Client>,
+    breakers: HashMap<Service, CircuitBreaker>,
+}
+
+static HTTP_STATE: LazyLock<Mutex<HttpState>> = LazyLock::new(Default::default);
+
+/// Replace the current configuration. Clients are rebuilt lazily with the new timeouts.
+pub fn configure(config: HttpConfig) {
+    let mut state = HTTP_STATE.lock();
+    state.config = config;
+    state.clients.clear();
+    state.breakers.clear();
+}
+
+pub fn config() -> HttpConfig {
+    HTTP_STATE.lock().config.clone()
+}
+
+/// The pooled client for `service`.
+pub(crate) fn client(service: Service) -> reqwest::Client {
+    let mut state = HTTP_STATE.lock();
+    let timeout = state.config.timeout(service);
+    state
+        .clients
+        .entry(service)
+        .or_insert_with(|| {
+            reqwest::Client::builder()
+                .timeout(timeout)
+                .build()
+                .unwrap_or_else(|e| {
+                    tracing::warn!("Failed to build HTTP client for {}: {}", service, e);
+                    reqwest::Client::new()
+                })
+        })
+        .clone()
+}
+
+/// Fail fast while the breaker of `service` is open.
+fn admit(service: Service) -> Result<()> {
+    let mut state = HTTP_STATE.lock();
+    let open_for = Duration::from_secs(state.config.circuit_open_secs);
+    let breaker = state.breakers.entry(service).or_default();
+
+    if !breaker.admit(Instant::now(), open_for) {
+        return Err(ApiError::ServerUnavailable {
+            message: format!("{} is temporarily unavailable", service),
+        }
+        .into());
+    }
+
+    Ok(())
+}
+
+fn record_success(service: Service) {
+    let mut state = HTTP_STATE.lock();
+    state.breakers.insert(service, CircuitBreaker::default());
+}
+
+fn record_failure(service: Service) {
+    let mut state = HTTP_STATE.lock();
+    let threshold = state.config.circuit_failure_threshold;
+    let open_for = Duration::from_secs(state.config.circuit_open_secs);
+    let breaker = state.breakers.entry(service).or_default();
+
+    if breaker.record_failure(Instant::now(), threshold, open_for) {
+        tracing::warn!(
+            "{} failed {} times in a row, pausing requests for {:?}",
+            service,
+            breaker.consecutive_failures,
+            open_for
+        );
+    }
+}
+
+/// Methods that can be repeated without changing the outcome on the server.
+fn is_idempotent(method: &Method) -> bool {
+    matches!(
+        *method,
+        Method::GET | Method::HEAD | Method::PUT | Method::DELETE | Method::OPTIONS
+    )
+}
+
+fn is_transient_status(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || matches!(status.as_u16(), 500 | 502 | 503 | 504)
+}
+
+/// Errors that say the backend is unreachable or slow rather than that the call was wrong.
+fn is_transient_error(error: &anyhow::Error) -> bool {
+    matches!(
+        ApiError::from(error),
+        ApiError::ServerUnavailable { .. } | ApiError::Timeout
+    )
+}
+
+pub(crate) trait RequestExt {
+    /// Send the request through the circuit breaker of `service`, retrying
+    /// transient failures.
+    ///
+    /// Connection errors are always retried since the request never reached the
+    /// server. Timeouts and 429/5xx responses are only retried for idempotent
+    /// methods, so that e.g. a POST creating a contract is never submitted twice.
+    async fn send_with_retry(self, service: Service) -> Result<Response>;
+}
+
+impl RequestExt for RequestBuilder {
+    async fn send_with_retry(self, service: Service) -> Result<Response> {
+        let config = config();
+        let (client, request) = self.build_split();
+        let mut request = request?;
+        let idempotent = is_idempotent(request.method());
+
+        let mut attempt = 0;
+        loop {
+            admit(service)?;
+
+            // Streaming bodies cannot be cloned, those requests get a single attempt.
+            let retry_request = if attempt < config.max_retries {
+                request.try_clone()
+            } else {
+                None
+            };
+            let outcome = client.execute(request).await;
+            let Some(retry_request) = retry_request else {
+                return settle(service, outcome);
+            };
+
+            match outcome {
+                Ok(response) if idempotent && is_transient_status(response.status()) => {
+                    record_failure(service);
+                    tracing::debug!(
+                        "{} answered {}, retrying (attempt {})",
+                        service,
+                        response.status(),
+                        attempt + 1
+                    );
+                }
+                Err(e) if e.is_connect() || (idempotent && e.is_timeout()) => {
+                    record_failure(service);
+                    tracing::debug!("Request to {} failed, retrying: {}", service, e);
+                }
+                outcome => return settle(service, outcome),
+            }
+
+            tokio::time::sleep(config.backoff(attempt)).await;
+            attempt += 1;
+            request = retry_request;
+        }
+    }
+}
+
+/// Report the final outcome of a request to the circuit breaker.
+fn settle(service: Service, outcome: reqwest::Result<Response>) -> Result<Response> {
+    match outcome {
+        Ok(response) => {
+            if is_transient_status(response.status()) {
+                record_failure(service);
+            } else {
+                record_success(service);
+            }
+            Ok(response)
+        }
+        Err(e) => {
+            record_failure(service);
+            Err(e.into())
+        }
+    }
+}
+
+/// Run an idempotent SDK call (e.g. an Ark or Boltz read) with the timeout,
+/// retries and circuit breaker of `service`.
+///
+/// Only timeouts and unreachable servers are retried and counted against the
+/// breaker; any other error means the service answered and is returned as is.
+/// Only use this for calls that are safe to repeat.
+pub(crate) async fn retry<T, F, Fut>(service: Service, mut op: F) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let config = config();
+    let timeout = config.timeout(service);
+
+    let mut attempt = 0;
+    loop {
+        admit(service)?;
+
+        let error = match tokio::time::timeout(timeout, op()).await {
+            Ok(Ok(value)) => {
+                record_success(service);
+                return Ok(value);
+            }
+            Ok(Err(e)) => e,
+            Err(_) => ApiError::Timeout.into(),
+        };
+
+        if !is_transient_error(&error) {
+            record_success(service);
+            return Err(error);
+        }
+
+        record_failure(service);
+        if attempt >= config.max_retries {
+            return Err(error);
+        }
+        tracing::debug!("Call to {} failed, retrying: {:#}", service, error);
+
+        tokio::time::sleep(config.backoff(attempt)).await;
+        attempt += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    const OPEN_FOR: Duration = Duration::from_secs(30);
+
+    #[test]
+    fn backoff_stays_within_capped_exponential() {
+        let config = HttpConfig {
+            base_delay_ms: 100,
+            max_delay_ms: 1_000,
+            ..HttpConfig::default()
+        };
+
+        for attempt in 0..64 {
+            let cap = (100u64 << attempt.min(16)).min(1_000);
+            for _ in 0..20 {
+                assert!(config.backoff(attempt) <= Duration::from_millis(cap));
+            }
+        }
+    }
+
+    #[test]
+    fn backoff_without_base_delay_is_zero() {
+        let config = HttpConfig {
+            base_delay_ms: 0,
+            ..HttpConfig::default()
+        };
+
+        assert_eq!(config.backoff(0), Duration::ZERO);
+        assert_eq!(config.backoff(10), Duration::ZERO);
+    }
+
+    #[test]
+    fn breaker_opens_after_threshold() {
+        let now = Instant::now();
+        let mut breaker = CircuitBreaker::default();
+
+        assert!(!breaker.record_failure(now, 3, OPEN_FOR));
+        assert!(!breaker.record_failure(now, 3, OPEN_FOR));
+        assert!(breaker.admit(now, OPEN_FOR));
+
+        assert!(breaker.record_failure(now, 3, OPEN_FOR));
+        assert!(!breaker.admit(now, OPEN_FOR));
+        assert!(!breaker.admit(now + OPEN_FOR - Duration::from_secs(1), OPEN_FOR));
+        // Further failures while open keep it open without reporting it again
+        assert!(!breaker.record_failure(now, 3, OPEN_FOR));
+    }
+
+    #[test]
+    fn breaker_lets_a_single_trial_call_through() {
+        let now = Instant::now();
+        let mut breaker = CircuitBreaker::default();
+        breaker.record_failure(now, 1, OPEN_FOR);
+
+        let later = now + OPEN_FOR;
+        assert!(breaker.admit(later, OPEN_FOR));
+        assert!(!breaker.admit(later, OPEN_FOR));
+
+        // A failed trial reopens the breaker for another period
+        breaker.record_failure(later, 1, OPEN_FOR);
+        assert!(!breaker.admit(later + OPEN_FOR - Duration::from_secs(1), OPEN_FOR));
+        assert!(breaker.admit(later + OPEN_FOR, OPEN_FOR));
+    }
+
+    #[test]
+    fn disabled_breaker_never_opens() {
+        let now = Instant::now();
+        let mut breaker = CircuitBreaker::default();
+
+        for _ in 0..100 {
+            assert!(!breaker.record_failure(now, 0, OPEN_FOR));
+        }
+        assert!(breaker.admit(now, OPEN_FOR));
+    }
+
+    #[test]
+    fn classifies_transient_errors() {
+        assert!(is_transient_error(&ApiError::Timeout.into()));
+        assert!(is_transient_error(
+            &ApiError::ServerUnavailable {
+                message: "down".to_string()
+            }
+            .into()
+        ));
+        assert!(is_transient_error(
+            &anyhow::Error::new(ApiError::Timeout).context("Failed settling")
+        ));
+        assert!(!is_transient_error(&anyhow::anyhow!("invalid invoice")));
+        assert!(!is_transient_error(
+            &ApiError::SwapExpired {
+                swap_id: "swap-1".to_string()
+            }
+            .into()
+        ));
+    }
+
+    #[tokio::test]
+    async fn retry_returns_permanent_errors_immediately() {
+        let calls = AtomicU32::new(0);
+
+        let result: Result<()> = retry(Service::Boltz, || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Err(anyhow::anyhow!("invalid invoice"))
+        })
+        .await;
+
+        assert!(result.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn retry_repeats_transient_errors() {
+        let calls = AtomicU32::new(0);
+
+        let result = retry(Service::Ark, || async {
+            if calls.fetch_add(1, Ordering::SeqCst) == 0 {
+                return Err(ApiError::Timeout.into());
+            }
+            Ok(42)
+        })
+        .await;
+
+        assert_eq!(result.unwrap(), 42);
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+}
@@ -7,7 +7,8 @@
 
 use crate::ark::mnemonic_file::{LENDASAT_DERIVATION_PATH, read_mnemonic_file};
 use anyhow::{Result, anyhow};
-use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
+use base64::Engine as _;
+use base64::engine::general_purpose::{STANDARD as BASE64, URL_SAFE_NO_PAD};
 use bitcoin::Network;
 use bitcoin::bip32::{DerivationPath, Xpriv};
 use bitcoin::consensus::encode::serialize_hex;
@@ -249,6 +250,16 @@ pub fn psbt_hex_to_base64(hex_psbt: &str) -> Result<String> {
     Ok(BASE64.encode(psbt_bytes))
 }
 
+/// Read the `exp` claim (unix seconds) of a JWT without verifying it.
+///
+/// Returns `None` if the token is malformed or has no expiry.
+pub fn jwt_expiry(token: &str) -> Option<i64> {
+    let payload = token.split('.').nth(1)?;
+    let bytes = URL_SAFE_NO_PAD.decode(payload.trim_end_matches('=')).ok()?;
+    let claims: serde_json::Value = serde_json::from_slice(&bytes).ok()?;
+    claims.get("exp")?.as_i64()
+}
+
 /// Verify a signature locally (for testing).
 /// Returns true if the signature is valid.
 #[allow(dead_code)]
@@ -305,6 +316,17 @@ mod tests {
         assert!(result);
     }
 
+    #[test]
+    fn test_jwt_expiry() {
+        let payload = URL_SAFE_NO_PAD.encode(r#"{"sub":"user","exp":1700000000}"#);
+        let token = format!("eyJhbGciOiJIUzI1NiJ9.{}.signature", payload);
+        assert_eq!(jwt_expiry(&token), Some(1700000000));
+
+        let payload = URL_SAFE_NO_PAD.encode(r#"{"sub":"user"}"#);
+        assert_eq!(jwt_expiry(&format!("header.{}.sig", payload)), None);
+        assert_eq!(jwt_expiry("not-a-jwt"), None);
+    }
+
     #[test]
     fn test_invalid_signature() {
         let secp = Secp256k1::new();
@@ -0,0 +1,425 @@
+//! Local verification of contract collateral addresses.
+//!
+//! The collateral address of a contract is built by the server. Before the
+//! wallet funds it, the address is rebuilt here from the contract's borrower
+//! and lender keys and its timelocks, so a malicious or buggy server cannot
+//! redirect the collateral to a script the borrower cannot recover from.
+//!
+//! Both collateral types are Taproot outputs with an unspendable internal key:
+//!
+//! - `BitcoinBtc`: an on-chain output with a cooperative leaf
+//!   `<B> CHECKSIGVERIFY <L> CHECKSIG` and a recovery leaf
+//!   `<expiry> CLTV DROP <B> CHECKSIG`.
+//! - `ArkadeBtc`: a VTXO of the wallet's Ark server `S`. Each spending path has
+//!   a collaborative leaf co-signed by the server and a unilateral exit leaf
+//!   delayed by the server's exit delay `D`:
+//!   `<B> CHECKSIGVERIFY <L> CHECKSIGVERIFY <S> CHECKSIG`,
+//!   `<expiry> CLTV DROP <B> CHECKSIGVERIFY <S> CHECKSIG`,
+//!   `<D> CSV DROP <B> CHECKSIGVERIFY <L> CHECKSIG` and
+//!   `<expiry> CLTV DROP <D> CSV DROP <B> CHECKSIG`.
+//!
+//! These leaves are not yet confirmed against a real Lendasat contract or a
+//! descriptor published by the server, so a mismatch is only reported. What
+//! blocks funding is a contract whose borrower key is not the wallet's.
+
+use crate::ark::signer::{self, SigningKey};
+use crate::lendasat::models::{CollateralAsset, Contract};
+use anyhow::{Result, anyhow, bail};
+use ark_core::ArkAddress;
+use ark_core::server::Info;
+use bitcoin::absolute::LockTime;
+use bitcoin::key::{Secp256k1, XOnlyPublicKey};
+use bitcoin::opcodes::all::{OP_CHECKSIG, OP_CHECKSIGVERIFY, OP_CLTV, OP_CSV, OP_DROP};
+use bitcoin::script::Builder;
+use bitcoin::taproot::TaprootBuilder;
+use bitcoin::{Address, Network, ScriptBuf, Sequence};
+use std::str::FromStr;
+use time::OffsetDateTime;
+use time::format_description::well_known::Rfc3339;
+
+/// BIP-341 "nothing up my sleeve" point, the internal key of script-only outputs.
+const UNSPENDABLE_KEY: &str = "50929b74c1a04954b78b4b6035e97a5e078a5a0f28ec96d547bfee9ace803ac0";
+
+/// Outcome of checking a contract's collateral address.
+#[derive(Debug, Clone)]
+pub struct CollateralCheck {
+    pub contract_id: String,
+    /// Whether the contract address matches the rebuilt one
+    pub verified: bool,
+    /// The address rebuilt from the contract terms, if they could be read
+    pub expected_address: Option<String>,
+    /// Why the check failed
+    pub reason: Option<String>,
+}
+
+/// Parameters of the wallet's Ark server the collateral tree depends on.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct ServerTerms {
+    pub network: Network,
+    pub signer_pk: XOnlyPublicKey,
+    pub exit_delay: Sequence,
+}
+
+impl ServerTerms {
+    pub(crate) fn from_info(server_info: &Info) -> Self {
+        Self {
+            network: server_info.network,
+            signer_pk: server_info.signer_pk.x_only_public_key().0,
+            exit_delay: server_info.unilateral_exit_delay,
+        }
+    }
+}
+
+/// The collateral address of `contract` to send funds to.
+///
+/// Fails unless the contract is locked to the wallet's Ark identity key. The
+/// address is also rebuilt from the contract terms, but a mismatch is only
+/// logged, see the module docs.
+pub(crate) fn funding_address(contract: &Contract, server_info: &Info) -> Result<String> {
+    let identity = signer::public_key(&SigningKey::Identity)?;
+    funding_address_for(contract, &ServerTerms::from_info(server_info), &identity)
+}
+
+fn funding_address_for(
+    contract: &Contract,
+    terms: &ServerTerms,
+    identity: &XOnlyPublicKey,
+) -> Result<String> {
+    check_borrower(contract, identity)?;
+
+    let contract_address = contract
+        .contract_address
+        .clone()
+        .ok_or_else(|| anyhow!("Contract {} has no collateral address yet", contract.id))?;
+
+    if let Err(e) = verify_terms(contract, terms, identity) {
+        tracing::warn!(
+            contract_id = %contract.id,
+            "Could not verify the collateral address against the contract terms: {e:#}"
+        );
+    }
+
+    Ok(contract_address)
+}
+
+fn verify_terms(
+    contract: &Contract,
+    terms: &ServerTerms,
+    identity: &XOnlyPublicKey,
+) -> Result<String> {
+    check_borrower(contract, identity)?;
+
+    let contract_address = contract
+        .contract_address
+        .as_deref()
+        .ok_or_else(|| anyhow!("Contract {} has no collateral address yet", contract.id))?;
+
+    let expected = expected_address(contract, terms)?;
+    if expected != contract_address {
+        bail!(
+            "Collateral address {} does not match the contract terms, expected {}",
+            contract_address,
+            expected
+        );
+    }
+
+    if let Some(collateral_script) = &contract.collateral_script {
+        let leaves = leaves(contract, terms)?;
+        let script_pubkey = output_script(&leaves)?;
+        let committed = std::iter::once(&script_pubkey)
+            .chain(&leaves)
+            .any(|script| {
+                script
+                    .to_hex_string()
+                    .eq_ignore_ascii_case(collateral_script)
+            });
+        if !committed {
+            bail!("Collateral script does not match the contract terms");
+        }
+    }
+
+    Ok(expected)
+}
+
+/// Fail unless the borrower key of `contract` is the wallet's `identity`.
+///
+/// A tree that matches the contract terms is worthless if the terms name a
+/// borrower key the wallet cannot sign with.
+pub(crate) fn check_borrower(contract: &Contract, identity: &XOnlyPublicKey) -> Result<()> {
+    let borrower = signer::parse_pubkey(&contract.borrower_pk)
+        .map_err(|e| anyhow!("Invalid borrower key: {}", e))?;
+    if borrower != *identity {
+        bail!(
+            "Contract {} is locked to borrower key {}, not to this wallet",
+            contract.id,
+            borrower
+        );
+    }
+    Ok(())
+}
+
+/// Check `contract` without failing, for display.
+pub(crate) fn check(contract: &Contract, server_info: &Info) -> CollateralCheck {
+    let terms = ServerTerms::from_info(server_info);
+    let result = signer::public_key(&SigningKey::Identity)
+        .and_then(|identity| verify_terms(contract, &terms, &identity));
+    let expected_address = match &result {
+        Ok(address) => Some(address.clone()),
+        Err(_) => expected_address(contract, &terms).ok(),
+    };
+
+    CollateralCheck {
+        contract_id: contract.id.clone(),
+        verified: result.is_ok(),
+        expected_address,
+        reason: result.err().map(|e| e.to_string()),
+    }
+}
+
+fn expected_address(contract: &Contract, terms: &ServerTerms) -> Result<String> {
+    let leaves = leaves(contract, terms)?;
+    let spend_info = spend_info(&leaves)?;
+    let output_key = spend_info.output_key();
+
+    let address = match contract.collateral_asset {
+        CollateralAsset::BitcoinBtc => Address::p2tr_tweaked(output_key, terms.network).to_string(),
+        CollateralAsset::ArkadeBtc => {
+            ArkAddress::new(terms.network, terms.signer_pk, output_key).encode()
+        }
+    };
+    Ok(address)
+}
+
+/// Tapscript leaves of the collateral output, in tree order.
+fn leaves(contract: &Contract, terms: &ServerTerms) -> Result<Vec<ScriptBuf>> {
+    let borrower = signer::parse_pubkey(&contract.borrower_pk)
+        .map_err(|e| anyhow!("Invalid borrower key: {}", e))?;
+    let lender = signer::parse_pubkey(&contract.lender_pk)
+        .map_err(|e| anyhow!("Invalid lender key: {}", e))?;
+    let expiry = expiry_lock_time(&contract.expiry)?;
+
+    let leaves = match contract.collateral_asset {
+        CollateralAsset::BitcoinBtc => vec![
+            multisig(&[borrower, lender]),
+            after(expiry, multisig(&[borrower])),
+        ],
+        CollateralAsset::ArkadeBtc => {
+            let server = terms.signer_pk;
+            let exit_delay = terms.exit_delay;
+            vec![
+                multisig(&[borrower, lender, server]),
+                after(expiry, multisig(&[borrower, server])),
+                delayed(exit_delay, multisig(&[borrower, lender])),
+                after(expiry, delayed(exit_delay, multisig(&[borrower]))),
+            ]
+        }
+    };
+    Ok(leaves)
+}
+
+fn spend_info(leaves: &[ScriptBuf]) -> Result<bitcoin::taproot::TaprootSpendInfo> {
+    // Two or four leaves, all at the same depth
+    let depth = leaves.len().next_power_of_two().trailing_zeros() as u8;
+    let internal_key = XOnlyPublicKey::from_str(UNSPENDABLE_KEY)?;
+
+    leaves
+        .iter()
+        .try_fold(TaprootBuilder::new(), |builder, leaf| {
+            builder.add_leaf(depth, leaf.clone())
+        })
+        .map_err(|e| anyhow!("Failed to build collateral tree: {}", e))?
+        .finalize(&Secp256k1::verification_only(), internal_key)
+        .map_err(|_| anyhow!("Failed to build collateral tree"))
+}
+
+fn output_script(leaves: &[ScriptBuf]) -> Result<ScriptBuf> {
+    Ok(ScriptBuf::new_p2tr_tweaked(
+        spend_info(leaves)?.output_key(),
+    ))
+}
+
+/// `<pk_1> CHECKSIGVERIFY ... <pk_n> CHECKSIG`
+fn multisig(keys: &[XOnlyPublicKey]) -> ScriptBuf {
+    let (last, rest) = keys.split_last().expect("at least one key");
+    rest.iter()
+        .fold(Builder::new(), |builder, key| {
+            builder.push_x_only_key(key).push_opcode(OP_CHECKSIGVERIFY)
+        })
+        .push_x_only_key(last)
+        .push_opcode(OP_CHECKSIG)
+        .into_script()
+}
+
+/// Prefix `script` with an absolute timelock.
+fn after(lock_time: LockTime, script: ScriptBuf) -> ScriptBuf {
+    let mut prefix = Builder::new()
+        .push_lock_time(lock_time)
+        .push_opcode(OP_CLTV)
+        .push_opcode(OP_DROP)
+        .into_script()
+        .into_bytes();
+    prefix.extend(script.into_bytes());
+    ScriptBuf::from_bytes(prefix)
+}
+
+/// Prefix `script` with a relative timelock.
+fn delayed(sequence: Sequence, script: ScriptBuf) -> ScriptBuf {
+    let mut prefix = Builder::new()
+        .push_sequence(sequence)
+        .push_opcode(OP_CSV)
+        .push_opcode(OP_DROP)
+        .into_script()
+        .into_bytes();
+    prefix.extend(script.into_bytes());
+    ScriptBuf::from_bytes(prefix)
+}
+
+fn expiry_lock_time(expiry: &str) -> Result<LockTime> {
+    let expiry = OffsetDateTime::parse(expiry, &Rfc3339)
+        .map_err(|e| anyhow!("Invalid contract expiry '{}': {}", expiry, e))?;
+    let timestamp = u32::try_from(expiry.unix_timestamp())
+        .map_err(|_| anyhow!("Contract expiry out of range"))?;
+    LockTime::from_time(timestamp).map_err(|e| anyhow!("Invalid contract expiry: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lendasat::models::fixtures;
+    use bitcoin::secp256k1::{Keypair, SecretKey};
+
+    fn key(byte: u8) -> XOnlyPublicKey {
+        let secret = SecretKey::from_slice(&[byte; 32]).unwrap();
+        Keypair::from_secret_key(&Secp256k1::new(), &secret)
+            .x_only_public_key()
+            .0
+    }
+
+    fn terms() -> ServerTerms {
+        ServerTerms {
+            network: Network::Signet,
+            signer_pk: key(3),
+            exit_delay: Sequence::from_height(144),
+        }
+    }
+
+    /// A contract between borrower key 1 and lender key 2 whose address was
+    /// built from its terms.
+    fn contract(asset: CollateralAsset) -> Contract {
+        let mut contract = fixtures::contract();
+        contract.collateral_asset = asset;
+        contract.borrower_pk = key(1).to_string();
+        contract.lender_pk = key(2).to_string();
+        contract.contract_address = Some(expected_address(&contract, &terms()).unwrap());
+        contract
+    }
+
+    #[test]
+    fn test_verify_accepts_matching_contract() {
+        for asset in [CollateralAsset::ArkadeBtc, CollateralAsset::BitcoinBtc] {
+            let contract = contract(asset);
+            let address = verify_terms(&contract, &terms(), &key(1)).unwrap();
+            assert_eq!(Some(address), contract.contract_address);
+        }
+    }
+
+    #[test]
+    fn test_funding_address_only_blocks_foreign_borrower_key() {
+        let mut contract = contract(CollateralAsset::ArkadeBtc);
+        let mut other = contract.clone();
+        other.lender_pk = key(4).to_string();
+        let unverified = expected_address(&other, &terms()).unwrap();
+        contract.contract_address = Some(unverified.clone());
+
+        // A tree we cannot rebuild is reported, not refused
+        let address = funding_address_for(&contract, &terms(), &key(1)).unwrap();
+        assert_eq!(address, unverified);
+
+        let err = funding_address_for(&contract, &terms(), &key(4)).unwrap_err();
+        assert!(err.to_string().contains("not to this wallet"));
+    }
+
+    #[test]
+    fn test_verify_rejects_foreign_borrower_key() {
+        // The address matches the terms, but the terms name someone else
+        let contract = contract(CollateralAsset::ArkadeBtc);
+        let err = verify_terms(&contract, &terms(), &key(4)).unwrap_err();
+        assert!(err.to_string().contains("not to this wallet"));
+    }
+
+    #[test]
+    fn test_verify_rejects_mismatched_address() {
+        let mut contract = contract(CollateralAsset::ArkadeBtc);
+        let mut other = contract.clone();
+        other.lender_pk = key(4).to_string();
+        contract.contract_address = Some(expected_address(&other, &terms()).unwrap());
+
+        let err = verify_terms(&contract, &terms(), &key(1)).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("does not match the contract terms")
+        );
+    }
+
+    #[test]
+    fn test_verify_rejects_other_server() {
+        let contract = contract(CollateralAsset::ArkadeBtc);
+        let other_server = ServerTerms {
+            signer_pk: key(4),
+            ..terms()
+        };
+        assert!(verify_terms(&contract, &other_server, &key(1)).is_err());
+
+        let other_delay = ServerTerms {
+            exit_delay: Sequence::from_height(512),
+            ..terms()
+        };
+        assert!(verify_terms(&contract, &other_delay, &key(1)).is_err());
+    }
+
+    #[test]
+    fn test_verify_checks_collateral_script() {
+        let mut contract = contract(CollateralAsset::BitcoinBtc);
+        let leaves = leaves(&contract, &terms()).unwrap();
+
+        contract.collateral_script = Some(leaves[1].to_hex_string());
+        assert!(verify_terms(&contract, &terms(), &key(1)).is_ok());
+
+        contract.collateral_script = Some(multisig(&[key(1)]).to_hex_string());
+        assert!(verify_terms(&contract, &terms(), &key(1)).is_err());
+    }
+
+    #[test]
+    fn test_bitcoin_leaves() {
+        let contract = contract(CollateralAsset::BitcoinBtc);
+        let leaves = leaves(&contract, &terms()).unwrap();
+        let (borrower, lender) = (key(1).serialize(), key(2).serialize());
+
+        // <B> CHECKSIGVERIFY <L> CHECKSIG
+        let mut cooperative = vec![0x20];
+        cooperative.extend(borrower);
+        cooperative.extend([0xad, 0x20]);
+        cooperative.extend(lender);
+        cooperative.push(0xac);
+        assert_eq!(leaves[0].as_bytes(), cooperative);
+
+        // <2026-01-31T00:00:00Z> CLTV DROP <B> CHECKSIG
+        let mut recovery = vec![0x04];
+        recovery.extend(1_769_817_600u32.to_le_bytes());
+        recovery.extend([0xb1, 0x75, 0x20]);
+        recovery.extend(borrower);
+        recovery.push(0xac);
+        assert_eq!(leaves[1].as_bytes(), recovery);
+    }
+
+    #[test]
+    fn test_arkade_leaves() {
+        let contract = contract(CollateralAsset::ArkadeBtc);
+        let leaves = leaves(&contract, &terms()).unwrap();
+        assert_eq!(leaves.len(), 4);
+        assert_eq!(leaves[0], multisig(&[key(1), key(2), key(3)]));
+        assert!(leaves[2].to_string().contains("OP_CSV"));
+        assert!(leaves[3].to_string().contains("OP_CLTV"));
+        assert!(leaves[3].to_string().contains("OP_CSV"));
+    }
+}
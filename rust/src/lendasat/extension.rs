@@ -0,0 +1,209 @@
+//! Quoting of loan extensions.
+//!
+//! An extension replaces a contract with a new one that keeps the collateral
+//! and the outstanding principal but runs longer, at the contract's
+//! `extension_interest_rate`. The server links both contracts through
+//! `extends_contract` and `extended_by_contract`.
+//!
+//! The server offers no extension quote, so quotes are computed locally:
+//!
+//! - Interest accrues simple and pro rata over a 365-day year on the
+//!   principal of the installments not paid yet (the loan amount if the
+//!   contract has no installments).
+//! - The extension is charged the origination fee rate of the original
+//!   contract on that principal. The rate is implied by the contract's
+//!   `origination_fee_sats` relative to the collateral backing the loan at
+//!   `initial_ltv`, which does not depend on the BTC price.
+//!
+//! The lender may still charge differently; the contract returned by the
+//! extension request is authoritative.
+
+use crate::lendasat::models::{Contract, InstallmentStatus};
+use anyhow::{Result, anyhow, bail};
+use time::format_description::well_known::Rfc3339;
+use time::{Duration, OffsetDateTime};
+
+/// Cost of extending a contract, quoted locally from its terms.
+///
+/// The `estimated_` amounts follow the assumptions in the module docs, not a
+/// quote of the lender.
+#[derive(Debug, Clone)]
+pub struct ExtensionQuote {
+    pub contract_id: String,
+    pub extension_days: i32,
+    /// Annual interest rate of the extension period
+    pub interest_rate: f64,
+    /// Principal carried over to the extended contract
+    pub principal: f64,
+    /// Interest for the extension period, assuming simple pro rata interest
+    pub estimated_interest: f64,
+    /// Origination fee rate implied by the original contract, as a fraction
+    /// of the principal
+    pub estimated_origination_fee_rate: f64,
+    /// Origination fee for the extension at that rate, in the loan asset
+    pub estimated_origination_fee: f64,
+    /// Cost of the extension itself: interest plus origination fee
+    pub estimated_cost: f64,
+    /// Everything due at the new expiry: the outstanding balance plus the
+    /// extension cost
+    pub estimated_total_due: f64,
+    pub current_expiry: String,
+    pub new_expiry: String,
+}
+
+/// Quote extending `contract` by `extension_days`.
+///
+/// See the module documentation for how interest and fees are estimated.
+pub fn quote(contract: &Contract, extension_days: i32) -> Result<ExtensionQuote> {
+    if !contract.can_extend {
+        bail!("Contract {} cannot be extended", contract.id);
+    }
+    if extension_days <= 0 || extension_days > contract.extension_max_duration_days {
+        bail!(
+            "Extension must be between 1 and {} days",
+            contract.extension_max_duration_days
+        );
+    }
+    let interest_rate = contract
+        .extension_interest_rate
+        .ok_or_else(|| anyhow!("Contract {} has no extension rate", contract.id))?;
+
+    let principal = outstanding_principal(contract);
+    let estimated_interest = principal * interest_rate * extension_days as f64 / 365.0;
+    let estimated_origination_fee_rate = origination_fee_rate(contract);
+    let estimated_origination_fee = principal * estimated_origination_fee_rate;
+    let estimated_cost = estimated_interest + estimated_origination_fee;
+
+    let current_expiry = OffsetDateTime::parse(&contract.expiry, &Rfc3339)
+        .map_err(|e| anyhow!("Invalid contract expiry '{}': {}", contract.expiry, e))?;
+    let new_expiry = (current_expiry + Duration::days(extension_days as i64))
+        .format(&Rfc3339)
+        .map_err(|e| anyhow!("Failed to format expiry: {}", e))?;
+
+    Ok(ExtensionQuote {
+        contract_id: contract.id.clone(),
+        extension_days,
+        interest_rate,
+        principal,
+        estimated_interest,
+        estimated_origination_fee_rate,
+        estimated_origination_fee,
+        estimated_cost,
+        estimated_total_due: contract.balance_outstanding + estimated_cost,
+        current_expiry: contract.expiry.clone(),
+        new_expiry,
+    })
+}
+
+fn outstanding_principal(contract: &Contract) -> f64 {
+    if contract.installments.is_empty() {
+        return contract.loan_amount;
+    }
+
+    contract
+        .installments
+        .iter()
+        .filter(|i| {
+            matches!(
+                i.status,
+                InstallmentStatus::Pending | InstallmentStatus::Late
+            )
+        })
+        .map(|i| i.principal)
+        .sum()
+}
+
+/// Origination fee of `contract` as a fraction of its loan amount.
+///
+/// At `initial_ltv` the loan is worth `initial_ltv * initial_collateral_sats`,
+/// so the fee relative to that is the fee relative to the loan amount.
+fn origination_fee_rate(contract: &Contract) -> f64 {
+    let loan_value_sats = contract.initial_ltv * contract.initial_collateral_sats as f64;
+    if loan_value_sats <= 0.0 || contract.origination_fee_sats <= 0 {
+        return 0.0;
+    }
+
+    contract.origination_fee_sats as f64 / loan_value_sats
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lendasat::models::fixtures;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-9,
+            "expected {expected}, got {actual}"
+        );
+    }
+
+    #[test]
+    fn quotes_pro_rata_interest() {
+        let contract = fixtures::contract();
+
+        let quote = quote(&contract, 73).unwrap();
+
+        assert_eq!(quote.principal, 1000.0);
+        assert_close(quote.estimated_interest, 1000.0 * 0.12 * 73.0 / 365.0);
+        assert_eq!(quote.estimated_origination_fee, 0.0);
+        assert_close(quote.estimated_cost, 24.0);
+        assert_close(quote.estimated_total_due, 1010.0 + 24.0);
+        assert_eq!(quote.new_expiry, "2026-04-14T00:00:00Z");
+    }
+
+    #[test]
+    fn charges_original_origination_fee_rate() {
+        let mut contract = fixtures::contract();
+        // 1% of a loan backed by 2_000_000 sats at 50% LTV
+        contract.origination_fee_sats = 10_000;
+
+        let quote = quote(&contract, 73).unwrap();
+
+        assert_close(quote.estimated_origination_fee_rate, 0.01);
+        assert_close(quote.estimated_origination_fee, 10.0);
+        assert_close(quote.estimated_cost, 24.0 + 10.0);
+        assert_close(quote.estimated_total_due, 1010.0 + 34.0);
+    }
+
+    #[test]
+    fn ignores_fee_without_initial_terms() {
+        let mut contract = fixtures::contract();
+        contract.origination_fee_sats = 10_000;
+        contract.initial_ltv = 0.0;
+
+        assert_eq!(origination_fee_rate(&contract), 0.0);
+    }
+
+    #[test]
+    fn uses_principal_of_unpaid_installments() {
+        let mut contract = fixtures::contract();
+        let mut paid = contract.installments[0].clone();
+        paid.id = "installment-0".to_string();
+        paid.principal = 400.0;
+        paid.status = InstallmentStatus::Confirmed;
+        contract.installments[0].principal = 600.0;
+        contract.installments.push(paid);
+
+        assert_eq!(quote(&contract, 30).unwrap().principal, 600.0);
+
+        contract.installments.clear();
+        assert_eq!(quote(&contract, 30).unwrap().principal, 1000.0);
+    }
+
+    #[test]
+    fn rejects_invalid_extensions() {
+        let contract = fixtures::contract();
+        assert!(quote(&contract, 0).is_err());
+        assert!(quote(&contract, 91).is_err());
+        assert!(quote(&contract, 90).is_ok());
+
+        let mut contract = fixtures::contract();
+        contract.can_extend = false;
+        assert!(quote(&contract, 30).is_err());
+
+        let mut contract = fixtures::contract();
+        contract.extension_interest_rate = None;
+        assert!(quote(&contract, 30).is_err());
+    }
+}
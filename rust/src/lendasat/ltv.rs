@@ -0,0 +1,241 @@
+//! Local loan-to-value assessment of active contracts.
+//!
+//! The server only reports a margin call once it changed the contract status.
+//! Combining the contract terms with a live BTC price lets the wallet warn
+//! earlier and show how far the price may fall before each threshold is hit.
+//! LTVs and thresholds are fractions, as in the contract (0.8 is 80%).
+
+use crate::lendasat::models::Contract;
+use crate::lendasat::watch::PollRound;
+use serde::{Deserialize, Serialize};
+
+const SATS_PER_BTC: f64 = 100_000_000.0;
+
+/// Threshold band the current LTV falls in, from least to most severe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum LtvLevel {
+    Healthy,
+    MarginCall1,
+    MarginCall2,
+    Liquidation,
+}
+
+/// Price at which a contract reaches one of its LTV thresholds.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LtvThreshold {
+    pub level: LtvLevel,
+    pub ltv: f64,
+    /// BTC price in the loan currency at which the threshold is reached
+    pub trigger_price: f64,
+    /// How far the price may fall before the threshold is reached, in percent
+    /// of the current price; negative once it is crossed
+    pub price_distance_percent: f64,
+}
+
+/// Current LTV of a contract at a given BTC price.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LtvStatus {
+    pub contract_id: String,
+    /// Loan currency the price and balance are expressed in
+    pub currency: String,
+    pub btc_price: f64,
+    pub collateral_sats: i64,
+    pub balance_outstanding: f64,
+    pub ltv: f64,
+    pub level: LtvLevel,
+    pub thresholds: Vec<LtvThreshold>,
+}
+
+/// Emitted when a contract's LTV moves into a different band.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LtvAlert {
+    pub previous_level: Option<LtvLevel>,
+    pub status: LtvStatus,
+}
+
+/// Item of the LTV monitor stream.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum LtvMonitorEvent {
+    /// A contract moved into a different band
+    Alert(LtvAlert),
+    /// A check of all active contracts finished
+    Checked(PollRound),
+}
+
+/// The alert for `status` given the band the contract was in before.
+///
+/// Contracts seen for the first time only alert when they are already past a
+/// threshold.
+pub fn alert(previous_level: Option<LtvLevel>, status: LtvStatus) -> Option<LtvAlert> {
+    let changed = match previous_level {
+        Some(level) => level != status.level,
+        None => status.level != LtvLevel::Healthy,
+    };
+
+    changed.then_some(LtvAlert {
+        previous_level,
+        status,
+    })
+}
+
+/// Assess `contract` at `btc_price`, quoted in the contract's loan currency.
+pub fn assess(contract: &Contract, btc_price: f64) -> LtvStatus {
+    let collateral_btc = contract.collateral_sats.max(0) as f64 / SATS_PER_BTC;
+    let ltv = ltv(
+        contract.balance_outstanding,
+        contract.collateral_sats,
+        btc_price,
+    );
+
+    let thresholds: Vec<LtvThreshold> = [
+        (LtvLevel::MarginCall1, contract.ltv_threshold_margin_call_1),
+        (LtvLevel::MarginCall2, contract.ltv_threshold_margin_call_2),
+        (LtvLevel::Liquidation, contract.ltv_threshold_liquidation),
+    ]
+    .into_iter()
+    .filter(|(_, threshold)| *threshold > 0.0)
+    .map(|(level, threshold)| {
+        let trigger_price = if collateral_btc > 0.0 {
+            contract.balance_outstanding / (collateral_btc * threshold)
+        } else {
+            f64::INFINITY
+        };
+        LtvThreshold {
+            level,
+            ltv: threshold,
+            trigger_price,
+            price_distance_percent: (btc_price - trigger_price) / btc_price * 100.0,
+        }
+    })
+    .collect();
+
+    let level = thresholds
+        .iter()
+        .filter(|t| ltv >= t.ltv)
+        .map(|t| t.level)
+        .max()
+        .unwrap_or(LtvLevel::Healthy);
+
+    LtvStatus {
+        contract_id: contract.id.clone(),
+        currency: contract.loan_asset.currency().to_string(),
+        btc_price,
+        collateral_sats: contract.collateral_sats,
+        balance_outstanding: contract.balance_outstanding,
+        ltv,
+        level,
+        thresholds,
+    }
+}
+
+/// LTV of `balance` against `collateral_sats` at `btc_price`.
+pub fn ltv(balance: f64, collateral_sats: i64, btc_price: f64) -> f64 {
+    let collateral_value = collateral_sats.max(0) as f64 / SATS_PER_BTC * btc_price;
+    if collateral_value > 0.0 {
+        balance / collateral_value
+    } else {
+        f64::INFINITY
+    }
+}
+
+/// Sats of collateral `balance` needs to be at `target_ltv` at `btc_price`.
+pub fn collateral_for_ltv(balance: f64, btc_price: f64, target_ltv: f64) -> u64 {
+    (balance / (target_ltv * btc_price) * SATS_PER_BTC)
+        .ceil()
+        .max(0.0) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lendasat::models::fixtures;
+
+    #[test]
+    fn computes_ltv() {
+        // 1000 against 0.02 BTC at 100k is 50%
+        assert_eq!(ltv(1_000.0, 2_000_000, 100_000.0), 0.5);
+        assert_eq!(ltv(0.0, 2_000_000, 100_000.0), 0.0);
+        assert_eq!(ltv(1_000.0, 0, 100_000.0), f64::INFINITY);
+        assert_eq!(ltv(1_000.0, -5, 100_000.0), f64::INFINITY);
+    }
+
+    #[test]
+    fn computes_collateral_for_ltv() {
+        assert_eq!(collateral_for_ltv(1_000.0, 100_000.0, 0.5), 2_000_000);
+        // Rounded up so the target is never missed
+        assert_eq!(collateral_for_ltv(1_000.0, 30_000.0, 0.5), 6_666_667);
+        assert_eq!(collateral_for_ltv(0.0, 100_000.0, 0.5), 0);
+
+        let sats = collateral_for_ltv(1_234.5, 87_654.3, 0.65);
+        assert!(ltv(1_234.5, sats as i64, 87_654.3) <= 0.65);
+    }
+
+    #[test]
+    fn assesses_healthy_contract() {
+        let contract = fixtures::contract();
+
+        let status = assess(&contract, 100_000.0);
+
+        // 1010 outstanding against 0.02 BTC
+        assert!((status.ltv - 0.505).abs() < 1e-9);
+        assert_eq!(status.level, LtvLevel::Healthy);
+        assert_eq!(status.currency, "USD");
+        let levels: Vec<_> = status.thresholds.iter().map(|t| t.level).collect();
+        assert_eq!(
+            levels,
+            vec![
+                LtvLevel::MarginCall1,
+                LtvLevel::MarginCall2,
+                LtvLevel::Liquidation
+            ]
+        );
+        let margin_call = &status.thresholds[0];
+        // 1010 / (0.02 * 0.7)
+        assert!((margin_call.trigger_price - 72_142.857_142_857).abs() < 1e-6);
+        assert!((margin_call.price_distance_percent - 27.857_142_857).abs() < 1e-6);
+    }
+
+    #[test]
+    fn assesses_level_past_thresholds() {
+        let contract = fixtures::contract();
+
+        assert_eq!(assess(&contract, 70_000.0).level, LtvLevel::MarginCall1);
+        assert_eq!(assess(&contract, 60_000.0).level, LtvLevel::MarginCall2);
+        let status = assess(&contract, 50_000.0);
+        assert_eq!(status.level, LtvLevel::Liquidation);
+        assert!(
+            status
+                .thresholds
+                .iter()
+                .all(|t| t.price_distance_percent < 0.0)
+        );
+    }
+
+    #[test]
+    fn skips_unset_thresholds() {
+        let mut contract = fixtures::contract();
+        contract.ltv_threshold_margin_call_2 = 0.0;
+
+        let status = assess(&contract, 60_000.0);
+
+        assert_eq!(status.thresholds.len(), 2);
+        assert_eq!(status.level, LtvLevel::MarginCall1);
+    }
+
+    #[test]
+    fn alerts_on_band_changes() {
+        let contract = fixtures::contract();
+        let healthy = assess(&contract, 100_000.0);
+        let margin_call = assess(&contract, 70_000.0);
+
+        assert!(alert(None, healthy.clone()).is_none());
+        assert!(alert(Some(LtvLevel::Healthy), healthy.clone()).is_none());
+
+        let first = alert(None, margin_call.clone()).unwrap();
+        assert_eq!(first.previous_level, None);
+        let worse = alert(Some(LtvLevel::Healthy), margin_call).unwrap();
+        assert_eq!(worse.previous_level, Some(LtvLevel::Healthy));
+        let recovered = alert(Some(LtvLevel::MarginCall1), healthy).unwrap();
+        assert_eq!(recovered.status.level, LtvLevel::Healthy);
+    }
+}
@@ -20,7 +20,13 @@
 //! 4. Verify signature and receive JWT token
 
 pub mod auth;
+pub mod collateral;
+pub mod extension;
+pub mod ltv;
 pub mod models;
+pub mod policy;
+pub mod repayment;
 pub mod storage;
+pub mod watch;
 
 pub use models::*;
@@ -36,6 +36,28 @@ impl LoanAsset {
         !self.is_fiat()
     }
 
+    /// Currency the loan is denominated in
+    pub fn currency(&self) -> &'static str {
+        match self {
+            Self::Eur => "EUR",
+            Self::Chf => "CHF",
+            Self::Mxn => "MXN",
+            _ => "USD",
+        }
+    }
+
+    /// LendaSwap token and chain ids used to pay this asset, if it can be
+    /// bought with a BTC to EVM swap
+    pub fn swap_token(&self) -> Option<(&'static str, &'static str)> {
+        match self {
+            Self::UsdcPol => Some(("usdc_pol", "polygon")),
+            Self::UsdtPol => Some(("usdt0_pol", "polygon")),
+            Self::UsdcEth => Some(("usdc_eth", "ethereum")),
+            Self::UsdtEth => Some(("usdt_eth", "ethereum")),
+            _ => None,
+        }
+    }
+
     pub fn display_name(&self) -> &'static str {
         match self {
             Self::UsdcPol => "USDC on Polygon",
@@ -114,6 +136,11 @@ impl ContractStatus {
         )
     }
 
+    /// Check if the server has seen the collateral deposit
+    pub fn is_collateral_seen(&self) -> bool {
+        matches!(self, Self::CollateralSeen) || self.is_active()
+    }
+
     /// Check if the contract allows claiming collateral
     pub fn can_claim(&self) -> bool {
         matches!(self, Self::RepaymentConfirmed)
@@ -355,6 +382,16 @@ impl Contract {
             .find(|i| i.status == InstallmentStatus::Pending || i.status == InstallmentStatus::Late)
     }
 
+    /// Sats the borrower has to deposit: the collateral plus the origination fee
+    pub fn required_collateral_sats(&self) -> u64 {
+        let collateral = if self.collateral_sats > 0 {
+            self.collateral_sats
+        } else {
+            self.initial_collateral_sats
+        };
+        (collateral + self.origination_fee_sats).max(0) as u64
+    }
+
     /// Check if all installments are paid
     pub fn is_fully_repaid(&self) -> bool {
         self.installments.iter().all(|i| {
@@ -392,6 +429,24 @@ pub struct CreateContractRequest {
     pub client_contract_id: Option<String>,
 }
 
+/// Request body for extending a contract
+///
+/// Not covered by any Lendasat API description available to this repo, so a
+/// server that does not know the route is reported as unsupported.
+#[derive(Debug, Clone, Serialize)]
+pub struct ExtendContractRequest {
+    pub new_duration: i32, // days added to the contract
+}
+
+/// Request body for reporting additional collateral
+///
+/// Like [`ExtendContractRequest`], not backed by a published API description.
+#[derive(Debug, Clone, Serialize)]
+pub struct CollateralTopUpRequest {
+    pub txid: String,
+    pub amount_sats: u64,
+}
+
 /// Serializes Option<f64> as null when None (instead of skipping the field)
 fn serialize_option_f64_as_null<S>(value: &Option<f64>, serializer: S) -> Result<S::Ok, S::Error>
 where
@@ -581,3 +636,70 @@ pub struct ContractFilters {
     pub sort_by: Option<String>,
     pub sort_order: Option<String>,
 }
+
+#[cfg(test)]
+pub(crate) mod fixtures {
+    use super::*;
+
+    /// An active Arkade-collateralised stablecoin loan of 1000 USDC with
+    /// one pending installment.
+    pub(crate) fn contract() -> Contract {
+        Contract {
+            id: "contract-1".to_string(),
+            status: ContractStatus::PrincipalGiven,
+            lender: LenderStats {
+                id: "lender-1".to_string(),
+                name: "Lender".to_string(),
+                joined_at: "2025-01-01T00:00:00Z".to_string(),
+                successful_contracts: 10,
+                vetted: true,
+                timezone: None,
+            },
+            lender_pk: String::new(),
+            borrower_pk: String::new(),
+            borrower_btc_address: String::new(),
+            borrower_derivation_path: None,
+            borrower_loan_address: None,
+            loan_asset: LoanAsset::UsdcPol,
+            collateral_asset: CollateralAsset::ArkadeBtc,
+            loan_amount: 1000.0,
+            interest: 10.0,
+            interest_rate: 0.12,
+            duration_days: 30,
+            expiry: "2026-01-31T00:00:00Z".to_string(),
+            collateral_sats: 2_000_000,
+            initial_collateral_sats: 2_000_000,
+            deposited_sats: 2_000_000,
+            initial_ltv: 0.5,
+            liquidation_price: 55_555.0,
+            ltv_threshold_margin_call_1: 0.7,
+            ltv_threshold_margin_call_2: 0.8,
+            ltv_threshold_liquidation: 0.9,
+            balance_outstanding: 1010.0,
+            contract_address: None,
+            collateral_script: None,
+            loan_repayment_address: Some("0x0000000000000000000000000000000000000001".to_string()),
+            btc_loan_repayment_address: None,
+            origination_fee_sats: 0,
+            installments: vec![Installment {
+                id: "installment-1".to_string(),
+                due_date: "2026-01-31T00:00:00Z".to_string(),
+                principal: 1000.0,
+                interest: 10.0,
+                status: InstallmentStatus::Pending,
+                paid_date: None,
+                payment_id: None,
+            }],
+            transactions: Vec::new(),
+            can_extend: true,
+            extension_interest_rate: Some(0.12),
+            extension_max_duration_days: 90,
+            extends_contract: None,
+            extended_by_contract: None,
+            client_contract_id: None,
+            requires_ark_settlement: None,
+            created_at: "2026-01-01T00:00:00Z".to_string(),
+            updated_at: "2026-01-01T00:00:00Z".to_string(),
+        }
+    }
+}
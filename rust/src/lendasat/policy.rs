@@ -0,0 +1,663 @@
+//! Local checks on server-built claim and recovery transactions.
+//!
+//! Lendasat builds the transactions that move collateral out of a contract.
+//! Before the wallet signs one, it must spend only the contract's collateral
+//! output, return the collateral to the wallet and pay a fee close to the fee
+//! rate that was requested, so a malicious or buggy server cannot redirect the
+//! collateral or burn it in fees.
+//!
+//! Only scripts derived from the wallet keys and the payout address the wallet
+//! itself sent when creating the contract count as the wallet's. Third parties
+//! may receive at most the contract's origination fee.
+//!
+//! On-chain spends that passed the checks are remembered, so finalizing and
+//! broadcasting refuse transactions the wallet did not approve.
+
+use crate::ark::psbt_analysis::{self, OutputOwner, PsbtSummary, PsbtWarning};
+use crate::ark::signer;
+use crate::lendasat::models::Contract;
+use anyhow::{Result, anyhow, bail};
+use bitcoin::psbt::Psbt;
+use bitcoin::{Network, ScriptBuf, Txid, XOnlyPublicKey};
+use parking_lot::Mutex;
+use std::collections::{HashMap, HashSet};
+use std::sync::LazyLock;
+
+/// Limits applied to claim and recovery PSBTs before signing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PsbtPolicy {
+    /// How far the fee rate may exceed the requested one, in percent. Covers
+    /// the uncertainty of estimating the size of the signed transaction.
+    pub max_fee_rate_overshoot_percent: u32,
+}
+
+impl Default for PsbtPolicy {
+    fn default() -> Self {
+        Self {
+            max_fee_rate_overshoot_percent: 25,
+        }
+    }
+}
+
+static POLICY: LazyLock<Mutex<PsbtPolicy>> = LazyLock::new(Default::default);
+
+/// Transactions that passed `check_collateral_spend`, with their contract.
+static APPROVED: LazyLock<Mutex<HashMap<Txid, String>>> = LazyLock::new(Default::default);
+
+pub fn configure(policy: PsbtPolicy) {
+    *POLICY.lock() = policy;
+}
+
+pub fn policy() -> PsbtPolicy {
+    POLICY.lock().clone()
+}
+
+/// What the wallet may treat as its own when checking a spend.
+pub(crate) struct WalletView {
+    pub network: Network,
+    pub scripts: HashSet<ScriptBuf>,
+    pub is_wallet_key: fn(&XOnlyPublicKey) -> bool,
+}
+
+impl WalletView {
+    /// The loaded wallet, plus the payout addresses stored for the contract.
+    pub(crate) fn load(payout_addresses: &[String]) -> Result<Self> {
+        let (network, mut scripts) = psbt_analysis::wallet_scripts()?;
+        for address in payout_addresses {
+            scripts.insert(psbt_analysis::address_script(address, network)?);
+        }
+
+        Ok(Self {
+            network,
+            scripts,
+            is_wallet_key: signer::is_wallet_key,
+        })
+    }
+}
+
+/// Check an on-chain claim or recovery PSBT of `contract` built for
+/// `fee_rate` sat/vB.
+///
+/// Returns the analysis of the PSBT if it may be signed.
+pub(crate) fn check_collateral_spend(
+    contract: &Contract,
+    psbt: &Psbt,
+    fee_rate: u32,
+    wallet: &WalletView,
+) -> Result<PsbtSummary> {
+    let policy = policy();
+
+    let contract_script = contract_script(contract, wallet.network)?;
+    check_inputs(contract, psbt, &contract_script)?;
+    let summary = check_outputs(contract, psbt, wallet)?;
+
+    let actual_fee_rate = summary
+        .fee_rate_sat_vb
+        .ok_or_else(|| anyhow!("Refusing to sign: fee cannot be determined"))?;
+    let max_fee_rate =
+        fee_rate as f64 * (100 + policy.max_fee_rate_overshoot_percent) as f64 / 100.0;
+    if actual_fee_rate > max_fee_rate {
+        bail!(
+            "Refusing to sign: fee rate {:.1} sat/vB exceeds the requested {} sat/vB",
+            actual_fee_rate,
+            fee_rate
+        );
+    }
+
+    Ok(summary)
+}
+
+/// Check the PSBTs of an offchain Ark claim of `contract`.
+///
+/// The checkpoint transactions must spend the collateral VTXOs and the Ark
+/// transaction must spend exactly their outputs and return them to the wallet.
+pub(crate) fn check_ark_claim(
+    contract: &Contract,
+    ark_psbt: &Psbt,
+    checkpoint_psbts: &[Psbt],
+    wallet: &WalletView,
+) -> Result<PsbtSummary> {
+    if checkpoint_psbts.is_empty() {
+        bail!("Refusing to sign: Ark claim has no checkpoint transactions");
+    }
+
+    let contract_script = contract_script(contract, wallet.network)?;
+    let mut checkpoint_outputs = HashMap::new();
+    for checkpoint in checkpoint_psbts {
+        check_inputs(contract, checkpoint, &contract_script)?;
+
+        let txid = checkpoint.unsigned_tx.compute_txid();
+        for (vout, output) in checkpoint.unsigned_tx.output.iter().enumerate() {
+            if output.script_pubkey != ScriptBuf::new_p2a() {
+                checkpoint_outputs.insert(bitcoin::OutPoint::new(txid, vout as u32), output);
+            }
+        }
+    }
+
+    for (idx, (txin, input)) in ark_psbt
+        .unsigned_tx
+        .input
+        .iter()
+        .zip(&ark_psbt.inputs)
+        .enumerate()
+    {
+        let Some(checkpoint_output) = checkpoint_outputs.remove(&txin.previous_output) else {
+            bail!(
+                "Refusing to sign: input {} does not spend a checkpoint of the contract",
+                idx
+            );
+        };
+        let spent = psbt_analysis::spent_output(input, txin.previous_output.vout);
+        if spent != Some(checkpoint_output) {
+            bail!(
+                "Refusing to sign: input {} misstates the checkpoint output it spends",
+                idx
+            );
+        }
+    }
+    if let Some(outpoint) = checkpoint_outputs.keys().next() {
+        bail!(
+            "Refusing to sign: checkpoint output {} is not spent by the Ark transaction",
+            outpoint
+        );
+    }
+
+    check_outputs(contract, ark_psbt, wallet)
+}
+
+/// Check the PSBTs settling the recoverable collateral VTXOs of `contract` in
+/// an Ark batch.
+///
+/// The intent must only spend the collateral and register outputs of the
+/// wallet. Forfeits pay the VTXOs to the server by design and only count once
+/// the batch creates the intent's outputs, so they merely have to spend
+/// nothing but the collateral besides their connector.
+pub(crate) fn check_ark_settlement(
+    contract: &Contract,
+    intent_psbt: &Psbt,
+    forfeit_psbts: &[Psbt],
+    wallet: &WalletView,
+) -> Result<PsbtSummary> {
+    let contract_script = contract_script(contract, wallet.network)?;
+    check_inputs(contract, intent_psbt, &contract_script)?;
+
+    for (idx, forfeit) in forfeit_psbts.iter().enumerate() {
+        // The last input is the connector output of the batch, every other
+        // input is a VTXO being forfeited
+        let vtxo_inputs = forfeit.unsigned_tx.input.len().saturating_sub(1);
+        if vtxo_inputs == 0 {
+            bail!(
+                "Refusing to sign: forfeit {} does not spend the contract's collateral",
+                idx
+            );
+        }
+
+        let inputs = forfeit.unsigned_tx.input.iter().zip(&forfeit.inputs);
+        for (input_idx, (txin, input)) in inputs.take(vtxo_inputs).enumerate() {
+            let spends_collateral = psbt_analysis::spent_output(input, txin.previous_output.vout)
+                .is_some_and(|spent| spent.script_pubkey == contract_script);
+            if !spends_collateral {
+                bail!(
+                    "Refusing to sign: input {} of forfeit {} does not spend the contract's collateral",
+                    input_idx,
+                    idx
+                );
+            }
+        }
+    }
+
+    check_outputs(contract, intent_psbt, wallet)
+}
+
+/// Remember that `txid` passed the policy for `contract_id`.
+pub(crate) fn approve(contract_id: &str, txid: Txid) {
+    APPROVED.lock().insert(txid, contract_id.to_string());
+}
+
+/// Fail unless `txid` was approved, for `contract_id` if given.
+pub(crate) fn ensure_approved(txid: Txid, contract_id: Option<&str>) -> Result<()> {
+    match APPROVED.lock().get(&txid) {
+        Some(approved) if contract_id.is_none_or(|id| id == approved) => Ok(()),
+        Some(approved) => bail!(
+            "Refusing: transaction {} was approved for contract {}",
+            txid,
+            approved
+        ),
+        None => bail!(
+            "Refusing: transaction {} was not signed through the collateral policy",
+            txid
+        ),
+    }
+}
+
+/// Sats a collateral spend may pay to third parties: the origination fee,
+/// which is taken from the collateral.
+fn external_allowance(contract: &Contract) -> u64 {
+    contract.origination_fee_sats.max(0) as u64
+}
+
+fn contract_script(contract: &Contract, network: Network) -> Result<ScriptBuf> {
+    let contract_address = contract
+        .contract_address
+        .as_deref()
+        .ok_or_else(|| anyhow!("Contract {} has no collateral address", contract.id))?;
+    psbt_analysis::address_script(contract_address, network)
+}
+
+/// Every input must spend the contract's collateral output.
+fn check_inputs(contract: &Contract, psbt: &Psbt, contract_script: &ScriptBuf) -> Result<()> {
+    for (idx, (txin, input)) in psbt.unsigned_tx.input.iter().zip(&psbt.inputs).enumerate() {
+        let spent = psbt_analysis::spent_output(input, txin.previous_output.vout)
+            .ok_or_else(|| anyhow!("Refusing to sign: input {} has no spent output", idx))?;
+        if spent.script_pubkey != *contract_script {
+            bail!(
+                "Refusing to sign: input {} does not spend the contract's collateral",
+                idx
+            );
+        }
+
+        if let Some(collateral_script) = &contract.collateral_script {
+            let matches = |script: &bitcoin::Script| {
+                script
+                    .to_hex_string()
+                    .eq_ignore_ascii_case(collateral_script)
+            };
+            let committed = matches(&spent.script_pubkey)
+                || input.witness_script.as_deref().is_some_and(matches)
+                || input
+                    .tap_scripts
+                    .values()
+                    .any(|(script, _)| matches(script));
+            if !committed {
+                bail!(
+                    "Refusing to sign: input {} does not match the contract's collateral script",
+                    idx
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// The collateral must return to the wallet; third parties get at most the
+/// allowance.
+fn check_outputs(contract: &Contract, psbt: &Psbt, wallet: &WalletView) -> Result<PsbtSummary> {
+    let summary =
+        psbt_analysis::summarize(psbt, wallet.network, &wallet.scripts, &wallet.is_wallet_key);
+
+    if let Some(warning) = summary.warnings.iter().find(|warning| {
+        matches!(
+            warning,
+            PsbtWarning::MissingInputAmount { .. }
+                | PsbtWarning::OutputsExceedInputs { .. }
+                | PsbtWarning::UnusualSighash { .. }
+        )
+    }) {
+        bail!("Refusing to sign: {:?}", warning);
+    }
+
+    if summary.to_wallet_sats == 0 {
+        bail!("Refusing to sign: no output returns the collateral to the wallet");
+    }
+
+    let allowance = external_allowance(contract);
+    if summary.to_external_sats > allowance {
+        let recipients = summary
+            .outputs
+            .iter()
+            .filter(|output| output.owner == OutputOwner::External)
+            .map(|output| output.address.as_deref().unwrap_or(&output.script_pubkey))
+            .collect::<Vec<_>>()
+            .join(", ");
+        bail!(
+            "Refusing to sign: {} sats go to {}, at most {} sats may leave the wallet",
+            summary.to_external_sats,
+            recipients,
+            allowance
+        );
+    }
+
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lendasat::models::fixtures;
+    use bitcoin::absolute::LockTime;
+    use bitcoin::key::{Keypair, Secp256k1};
+    use bitcoin::psbt::PsbtSighashType;
+    use bitcoin::secp256k1::SecretKey;
+    use bitcoin::transaction::Version;
+    use bitcoin::{Address, Amount, OutPoint, Sequence, Transaction, TxIn, TxOut, Witness};
+
+    const COLLATERAL: u8 = 9;
+    const WALLET: u8 = 1;
+    const STRANGER: u8 = 7;
+
+    fn p2tr(secret: u8) -> ScriptBuf {
+        let secp = Secp256k1::new();
+        let sk = SecretKey::from_slice(&[secret; 32]).unwrap();
+        let pk = Keypair::from_secret_key(&secp, &sk).x_only_public_key().0;
+        ScriptBuf::new_p2tr(&secp, pk, None)
+    }
+
+    fn address(secret: u8) -> String {
+        Address::from_script(&p2tr(secret), Network::Regtest)
+            .unwrap()
+            .to_string()
+    }
+
+    fn contract() -> Contract {
+        let mut contract = fixtures::contract();
+        contract.contract_address = Some(address(COLLATERAL));
+        contract
+    }
+
+    fn wallet() -> WalletView {
+        WalletView {
+            network: Network::Regtest,
+            scripts: HashSet::from([p2tr(WALLET)]),
+            is_wallet_key: |_| false,
+        }
+    }
+
+    /// A PSBT spending `inputs` (outpoint and spent output) to `outputs`.
+    fn psbt(inputs: Vec<(OutPoint, TxOut)>, outputs: Vec<(ScriptBuf, u64)>) -> Psbt {
+        let tx = Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: inputs
+                .iter()
+                .map(|(previous_output, _)| TxIn {
+                    previous_output: *previous_output,
+                    script_sig: ScriptBuf::new(),
+                    sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+                    witness: Witness::new(),
+                })
+                .collect(),
+            output: outputs
+                .into_iter()
+                .map(|(script_pubkey, sats)| TxOut {
+                    value: Amount::from_sat(sats),
+                    script_pubkey,
+                })
+                .collect(),
+        };
+        let mut psbt = Psbt::from_unsigned_tx(tx).unwrap();
+        for (input, (_, spent)) in psbt.inputs.iter_mut().zip(inputs) {
+            input.witness_utxo = Some(spent);
+        }
+        psbt
+    }
+
+    fn output(secret: u8, sats: u64) -> TxOut {
+        TxOut {
+            value: Amount::from_sat(sats),
+            script_pubkey: p2tr(secret),
+        }
+    }
+
+    /// Spends 100k sats of collateral to `outputs`.
+    fn claim(outputs: Vec<(ScriptBuf, u64)>) -> Psbt {
+        psbt(
+            vec![(OutPoint::null(), output(COLLATERAL, 100_000))],
+            outputs,
+        )
+    }
+
+    fn refusal(result: Result<PsbtSummary>) -> String {
+        result.unwrap_err().to_string()
+    }
+
+    #[test]
+    fn test_accepts_claim_to_wallet() {
+        // 111 vB, so 1000 sats are 9 sat/vB
+        let psbt = claim(vec![(p2tr(WALLET), 99_000)]);
+        let summary = check_collateral_spend(&contract(), &psbt, 10, &wallet()).unwrap();
+        assert_eq!(summary.to_wallet_sats, 99_000);
+        assert_eq!(summary.fee_sats, Some(1_000));
+    }
+
+    #[test]
+    fn test_refuses_foreign_input() {
+        let psbt = psbt(
+            vec![(OutPoint::null(), output(STRANGER, 100_000))],
+            vec![(p2tr(WALLET), 99_000)],
+        );
+        let err = refusal(check_collateral_spend(&contract(), &psbt, 10, &wallet()));
+        assert!(err.contains("does not spend the contract's collateral"));
+    }
+
+    #[test]
+    fn test_refuses_input_without_spent_output() {
+        let mut psbt = claim(vec![(p2tr(WALLET), 99_000)]);
+        psbt.inputs[0].witness_utxo = None;
+        let err = refusal(check_collateral_spend(&contract(), &psbt, 10, &wallet()));
+        assert!(err.contains("has no spent output"));
+    }
+
+    #[test]
+    fn test_refuses_other_collateral_script() {
+        let mut contract = contract();
+        contract.collateral_script = Some(p2tr(STRANGER).to_hex_string());
+        let psbt = claim(vec![(p2tr(WALLET), 99_000)]);
+        let err = refusal(check_collateral_spend(&contract, &psbt, 10, &wallet()));
+        assert!(err.contains("collateral script"));
+    }
+
+    #[test]
+    fn test_refuses_unusual_sighash() {
+        let mut psbt = claim(vec![(p2tr(WALLET), 99_000)]);
+        psbt.inputs[0].sighash_type = Some(PsbtSighashType::from_u32(0x83));
+        let err = refusal(check_collateral_spend(&contract(), &psbt, 10, &wallet()));
+        assert!(err.contains("UnusualSighash"));
+    }
+
+    #[test]
+    fn test_refuses_overspending_outputs() {
+        let psbt = claim(vec![(p2tr(WALLET), 150_000)]);
+        let err = refusal(check_collateral_spend(&contract(), &psbt, 10, &wallet()));
+        assert!(err.contains("OutputsExceedInputs"));
+    }
+
+    #[test]
+    fn test_refuses_server_provided_payout_address() {
+        // The contract names the stranger as payout address, the wallet never did
+        let mut contract = contract();
+        contract.borrower_btc_address = address(STRANGER);
+        let psbt = claim(vec![(p2tr(STRANGER), 99_000)]);
+        let err = refusal(check_collateral_spend(&contract, &psbt, 10, &wallet()));
+        assert!(err.contains("no output returns the collateral"));
+    }
+
+    #[test]
+    fn test_caps_external_outputs_at_origination_fee() {
+        // The largest output goes to the wallet, a smaller one is skimmed off
+        let psbt = claim(vec![(p2tr(WALLET), 89_500), (p2tr(STRANGER), 9_500)]);
+        let err = refusal(check_collateral_spend(&contract(), &psbt, 10, &wallet()));
+        assert!(err.contains("9500 sats go to"));
+
+        let mut contract = contract();
+        contract.origination_fee_sats = 9_500;
+        let summary = check_collateral_spend(&contract, &psbt, 10, &wallet()).unwrap();
+        assert_eq!(summary.to_external_sats, 9_500);
+    }
+
+    #[test]
+    fn test_refuses_excessive_fee_rate() {
+        let psbt = claim(vec![(p2tr(WALLET), 99_000)]);
+        let err = refusal(check_collateral_spend(&contract(), &psbt, 2, &wallet()));
+        assert!(err.contains("exceeds the requested 2 sat/vB"));
+    }
+
+    /// A checkpoint moving the collateral to a checkpoint output plus anchor,
+    /// and the Ark transaction spending it to `outputs`.
+    fn ark_claim(outputs: Vec<(ScriptBuf, u64)>) -> (Psbt, Vec<Psbt>) {
+        let checkpoint = psbt(
+            vec![(OutPoint::null(), output(COLLATERAL, 100_000))],
+            vec![(p2tr(8), 100_000), (ScriptBuf::new_p2a(), 0)],
+        );
+        let checkpoint_output = OutPoint::new(checkpoint.unsigned_tx.compute_txid(), 0);
+        let mut outputs = outputs;
+        outputs.push((ScriptBuf::new_p2a(), 0));
+        let ark = psbt(vec![(checkpoint_output, output(8, 100_000))], outputs);
+        (ark, vec![checkpoint])
+    }
+
+    #[test]
+    fn test_accepts_ark_claim_to_wallet() {
+        let (ark, checkpoints) = ark_claim(vec![(p2tr(WALLET), 100_000)]);
+        let summary = check_ark_claim(&contract(), &ark, &checkpoints, &wallet()).unwrap();
+        assert_eq!(summary.to_wallet_sats, 100_000);
+    }
+
+    #[test]
+    fn test_refuses_ark_claim_without_checkpoints() {
+        let (ark, _) = ark_claim(vec![(p2tr(WALLET), 100_000)]);
+        let err = refusal(check_ark_claim(&contract(), &ark, &[], &wallet()));
+        assert!(err.contains("no checkpoint"));
+    }
+
+    #[test]
+    fn test_refuses_checkpoint_of_other_funds() {
+        let (ark, mut checkpoints) = ark_claim(vec![(p2tr(WALLET), 100_000)]);
+        checkpoints[0].inputs[0].witness_utxo = Some(output(STRANGER, 100_000));
+        let err = refusal(check_ark_claim(&contract(), &ark, &checkpoints, &wallet()));
+        assert!(err.contains("does not spend the contract's collateral"));
+    }
+
+    #[test]
+    fn test_refuses_ark_input_outside_checkpoints() {
+        let (mut ark, checkpoints) = ark_claim(vec![(p2tr(WALLET), 100_000)]);
+        ark.unsigned_tx.input[0].previous_output = OutPoint::null();
+        let err = refusal(check_ark_claim(&contract(), &ark, &checkpoints, &wallet()));
+        assert!(err.contains("does not spend a checkpoint"));
+    }
+
+    #[test]
+    fn test_refuses_misstated_checkpoint_amount() {
+        let (mut ark, checkpoints) = ark_claim(vec![(p2tr(WALLET), 100_000)]);
+        ark.inputs[0].witness_utxo = Some(output(8, 200_000));
+        let err = refusal(check_ark_claim(&contract(), &ark, &checkpoints, &wallet()));
+        assert!(err.contains("misstates the checkpoint output"));
+    }
+
+    #[test]
+    fn test_refuses_unspent_checkpoint_output() {
+        let (ark, mut checkpoints) = ark_claim(vec![(p2tr(WALLET), 100_000)]);
+        checkpoints.push(psbt(
+            vec![(OutPoint::null(), output(COLLATERAL, 50_000))],
+            vec![(p2tr(STRANGER), 50_000)],
+        ));
+        let err = refusal(check_ark_claim(&contract(), &ark, &checkpoints, &wallet()));
+        assert!(err.contains("is not spent by the Ark transaction"));
+    }
+
+    #[test]
+    fn test_refuses_ark_claim_to_stranger() {
+        let (ark, checkpoints) = ark_claim(vec![(p2tr(STRANGER), 100_000)]);
+        let err = refusal(check_ark_claim(&contract(), &ark, &checkpoints, &wallet()));
+        assert!(err.contains("no output returns the collateral"));
+    }
+
+    fn settlement(outputs: Vec<(ScriptBuf, u64)>) -> (Psbt, Vec<Psbt>) {
+        let intent = psbt(
+            vec![
+                (OutPoint::null(), output(COLLATERAL, 0)),
+                (OutPoint::null(), output(COLLATERAL, 100_000)),
+            ],
+            outputs,
+        );
+        let forfeit = psbt(
+            vec![
+                (OutPoint::null(), output(COLLATERAL, 100_000)),
+                (OutPoint::null(), output(8, 330)),
+            ],
+            vec![(p2tr(8), 100_330)],
+        );
+        (intent, vec![forfeit])
+    }
+
+    #[test]
+    fn test_accepts_settlement_to_wallet() {
+        let (intent, forfeits) = settlement(vec![(p2tr(WALLET), 100_000)]);
+        assert!(check_ark_settlement(&contract(), &intent, &forfeits, &wallet()).is_ok());
+    }
+
+    #[test]
+    fn test_refuses_intent_spending_other_funds() {
+        let (mut intent, forfeits) = settlement(vec![(p2tr(WALLET), 100_000)]);
+        intent.inputs[1].witness_utxo = Some(output(STRANGER, 100_000));
+        let err = refusal(check_ark_settlement(
+            &contract(),
+            &intent,
+            &forfeits,
+            &wallet(),
+        ));
+        assert!(err.contains("does not spend the contract's collateral"));
+    }
+
+    #[test]
+    fn test_refuses_forfeit_of_other_funds() {
+        let (intent, mut forfeits) = settlement(vec![(p2tr(WALLET), 100_000)]);
+        forfeits[0].inputs[0].witness_utxo = Some(output(STRANGER, 100_000));
+        let err = refusal(check_ark_settlement(
+            &contract(),
+            &intent,
+            &forfeits,
+            &wallet(),
+        ));
+        assert!(err.contains("forfeit 0"));
+    }
+
+    #[test]
+    fn test_refuses_forfeit_of_other_funds_next_to_collateral() {
+        let (intent, mut forfeits) = settlement(vec![(p2tr(WALLET), 100_000)]);
+        forfeits[0] = psbt(
+            vec![
+                (OutPoint::null(), output(COLLATERAL, 100_000)),
+                (OutPoint::null(), output(WALLET, 50_000)),
+                (OutPoint::null(), output(8, 330)),
+            ],
+            vec![(p2tr(8), 150_330)],
+        );
+        let err = refusal(check_ark_settlement(
+            &contract(),
+            &intent,
+            &forfeits,
+            &wallet(),
+        ));
+        assert!(err.contains("input 1 of forfeit 0"));
+    }
+
+    #[test]
+    fn test_refuses_intent_to_stranger() {
+        let (intent, forfeits) = settlement(vec![(p2tr(STRANGER), 100_000)]);
+        let err = refusal(check_ark_settlement(
+            &contract(),
+            &intent,
+            &forfeits,
+            &wallet(),
+        ));
+        assert!(err.contains("no output returns the collateral"));
+    }
+
+    #[test]
+    fn test_approvals() {
+        let approved = claim(vec![(p2tr(WALLET), 99_000)])
+            .unsigned_tx
+            .compute_txid();
+        let unknown = claim(vec![(p2tr(WALLET), 98_000)])
+            .unsigned_tx
+            .compute_txid();
+
+        approve("contract-1", approved);
+
+        assert!(ensure_approved(approved, None).is_ok());
+        assert!(ensure_approved(approved, Some("contract-1")).is_ok());
+        assert!(ensure_approved(approved, Some("contract-2")).is_err());
+        assert!(ensure_approved(unknown, None).is_err());
+    }
+}
@@ -0,0 +1,427 @@
+//! Automated installment repayment through LendaSwap.
+//!
+//! Stablecoin loans are repaid by swapping BTC from the Ark wallet to the
+//! loan asset, delivered to the contract's `loan_repayment_address`. A
+//! repayment quotes the next installment, creates a BTC to EVM swap, funds
+//! its Arkade HTLC, claims the EVM side via Gelato and finally reports the
+//! EVM claim transaction to Lendasat. Progress is stored on disk after every
+//! step, so an interrupted repayment resumes where it stopped instead of
+//! funding a second swap.
+
+use crate::ark::client;
+use crate::ark::tx_metadata_db::{DestinationType, TxMetadataUpdate, record_send};
+use crate::error::ApiError;
+use crate::lendasat::models::Contract;
+use crate::lendaswap::{self, SwapInfo, SwapStatusSimple};
+use anyhow::{Result, anyhow, bail};
+use bitcoin::Amount;
+use parking_lot::Mutex;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::{Arc, LazyLock};
+
+/// File name of the repayment progress inside the data directory.
+pub const REPAYMENT_FILE: &str = "lendasat_repayments.json";
+
+/// Per-contract locks, so only one repayment of a contract runs at a time.
+static LOCKS: LazyLock<Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>> =
+    LazyLock::new(Default::default);
+
+/// Step a repayment has reached.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RepaymentStep {
+    /// Installment quoted, no swap created yet
+    Quoted,
+    /// Swap created, waiting to be funded
+    SwapCreated,
+    /// Payment to the swap HTLC was requested
+    Funding,
+    /// HTLC funded, waiting for LendaSwap to lock the stablecoins
+    Funded,
+    /// Stablecoins claimed via Gelato, waiting for the EVM claim transaction
+    Claimed,
+    /// Installment reported as paid to Lendasat
+    Completed,
+    /// Swap expired or failed; funds may need a refund through LendaSwap
+    Failed,
+}
+
+/// Persisted progress of an installment repayment.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RepaymentState {
+    pub contract_id: String,
+    pub installment_id: String,
+    pub amount_usd: f64,
+    pub target_token: String,
+    pub target_chain: String,
+    pub repayment_address: String,
+    pub step: RepaymentStep,
+    pub swap_id: Option<String>,
+    /// Arkade HTLC address funding the swap
+    pub htlc_address: Option<String>,
+    pub sats_to_send: Option<u64>,
+    pub funding_txid: Option<String>,
+    /// EVM transaction delivering the stablecoins, reported to Lendasat
+    pub evm_claim_txid: Option<String>,
+    /// Why the repayment failed
+    pub error: Option<String>,
+    pub started_at: i64,
+    pub updated_at: i64,
+}
+
+impl RepaymentState {
+    /// Whether sats may have left the wallet without the installment being paid
+    pub fn funds_in_transit(&self) -> bool {
+        matches!(
+            self.step,
+            RepaymentStep::Funding | RepaymentStep::Funded | RepaymentStep::Claimed
+        )
+    }
+}
+
+/// Quote a repayment of the next installment of `contract`.
+pub fn quote(contract: &Contract) -> Result<RepaymentState> {
+    let installment = contract
+        .next_installment()
+        .ok_or_else(|| anyhow!("Contract {} has no installment due", contract.id))?;
+    let (target_token, target_chain) = contract.loan_asset.swap_token().ok_or_else(|| {
+        anyhow!(
+            "{} cannot be repaid through LendaSwap",
+            contract.loan_asset.display_name()
+        )
+    })?;
+    let repayment_address = contract
+        .loan_repayment_address
+        .clone()
+        .ok_or_else(|| anyhow!("Contract {} has no repayment address", contract.id))?;
+
+    let now = now_unix();
+    Ok(RepaymentState {
+        contract_id: contract.id.clone(),
+        installment_id: installment.id.clone(),
+        amount_usd: installment.total(),
+        target_token: target_token.to_string(),
+        target_chain: target_chain.to_string(),
+        repayment_address,
+        step: RepaymentStep::Quoted,
+        swap_id: None,
+        htlc_address: None,
+        sats_to_send: None,
+        funding_txid: None,
+        evm_claim_txid: None,
+        error: None,
+        started_at: now,
+        updated_at: now,
+    })
+}
+
+fn load_all(data_dir: &str) -> Result<HashMap<String, RepaymentState>> {
+    let path = Path::new(data_dir).join(REPAYMENT_FILE);
+
+    if !path.exists() {
+        return Ok(HashMap::new());
+    }
+
+    let content =
+        fs::read_to_string(&path).map_err(|e| anyhow!("Failed to read repayment file: {}", e))?;
+
+    serde_json::from_str(&content).map_err(|e| anyhow!("Failed to parse repayment file: {}", e))
+}
+
+fn write_all(data_dir: &str, states: &HashMap<String, RepaymentState>) -> Result<()> {
+    let path = Path::new(data_dir).join(REPAYMENT_FILE);
+
+    let content = serde_json::to_string_pretty(states)
+        .map_err(|e| anyhow!("Failed to serialize repayment state: {}", e))?;
+
+    fs::write(&path, content).map_err(|e| anyhow!("Failed to write repayment file: {}", e))?;
+
+    Ok(())
+}
+
+/// Lock the repayment of a contract.
+///
+/// Hold the guard from loading the state until the last save, so concurrent
+/// calls cannot run the same step twice.
+pub(crate) async fn lock(contract_id: &str) -> tokio::sync::OwnedMutexGuard<()> {
+    let lock = LOCKS
+        .lock()
+        .entry(contract_id.to_string())
+        .or_default()
+        .clone();
+    lock.lock_owned().await
+}
+
+/// Load the stored repayment progress of a contract, if one was started.
+pub fn load_state(data_dir: &str, contract_id: &str) -> Result<Option<RepaymentState>> {
+    Ok(load_all(data_dir)?.remove(contract_id))
+}
+
+pub(crate) fn save_state(data_dir: &str, state: &mut RepaymentState) -> Result<()> {
+    state.updated_at = now_unix();
+
+    let mut states = load_all(data_dir)?;
+    states.insert(state.contract_id.clone(), state.clone());
+    write_all(data_dir, &states)
+}
+
+/// Delete the stored repayment progress of a contract.
+///
+/// Only allowed before the swap was funded, after it completed or failed.
+pub fn clear_state(data_dir: &str, contract_id: &str) -> Result<()> {
+    let mut states = load_all(data_dir)?;
+
+    match states.get(contract_id) {
+        None => return Ok(()),
+        Some(state) if state.funds_in_transit() => {
+            bail!("Cannot cancel a repayment while funds are in transit");
+        }
+        Some(_) => {}
+    }
+
+    states.remove(contract_id);
+    write_all(data_dir, &states)
+}
+
+/// Run the swap side of a repayment as far as possible.
+///
+/// Returns the reached state. It stops at `Claimed` once the EVM claim
+/// transaction is known; reporting it to Lendasat is left to the caller. While
+/// LendaSwap is still processing, the state is returned unchanged; call again
+/// later to continue.
+pub async fn run(data_dir: &str, mut state: RepaymentState) -> Result<RepaymentState> {
+    tracing::info!(
+        contract_id = %state.contract_id,
+        step = ?state.step,
+        "Running Lendasat repayment"
+    );
+
+    if state.step == RepaymentStep::Quoted {
+        let token = lendaswap::parse_token_id(&state.target_token)?;
+        let chain = lendaswap::parse_evm_chain(&state.target_chain)?;
+        let amount = Decimal::from_str(&state.amount_usd.to_string())
+            .map_err(|e| anyhow!("Invalid amount: {}", e))?;
+
+        let swap = lendaswap::create_btc_to_evm_swap(
+            state.repayment_address.clone(),
+            amount,
+            token,
+            chain,
+            None,
+        )
+        .await?;
+
+        if swap.sats_receive <= 0 {
+            bail!(
+                "LendaSwap quoted {} sats for swap {}",
+                swap.sats_receive,
+                swap.common.id
+            );
+        }
+
+        state.swap_id = Some(swap.common.id.to_string());
+        state.htlc_address = Some(swap.htlc_address_arkade.clone());
+        state.sats_to_send = Some(swap.sats_receive as u64);
+        state.step = RepaymentStep::SwapCreated;
+        save_state(data_dir, &mut state)?;
+    }
+
+    let swap_id = state
+        .swap_id
+        .clone()
+        .ok_or_else(|| anyhow!("Repayment state is missing the swap id"))?;
+
+    if state.step == RepaymentStep::Funding && state.funding_txid.is_none() {
+        recover_funding(data_dir, &mut state, &swap_id).await?;
+    }
+
+    if state.step == RepaymentStep::SwapCreated {
+        let swap = swap_info(&swap_id).await?;
+        if !matches!(swap.status, SwapStatusSimple::WaitingForDeposit) {
+            return fail(data_dir, state, &swap);
+        }
+
+        let address = state
+            .htlc_address
+            .clone()
+            .ok_or_else(|| anyhow!("Repayment state is missing the HTLC address"))?;
+        let amount = state
+            .sats_to_send
+            .map(Amount::from_sat)
+            .ok_or_else(|| anyhow!("Repayment state is missing the swap amount"))?;
+
+        let balance = client::balance().await?;
+        let available = balance.offchain.confirmed() + balance.offchain.pre_confirmed();
+        if available < amount {
+            bail!(ApiError::InsufficientFunds {
+                needed_sats: amount.to_sat(),
+                available_sats: available.to_sat(),
+            });
+        }
+
+        // Saved before sending: a resumed repayment never funds the swap twice
+        state.step = RepaymentStep::Funding;
+        state.error = None;
+        save_state(data_dir, &mut state)?;
+
+        tracing::info!(%amount, %address, swap_id, "Funding repayment swap");
+
+        let txid = match client::send(address, amount, None).await {
+            Ok(txid) => txid.to_string(),
+            Err(e) => {
+                state.error = Some(format!("Failed to fund swap: {:#}", e));
+                save_state(data_dir, &mut state)?;
+                return Err(e);
+            }
+        };
+        record_send(
+            &txid,
+            TxMetadataUpdate {
+                destination_type: Some(DestinationType::LendasatRepayment),
+                reference: Some(state.contract_id.clone()),
+                ..Default::default()
+            },
+        )
+        .await;
+
+        state.funding_txid = Some(txid);
+        state.step = RepaymentStep::Funded;
+        save_state(data_dir, &mut state)?;
+    }
+
+    if matches!(state.step, RepaymentStep::Funding | RepaymentStep::Funded) {
+        let swap = swap_info(&swap_id).await?;
+        match swap.status {
+            // The payment may still be on its way; only expiry proves it never arrived
+            SwapStatusSimple::WaitingForDeposit | SwapStatusSimple::Processing
+                if !swap.can_claim_gelato =>
+            {
+                tracing::info!(swap_id, "Waiting for LendaSwap to fund the EVM HTLC");
+                return Ok(state);
+            }
+            SwapStatusSimple::WaitingForDeposit | SwapStatusSimple::Processing => {
+                lendaswap::claim_gelato(&swap_id, None).await?;
+            }
+            SwapStatusSimple::Completed => {}
+            _ => return fail(data_dir, state, &swap),
+        }
+
+        state.step = RepaymentStep::Claimed;
+        save_state(data_dir, &mut state)?;
+    }
+
+    if state.step == RepaymentStep::Claimed && state.evm_claim_txid.is_none() {
+        let swap = swap_info(&swap_id).await?;
+        match swap.evm_htlc_claim_txid {
+            Some(txid) => {
+                state.evm_claim_txid = Some(txid);
+                save_state(data_dir, &mut state)?;
+            }
+            None => tracing::info!(swap_id, "Waiting for the EVM claim transaction"),
+        }
+    }
+
+    Ok(state)
+}
+
+/// Mark a repayment as completed once Lendasat accepted the payment.
+pub fn complete(data_dir: &str, mut state: RepaymentState) -> Result<RepaymentState> {
+    state.step = RepaymentStep::Completed;
+    save_state(data_dir, &mut state)?;
+
+    tracing::info!(contract_id = %state.contract_id, "Lendasat repayment completed");
+
+    Ok(state)
+}
+
+/// Resolve a repayment left in `Funding` without a funding transaction.
+///
+/// A send that failed may still have reached the Ark server. If the wallet
+/// history shows a payment of the swap amount since the repayment started,
+/// the swap was funded. If not and LendaSwap has not seen a deposit either,
+/// the repayment stops with an error: only the user can tell whether the
+/// funds left, see [`retry_funding`]. Otherwise the state is left as is.
+async fn recover_funding(data_dir: &str, state: &mut RepaymentState, swap_id: &str) -> Result<()> {
+    let address = state
+        .htlc_address
+        .clone()
+        .ok_or_else(|| anyhow!("Repayment state is missing the HTLC address"))?;
+    let amount = state
+        .sats_to_send
+        .map(Amount::from_sat)
+        .ok_or_else(|| anyhow!("Repayment state is missing the swap amount"))?;
+
+    if let Some(txid) = client::find_payment(&address, amount, state.started_at).await? {
+        tracing::info!(swap_id, %txid, "Found funding of repayment swap");
+        state.funding_txid = Some(txid.to_string());
+        state.step = RepaymentStep::Funded;
+        state.error = None;
+        return save_state(data_dir, state);
+    }
+
+    let swap = swap_info(swap_id).await?;
+    let waiting =
+        matches!(swap.status, SwapStatusSimple::WaitingForDeposit) && !swap.can_claim_gelato;
+    if waiting {
+        tracing::warn!(swap_id, "Cannot tell whether the repayment swap was funded");
+        let error = format!(
+            "Could not find the payment of {} to swap {}. Check the wallet history before \
+             funding the swap again",
+            amount, swap_id
+        );
+        state.error = Some(error.clone());
+        save_state(data_dir, state)?;
+        bail!(error);
+    }
+
+    Ok(())
+}
+
+/// Fund the swap of an interrupted repayment again.
+///
+/// Only for a repayment stuck in `Funding` without a funding transaction, once
+/// the user has checked that no payment to the swap left the wallet.
+pub fn retry_funding(data_dir: &str, contract_id: &str) -> Result<RepaymentState> {
+    let mut state = load_state(data_dir, contract_id)?
+        .ok_or_else(|| anyhow!("No repayment of contract {} was started", contract_id))?;
+    if state.step != RepaymentStep::Funding || state.funding_txid.is_some() {
+        bail!(
+            "Repayment of contract {} is not waiting for its funding to be resolved",
+            contract_id
+        );
+    }
+
+    tracing::warn!(contract_id, "Funding repayment swap again on user request");
+
+    state.step = RepaymentStep::SwapCreated;
+    state.error = None;
+    save_state(data_dir, &mut state)?;
+
+    Ok(state)
+}
+
+async fn swap_info(swap_id: &str) -> Result<SwapInfo> {
+    let data = lendaswap::get_swap(swap_id).await?;
+    Ok(SwapInfo::from_extended_data(&data))
+}
+
+fn fail(data_dir: &str, mut state: RepaymentState, swap: &SwapInfo) -> Result<RepaymentState> {
+    tracing::warn!(swap_id = %swap.id, status = ?swap.status, "Lendasat repayment failed");
+
+    state.error = Some(format!("Swap ended in status {}", swap.detailed_status));
+    state.step = RepaymentStep::Failed;
+    save_state(data_dir, &mut state)?;
+
+    Ok(state)
+}
+
+fn now_unix() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
@@ -4,10 +4,13 @@
 
 use anyhow::{Result, anyhow};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
 
 const LENDASAT_AUTH_FILE: &str = "lendasat_auth.json";
+const LENDASAT_PAYOUT_FILE: &str = "lendasat_payout_addresses.json";
+const LENDASAT_FUNDING_FILE: &str = "lendasat_fundings.json";
 
 /// Stored authentication data
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -22,24 +25,21 @@ pub struct StoredAuth {
 }
 
 impl StoredAuth {
+    /// Unix timestamp after which the token should be considered expired
+    pub fn expiry(&self) -> i64 {
+        // If no expiry set, assume token is valid for 24 hours
+        let one_day = 24 * 60 * 60;
+        self.expires_at.unwrap_or(self.created_at + one_day)
+    }
+
     /// Check if the token is potentially expired
     /// (actual validation happens on the server)
     pub fn is_potentially_expired(&self) -> bool {
-        if let Some(expires_at) = self.expires_at {
-            let now = std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .map(|d| d.as_secs() as i64)
-                .unwrap_or(0);
-            now >= expires_at
-        } else {
-            // If no expiry set, assume token is valid for 24 hours
-            let now = std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .map(|d| d.as_secs() as i64)
-                .unwrap_or(0);
-            let one_day = 24 * 60 * 60;
-            now >= self.created_at + one_day
-        }
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or(0);
+        now >= self.expiry()
     }
 }
 
@@ -88,3 +88,83 @@ pub fn has_auth(data_dir: &str) -> bool {
     let path = Path::new(data_dir).join(LENDASAT_AUTH_FILE);
     path.exists()
 }
+
+fn load_payout_addresses(data_dir: &str) -> Result<HashMap<String, String>> {
+    let path = Path::new(data_dir).join(LENDASAT_PAYOUT_FILE);
+
+    if !path.exists() {
+        return Ok(HashMap::new());
+    }
+
+    let content = fs::read_to_string(&path)
+        .map_err(|e| anyhow!("Failed to read payout address file: {}", e))?;
+
+    serde_json::from_str(&content)
+        .map_err(|e| anyhow!("Failed to parse payout address file: {}", e))
+}
+
+/// Remember the collateral payout address the wallet sent when creating a
+/// contract. Unlike the address in the contract returned by the server, it is
+/// known to belong to the wallet.
+pub fn save_payout_address(data_dir: &str, contract_id: &str, address: &str) -> Result<()> {
+    let path = Path::new(data_dir).join(LENDASAT_PAYOUT_FILE);
+
+    let mut addresses = load_payout_addresses(data_dir)?;
+    addresses.insert(contract_id.to_string(), address.to_string());
+
+    let content = serde_json::to_string_pretty(&addresses)
+        .map_err(|e| anyhow!("Failed to serialize payout addresses: {}", e))?;
+
+    fs::write(&path, content).map_err(|e| anyhow!("Failed to write payout address file: {}", e))?;
+
+    Ok(())
+}
+
+/// The payout address stored for a contract created by this wallet
+pub fn load_payout_address(data_dir: &str, contract_id: &str) -> Result<Option<String>> {
+    Ok(load_payout_addresses(data_dir)?.remove(contract_id))
+}
+
+/// A collateral deposit the wallet started
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FundingMarker {
+    pub address: String,
+    pub amount_sats: u64,
+    pub started_at: i64,
+    /// Set once the send returned
+    pub txid: Option<String>,
+}
+
+fn load_fundings(data_dir: &str) -> Result<HashMap<String, FundingMarker>> {
+    let path = Path::new(data_dir).join(LENDASAT_FUNDING_FILE);
+
+    if !path.exists() {
+        return Ok(HashMap::new());
+    }
+
+    let content =
+        fs::read_to_string(&path).map_err(|e| anyhow!("Failed to read funding file: {}", e))?;
+
+    serde_json::from_str(&content).map_err(|e| anyhow!("Failed to parse funding file: {}", e))
+}
+
+/// Remember a collateral deposit of a contract. Saved before sending, so an
+/// interrupted funding is looked up instead of paid a second time.
+pub fn save_funding(data_dir: &str, contract_id: &str, marker: &FundingMarker) -> Result<()> {
+    let path = Path::new(data_dir).join(LENDASAT_FUNDING_FILE);
+
+    let mut fundings = load_fundings(data_dir)?;
+    fundings.insert(contract_id.to_string(), marker.clone());
+
+    let content = serde_json::to_string_pretty(&fundings)
+        .map_err(|e| anyhow!("Failed to serialize fundings: {}", e))?;
+
+    fs::write(&path, content).map_err(|e| anyhow!("Failed to write funding file: {}", e))?;
+
+    Ok(())
+}
+
+/// The collateral deposit started for a contract, if any
+pub fn load_funding(data_dir: &str, contract_id: &str) -> Result<Option<FundingMarker>> {
+    Ok(load_fundings(data_dir)?.remove(contract_id))
+}
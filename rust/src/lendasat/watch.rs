@@ -0,0 +1,205 @@
+//! Change detection between successive fetches of a contract.
+
+use crate::lendasat::models::{Contract, ContractStatus, Installment, LoanTransaction};
+use serde::{Deserialize, Serialize};
+
+/// End of a polling round of a contract stream, sent after every round.
+///
+/// Lets the app show when the data was last refreshed. Sending it is also how
+/// a stream notices it was closed while nothing changed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PollRound {
+    /// Unix timestamp of the end of the round
+    pub finished_at: i64,
+    /// Number of contracts looked at, `None` if the round failed
+    pub contracts: Option<u32>,
+}
+
+impl PollRound {
+    /// A round over `contracts` that ends now.
+    pub(crate) fn now(contracts: Option<u32>) -> Self {
+        let finished_at = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or(0);
+        Self {
+            finished_at,
+            contracts,
+        }
+    }
+}
+
+/// What changed in a contract since it was last seen.
+#[derive(Debug, Clone)]
+pub struct ContractUpdate {
+    pub contract_id: String,
+    /// Status before the change, `None` when the contract is seen for the first time
+    pub previous_status: Option<ContractStatus>,
+    pub status: ContractStatus,
+    /// Installments that are new or whose status changed
+    pub changed_installments: Vec<Installment>,
+    /// Transactions that are new or whose type, txid or timestamp changed
+    pub changed_transactions: Vec<LoanTransaction>,
+    /// The contract as it is now
+    pub contract: Contract,
+}
+
+/// Item of the contract subscription stream.
+#[derive(Debug, Clone)]
+pub enum ContractWatchEvent {
+    /// A contract changed or was seen for the first time
+    Update(ContractUpdate),
+    /// A poll of all contracts finished
+    Polled(PollRound),
+}
+
+/// Compare `current` with its `previous` version.
+///
+/// Returns `None` if neither the status, the installments nor the
+/// transactions changed. A contract without a previous version is always
+/// reported.
+pub fn diff(previous: Option<&Contract>, current: &Contract) -> Option<ContractUpdate> {
+    let changed_installments: Vec<Installment> = current
+        .installments
+        .iter()
+        .filter(|installment| {
+            let before =
+                previous.and_then(|p| p.installments.iter().find(|i| i.id == installment.id));
+            match before {
+                Some(before) => {
+                    before.status != installment.status || before.paid_date != installment.paid_date
+                }
+                None => previous.is_some(),
+            }
+        })
+        .cloned()
+        .collect();
+
+    let changed_transactions: Vec<LoanTransaction> = current
+        .transactions
+        .iter()
+        .filter(|tx| {
+            let before = previous.and_then(|p| p.transactions.iter().find(|t| t.id == tx.id));
+            match before {
+                Some(before) => {
+                    before.transaction_type != tx.transaction_type
+                        || before.txid != tx.txid
+                        || before.timestamp != tx.timestamp
+                }
+                None => previous.is_some(),
+            }
+        })
+        .cloned()
+        .collect();
+
+    let status_changed = previous.map(|p| p.status) != Some(current.status);
+    if !status_changed && changed_installments.is_empty() && changed_transactions.is_empty() {
+        return None;
+    }
+
+    Some(ContractUpdate {
+        contract_id: current.id.clone(),
+        previous_status: previous.map(|p| p.status),
+        status: current.status,
+        changed_installments,
+        changed_transactions,
+        contract: current.clone(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lendasat::models::{InstallmentStatus, fixtures};
+
+    fn transaction(id: i64) -> LoanTransaction {
+        LoanTransaction {
+            id,
+            contract_id: "contract-1".to_string(),
+            transaction_type: "Deposit".to_string(),
+            txid: format!("txid-{}", id),
+            timestamp: String::new(),
+        }
+    }
+
+    #[test]
+    fn reports_new_contract() {
+        let contract = fixtures::contract();
+
+        let update = diff(None, &contract).unwrap();
+
+        assert_eq!(update.previous_status, None);
+        assert_eq!(update.status, contract.status);
+        // Nothing changed relative to an earlier version
+        assert!(update.changed_installments.is_empty());
+        assert!(update.changed_transactions.is_empty());
+    }
+
+    #[test]
+    fn ignores_unchanged_contract() {
+        let contract = fixtures::contract();
+
+        assert!(diff(Some(&contract), &contract.clone()).is_none());
+    }
+
+    #[test]
+    fn reports_status_change() {
+        let previous = fixtures::contract();
+        let mut current = previous.clone();
+        current.status = ContractStatus::Undercollateralized;
+
+        let update = diff(Some(&previous), &current).unwrap();
+
+        assert_eq!(update.previous_status, Some(ContractStatus::PrincipalGiven));
+        assert_eq!(update.status, ContractStatus::Undercollateralized);
+    }
+
+    #[test]
+    fn reports_changed_and_new_installments() {
+        let previous = fixtures::contract();
+        let mut current = previous.clone();
+        current.installments[0].status = InstallmentStatus::Paid;
+        current.installments[0].paid_date = Some("2026-01-15T00:00:00Z".to_string());
+        let mut added = current.installments[0].clone();
+        added.id = "installment-2".to_string();
+        added.status = InstallmentStatus::Pending;
+        current.installments.push(added);
+
+        let update = diff(Some(&previous), &current).unwrap();
+
+        let ids: Vec<_> = update
+            .changed_installments
+            .iter()
+            .map(|i| i.id.as_str())
+            .collect();
+        assert_eq!(ids, vec!["installment-1", "installment-2"]);
+        assert_eq!(update.previous_status, Some(update.status));
+    }
+
+    #[test]
+    fn reports_only_new_transactions() {
+        let mut previous = fixtures::contract();
+        previous.transactions = vec![transaction(1)];
+        let mut current = previous.clone();
+        current.transactions.push(transaction(2));
+
+        let update = diff(Some(&previous), &current).unwrap();
+
+        let ids: Vec<_> = update.changed_transactions.iter().map(|t| t.id).collect();
+        assert_eq!(ids, vec![2]);
+        assert!(update.changed_installments.is_empty());
+    }
+
+    #[test]
+    fn reports_changed_transactions() {
+        let mut previous = fixtures::contract();
+        previous.transactions = vec![transaction(1), transaction(2)];
+        let mut current = previous.clone();
+        current.transactions[1].timestamp = "2026-01-15T00:00:00Z".to_string();
+
+        let update = diff(Some(&previous), &current).unwrap();
+
+        let ids: Vec<_> = update.changed_transactions.iter().map(|t| t.id).collect();
+        assert_eq!(ids, vec![2]);
+    }
+}
@@ -10,6 +10,7 @@ pub mod storage;
 pub mod swap_db;
 pub mod vtxo_swap_db;
 
+use crate::error::ApiError;
 use crate::lendaswap::storage::FileWalletStorage;
 use crate::lendaswap::swap_db::SwapDb;
 use crate::lendaswap::vtxo_swap_db::VtxoSwapDb;
@@ -142,7 +143,7 @@ pub async fn get_asset_pairs() -> Result<Vec<AssetPair>> {
     let guard = lock.read().await;
     let client = guard
         .as_ref()
-        .ok_or_else(|| anyhow!("LendaSwap client not initialized"))?;
+        .ok_or_else(|| ApiError::not_initialized("LendaSwap client"))?;
 
     client
         .get_asset_pairs()
@@ -156,7 +157,7 @@ pub async fn get_quote(from: TokenId, to: TokenId, base_amount_sats: u64) -> Res
     let guard = lock.read().await;
     let client = guard
         .as_ref()
-        .ok_or_else(|| anyhow!("LendaSwap client not initialized"))?;
+        .ok_or_else(|| ApiError::not_initialized("LendaSwap client"))?;
 
     let request = QuoteRequest {
         from,
@@ -190,7 +191,7 @@ pub async fn create_btc_to_evm_swap(
     let guard = lock.read().await;
     let client = guard.as_ref().ok_or_else(|| {
         tracing::error!("[LendaSwap] client not initialized!");
-        anyhow!("LendaSwap client not initialized")
+        ApiError::not_initialized("LendaSwap client")
     })?;
 
     tracing::info!("[LendaSwap] calling SDK create_arkade_to_evm_swap...");
@@ -242,7 +243,7 @@ pub async fn create_evm_to_btc_swap(
     let guard = lock.read().await;
     let client = guard
         .as_ref()
-        .ok_or_else(|| anyhow!("LendaSwap client not initialized"))?;
+        .ok_or_else(|| ApiError::not_initialized("LendaSwap client"))?;
 
     client
         .create_evm_to_arkade_swap(
@@ -269,7 +270,7 @@ pub async fn create_evm_to_lightning_swap(
     let guard = lock.read().await;
     let client = guard
         .as_ref()
-        .ok_or_else(|| anyhow!("LendaSwap client not initialized"))?;
+        .ok_or_else(|| ApiError::not_initialized("LendaSwap client"))?;
 
     client
         .create_evm_to_lightning_swap(
@@ -291,7 +292,7 @@ pub async fn get_swap(swap_id: &str) -> Result<ExtendedSwapStorageData> {
     let guard = lock.read().await;
     let client = guard.as_ref().ok_or_else(|| {
         tracing::error!("[LendaSwap] get_swap - client not initialized!");
-        anyhow!("LendaSwap client not initialized")
+        ApiError::not_initialized("LendaSwap client")
     })?;
 
     tracing::debug!("[LendaSwap] calling SDK get_swap...");
@@ -326,7 +327,7 @@ pub async fn list_swaps() -> Result<Vec<ExtendedSwapStorageData>> {
     let guard = lock.read().await;
     let client = guard.as_ref().ok_or_else(|| {
         tracing::error!("[LendaSwap] list_swaps - client not initialized!");
-        anyhow!("LendaSwap client not initialized")
+        ApiError::not_initialized("LendaSwap client")
     })?;
 
     let result = client.list_all().await;
@@ -372,7 +373,7 @@ pub async fn claim_gelato(swap_id: &str, secret: Option<String>) -> Result<()> {
     let guard = lock.read().await;
     let client = guard.as_ref().ok_or_else(|| {
         tracing::error!("[LendaSwap] claim_gelato - client not initialized!");
-        anyhow!("LendaSwap client not initialized")
+        ApiError::not_initialized("LendaSwap client")
     })?;
 
     tracing::info!("[LendaSwap] calling SDK claim_gelato...");
@@ -398,7 +399,7 @@ pub async fn claim_vhtlc(swap_id: &str) -> Result<String> {
     let guard = lock.read().await;
     let client = guard.as_ref().ok_or_else(|| {
         tracing::error!("[LendaSwap] claim_vhtlc - client not initialized!");
-        anyhow!("LendaSwap client not initialized")
+        ApiError::not_initialized("LendaSwap client")
     })?;
 
     tracing::info!("[LendaSwap] calling SDK claim_vhtlc...");
@@ -426,7 +427,7 @@ pub async fn refund_vhtlc(swap_id: &str, refund_address: &str) -> Result<String>
     let guard = lock.read().await;
     let client = guard
         .as_ref()
-        .ok_or_else(|| anyhow!("LendaSwap client not initialized"))?;
+        .ok_or_else(|| ApiError::not_initialized("LendaSwap client"))?;
 
     client
         .refund_vhtlc(swap_id, refund_address)
@@ -443,7 +444,7 @@ pub async fn refund_onchain_htlc(swap_id: &str, refund_address: &str) -> Result<
     let guard = lock.read().await;
     let client = guard
         .as_ref()
-        .ok_or_else(|| anyhow!("LendaSwap client not initialized"))?;
+        .ok_or_else(|| ApiError::not_initialized("LendaSwap client"))?;
 
     tracing::info!(
         "[LendaSwap] refund_onchain_htlc called for swap {} to address {}",
@@ -482,7 +483,7 @@ pub async fn recover_swaps() -> Result<Vec<ExtendedSwapStorageData>> {
     let guard = lock.read().await;
     let client = guard
         .as_ref()
-        .ok_or_else(|| anyhow!("LendaSwap client not initialized"))?;
+        .ok_or_else(|| ApiError::not_initialized("LendaSwap client"))?;
 
     client
         .recover_swaps()
@@ -496,7 +497,7 @@ pub async fn delete_swap(swap_id: &str) -> Result<()> {
     let guard = lock.read().await;
     let client = guard
         .as_ref()
-        .ok_or_else(|| anyhow!("LendaSwap client not initialized"))?;
+        .ok_or_else(|| ApiError::not_initialized("LendaSwap client"))?;
 
     client
         .delete_swap(swap_id.to_string())
@@ -567,7 +568,7 @@ pub async fn get_mnemonic() -> Result<String> {
     let guard = lock.read().await;
     let client = guard
         .as_ref()
-        .ok_or_else(|| anyhow!("LendaSwap client not initialized"))?;
+        .ok_or_else(|| ApiError::not_initialized("LendaSwap client"))?;
 
     client
         .get_mnemonic()
@@ -581,7 +582,7 @@ pub async fn get_user_id_xpub() -> Result<String> {
     let guard = lock.read().await;
     let client = guard
         .as_ref()
-        .ok_or_else(|| anyhow!("LendaSwap client not initialized"))?;
+        .ok_or_else(|| ApiError::not_initialized("LendaSwap client"))?;
 
     client
         .get_user_id_xpub()
@@ -641,7 +642,7 @@ impl From<ApiSwapStatus> for SwapStatusSimple {
 pub struct SwapInfo {
     pub id: String,
     pub status: SwapStatusSimple,
-    pub direction: String, // "btc_to_evm" or "evm_to_btc"
+    pub direction: String, // "btc_to_evm", "evm_to_btc" or "btc_to_arkade"
     pub source_token: String,
     pub target_token: String,
     pub source_amount_sats: i64,
@@ -741,8 +742,37 @@ impl SwapInfo {
                     refund_locktime: Some(r.common.evm_refund_locktime as i64),
                 }
             }
-            GetSwapResponse::BtcToArkade(_) => {
-                unimplemented!("not supported at the moment")
+            GetSwapResponse::BtcToArkade(r) => {
+                // For BTC→Arkade: on-chain BTC in, the wallet claims the Arkade
+                // VHTLC once the server has funded it
+                let can_claim = matches!(r.status, ApiSwapStatus::ServerFunded);
+                let can_refund = matches!(
+                    r.status,
+                    ApiSwapStatus::ClientInvalidFunded | ApiSwapStatus::ClientFundedTooLate
+                );
+
+                SwapInfo {
+                    id: r.id.to_string(),
+                    status: r.status.into(),
+                    direction: "btc_to_arkade".to_string(),
+                    source_token: r.source_token.as_str().to_string(),
+                    target_token: r.target_token.as_str().to_string(),
+                    source_amount_sats: r.sats_receive,
+                    // Both legs are BTC, there is no stablecoin amount
+                    target_amount_usd: 0.0,
+                    created_at: r.created_at.to_string(),
+                    ln_invoice: None,
+                    arkade_htlc_address: None,
+                    evm_htlc_address: None,
+                    fee_sats: r.fee_sats,
+                    can_claim_gelato: false,
+                    can_claim_vhtlc: can_claim,
+                    can_refund,
+                    detailed_status: format!("{:?}", r.status),
+                    evm_htlc_claim_txid: None,
+                    // For BTC→Arkade: use btc_refund_locktime (on-chain HTLC)
+                    refund_locktime: Some(r.btc_refund_locktime),
+                }
             }
         }
     }
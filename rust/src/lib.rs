@@ -1,9 +1,12 @@
 pub mod api;
 pub mod ark;
+pub mod error;
+pub mod http;
 pub mod lendasat;
 pub mod lendaswap;
 pub mod logger;
 pub mod models;
+pub mod offline;
 mod state;
 
 #[allow(clippy::all)]
@@ -1,5 +1,10 @@
+use crate::http;
+use crate::http::RequestExt;
+use crate::http::Service;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use time::macros::format_description;
+use time::{Date, OffsetDateTime};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExchangeRates {
@@ -71,7 +76,10 @@ struct ExchangeRateApiResponse {
 pub async fn fetch_exchange_rates() -> anyhow::Result<ExchangeRates> {
     let url = "https://open.exchangerate-api.com/v6/latest/USD";
 
-    let response = reqwest::get(url).await?;
+    let response = http::client(Service::Prices)
+        .get(url)
+        .send_with_retry(Service::Prices)
+        .await?;
 
     if !response.status().is_success() {
         anyhow::bail!("Failed to fetch exchange rates: {}", response.status());
@@ -89,3 +97,95 @@ pub async fn fetch_exchange_rates() -> anyhow::Result<ExchangeRates> {
         timestamp: api_response.time_last_update_unix,
     })
 }
+
+#[derive(Debug, Deserialize)]
+struct HistoricalRatesResponse {
+    rates: BTreeMap<String, HashMap<String, f64>>,
+}
+
+/// Daily exchange rates from USD to `currency` since the unix timestamp
+/// `since`, as `(unix timestamp, rate)` oldest first.
+///
+/// Uses the ECB reference rates served by Frankfurter, which are published on
+/// working days only.
+pub async fn fetch_historical_exchange_rates(
+    currency: FiatCurrency,
+    since: i64,
+) -> anyhow::Result<Vec<(i64, f64)>> {
+    if currency == FiatCurrency::USD {
+        return Ok(vec![(since, 1.0)]);
+    }
+
+    let start = OffsetDateTime::from_unix_timestamp(since)?
+        .date()
+        .format(format_description!("[year]-[month]-[day]"))?;
+    let url = format!(
+        "https://api.frankfurter.app/{}..?from=USD&to={}",
+        start,
+        currency.code()
+    );
+
+    let response = http::client(Service::Prices)
+        .get(&url)
+        .send_with_retry(Service::Prices)
+        .await?;
+
+    if !response.status().is_success() {
+        anyhow::bail!(
+            "Failed to fetch historical exchange rates: {}",
+            response.status()
+        );
+    }
+
+    let api_response: HistoricalRatesResponse = response.json().await?;
+    let rates = parse_historical_rates(api_response, currency);
+
+    if rates.is_empty() {
+        anyhow::bail!("No historical exchange rates for {}", currency.code());
+    }
+
+    Ok(rates)
+}
+
+fn parse_historical_rates(
+    response: HistoricalRatesResponse,
+    currency: FiatCurrency,
+) -> Vec<(i64, f64)> {
+    response
+        .rates
+        .into_iter()
+        .filter_map(|(day, rates)| {
+            let date = Date::parse(&day, format_description!("[year]-[month]-[day]")).ok()?;
+            let rate = rates.get(currency.code())?;
+            Some((date.midnight().assume_utc().unix_timestamp(), *rate))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_historical_rates() {
+        let response: HistoricalRatesResponse = serde_json::from_str(
+            r#"{
+                "amount": 1.0,
+                "base": "USD",
+                "start_date": "2024-01-02",
+                "end_date": "2024-01-03",
+                "rates": {
+                    "2024-01-03": {"EUR": 0.915},
+                    "2024-01-02": {"EUR": 0.91},
+                    "2024-01-04": {"GBP": 0.79},
+                    "not-a-date": {"EUR": 1.0}
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let rates = parse_historical_rates(response, FiatCurrency::EUR);
+
+        assert_eq!(rates, vec![(1_704_153_600, 0.91), (1_704_240_000, 0.915)]);
+    }
+}
@@ -0,0 +1,701 @@
+//! Offline support: last-known state per subsystem and a queue of deferred actions.
+//!
+//! Every successful refresh of the balance, the swap list or the loan contracts
+//! is stored here. When the backend cannot be reached the API functions serve
+//! the stored copy instead and flag it as stale. Actions that need the network
+//! (settling, claiming a swap, marking an installment paid) are queued when
+//! they fail for lack of connectivity and replayed once it returns. Actions
+//! that are rejected, or keep failing for [`MAX_REPLAY_ATTEMPTS`] replays, are
+//! parked until the user retries or removes them.
+
+use crate::error::ApiError;
+use crate::state::OFFLINE_DB;
+use anyhow::{Result, anyhow};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::Arc;
+
+/// File name of the offline store inside the data directory.
+pub const OFFLINE_DB_FILE: &str = "offline.sqlite";
+
+/// Failed replays after which a queued action is parked.
+pub const MAX_REPLAY_ATTEMPTS: u32 = 5;
+
+/// Part of the app whose state is cached for offline use.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Subsystem {
+    Balance,
+    TxHistory,
+    Swaps,
+    Contracts,
+}
+
+impl Subsystem {
+    const ALL: [Subsystem; 4] = [
+        Subsystem::Balance,
+        Subsystem::TxHistory,
+        Subsystem::Swaps,
+        Subsystem::Contracts,
+    ];
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            Subsystem::Balance => "Balance",
+            Subsystem::TxHistory => "TxHistory",
+            Subsystem::Swaps => "Swaps",
+            Subsystem::Contracts => "Contracts",
+        }
+    }
+}
+
+/// Freshness of the cached state of a subsystem.
+#[derive(Debug, Clone)]
+pub struct CacheStatus {
+    pub subsystem: Subsystem,
+    /// Unix timestamp of the last successful refresh, `None` if there was none yet
+    pub updated_at: Option<i64>,
+    /// Whether the last refresh failed and the cached state was served instead
+    pub is_stale: bool,
+}
+
+/// An action that is replayed once the backend can be reached again.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum QueuedAction {
+    /// Settle all VTXOs and boarding outputs
+    Settle,
+    /// Claim a LendaSwap swap via Gelato
+    ClaimSwapGelato { swap_id: String },
+    /// Claim the VHTLC of a LendaSwap EVM to BTC swap
+    ClaimSwapVhtlc { swap_id: String },
+    /// Report a Lendasat installment payment
+    MarkInstallmentPaid {
+        contract_id: String,
+        installment_id: String,
+        payment_txid: String,
+    },
+    /// Report additional collateral sent to a Lendasat contract
+    ReportCollateralTopUp {
+        contract_id: String,
+        txid: String,
+        amount_sats: u64,
+    },
+}
+
+/// A queued action as stored.
+#[derive(Debug, Clone)]
+pub struct QueuedOperation {
+    pub id: i64,
+    pub action: QueuedAction,
+    /// Unix timestamp of when the action was queued
+    pub created_at: i64,
+    /// Number of replays that failed while the backend was reachable
+    pub attempts: u32,
+    /// Error of the last failed replay
+    pub last_error: Option<String>,
+    /// No longer replayed automatically because it was rejected or failed
+    /// too often; see `retry_queued_action`
+    pub is_parked: bool,
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+struct QueuedOperationRow {
+    id: i64,
+    action: String,
+    created_at: i64,
+    attempts: i64,
+    last_error: Option<String>,
+    parked: bool,
+}
+
+impl TryFrom<QueuedOperationRow> for QueuedOperation {
+    type Error = anyhow::Error;
+
+    fn try_from(row: QueuedOperationRow) -> Result<Self> {
+        let action = serde_json::from_str(&row.action)
+            .map_err(|e| anyhow!("Invalid queued action {}: {}", row.id, e))?;
+
+        Ok(QueuedOperation {
+            id: row.id,
+            action,
+            created_at: row.created_at,
+            attempts: row.attempts as u32,
+            last_error: row.last_error,
+            is_parked: row.parked,
+        })
+    }
+}
+
+/// SQLite-based store for cached state and queued actions.
+pub struct OfflineDb {
+    pool: sqlx::SqlitePool,
+}
+
+impl OfflineDb {
+    /// Create a new OfflineDb with the given data directory.
+    /// The SQLite database will be created at `{data_dir}/offline.sqlite`.
+    pub async fn new(data_dir: &str) -> Result<Self> {
+        let db_path = Path::new(data_dir).join(OFFLINE_DB_FILE);
+        let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
+
+        let pool = sqlx::SqlitePool::connect(&db_url)
+            .await
+            .map_err(|e| anyhow!("Failed to connect to SQLite database: {}", e))?;
+
+        Self::run_migrations(&pool).await?;
+
+        Ok(Self { pool })
+    }
+
+    async fn run_migrations(pool: &sqlx::SqlitePool) -> Result<()> {
+        // `scope` separates variants of the same subsystem, e.g. contract filters
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS state_cache (
+                subsystem TEXT NOT NULL,
+                scope TEXT NOT NULL,
+                payload TEXT,
+                updated_at INTEGER NOT NULL,
+                is_stale INTEGER NOT NULL DEFAULT 0,
+                PRIMARY KEY (subsystem, scope)
+            )
+            "#,
+        )
+        .execute(pool)
+        .await
+        .map_err(|e| anyhow!("Failed to create state_cache table: {}", e))?;
+
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS action_queue (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                action TEXT NOT NULL UNIQUE,
+                created_at INTEGER NOT NULL,
+                attempts INTEGER NOT NULL DEFAULT 0,
+                last_error TEXT,
+                parked INTEGER NOT NULL DEFAULT 0
+            )
+            "#,
+        )
+        .execute(pool)
+        .await
+        .map_err(|e| anyhow!("Failed to create action_queue table: {}", e))?;
+
+        Ok(())
+    }
+
+    /// Record a successful refresh, replacing the cached payload if one is given.
+    pub async fn store(
+        &self,
+        subsystem: Subsystem,
+        scope: &str,
+        payload: Option<String>,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO state_cache (subsystem, scope, payload, updated_at, is_stale)
+            VALUES (?, ?, ?, ?, 0)
+            ON CONFLICT(subsystem, scope) DO UPDATE SET
+                payload = COALESCE(excluded.payload, payload),
+                updated_at = excluded.updated_at,
+                is_stale = 0
+            "#,
+        )
+        .bind(subsystem.as_str())
+        .bind(scope)
+        .bind(payload)
+        .bind(now_unix())
+        .execute(&self.pool)
+        .await
+        .map_err(|e| anyhow!("Failed to store {} state: {}", subsystem.as_str(), e))?;
+
+        Ok(())
+    }
+
+    /// Load the cached payload and flag it as stale.
+    pub async fn load_stale(&self, subsystem: Subsystem, scope: &str) -> Result<Option<String>> {
+        let payload: Option<(Option<String>,)> = sqlx::query_as(
+            "UPDATE state_cache SET is_stale = 1 WHERE subsystem = ? AND scope = ? RETURNING payload",
+        )
+        .bind(subsystem.as_str())
+        .bind(scope)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| anyhow!("Failed to load {} state: {}", subsystem.as_str(), e))?;
+
+        Ok(payload.and_then(|(p,)| p))
+    }
+
+    /// Whether the last refresh of `subsystem` failed and served the cache.
+    pub async fn is_stale(&self, subsystem: Subsystem, scope: &str) -> Result<bool> {
+        let stale: Option<(bool,)> =
+            sqlx::query_as("SELECT is_stale FROM state_cache WHERE subsystem = ? AND scope = ?")
+                .bind(subsystem.as_str())
+                .bind(scope)
+                .fetch_optional(&self.pool)
+                .await
+                .map_err(|e| anyhow!("Failed to load {} state: {}", subsystem.as_str(), e))?;
+
+        Ok(stale.is_some_and(|(s,)| s))
+    }
+
+    /// Freshness of every subsystem, using its most recently refreshed scope.
+    pub async fn status(&self) -> Result<Vec<CacheStatus>> {
+        let rows: Vec<(String, i64, bool)> =
+            sqlx::query_as("SELECT subsystem, updated_at, is_stale FROM state_cache")
+                .fetch_all(&self.pool)
+                .await
+                .map_err(|e| anyhow!("Failed to load cache status: {}", e))?;
+
+        Ok(Subsystem::ALL
+            .into_iter()
+            .map(|subsystem| {
+                let latest = rows
+                    .iter()
+                    .filter(|(name, _, _)| name == subsystem.as_str())
+                    .max_by_key(|(_, updated_at, is_stale)| (*updated_at, *is_stale));
+                CacheStatus {
+                    subsystem,
+                    updated_at: latest.map(|(_, updated_at, _)| *updated_at),
+                    is_stale: latest.is_some_and(|(_, _, is_stale)| *is_stale),
+                }
+            })
+            .collect())
+    }
+
+    /// Queue an action. Queuing the same action twice keeps the first entry.
+    pub async fn enqueue(&self, action: &QueuedAction) -> Result<()> {
+        let json = serde_json::to_string(action)
+            .map_err(|e| anyhow!("Failed to serialize queued action: {}", e))?;
+
+        sqlx::query("INSERT OR IGNORE INTO action_queue (action, created_at) VALUES (?, ?)")
+            .bind(json)
+            .bind(now_unix())
+            .execute(&self.pool)
+            .await
+            .map_err(|e| anyhow!("Failed to queue action: {}", e))?;
+
+        Ok(())
+    }
+
+    /// All queued actions, oldest first.
+    pub async fn queued(&self) -> Result<Vec<QueuedOperation>> {
+        self.load_queue(false).await
+    }
+
+    /// Queued actions that are replayed automatically, oldest first.
+    pub async fn runnable(&self) -> Result<Vec<QueuedOperation>> {
+        self.load_queue(true).await
+    }
+
+    async fn load_queue(&self, runnable_only: bool) -> Result<Vec<QueuedOperation>> {
+        let filter = if runnable_only {
+            "WHERE parked = 0"
+        } else {
+            ""
+        };
+        let rows: Vec<QueuedOperationRow> = sqlx::query_as(&format!(
+            "SELECT id, action, created_at, attempts, last_error, parked FROM action_queue \
+             {filter} ORDER BY id"
+        ))
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| anyhow!("Failed to load queued actions: {}", e))?;
+
+        rows.into_iter().map(QueuedOperation::try_from).collect()
+    }
+
+    pub async fn remove(&self, id: i64) -> Result<()> {
+        sqlx::query("DELETE FROM action_queue WHERE id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| anyhow!("Failed to remove queued action: {}", e))?;
+
+        Ok(())
+    }
+
+    /// Record a failed replay. Returns the number of failed attempts so far.
+    pub async fn record_failure(&self, id: i64, error: &str) -> Result<u32> {
+        let attempts: Option<(i64,)> = sqlx::query_as(
+            "UPDATE action_queue SET attempts = attempts + 1, last_error = ? WHERE id = ? \
+             RETURNING attempts",
+        )
+        .bind(error)
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| anyhow!("Failed to update queued action: {}", e))?;
+
+        Ok(attempts.map_or(0, |(a,)| a as u32))
+    }
+
+    /// Record a replay that could not run yet, e.g. because the backend is
+    /// unreachable. Does not count as an attempt.
+    pub async fn record_offline(&self, id: i64, error: &str) -> Result<()> {
+        sqlx::query("UPDATE action_queue SET last_error = ? WHERE id = ?")
+            .bind(error)
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| anyhow!("Failed to update queued action: {}", e))?;
+
+        Ok(())
+    }
+
+    /// Stop replaying an action automatically.
+    pub async fn park(&self, id: i64) -> Result<()> {
+        sqlx::query("UPDATE action_queue SET parked = 1 WHERE id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| anyhow!("Failed to park queued action: {}", e))?;
+
+        Ok(())
+    }
+
+    /// Replay a parked action again, with its attempts reset.
+    pub async fn unpark(&self, id: i64) -> Result<()> {
+        sqlx::query("UPDATE action_queue SET parked = 0, attempts = 0 WHERE id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| anyhow!("Failed to retry queued action: {}", e))?;
+
+        Ok(())
+    }
+}
+
+/// Get the offline store of the loaded wallet.
+pub(crate) fn get_db() -> Result<Arc<OfflineDb>> {
+    let lock = OFFLINE_DB
+        .try_get()
+        .ok_or_else(|| ApiError::not_initialized("Offline store"))?;
+    let guard = lock.read();
+    Ok(Arc::clone(&*guard))
+}
+
+/// Whether `error` means the backend could not be reached, as opposed to a
+/// request it rejected.
+///
+/// A missing Ark client counts as well: after a cold start without
+/// connectivity the wallet is loaded but the client never connected.
+pub(crate) fn is_offline_error(error: &ApiError) -> bool {
+    match error {
+        ApiError::ServerUnavailable { .. } | ApiError::Timeout => true,
+        ApiError::NotInitialized { component } => component == "Ark client",
+        _ => false,
+    }
+}
+
+/// Whether `error` means the backend rejected the action for good, so
+/// replaying it unchanged cannot succeed.
+///
+/// An expired login or a client that is not set up yet can still be fixed
+/// and does not count.
+pub(crate) fn is_permanent_error(error: &ApiError) -> bool {
+    matches!(
+        error,
+        ApiError::InsufficientFunds { .. }
+            | ApiError::InvalidAddress { .. }
+            | ApiError::NetworkMismatch { .. }
+            | ApiError::SwapExpired { .. }
+            | ApiError::Other { .. }
+    )
+}
+
+/// Whether the last refresh of `subsystem` served the cache, i.e. the
+/// backend was unreachable. Errors are logged and count as not stale.
+pub(crate) async fn is_stale(subsystem: Subsystem) -> bool {
+    let result = match get_db() {
+        Ok(db) => db.is_stale(subsystem, "").await,
+        Err(e) => Err(e),
+    };
+
+    result.unwrap_or_else(|e| {
+        tracing::warn!("Failed to load {} state: {:#}", subsystem.as_str(), e);
+        false
+    })
+}
+
+/// Remember a freshly fetched state, or serve the cached one if the fetch
+/// failed because the backend is unreachable.
+///
+/// Caching is best effort: storage errors are only logged.
+pub(crate) async fn cached<T>(
+    subsystem: Subsystem,
+    scope: &str,
+    result: Result<T, ApiError>,
+) -> Result<T, ApiError>
+where
+    T: Serialize + DeserializeOwned,
+{
+    match result {
+        Ok(value) => {
+            let stored = match (get_db(), serde_json::to_string(&value)) {
+                (Ok(db), Ok(json)) => db.store(subsystem, scope, Some(json)).await,
+                (Err(e), _) => Err(e),
+                (_, Err(e)) => Err(e.into()),
+            };
+            if let Err(e) = stored {
+                tracing::warn!("Failed to cache {} state: {:#}", subsystem.as_str(), e);
+            }
+            Ok(value)
+        }
+        Err(error) if is_offline_error(&error) => {
+            let cached = match get_db() {
+                Ok(db) => db.load_stale(subsystem, scope).await,
+                Err(e) => Err(e),
+            };
+            match cached {
+                Ok(Some(json)) => match serde_json::from_str(&json) {
+                    Ok(value) => {
+                        tracing::warn!("Serving cached {} state: {}", subsystem.as_str(), error);
+                        Ok(value)
+                    }
+                    Err(e) => {
+                        tracing::warn!("Invalid cached {} state: {}", subsystem.as_str(), e);
+                        Err(error)
+                    }
+                },
+                Ok(None) => Err(error),
+                Err(e) => {
+                    tracing::warn!(
+                        "Failed to load cached {} state: {:#}",
+                        subsystem.as_str(),
+                        e
+                    );
+                    Err(error)
+                }
+            }
+        }
+        Err(error) => Err(error),
+    }
+}
+
+/// Update the freshness of a subsystem whose data is cached elsewhere.
+pub(crate) async fn record_refresh(subsystem: Subsystem, succeeded: bool) {
+    let result = match get_db() {
+        Ok(db) if succeeded => db.store(subsystem, "", None).await,
+        Ok(db) => db.load_stale(subsystem, "").await.map(|_| ()),
+        Err(e) => Err(e),
+    };
+
+    if let Err(e) = result {
+        tracing::warn!("Failed to update {} state: {:#}", subsystem.as_str(), e);
+    }
+}
+
+/// Queue `action` if it failed because the backend is unreachable.
+///
+/// Returns the error unchanged so the caller still reports the failure.
+pub(crate) async fn defer_if_offline(action: QueuedAction, error: ApiError) -> ApiError {
+    if is_offline_error(&error) {
+        defer(&action).await;
+    }
+
+    error
+}
+
+/// Queue `action` to be replayed later, logging instead of failing if the
+/// queue is unavailable.
+pub(crate) async fn defer(action: &QueuedAction) {
+    let result = match get_db() {
+        Ok(db) => db.enqueue(action).await,
+        Err(e) => Err(e),
+    };
+
+    match result {
+        Ok(()) => tracing::info!("Queued {:?} until it can be replayed", action),
+        Err(e) => tracing::warn!("Failed to queue {:?}: {:#}", action, e),
+    }
+}
+
+fn now_unix() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parking_lot::RwLock;
+
+    async fn open_db() -> OfflineDb {
+        let dir = std::env::temp_dir().join(format!("offline-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        OfflineDb::new(dir.to_str().unwrap()).await.unwrap()
+    }
+
+    fn offline() -> ApiError {
+        ApiError::ServerUnavailable {
+            message: "error sending request".to_string(),
+        }
+    }
+
+    #[test]
+    fn classifies_offline_errors() {
+        assert!(is_offline_error(&offline()));
+        assert!(is_offline_error(&ApiError::Timeout));
+        assert!(is_offline_error(&ApiError::not_initialized("Ark client")));
+        assert!(!is_offline_error(&ApiError::not_initialized("Lendasat")));
+        assert!(!is_offline_error(&ApiError::AuthRequired));
+        assert!(!is_offline_error(&ApiError::Other {
+            message: "rejected".to_string(),
+        }));
+    }
+
+    #[tokio::test]
+    async fn stale_state_keeps_last_payload() {
+        let db = open_db().await;
+
+        assert_eq!(db.load_stale(Subsystem::Balance, "").await.unwrap(), None);
+
+        db.store(Subsystem::Balance, "", Some("1".to_string()))
+            .await
+            .unwrap();
+        // A refresh without payload only updates the freshness
+        db.store(Subsystem::Balance, "", None).await.unwrap();
+        assert_eq!(
+            db.load_stale(Subsystem::Balance, "").await.unwrap(),
+            Some("1".to_string())
+        );
+
+        let status = db.status().await.unwrap();
+        let balance = status
+            .iter()
+            .find(|s| s.subsystem == Subsystem::Balance)
+            .unwrap();
+        assert!(balance.is_stale);
+        assert!(balance.updated_at.is_some());
+        let swaps = status
+            .iter()
+            .find(|s| s.subsystem == Subsystem::Swaps)
+            .unwrap();
+        assert!(!swaps.is_stale);
+        assert_eq!(swaps.updated_at, None);
+
+        db.store(Subsystem::Balance, "", Some("2".to_string()))
+            .await
+            .unwrap();
+        let status = db.status().await.unwrap();
+        assert!(!status[0].is_stale);
+    }
+
+    #[tokio::test]
+    async fn queue_deduplicates_and_records_failures() {
+        let db = open_db().await;
+        let claim = QueuedAction::ClaimSwapGelato {
+            swap_id: "swap-1".to_string(),
+        };
+
+        db.enqueue(&QueuedAction::Settle).await.unwrap();
+        db.enqueue(&claim).await.unwrap();
+        db.enqueue(&QueuedAction::Settle).await.unwrap();
+
+        let queued = db.queued().await.unwrap();
+        assert_eq!(queued.len(), 2);
+        assert_eq!(queued[0].action, QueuedAction::Settle);
+        assert_eq!(queued[1].action, claim);
+
+        assert_eq!(
+            db.record_failure(queued[0].id, "rejected").await.unwrap(),
+            1
+        );
+        db.record_offline(queued[0].id, "offline").await.unwrap();
+        assert_eq!(
+            db.record_failure(queued[0].id, "rejected").await.unwrap(),
+            2
+        );
+        db.record_offline(queued[0].id, "still offline")
+            .await
+            .unwrap();
+        db.remove(queued[1].id).await.unwrap();
+
+        let queued = db.queued().await.unwrap();
+        assert_eq!(queued.len(), 1);
+        // Offline failures do not count as attempts
+        assert_eq!(queued[0].attempts, 2);
+        assert_eq!(queued[0].last_error.as_deref(), Some("still offline"));
+    }
+
+    #[tokio::test]
+    async fn parked_actions_are_not_runnable() {
+        let db = open_db().await;
+        db.enqueue(&QueuedAction::Settle).await.unwrap();
+        let id = db.queued().await.unwrap()[0].id;
+        db.record_failure(id, "rejected").await.unwrap();
+
+        db.park(id).await.unwrap();
+
+        assert!(db.runnable().await.unwrap().is_empty());
+        let queued = db.queued().await.unwrap();
+        assert!(queued[0].is_parked);
+
+        db.unpark(id).await.unwrap();
+
+        let runnable = db.runnable().await.unwrap();
+        assert_eq!(runnable.len(), 1);
+        assert!(!runnable[0].is_parked);
+        assert_eq!(runnable[0].attempts, 0);
+    }
+
+    #[test]
+    fn classifies_permanent_errors() {
+        assert!(is_permanent_error(&ApiError::Other {
+            message: "Failed to mark installment paid (400 Bad Request): already paid".to_string(),
+        }));
+        assert!(is_permanent_error(&ApiError::SwapExpired {
+            swap_id: "swap-1".to_string(),
+        }));
+        assert!(!is_permanent_error(&offline()));
+        assert!(!is_permanent_error(&ApiError::AuthRequired));
+        assert!(!is_permanent_error(&ApiError::not_initialized("Lendasat")));
+    }
+
+    /// The only test using the global store.
+    #[tokio::test]
+    async fn serves_cache_and_queues_while_offline() {
+        let db = Arc::new(open_db().await);
+        match OFFLINE_DB.try_get() {
+            Some(lock) => *lock.write() = Arc::clone(&db),
+            None => {
+                OFFLINE_DB.set(RwLock::new(Arc::clone(&db)));
+            }
+        }
+
+        // Nothing cached yet: the error is passed through
+        let result: Result<u64, ApiError> = cached(Subsystem::Balance, "", Err(offline())).await;
+        assert_eq!(result, Err(offline()));
+
+        assert_eq!(cached(Subsystem::Balance, "", Ok(42u64)).await, Ok(42));
+        assert_eq!(
+            cached::<u64>(Subsystem::Balance, "", Err(offline())).await,
+            Ok(42)
+        );
+        // Cold start: the Ark client never connected
+        assert_eq!(
+            cached::<u64>(
+                Subsystem::Balance,
+                "",
+                Err(ApiError::not_initialized("Ark client"))
+            )
+            .await,
+            Ok(42)
+        );
+        // Rejections are not hidden behind the cache
+        assert_eq!(
+            cached::<u64>(Subsystem::Balance, "", Err(ApiError::AuthRequired)).await,
+            Err(ApiError::AuthRequired)
+        );
+
+        let error = defer_if_offline(QueuedAction::Settle, offline()).await;
+        assert_eq!(error, offline());
+        defer_if_offline(QueuedAction::Settle, ApiError::AuthRequired).await;
+        let queued = db.queued().await.unwrap();
+        assert_eq!(queued.len(), 1);
+        assert_eq!(queued[0].action, QueuedAction::Settle);
+    }
+}
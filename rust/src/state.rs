@@ -1,7 +1,10 @@
 use crate::ark::esplora::EsploraClient;
+use crate::ark::history_db::HistoryDb;
 use crate::ark::storage::InMemoryDb;
+use crate::ark::tx_metadata_db::TxMetadataDb;
 use crate::frb_generated::StreamSink;
 use crate::logger::LogEntry;
+use crate::offline::OfflineDb;
 use ark_bdk_wallet::Wallet;
 use ark_client::{Bip32KeyProvider, Client, Error, KeyProvider, SqliteSwapStorage};
 use bitcoin::XOnlyPublicKey;
@@ -9,11 +12,21 @@ use bitcoin::key::Keypair;
 use parking_lot::RwLock;
 use state::InitCell;
 use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
 
 pub static LOG_STREAM_SINK: InitCell<RwLock<Arc<StreamSink<LogEntry>>>> = InitCell::new();
 
-/// Store esplora URL for creating separate esplora clients when needed
-pub static ESPLORA_URL: InitCell<RwLock<String>> = InitCell::new();
+/// Esplora client shared with the Ark client, with failover between endpoints
+pub static ESPLORA_CLIENT: InitCell<RwLock<Arc<EsploraClient>>> = InitCell::new();
+
+/// Local transaction metadata (labels, notes, counterparties) of the loaded wallet
+pub static TX_METADATA_DB: InitCell<RwLock<Arc<TxMetadataDb>>> = InitCell::new();
+
+/// Local cache of the transaction history of the loaded wallet
+pub static HISTORY_DB: InitCell<RwLock<Arc<HistoryDb>>> = InitCell::new();
+
+/// Cached state and queued actions of the loaded wallet, for offline use
+pub static OFFLINE_DB: InitCell<RwLock<Arc<OfflineDb>>> = InitCell::new();
 
 /// Unified key provider wrapper for HD wallets using Bip32KeyProvider
 pub enum UnifiedKeyProvider {
@@ -81,3 +94,15 @@ pub type ArkClient =
 
 #[allow(clippy::type_complexity)]
 pub static ARK_CLIENT: InitCell<RwLock<Arc<ArkClient>>> = InitCell::new();
+
+/// URL of the Ark server `ARK_CLIENT` is connected to
+pub static ARK_SERVER_URL: InitCell<RwLock<String>> = InitCell::new();
+
+/// Boarding outputs of the loaded wallet, shared with its BDK wallet
+pub static WALLET_DB: InitCell<RwLock<InMemoryDb>> = InitCell::new();
+
+/// Key provider shared with the Ark client, for inspecting discovered keys
+pub static KEY_PROVIDER: InitCell<RwLock<Arc<UnifiedKeyProvider>>> = InitCell::new();
+
+/// Set when key discovery failed during setup and a rescan is needed
+pub static KEY_DISCOVERY_INCOMPLETE: AtomicBool = AtomicBool::new(false);